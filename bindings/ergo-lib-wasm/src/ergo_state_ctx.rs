@@ -14,12 +14,13 @@ pub struct ErgoStateContext(chain::ergo_state_context::ErgoStateContext);
 
 #[wasm_bindgen]
 impl ErgoStateContext {
-    /// Create new context from pre-header
+    /// Create new context from pre-header (with the mainnet launch system parameters)
     #[wasm_bindgen(constructor)]
     pub fn new(pre_header: PreHeader) -> Self {
-        chain::ergo_state_context::ErgoStateContext {
-            pre_header: pre_header.into(),
-        }
+        chain::ergo_state_context::ErgoStateContext::new(
+            pre_header.into(),
+            chain::parameters::Parameters::default(),
+        )
         .into()
     }
 
@@ -1,7 +1,7 @@
 //! Ergo chain types
 
 #[cfg(feature = "json")]
-mod json;
+pub(crate) mod json;
 
 mod base16_bytes;
 mod digest32;
@@ -9,9 +9,17 @@ mod digest32;
 pub use base16_bytes::*;
 pub use digest32::*;
 
+pub mod autolykos_pow_scheme;
 pub mod block_header;
 pub mod contract;
+pub mod difficulty_adjustment;
 pub mod ergo_box;
 pub mod ergo_state_context;
+pub mod extension;
+pub mod header_chain;
+pub mod merkle_tree;
+pub mod nipopow;
+pub mod parameters;
+pub mod storage_rent;
 pub mod token;
 pub mod transaction;
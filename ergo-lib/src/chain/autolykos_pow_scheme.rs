@@ -0,0 +1,454 @@
+//! Autolykos proof-of-work (v1 and v2) verification
+//! (see <https://docs.ergoplatform.com/ErgoPow.pdf>)
+
+use std::convert::TryInto;
+
+use ergotree_ir::serialization::SigmaSerializable;
+use ergotree_ir::sigma_protocol::dlog_group;
+use k256::FieldBytes;
+use k256::Scalar;
+use num_bigint::BigUint;
+use num_bigint::Sign;
+use thiserror::Error;
+
+use super::blake2b256_hash;
+use super::block_header::BlockHeader;
+
+/// Autolykos PoW puzzle scheme parameters
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct AutolykosPowScheme {
+    /// Number of elements in one solution
+    k: u32,
+    /// Binary logarithm of the initial table size `N`
+    n: u32,
+}
+
+/// Autolykos PoW verification errors
+#[derive(Error, PartialEq, Eq, Debug, Clone)]
+pub enum AutolykosPowSchemeError {
+    /// Difficulty decoded from `n_bits` is not positive
+    #[error("Invalid difficulty (nBits: {0})")]
+    InvalidDifficulty(u64),
+    /// The PoW hit (v2) or the distance `d` (v1) is not less than the target `b`
+    #[error("PoW hit {hit} is not less than the target {target}")]
+    HitAboveTarget {
+        /// PoW hit
+        hit: BigUint,
+        /// Target decoded from the difficulty
+        target: BigUint,
+    },
+    /// Miner or one-time public key is the identity element (v1)
+    #[error("Public key of the PoW solution is the identity element")]
+    IdentityPublicKey,
+    /// `w^f == g^d * pk` does not hold (v1)
+    #[error("PoW solution points do not satisfy w^f == g^d * pk")]
+    InvalidSolutionPoints,
+}
+
+impl Default for AutolykosPowScheme {
+    fn default() -> Self {
+        // mainnet and testnet parameters
+        AutolykosPowScheme { k: 32, n: 26 }
+    }
+}
+
+impl AutolykosPowScheme {
+    /// Height of the first increase of the table size `N` (v2)
+    pub const INCREASE_START: u32 = 600 * 1024;
+    /// Period of the table size `N` increases (v2)
+    pub const INCREASE_PERIOD_FOR_N: u32 = 50 * 1024;
+    /// Height after which the table size `N` stops increasing (v2)
+    pub const N_INCREASEMENT_HEIGHT_MAX: u32 = 4198400;
+
+    /// Create a scheme with `k` elements in a solution and `2^n` initial table size
+    pub fn new(k: u32, n: u32) -> Self {
+        AutolykosPowScheme { k, n }
+    }
+
+    /// Initial table size `N`
+    pub fn n_base(&self) -> u32 {
+        1 << self.n
+    }
+
+    /// Table size `N` for the given block version and height. Constant for v1, for v2
+    /// it grows by 5% every [`Self::INCREASE_PERIOD_FOR_N`] blocks starting from
+    /// [`Self::INCREASE_START`] up to [`Self::N_INCREASEMENT_HEIGHT_MAX`]
+    pub fn calc_big_n(&self, version: u8, height: u32) -> u32 {
+        let n_base = self.n_base();
+        if version == BlockHeader::INITIAL_VERSION {
+            return n_base;
+        }
+        let height = height.min(Self::N_INCREASEMENT_HEIGHT_MAX);
+        if height < Self::INCREASE_START {
+            n_base
+        } else {
+            let iters_number = (height - Self::INCREASE_START) / Self::INCREASE_PERIOD_FOR_N + 1;
+            (0..iters_number).fold(n_base, |step, _| step / 100 * 105)
+        }
+    }
+
+    /// Message the PoW is computed for (hash of the header without the PoW solution)
+    pub fn msg_by_header(header: &BlockHeader) -> [u8; 32] {
+        *blake2b256_hash(&header.serialize_without_pow()).0
+    }
+
+    /// Pseudo-random indexes (`k` of them) of the table elements the solution consists of
+    pub fn gen_indexes(&self, seed: &[u8], big_n: u32) -> Vec<u32> {
+        let hash = blake2b256_hash(seed);
+        let mut extended_hash = hash.0.to_vec();
+        extended_hash.extend_from_slice(&hash.0[..3]);
+        (0..self.k as usize)
+            .map(|i| {
+                #[allow(clippy::unwrap_used)]
+                let idx_bytes: [u8; 4] = extended_hash[i..i + 4].try_into().unwrap();
+                u32::from_be_bytes(idx_bytes) % big_n
+            })
+            .collect()
+    }
+
+    /// PoW hit of the solution, which is valid if the hit is less than the target
+    /// (for v1 the hit is the distance `d` of the solution)
+    pub fn pow_hit(&self, header: &BlockHeader) -> BigUint {
+        if header.version == BlockHeader::INITIAL_VERSION {
+            return header.autolykos_solution.pow_distance.clone();
+        }
+        let msg = Self::msg_by_header(header);
+        let nonce = &header.autolykos_solution.nonce;
+        let height_bytes = header.height.to_be_bytes();
+        let big_n = self.calc_big_n(header.version, header.height);
+        let big_m = calc_big_m();
+
+        let msg_nonce_hash = blake2b256_hash(&[&msg[..], &nonce[..]].concat());
+        #[allow(clippy::unwrap_used)]
+        let prei8 = u64::from_be_bytes(msg_nonce_hash.0[24..].try_into().unwrap());
+        let i = ((prei8 % big_n as u64) as u32).to_be_bytes();
+        let f = blake2b256_hash(&[&i[..], &height_bytes[..], &big_m[..]].concat());
+        let seed = [&f.0[1..], &msg[..], &nonce[..]].concat();
+        let sum: BigUint = self
+            .gen_indexes(&seed, big_n)
+            .into_iter()
+            .map(|idx| {
+                let e = blake2b256_hash(
+                    &[&idx.to_be_bytes()[..], &height_bytes[..], &big_m[..]].concat(),
+                );
+                BigUint::from_bytes_be(&e.0[1..])
+            })
+            .sum();
+        BigUint::from_bytes_be(blake2b256_hash(&as_unsigned_bytes_32(&sum)).0.as_ref())
+    }
+
+    /// Verify the PoW solution of the header against the difficulty encoded in `n_bits`
+    pub fn validate(&self, header: &BlockHeader) -> Result<(), AutolykosPowSchemeError> {
+        let target = target_b(header.n_bits)?;
+        if header.version == BlockHeader::INITIAL_VERSION {
+            self.validate_v1(header, target)
+        } else {
+            let hit = self.pow_hit(header);
+            if hit < target {
+                Ok(())
+            } else {
+                Err(AutolykosPowSchemeError::HitAboveTarget { hit, target })
+            }
+        }
+    }
+
+    fn validate_v1(
+        &self,
+        header: &BlockHeader,
+        target: BigUint,
+    ) -> Result<(), AutolykosPowSchemeError> {
+        let s = &header.autolykos_solution;
+        if s.pow_distance >= target {
+            return Err(AutolykosPowSchemeError::HitAboveTarget {
+                hit: s.pow_distance.clone(),
+                target,
+            });
+        }
+        if dlog_group::is_identity(&s.miner_pk) || dlog_group::is_identity(&s.pow_onetime_pk) {
+            return Err(AutolykosPowSchemeError::IdentityPublicKey);
+        }
+        let f = self.sum_of_elements_v1(header);
+        let left = dlog_group::exponentiate(&s.pow_onetime_pk, &to_scalar(&f));
+        let right = dlog_group::exponentiate(&dlog_group::generator(), &to_scalar(&s.pow_distance))
+            * &s.miner_pk;
+        if left == right {
+            Ok(())
+        } else {
+            Err(AutolykosPowSchemeError::InvalidSolutionPoints)
+        }
+    }
+
+    /// Sum of the table elements of the v1 solution modulo the group order (`f` in
+    /// `w^f == g^d * pk`), an element is `hashModQ(index ++ M ++ pk ++ msg ++ w)`
+    fn sum_of_elements_v1(&self, header: &BlockHeader) -> BigUint {
+        let s = &header.autolykos_solution;
+        let msg = Self::msg_by_header(header);
+        let pk_bytes = s.miner_pk.sigma_serialize_bytes();
+        let w_bytes = s.pow_onetime_pk.sigma_serialize_bytes();
+        let big_m = calc_big_m();
+        let seed = [&msg[..], &s.nonce[..]].concat();
+        self.gen_indexes(&seed, self.calc_big_n(header.version, header.height))
+            .into_iter()
+            .map(|idx| {
+                hash_mod_q(
+                    &[
+                        &idx.to_be_bytes()[..],
+                        &big_m[..],
+                        &pk_bytes[..],
+                        &msg[..],
+                        &w_bytes[..],
+                    ]
+                    .concat(),
+                )
+            })
+            .sum::<BigUint>()
+            % group_order()
+    }
+}
+
+/// Hash of the input as a number modulo the group order `q` (v1). The hash is re-hashed until it
+/// is in the range `[0, 2^256 / q * q)` (i.e. below `q`) so that the result is uniform
+fn hash_mod_q(input: &[u8]) -> BigUint {
+    let q = group_order();
+    let valid_range = (BigUint::from(1u8) << 256) / &q * &q;
+    let mut hash = blake2b256_hash(input);
+    loop {
+        let v = BigUint::from_bytes_be(hash.0.as_ref());
+        if v < valid_range {
+            return v % &q;
+        }
+        hash = blake2b256_hash(hash.0.as_ref());
+    }
+}
+
+/// Order of the secp256k1 group
+pub fn group_order() -> BigUint {
+    #[allow(clippy::unwrap_used)]
+    BigUint::parse_bytes(
+        b"fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141",
+        16,
+    )
+    .unwrap()
+}
+
+/// PoW target `b` for the difficulty encoded in `n_bits` (group order divided by the difficulty)
+pub fn target_b(n_bits: u64) -> Result<BigUint, AutolykosPowSchemeError> {
+    let difficulty = super::block_header::decode_compact_bits(n_bits);
+    match difficulty.to_biguint() {
+        Some(d) if difficulty.sign() == Sign::Plus => Ok(group_order() / d),
+        _ => Err(AutolykosPowSchemeError::InvalidDifficulty(n_bits)),
+    }
+}
+
+/// Constant data (8192 bytes) to be added to the hashed data to slow down the computation
+fn calc_big_m() -> Vec<u8> {
+    (0u64..1024).flat_map(|i| i.to_be_bytes()).collect()
+}
+
+fn as_unsigned_bytes_32(v: &BigUint) -> [u8; 32] {
+    let bytes = v.to_bytes_be();
+    let mut res = [0u8; 32];
+    res[32 - bytes.len()..].copy_from_slice(&bytes);
+    res
+}
+
+fn to_scalar(v: &BigUint) -> Scalar {
+    let reduced = v % group_order();
+    let bytes: FieldBytes = as_unsigned_bytes_32(&reduced).into();
+    Scalar::from_bytes_reduced(&bytes)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::chain::block_header::tests::MAINNET_HEADER_471746;
+    use crate::chain::block_header::AutolykosSolution;
+    use ergotree_ir::sigma_protocol::dlog_group::EcPoint;
+
+    #[test]
+    fn calc_big_n_schedule() {
+        // see AutolykosPowSchemeSpec in the node
+        let pow = AutolykosPowScheme::default();
+        let n_base = pow.n_base();
+        assert_eq!(n_base, 67108864);
+        assert_eq!(pow.calc_big_n(1, 700000), n_base);
+        assert_eq!(pow.calc_big_n(2, 500000), n_base);
+        assert_eq!(pow.calc_big_n(2, 600 * 1024 - 1), n_base);
+        assert_eq!(pow.calc_big_n(2, 600 * 1024), 70464240);
+        assert_eq!(pow.calc_big_n(2, 650 * 1024), 73987410);
+        assert_eq!(pow.calc_big_n(2, 700000), 73987410);
+        assert_eq!(pow.calc_big_n(2, 788400), 81571035);
+        assert_eq!(pow.calc_big_n(2, 1051200), 104107290);
+        assert_eq!(pow.calc_big_n(2, 4198400), 2143944600);
+        assert_eq!(pow.calc_big_n(2, 41984000), 2143944600);
+    }
+
+    #[test]
+    fn target_from_n_bits() {
+        assert_eq!(
+            target_b(117586360).unwrap(),
+            group_order() / BigUint::from(626412390187008u64)
+        );
+        assert_eq!(target_b(0x01010000).unwrap(), group_order());
+        assert_eq!(
+            target_b(0).unwrap_err(),
+            AutolykosPowSchemeError::InvalidDifficulty(0)
+        );
+        assert_eq!(
+            target_b(0x04923456).unwrap_err(),
+            AutolykosPowSchemeError::InvalidDifficulty(0x04923456)
+        );
+    }
+
+    #[test]
+    fn gen_indexes_in_range() {
+        let pow = AutolykosPowScheme::default();
+        let indexes = pow.gen_indexes(&[1, 2, 3], 1000);
+        assert_eq!(indexes.len(), 32);
+        assert!(indexes.iter().all(|i| *i < 1000));
+    }
+
+    #[test]
+    fn validate_mainnet_v2() {
+        let header: BlockHeader = serde_json::from_str(MAINNET_HEADER_471746).unwrap();
+        let pow = AutolykosPowScheme::default();
+        assert!(pow.pow_hit(&header) < target_b(header.n_bits).unwrap());
+        assert_eq!(pow.validate(&header), Ok(()));
+    }
+
+    #[test]
+    fn validate_v2_first_increase_in_big_n() {
+        // see "test vectors for first increase in N value (height 614,400)" in the node's
+        // AutolykosPowSchemeSpec
+        let json = r#"{
+            "extensionId": "00cce45975d87414e8bdd8146bc88815be59cd9fe37a125b5021101e05675a18",
+            "difficulty": "16384",
+            "votes": "000000",
+            "timestamp": 4928911477310178288,
+            "size": 223,
+            "stateRoot": "5c8c00b8403d3701557181c8df800001b6d5009e2201c6ff807d71808c00019780",
+            "height": 614400,
+            "nBits": 37748736,
+            "version": 2,
+            "id": "5603a937ec1988220fc44fb5022fb82d5565b961f005ebb55d85bd5a9e6f801f",
+            "adProofsRoot": "5d3f80dcff7f5e7f59007294c180808d0158d1ff6ba10000f901c7f0ef87dcff",
+            "transactionsRoot": "f17fffacb6ff7f7f1180d2ff7f1e24ffffe1ff937f807f0797b9ff6ebdae007e",
+            "extensionHash": "1480887f80007f4b01cf7f013ff1ffff564a0000b9a54f00770e807f41ff88c0",
+            "powSolutions": {
+              "pk": "03bedaee069ff4829500b3c07c4d5fe6b3ea3d3bf76c5c28c1d4dcdb1bed0ade0c",
+              "n": "0000000000003105"
+            },
+            "adProofsId": "dec129290a763f4de41f04e87e2b661dd59758af6bdd00dd51f5d97c3a8cb9b5",
+            "transactionsId": "eba1dd82cf51147232e09c1f72b37c554c30f63274d5093bff36849a83472a42",
+            "parentId": "ac2101807f0000ca01ff0119db227f202201007f62000177a080005d440896d0"
+        }"#;
+        let header: BlockHeader = serde_json::from_str(json).unwrap();
+        let pow = AutolykosPowScheme::default();
+        assert_eq!(pow.calc_big_n(header.version, header.height), 70464240);
+        assert_eq!(pow.validate(&header), Ok(()));
+    }
+
+    #[test]
+    fn validate_mainnet_v2_wrong_nonce() {
+        let mut header: BlockHeader = serde_json::from_str(MAINNET_HEADER_471746).unwrap();
+        header.autolykos_solution.nonce[7] ^= 1;
+        assert!(matches!(
+            AutolykosPowScheme::default().validate(&header),
+            Err(AutolykosPowSchemeError::HitAboveTarget { .. })
+        ));
+    }
+
+    #[test]
+    fn validate_mainnet_v2_higher_difficulty() {
+        let mut header: BlockHeader = serde_json::from_str(MAINNET_HEADER_471746).unwrap();
+        // the same solution is not enough for the difficulty * 2^64
+        header.n_bits = 0x0f0239b8;
+        assert!(AutolykosPowScheme::default().validate(&header).is_err());
+    }
+
+    /// v1 header with a solution for the difficulty 1 (`w = g^y`, `pk = g^x`, `d = y*f - x`)
+    fn solved_v1_header(x: u64, y: u64) -> BlockHeader {
+        let mut header: BlockHeader = serde_json::from_str(MAINNET_HEADER_471746).unwrap();
+        header.version = BlockHeader::INITIAL_VERSION;
+        header.n_bits = 0x01010000;
+        let q = group_order();
+        let g = dlog_group::generator();
+        let pk: EcPoint = dlog_group::exponentiate(&g, &to_scalar(&BigUint::from(x)));
+        let w: EcPoint = dlog_group::exponentiate(&g, &to_scalar(&BigUint::from(y)));
+        header.autolykos_solution = AutolykosSolution {
+            miner_pk: pk.clone().into(),
+            pow_onetime_pk: w.clone().into(),
+            nonce: [7u8; 8],
+            pow_distance: BigUint::from(0u8),
+        };
+        // f = sum(hashModQ(index ++ M ++ pk ++ msg ++ w)) mod q as in the node's v1
+        // AutolykosPowScheme.genElement, computed here without `sum_of_elements_v1`
+        let pow = AutolykosPowScheme::default();
+        let msg = AutolykosPowScheme::msg_by_header(&header);
+        let seed = [&msg[..], &[7u8; 8][..]].concat();
+        let f = pow
+            .gen_indexes(&seed, pow.n_base())
+            .into_iter()
+            .map(|idx| {
+                let mut input = idx.to_be_bytes().to_vec();
+                input.extend((0u64..1024).flat_map(|i| i.to_be_bytes()));
+                input.extend(pk.sigma_serialize_bytes());
+                input.extend_from_slice(&msg);
+                input.extend(w.sigma_serialize_bytes());
+                let e = BigUint::from_bytes_be(blake2b256_hash(&input).0.as_ref());
+                // hashes not below q are re-hashed, which is negligibly rare
+                assert!(e < q);
+                e
+            })
+            .sum::<BigUint>()
+            % &q;
+        header.autolykos_solution.pow_distance =
+            (BigUint::from(y) * f + &q - BigUint::from(x)) % &q;
+        header.id = header.calc_id();
+        header
+    }
+
+    #[test]
+    fn hash_mod_q_full_hash() {
+        // the whole 32 bytes of the hash are used (the first byte is not dropped as in v2)
+        let input = [1u8, 2, 3];
+        let hash = BigUint::from_bytes_be(blake2b256_hash(&input).0.as_ref());
+        assert!(hash < group_order());
+        assert_eq!(hash_mod_q(&input), hash);
+        assert_eq!(
+            (BigUint::from(1u8) << 256) / group_order() * group_order(),
+            group_order()
+        );
+    }
+
+    #[test]
+    fn validate_v1() {
+        let header = solved_v1_header(123456789, 987654321);
+        let pow = AutolykosPowScheme::default();
+        assert_eq!(pow.validate(&header), Ok(()));
+        let parsed = BlockHeader::sigma_parse_bytes(&header.sigma_serialize_bytes()).unwrap();
+        assert_eq!(pow.validate(&parsed), Ok(()));
+
+        let mut wrong_d = header.clone();
+        wrong_d.autolykos_solution.pow_distance += 1u8;
+        assert_eq!(
+            pow.validate(&wrong_d),
+            Err(AutolykosPowSchemeError::InvalidSolutionPoints)
+        );
+
+        let mut wrong_msg = header.clone();
+        wrong_msg.timestamp += 1;
+        assert_eq!(
+            pow.validate(&wrong_msg),
+            Err(AutolykosPowSchemeError::InvalidSolutionPoints)
+        );
+
+        let mut identity_pk = header;
+        identity_pk.autolykos_solution.miner_pk = dlog_group::identity().into();
+        assert_eq!(
+            pow.validate(&identity_pk),
+            Err(AutolykosPowSchemeError::IdentityPublicKey)
+        );
+    }
+}
@@ -2,21 +2,35 @@
 
 use std::convert::TryFrom;
 use std::convert::TryInto;
+use std::io;
 
 use ergotree_ir::mir::header::PreHeader;
+use ergotree_ir::serialization::sigma_byte_reader::SigmaByteRead;
+use ergotree_ir::serialization::sigma_byte_writer::SigmaByteWrite;
+use ergotree_ir::serialization::sigma_byte_writer::SigmaByteWriter;
+use ergotree_ir::serialization::SerializationError;
+use ergotree_ir::serialization::SigmaSerializable;
 use ergotree_ir::sigma_protocol::dlog_group;
+use ergotree_ir::sigma_protocol::dlog_group::EcPoint;
+use num_bigint::BigInt;
+use num_bigint::BigUint;
 #[cfg(feature = "json")]
 use serde::{Deserialize, Serialize};
 
+use super::blake2b256_hash;
+use super::ADDigest;
 use super::Base16DecodedBytes;
 use super::Base16EncodedBytes;
 use super::Digest32;
 use thiserror::Error;
 
+#[cfg(feature = "json")]
+use super::json;
+
 /// Block id
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
-#[derive(PartialEq, Eq, Debug, Clone)]
-pub struct BlockId(Digest32);
+#[derive(PartialEq, Eq, Hash, Debug, Clone)]
+pub struct BlockId(pub Digest32);
 
 /// Votes for changing system parameters
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
@@ -67,26 +81,422 @@ impl From<Votes> for Vec<u8> {
     }
 }
 
+/// Solution of the Autolykos PoW puzzle
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "json",
+    serde(
+        into = "json::block_header::AutolykosSolutionJson",
+        try_from = "json::block_header::AutolykosSolutionJson"
+    )
+)]
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct AutolykosSolution {
+    /// Public key of the miner (`pk`)
+    pub miner_pk: Box<EcPoint>,
+    /// One-time public key (`w`). Used in Autolykos v1 only, the group generator for v2
+    pub pow_onetime_pk: Box<EcPoint>,
+    /// Nonce (`n`)
+    pub nonce: [u8; AutolykosSolution::NONCE_SIZE],
+    /// Distance between the pseudo-random number corresponding to the solution and the
+    /// origin (`d`). Used in Autolykos v1 only, zero for v2
+    pub pow_distance: BigUint,
+}
+
+impl AutolykosSolution {
+    /// Size of the nonce in bytes
+    pub const NONCE_SIZE: usize = 8;
+
+    /// Solution for Autolykos v2, where `w` and `d` are not used
+    pub fn new_v2(miner_pk: Box<EcPoint>, nonce: [u8; AutolykosSolution::NONCE_SIZE]) -> Self {
+        AutolykosSolution {
+            miner_pk,
+            pow_onetime_pk: dlog_group::generator().into(),
+            nonce,
+            pow_distance: BigUint::from(0u8),
+        }
+    }
+
+    /// Serialize the solution in the format of the given block version
+    /// (`w` and `d` are written for the version 1 only)
+    pub fn sigma_serialize_for_version<W: SigmaByteWrite>(
+        &self,
+        version: u8,
+        w: &mut W,
+    ) -> Result<(), io::Error> {
+        self.miner_pk.sigma_serialize(w)?;
+        if version == BlockHeader::INITIAL_VERSION {
+            self.pow_onetime_pk.sigma_serialize(w)?;
+            w.write_all(&self.nonce)?;
+            let d_bytes = self.pow_distance.to_bytes_be();
+            w.put_u8(d_bytes.len() as u8)?;
+            w.write_all(&d_bytes)?;
+        } else {
+            w.write_all(&self.nonce)?;
+        }
+        Ok(())
+    }
+
+    /// Parse the solution in the format of the given block version
+    pub fn sigma_parse_for_version<R: SigmaByteRead>(
+        version: u8,
+        r: &mut R,
+    ) -> Result<Self, SerializationError> {
+        let miner_pk = EcPoint::sigma_parse(r)?.into();
+        if version == BlockHeader::INITIAL_VERSION {
+            let pow_onetime_pk = EcPoint::sigma_parse(r)?.into();
+            let mut nonce = [0u8; AutolykosSolution::NONCE_SIZE];
+            r.read_exact(&mut nonce)?;
+            let d_len = r.get_u8()?;
+            let mut d_bytes = vec![0u8; d_len as usize];
+            r.read_exact(&mut d_bytes)?;
+            Ok(AutolykosSolution {
+                miner_pk,
+                pow_onetime_pk,
+                nonce,
+                pow_distance: BigUint::from_bytes_be(&d_bytes),
+            })
+        } else {
+            let mut nonce = [0u8; AutolykosSolution::NONCE_SIZE];
+            r.read_exact(&mut nonce)?;
+            Ok(AutolykosSolution::new_v2(miner_pk, nonce))
+        }
+    }
+}
+
 /// Block header
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "json",
+    serde(
+        into = "json::block_header::BlockHeaderJson",
+        try_from = "json::block_header::BlockHeaderJson"
+    )
+)]
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub struct BlockHeader {
     /// Block version, to be increased on every soft and hardfork
     pub version: u8,
+    /// Block id, blake2b256 hash of the serialized header (see [`BlockHeader::calc_id`])
+    pub id: BlockId,
     /// Id of a parent block
-    #[cfg_attr(feature = "json", serde(rename = "parentId"))]
     pub parent_id: BlockId,
+    /// Hash of ADProofs for transactions in a block
+    pub ad_proofs_root: Digest32,
+    /// AvlTree of a state after block application
+    pub state_root: ADDigest,
+    /// Root hash (for a Merkle tree) of transactions in a block
+    pub transaction_root: Digest32,
     /// Timestamp of a block in ms from UNIX epoch
     pub timestamp: u64,
-    /// Current difficulty in a compressed view.
-    #[cfg_attr(feature = "json", serde(rename = "nBits"))]
+    /// Current difficulty in a compressed view
     pub n_bits: u64,
     /// Block height
     pub height: u32,
+    /// Root hash of extension section
+    pub extension_root: Digest32,
+    /// Solution for the Autolykos PoW puzzle
+    pub autolykos_solution: AutolykosSolution,
     /// Votes
     pub votes: Votes,
 }
 
+impl BlockHeader {
+    /// Version of the blocks mined with Autolykos v1
+    pub const INITIAL_VERSION: u8 = 1;
+    /// Type id of the header block section
+    pub const HEADER_TYPE_ID: u8 = 101;
+    /// Type id of the block transactions section
+    pub const TRANSACTIONS_TYPE_ID: u8 = 102;
+    /// Type id of the ADProofs block section
+    pub const AD_PROOFS_TYPE_ID: u8 = 104;
+    /// Type id of the extension block section
+    pub const EXTENSION_TYPE_ID: u8 = 108;
+
+    /// Serialized header without the PoW solution (the message the PoW is computed for)
+    pub fn serialize_without_pow(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut w = SigmaByteWriter::new(&mut data, None);
+        #[allow(clippy::expect_used)]
+        self.sigma_serialize_without_pow(&mut w)
+            // writing to a Vec can't fail
+            .expect("serialization failed");
+        data
+    }
+
+    fn sigma_serialize_without_pow<W: SigmaByteWrite>(&self, w: &mut W) -> Result<(), io::Error> {
+        w.put_u8(self.version)?;
+        self.parent_id.0.sigma_serialize(w)?;
+        self.ad_proofs_root.sigma_serialize(w)?;
+        self.transaction_root.sigma_serialize(w)?;
+        self.state_root.sigma_serialize(w)?;
+        w.put_u64(self.timestamp)?;
+        self.extension_root.sigma_serialize(w)?;
+        // nBits is serialized as 4 bytes (big-endian)
+        w.write_all(&(self.n_bits as u32).to_be_bytes())?;
+        w.put_u32(self.height)?;
+        w.write_all(&self.votes.0)?;
+        if self.version > BlockHeader::INITIAL_VERSION {
+            // number of bytes of the fields added in the future versions (none so far)
+            w.put_u8(0)?;
+        }
+        Ok(())
+    }
+
+    /// Calculate the block id (blake2b256 hash of the serialized header)
+    pub fn calc_id(&self) -> BlockId {
+        BlockId(blake2b256_hash(&self.sigma_serialize_bytes()))
+    }
+
+    /// Id of the block section of the given type with the given root hash
+    pub fn section_id(&self, type_id: u8, root: &Digest32) -> Digest32 {
+        let mut bytes = Vec::with_capacity(1 + 2 * Digest32::SIZE);
+        bytes.push(type_id);
+        bytes.extend_from_slice(self.id.0 .0.as_ref());
+        bytes.extend_from_slice(root.0.as_ref());
+        blake2b256_hash(&bytes)
+    }
+
+    /// Id of the block transactions section
+    pub fn transactions_id(&self) -> Digest32 {
+        self.section_id(BlockHeader::TRANSACTIONS_TYPE_ID, &self.transaction_root)
+    }
+
+    /// Id of the ADProofs block section
+    pub fn ad_proofs_id(&self) -> Digest32 {
+        self.section_id(BlockHeader::AD_PROOFS_TYPE_ID, &self.ad_proofs_root)
+    }
+
+    /// Id of the extension block section
+    pub fn extension_id(&self) -> Digest32 {
+        self.section_id(BlockHeader::EXTENSION_TYPE_ID, &self.extension_root)
+    }
+
+    /// Difficulty decoded from `n_bits`
+    pub fn required_difficulty(&self) -> BigInt {
+        decode_compact_bits(self.n_bits)
+    }
+}
+
+/// Decode the difficulty from the compact ("nBits") form, the same as in Bitcoin:
+/// the highest byte is the size in bytes, the next three bytes are the most significant
+/// bytes of the value with the sign in the highest bit
+pub fn decode_compact_bits(n_bits: u64) -> BigInt {
+    let size = ((n_bits >> 24) & 0xff) as usize;
+    let mut bytes = vec![0u8; size];
+    if size >= 1 {
+        bytes[0] = ((n_bits >> 16) & 0xff) as u8;
+    }
+    if size >= 2 {
+        bytes[1] = ((n_bits >> 8) & 0xff) as u8;
+    }
+    if size >= 3 {
+        bytes[2] = (n_bits & 0xff) as u8;
+    }
+    let is_negative = size >= 1 && bytes[0] & 0x80 != 0;
+    if is_negative {
+        bytes[0] &= 0x7f;
+    }
+    let value = BigInt::from_bytes_be(num_bigint::Sign::Plus, &bytes);
+    if is_negative {
+        -value
+    } else {
+        value
+    }
+}
+
+/// Encode the difficulty into the compact ("nBits") form (see [`decode_compact_bits`]),
+/// only the three most significant bytes of the value are kept
+pub fn encode_compact_bits(difficulty: &BigInt) -> u64 {
+    let magnitude = difficulty.magnitude();
+    // the size of the minimal big-endian representation with the sign bit
+    let mut size = magnitude.bits() / 8 + 1;
+    let mantissa = if size <= 3 {
+        magnitude << (8 * (3 - size))
+    } else {
+        magnitude >> (8 * (size - 3))
+    };
+    let mut result = mantissa.to_u64_digits().first().copied().unwrap_or(0);
+    // the 0x00800000 bit is the sign, so move the mantissa down a byte if it's set
+    if result & 0x0080_0000 != 0 {
+        result >>= 8;
+        size += 1;
+    }
+    result |= size << 24;
+    if difficulty.sign() == num_bigint::Sign::Minus {
+        result |= 0x0080_0000;
+    }
+    result
+}
+
+impl SigmaSerializable for BlockHeader {
+    fn sigma_serialize<W: SigmaByteWrite>(&self, w: &mut W) -> Result<(), io::Error> {
+        self.sigma_serialize_without_pow(w)?;
+        self.autolykos_solution
+            .sigma_serialize_for_version(self.version, w)
+    }
+
+    fn sigma_parse<R: SigmaByteRead>(r: &mut R) -> Result<Self, SerializationError> {
+        let version = r.get_u8()?;
+        let parent_id = BlockId(Digest32::sigma_parse(r)?);
+        let ad_proofs_root = Digest32::sigma_parse(r)?;
+        let transaction_root = Digest32::sigma_parse(r)?;
+        let state_root = ADDigest::sigma_parse(r)?;
+        let timestamp = r.get_u64()?;
+        let extension_root = Digest32::sigma_parse(r)?;
+        let mut n_bits_bytes = [0u8; 4];
+        r.read_exact(&mut n_bits_bytes)?;
+        let n_bits = u32::from_be_bytes(n_bits_bytes) as u64;
+        let height = r.get_u32()?;
+        let mut votes = [0u8; 3];
+        r.read_exact(&mut votes)?;
+        if version > BlockHeader::INITIAL_VERSION {
+            let new_fields_size = r.get_u8()?;
+            if new_fields_size > 0 {
+                return Err(SerializationError::NotImplementedYet(format!(
+                    "block header v{} with {} bytes of the new fields",
+                    version, new_fields_size
+                )));
+            }
+        }
+        let autolykos_solution = AutolykosSolution::sigma_parse_for_version(version, r)?;
+        let mut header = BlockHeader {
+            version,
+            id: BlockId(Digest32::zero()),
+            parent_id,
+            ad_proofs_root,
+            state_root,
+            transaction_root,
+            timestamp,
+            n_bits,
+            height,
+            extension_root,
+            autolykos_solution,
+            votes: Votes(votes),
+        };
+        header.id = header.calc_id();
+        Ok(header)
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<BlockHeader> for json::block_header::BlockHeaderJson {
+    fn from(v: BlockHeader) -> Self {
+        json::block_header::BlockHeaderJson {
+            difficulty: Some(v.required_difficulty().to_string()),
+            // the node reports the size of the stored header, prefixed with the section type id
+            size: Some(v.sigma_serialize_bytes().len() + 1),
+            extension_id: Some(v.extension_id()),
+            transactions_id: Some(v.transactions_id()),
+            ad_proofs_id: Some(v.ad_proofs_id()),
+            id: v.id,
+            version: v.version,
+            parent_id: v.parent_id,
+            ad_proofs_root: v.ad_proofs_root,
+            state_root: v.state_root,
+            transaction_root: v.transaction_root,
+            timestamp: v.timestamp,
+            n_bits: v.n_bits,
+            height: v.height,
+            extension_root: v.extension_root,
+            autolykos_solution: v.autolykos_solution.into(),
+            votes: v.votes,
+        }
+    }
+}
+
+/// Errors on parsing BlockHeader from JSON
+#[cfg(feature = "json")]
+#[derive(Error, PartialEq, Eq, Debug, Clone)]
+pub enum BlockHeaderFromJsonError {
+    /// Block id parsed from JSON differs from calculated from serialized bytes
+    #[error("Block id parsed from JSON differs from calculated from serialized bytes")]
+    InvalidBlockId,
+    /// Invalid PoW solution
+    #[error("Invalid PoW solution: {0}")]
+    InvalidSolution(#[from] AutolykosSolutionFromJsonError),
+}
+
+#[cfg(feature = "json")]
+impl TryFrom<json::block_header::BlockHeaderJson> for BlockHeader {
+    type Error = BlockHeaderFromJsonError;
+    fn try_from(h: json::block_header::BlockHeaderJson) -> Result<Self, Self::Error> {
+        let mut header = BlockHeader {
+            version: h.version,
+            id: BlockId(Digest32::zero()),
+            parent_id: h.parent_id,
+            ad_proofs_root: h.ad_proofs_root,
+            state_root: h.state_root,
+            transaction_root: h.transaction_root,
+            timestamp: h.timestamp,
+            n_bits: h.n_bits,
+            height: h.height,
+            extension_root: h.extension_root,
+            autolykos_solution: h.autolykos_solution.try_into()?,
+            votes: h.votes,
+        };
+        header.id = header.calc_id();
+        if header.id == h.id {
+            Ok(header)
+        } else {
+            Err(BlockHeaderFromJsonError::InvalidBlockId)
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<AutolykosSolution> for json::block_header::AutolykosSolutionJson {
+    #[allow(clippy::unwrap_used)]
+    fn from(v: AutolykosSolution) -> Self {
+        json::block_header::AutolykosSolutionJson {
+            miner_pk: *v.miner_pk,
+            pow_onetime_pk: Some(*v.pow_onetime_pk),
+            nonce: Base16DecodedBytes(v.nonce.to_vec()),
+            // decimal digits are a valid JSON number
+            pow_distance: Some(
+                serde_json::value::RawValue::from_string(v.pow_distance.to_string()).unwrap(),
+            ),
+        }
+    }
+}
+
+/// Errors on parsing AutolykosSolution from JSON
+#[cfg(feature = "json")]
+#[derive(Error, PartialEq, Eq, Debug, Clone)]
+pub enum AutolykosSolutionFromJsonError {
+    /// Nonce has invalid size
+    #[error("Invalid nonce size: {0}")]
+    InvalidNonceSize(usize),
+    /// `d` is not a non-negative integer
+    #[error("Invalid distance (d): {0}")]
+    InvalidDistance(String),
+}
+
+#[cfg(feature = "json")]
+impl TryFrom<json::block_header::AutolykosSolutionJson> for AutolykosSolution {
+    type Error = AutolykosSolutionFromJsonError;
+    fn try_from(s: json::block_header::AutolykosSolutionJson) -> Result<Self, Self::Error> {
+        let nonce_len = s.nonce.0.len();
+        let nonce = s
+            .nonce
+            .0
+            .try_into()
+            .map_err(|_| AutolykosSolutionFromJsonError::InvalidNonceSize(nonce_len))?;
+        let mut solution = AutolykosSolution::new_v2(s.miner_pk.into(), nonce);
+        if let Some(w) = s.pow_onetime_pk {
+            solution.pow_onetime_pk = w.into();
+        }
+        if let Some(d) = s.pow_distance {
+            // either a JSON number or a string with the decimal digits
+            let d_str = d.get().trim().trim_matches('"');
+            solution.pow_distance = d_str
+                .parse::<BigUint>()
+                .map_err(|_| AutolykosSolutionFromJsonError::InvalidDistance(d_str.to_string()))?;
+        }
+        Ok(solution)
+    }
+}
+
 impl From<BlockHeader> for PreHeader {
     fn from(bh: BlockHeader) -> Self {
         PreHeader {
@@ -95,8 +505,206 @@ impl From<BlockHeader> for PreHeader {
             timestamp: bh.timestamp,
             n_bits: bh.n_bits,
             height: bh.height,
-            miner_pk: dlog_group::identity().into(), // TODO: get from bh.powSolution when its implemented
+            miner_pk: bh.autolykos_solution.miner_pk,
             votes: bh.votes.into(),
         }
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+pub(crate) mod tests {
+    use super::*;
+    use ergotree_ir::serialization::sigma_serialize_roundtrip;
+    use ergotree_ir::sigma_protocol::dlog_group;
+    use num_bigint::BigInt;
+    use proptest::prelude::*;
+
+    /// Mainnet block 4caa17e62fe66ba7bd69597afdc996ae35b1ff12e0ba90c22ff288a4de10e91b header as
+    /// returned by the node's `/blocks/{id}/header`
+    pub(crate) const MAINNET_HEADER_471746: &str = r#"{
+        "extensionId": "d16f25b14457186df4c5f6355579cc769261ce1aebc8209949ca6feadbac5a3f",
+        "difficulty": "626412390187008",
+        "votes": "040000",
+        "timestamp": 1618929697400,
+        "size": 221,
+        "stateRoot": "8ad868627ea4f7de6e2a2fe3f98fafe57f914e0f2ef3331c006def36c697f92713",
+        "height": 471746,
+        "nBits": 117586360,
+        "version": 2,
+        "id": "4caa17e62fe66ba7bd69597afdc996ae35b1ff12e0ba90c22ff288a4de10e91b",
+        "adProofsRoot": "d882aaf42e0a95eb95fcce5c3705adf758e591532f733efe790ac3c404730c39",
+        "transactionsRoot": "63eaa9aff76a1de3d71c81e4b2d92e8d97ae572a8e9ab9e66599ed0912dd2f8b",
+        "extensionHash": "3f91f3c680beb26615fdec251aee3f81aaf5a02740806c167c0f3c929471df44",
+        "powSolutions": {
+          "pk": "02b3a06d6eaa8671431ba1db4dd427a77f75a5c2acbd71bfb725d38adc2b55f669",
+          "w": "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+          "n": "5939ecfee6b0d7f4",
+          "d": 0
+        },
+        "adProofsId": "86eaa41f328bee598e33e52c9e515952ad3b7874102f762847f17318a776a7ae",
+        "transactionsId": "ac80245714f25aa2fafe5494ad02a26d46e7955b8f5709f3659f1b9440797b3e",
+        "parentId": "6481752bace5fa5acba5d5ef7124d48826664742d46c974c98a2d60ace229a34"
+    }"#;
+
+    /// First header (height 1) of a test chain with the difficulty 1, where any v2 PoW
+    /// solution is valid
+    pub(crate) fn difficulty_1_genesis() -> BlockHeader {
+        let mut header: BlockHeader = sigma_test_util::force_any_val();
+        header.version = 2;
+        header.height = 1;
+        header.timestamp = 1_000_000;
+        header.n_bits = encode_compact_bits(&BigInt::from(1));
+        header.autolykos_solution.pow_onetime_pk = dlog_group::generator().into();
+        header.autolykos_solution.pow_distance = 0u8.into();
+        header.extension_root = Digest32::zero();
+        header.id = header.calc_id();
+        header
+    }
+
+    /// Next header of the test chain (see [`difficulty_1_genesis`]) mined `interval_ms` after
+    /// the parent
+    pub(crate) fn difficulty_1_child(parent: &BlockHeader, interval_ms: u64) -> BlockHeader {
+        let mut header = parent.clone();
+        header.parent_id = parent.id.clone();
+        header.height = parent.height + 1;
+        header.timestamp = parent.timestamp + interval_ms;
+        header.id = header.calc_id();
+        header
+    }
+
+    impl Arbitrary for AutolykosSolution {
+        type Parameters = u8;
+        type Strategy = BoxedStrategy<Self>;
+
+        fn arbitrary_with(version: Self::Parameters) -> Self::Strategy {
+            if version == BlockHeader::INITIAL_VERSION {
+                (
+                    any::<EcPoint>(),
+                    any::<EcPoint>(),
+                    any::<[u8; 8]>(),
+                    proptest::collection::vec(any::<u8>(), 0..32),
+                )
+                    .prop_map(|(pk, w, nonce, d)| AutolykosSolution {
+                        miner_pk: pk.into(),
+                        pow_onetime_pk: w.into(),
+                        nonce,
+                        pow_distance: BigUint::from_bytes_be(&d),
+                    })
+                    .boxed()
+            } else {
+                (any::<EcPoint>(), any::<[u8; 8]>())
+                    .prop_map(|(pk, nonce)| AutolykosSolution::new_v2(pk.into(), nonce))
+                    .boxed()
+            }
+        }
+    }
+
+    impl Arbitrary for BlockHeader {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Self>;
+
+        fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+            (1u8..=2)
+                .prop_flat_map(|version| {
+                    (
+                        Just(version),
+                        any::<Digest32>(),
+                        any::<Digest32>(),
+                        any::<ADDigest>(),
+                        any::<Digest32>(),
+                        any::<u64>(),
+                        any::<u32>(),
+                        any::<u32>(),
+                        any::<Digest32>(),
+                        any_with::<AutolykosSolution>(version),
+                        any::<[u8; 3]>(),
+                    )
+                })
+                .prop_map(
+                    |(
+                        version,
+                        parent_id,
+                        ad_proofs_root,
+                        state_root,
+                        transaction_root,
+                        timestamp,
+                        n_bits,
+                        height,
+                        extension_root,
+                        autolykos_solution,
+                        votes,
+                    )| {
+                        let mut header = BlockHeader {
+                            version,
+                            id: BlockId(Digest32::zero()),
+                            parent_id: BlockId(parent_id),
+                            ad_proofs_root,
+                            state_root,
+                            transaction_root,
+                            timestamp,
+                            n_bits: n_bits as u64,
+                            height,
+                            extension_root,
+                            autolykos_solution,
+                            votes: Votes(votes),
+                        };
+                        header.id = header.calc_id();
+                        header
+                    },
+                )
+                .boxed()
+        }
+    }
+
+    proptest! {
+
+        #[test]
+        fn ser_roundtrip(v in any::<BlockHeader>()) {
+            prop_assert_eq![sigma_serialize_roundtrip(&v), v];
+        }
+    }
+
+    #[test]
+    fn decode_compact_bits_node_vectors() {
+        assert_eq!(
+            decode_compact_bits(117586360),
+            BigInt::from(626412390187008u64)
+        );
+        assert_eq!(decode_compact_bits(0x01003456), BigInt::from(0));
+        assert_eq!(decode_compact_bits(0x01123456), BigInt::from(0x12));
+        assert_eq!(
+            decode_compact_bits(0x04923456),
+            BigInt::from(-0x12345600i64)
+        );
+        assert_eq!(
+            decode_compact_bits(0x05123456),
+            BigInt::from(0x1234560000i64)
+        );
+    }
+
+    #[test]
+    fn encode_compact_bits_node_vectors() {
+        assert_eq!(
+            encode_compact_bits(&BigInt::from(626412390187008u64)),
+            117586360
+        );
+        assert_eq!(encode_compact_bits(&BigInt::from(0)), 0x01000000);
+        assert_eq!(encode_compact_bits(&BigInt::from(1)), 0x01010000);
+        assert_eq!(encode_compact_bits(&BigInt::from(0x12)), 0x01120000);
+        assert_eq!(encode_compact_bits(&BigInt::from(0x80)), 0x02008000);
+        assert_eq!(
+            encode_compact_bits(&BigInt::from(0x1234560000i64)),
+            0x05123456
+        );
+        assert_eq!(
+            encode_compact_bits(&BigInt::from(-0x12345600i64)),
+            0x04923456
+        );
+        // precision is limited to the three most significant bytes
+        assert_eq!(
+            decode_compact_bits(encode_compact_bits(&BigInt::from(0x1234567890u64))),
+            BigInt::from(0x1234560000u64)
+        );
+    }
+}
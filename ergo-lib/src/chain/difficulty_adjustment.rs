@@ -0,0 +1,409 @@
+//! Difficulty retargeting: linear least squares over the last epochs and its EIP-37 variant
+//! (see <https://github.com/ergoplatform/eips/blob/master/eip-0037.md>)
+
+use num_bigint::BigInt;
+use thiserror::Error;
+
+use super::block_header::decode_compact_bits;
+use super::block_header::encode_compact_bits;
+use super::block_header::BlockHeader;
+
+/// Difficulty calculation errors
+#[derive(Error, PartialEq, Eq, Debug, Clone)]
+pub enum DifficultyAdjustmentError {
+    /// Epoch end header timestamp is not greater than the epoch start header timestamp
+    #[error("Epoch ending at height {end_height} has non-positive duration ({duration} ms)")]
+    NonPositiveEpochDuration {
+        /// Height of the header at the end of the epoch
+        end_height: u32,
+        /// Difference of the epoch end and start header timestamps
+        duration: i128,
+    },
+}
+
+/// Difficulty adjustment settings of the network
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct DifficultySettings {
+    /// Number of blocks in the difficulty epoch
+    pub epoch_length: u32,
+    /// Desired interval between blocks in ms
+    pub block_interval_ms: u64,
+    /// Number of the last epochs the difficulty is calculated from
+    pub use_last_epochs: u32,
+    /// Difficulty used when the calculated one is less than 1
+    pub initial_difficulty: BigInt,
+    /// Height from which the EIP-37 retargeting is used (`None` if it's not activated)
+    pub eip37_activation_height: Option<u32>,
+    /// Number of blocks in the difficulty epoch after the EIP-37 activation
+    pub eip37_epoch_length: u32,
+}
+
+impl DifficultySettings {
+    /// Mainnet settings
+    pub fn mainnet() -> Self {
+        DifficultySettings {
+            epoch_length: 1024,
+            block_interval_ms: 120_000,
+            use_last_epochs: 8,
+            initial_difficulty: BigInt::from(0x0117_6500_0000u64),
+            eip37_activation_height: Some(844_673),
+            eip37_epoch_length: 128,
+        }
+    }
+}
+
+/// Difficulty calculator
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct DifficultyAdjustment {
+    settings: DifficultySettings,
+}
+
+impl DifficultyAdjustment {
+    const PRECISION: u64 = 1_000_000_000;
+
+    /// Create a calculator for the given settings
+    pub fn new(settings: DifficultySettings) -> Self {
+        DifficultyAdjustment { settings }
+    }
+
+    /// Difficulty settings
+    pub fn settings(&self) -> &DifficultySettings {
+        &self.settings
+    }
+
+    /// Whether the EIP-37 retargeting is used for the block at the given height
+    pub fn is_eip37_active(&self, height: u32) -> bool {
+        self.settings
+            .eip37_activation_height
+            .map(|h| height >= h)
+            .unwrap_or(false)
+    }
+
+    /// Length of the difficulty epoch for the block at the given height
+    pub fn epoch_length(&self, height: u32) -> u32 {
+        if self.is_eip37_active(height) {
+            self.settings.eip37_epoch_length
+        } else {
+            self.settings.epoch_length
+        }
+    }
+
+    /// Heights of the previous headers needed to calculate the difficulty of the block at the
+    /// given height (ascending). Only the parent is needed if it's not the end of an epoch.
+    pub fn previous_heights_required_for_recalculation(
+        &self,
+        height: u32,
+        epoch_length: u32,
+    ) -> Vec<u32> {
+        let parent_height = height.saturating_sub(1);
+        if epoch_length > 1 && parent_height.checked_rem(epoch_length) == Some(0) {
+            let mut heights: Vec<u32> = (0..=self.settings.use_last_epochs)
+                .filter_map(|i| parent_height.checked_sub(i * epoch_length))
+                .collect();
+            heights.reverse();
+            heights
+        } else {
+            vec![parent_height]
+        }
+    }
+
+    /// Required difficulty of the block after the `parent`, where `previous_headers` are the
+    /// headers at [`Self::previous_heights_required_for_recalculation`] (the parent is the last
+    /// one)
+    pub fn required_difficulty_after(
+        &self,
+        parent: &BlockHeader,
+        previous_headers: &[BlockHeader],
+    ) -> Result<BigInt, DifficultyAdjustmentError> {
+        let height = parent.height + 1;
+        let epoch_length = self.epoch_length(height);
+        if previous_headers.len() < 2 {
+            Ok(parent.required_difficulty())
+        } else if self.is_eip37_active(height) {
+            self.eip37_calculate(previous_headers, epoch_length)
+        } else {
+            self.calculate(previous_headers, epoch_length)
+        }
+    }
+
+    /// Difficulty predicted with the linear least squares over the given epoch boundary
+    /// headers (ascending by height, `epoch_length` apart)
+    pub fn calculate(
+        &self,
+        previous_headers: &[BlockHeader],
+        epoch_length: u32,
+    ) -> Result<BigInt, DifficultyAdjustmentError> {
+        let uncompressed_diff = match (previous_headers.first(), previous_headers.last()) {
+            (Some(first), Some(last))
+                if previous_headers.len() > 1 && first.timestamp < last.timestamp =>
+            {
+                let data: Vec<(u32, BigInt)> = previous_headers
+                    .windows(2)
+                    .map(|w| {
+                        let (start, end) = (&w[0], &w[1]);
+                        self.bitcoin_calculate(start, end, epoch_length)
+                            .map(|diff| (end.height, diff))
+                    })
+                    .collect::<Result<_, _>>()?;
+                let diff = interpolate(&data, epoch_length);
+                if diff >= BigInt::from(1) {
+                    diff
+                } else {
+                    self.settings.initial_difficulty.clone()
+                }
+            }
+            (Some(first), _) => first.required_difficulty(),
+            (None, _) => self.settings.initial_difficulty.clone(),
+        };
+        Ok(normalize(&uncompressed_diff))
+    }
+
+    /// Difficulty according to EIP-37: the average of the predicted (limited to the 50% change)
+    /// and the Bitcoin-like difficulty, limited to the 50% change of the last difficulty
+    pub fn eip37_calculate(
+        &self,
+        previous_headers: &[BlockHeader],
+        epoch_length: u32,
+    ) -> Result<BigInt, DifficultyAdjustmentError> {
+        let (start, last) = match previous_headers {
+            [.., start, last] => (start, last),
+            [last] => return Ok(last.required_difficulty()),
+            [] => return Ok(self.settings.initial_difficulty.clone()),
+        };
+        let last_diff = last.required_difficulty();
+        let predictive_diff = self.calculate(previous_headers, epoch_length)?;
+        let limited_predictive_diff = limit_change(predictive_diff, &last_diff);
+        let classic_diff = self.bitcoin_calculate(start, last, epoch_length)?;
+        let avg = (classic_diff + limited_predictive_diff) / 2;
+        Ok(normalize(&limit_change(avg, &last_diff)))
+    }
+
+    /// Difficulty of the epoch ending with `end` scaled by the ratio of the desired and actual
+    /// epoch duration (as in Bitcoin), fails if the epoch duration is not positive
+    pub fn bitcoin_calculate(
+        &self,
+        start: &BlockHeader,
+        end: &BlockHeader,
+        epoch_length: u32,
+    ) -> Result<BigInt, DifficultyAdjustmentError> {
+        let duration = end.timestamp as i128 - start.timestamp as i128;
+        if duration <= 0 {
+            return Err(DifficultyAdjustmentError::NonPositiveEpochDuration {
+                end_height: end.height,
+                duration,
+            });
+        }
+        Ok(
+            end.required_difficulty() * self.settings.block_interval_ms * epoch_length
+                / BigInt::from(duration),
+        )
+    }
+}
+
+/// Limit the change of the difficulty to 50% of the last difficulty
+fn limit_change(diff: BigInt, last_diff: &BigInt) -> BigInt {
+    if &diff > last_diff {
+        diff.min(last_diff * 3 / 2)
+    } else {
+        diff.max(last_diff / 2)
+    }
+}
+
+/// Drop the precision lost in the compact (`n_bits`) form
+fn normalize(diff: &BigInt) -> BigInt {
+    decode_compact_bits(encode_compact_bits(diff))
+}
+
+/// Linear least squares fit `y = a + bx` of the (height, difficulty) points extrapolated to
+/// the next epoch
+fn interpolate(data: &[(u32, BigInt)], epoch_length: u32) -> BigInt {
+    match data {
+        [] => BigInt::from(0),
+        [(_, diff)] => diff.clone(),
+        _ => {
+            let precision = BigInt::from(DifficultyAdjustment::PRECISION);
+            let size = BigInt::from(data.len());
+            let x_sum: BigInt = data.iter().map(|(h, _)| BigInt::from(*h)).sum();
+            let y_sum: BigInt = data.iter().map(|(_, d)| d.clone()).sum();
+            let xy_sum: BigInt = data.iter().map(|(h, d)| d * *h).sum();
+            let x2_sum: BigInt = data
+                .iter()
+                .map(|(h, _)| BigInt::from(*h) * BigInt::from(*h))
+                .sum();
+            let denominator = &x2_sum * &size - &x_sum * &x_sum;
+            if denominator == BigInt::from(0) {
+                // all points at the same height
+                return y_sum / size;
+            }
+            let b = (&xy_sum * &size - &x_sum * &y_sum) * &precision / denominator;
+            let a = (&y_sum * &precision - &b * &x_sum) / &size / &precision;
+            let point = data.iter().map(|(h, _)| *h).max().unwrap_or(0) + epoch_length;
+            a + b * point / precision
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(height: u32, timestamp: u64, difficulty: u64) -> BlockHeader {
+        let mut h: BlockHeader = sigma_test_util::force_any_val();
+        h.height = height;
+        h.timestamp = timestamp;
+        h.n_bits = encode_compact_bits(&BigInt::from(difficulty));
+        h
+    }
+
+    fn settings(epoch_length: u32) -> DifficultySettings {
+        DifficultySettings {
+            epoch_length,
+            block_interval_ms: 120_000,
+            use_last_epochs: 8,
+            initial_difficulty: BigInt::from(1),
+            eip37_activation_height: None,
+            eip37_epoch_length: 128,
+        }
+    }
+
+    #[test]
+    fn previous_heights() {
+        let da = DifficultyAdjustment::new(settings(1024));
+        assert_eq!(
+            da.previous_heights_required_for_recalculation(1025, 1024),
+            vec![0, 1024]
+        );
+        assert_eq!(
+            da.previous_heights_required_for_recalculation(1026, 1024),
+            vec![1025]
+        );
+        assert_eq!(
+            da.previous_heights_required_for_recalculation(10 * 1024 + 1, 1024),
+            (2..=10).map(|i| i * 1024).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            da.previous_heights_required_for_recalculation(844_673, 1024),
+            vec![844_672]
+        );
+        assert_eq!(
+            da.previous_heights_required_for_recalculation(6599 * 128 + 1, 128),
+            (6591..=6599).map(|i| i * 128).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn interpolate_linear() {
+        let data = vec![(1024, BigInt::from(100)), (2048, BigInt::from(200))];
+        assert_eq!(interpolate(&data, 1024), BigInt::from(300));
+        let data = vec![(1024, BigInt::from(100)); 3];
+        assert_eq!(interpolate(&data[..1], 1024), BigInt::from(100));
+    }
+
+    #[test]
+    fn calculate_stable_and_changing_rate() {
+        let epoch = 128;
+        let da = DifficultyAdjustment::new(settings(epoch));
+        let epoch_ms = 120_000 * epoch as u64;
+        let stable: Vec<BlockHeader> = (0..=8)
+            .map(|i| header(i * epoch, i as u64 * epoch_ms, 1_000_000))
+            .collect();
+        assert_eq!(da.calculate(&stable, epoch), Ok(BigInt::from(1_000_000)));
+        // twice as fast blocks in every epoch
+        let fast: Vec<BlockHeader> = (0..=8)
+            .map(|i| header(i * epoch, i as u64 * epoch_ms / 2, 1_000_000))
+            .collect();
+        assert_eq!(da.calculate(&fast, epoch), Ok(BigInt::from(2_000_000)));
+        // the only header or no time passed
+        assert_eq!(
+            da.calculate(&stable[..1], epoch),
+            Ok(stable[0].required_difficulty())
+        );
+        let same_time: Vec<BlockHeader> =
+            (0..=1).map(|i| header(i * epoch, 0, 1_000_000)).collect();
+        assert_eq!(da.calculate(&same_time, epoch), Ok(BigInt::from(1_000_000)));
+    }
+
+    #[test]
+    fn eip37_limits_the_change() {
+        let epoch = 128;
+        let da = DifficultyAdjustment::new(settings(epoch));
+        let epoch_ms = 120_000 * epoch as u64;
+        // 10 times faster blocks: both the predicted and the Bitcoin-like difficulty are 10x
+        let fast = vec![
+            header(epoch, 0, 1_000_000),
+            header(2 * epoch, epoch_ms / 10, 1_000_000),
+        ];
+        assert_eq!(
+            da.eip37_calculate(&fast, epoch),
+            Ok(BigInt::from(1_500_000))
+        );
+        // 10 times slower blocks
+        let slow = vec![
+            header(epoch, 0, 1_000_000),
+            header(2 * epoch, epoch_ms * 10, 1_000_000),
+        ];
+        assert_eq!(da.eip37_calculate(&slow, epoch), Ok(BigInt::from(500_000)));
+        // 20% faster blocks: (1.2 + 1.2) / 2
+        let faster = vec![
+            header(epoch, 0, 1_000_000),
+            header(2 * epoch, epoch_ms * 5 / 6, 1_000_000),
+        ];
+        assert_eq!(
+            da.eip37_calculate(&faster, epoch),
+            Ok(BigInt::from(1_200_000))
+        );
+    }
+
+    #[test]
+    fn required_difficulty_after_uses_eip37_after_activation() {
+        let mut s = settings(1024);
+        s.eip37_activation_height = Some(2 * 128 + 1);
+        let da = DifficultyAdjustment::new(s);
+        assert_eq!(da.epoch_length(2 * 128), 1024);
+        assert_eq!(da.epoch_length(2 * 128 + 1), 128);
+        let epoch_ms = 120_000 * 128;
+        let headers = vec![
+            header(128, 0, 1_000_000),
+            header(2 * 128, epoch_ms / 10, 1_000_000),
+        ];
+        assert_eq!(
+            da.required_difficulty_after(&headers[1], &headers),
+            Ok(BigInt::from(1_500_000))
+        );
+        // not an epoch boundary
+        assert_eq!(
+            da.required_difficulty_after(&headers[1], &headers[1..]),
+            Ok(BigInt::from(1_000_000))
+        );
+    }
+
+    #[test]
+    fn non_positive_epoch_duration() {
+        let epoch = 128;
+        let da = DifficultyAdjustment::new(settings(epoch));
+        let start = header(epoch, 1_000, 1_000_000);
+        for timestamp in [1_000, 999].iter() {
+            let end = header(2 * epoch, *timestamp, 1_000_000);
+            assert_eq!(
+                da.bitcoin_calculate(&start, &end, epoch),
+                Err(DifficultyAdjustmentError::NonPositiveEpochDuration {
+                    end_height: 2 * epoch,
+                    duration: *timestamp as i128 - 1_000
+                })
+            );
+        }
+        // the last epoch has no duration while the first one has
+        let headers = vec![
+            header(epoch, 0, 1_000_000),
+            header(2 * epoch, 1_000, 1_000_000),
+            header(3 * epoch, 1_000, 1_000_000),
+        ];
+        assert_eq!(
+            da.eip37_calculate(&headers, epoch),
+            Err(DifficultyAdjustmentError::NonPositiveEpochDuration {
+                end_height: 3 * epoch,
+                duration: 0
+            })
+        );
+    }
+}
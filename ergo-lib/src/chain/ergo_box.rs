@@ -198,7 +198,7 @@ impl IrErgoBox for ErgoBox {
     }
 
     fn script_bytes(&self) -> Vec<i8> {
-        self.sigma_serialize_bytes().as_vec_i8()
+        self.ergo_tree.sigma_serialize_bytes().as_vec_i8()
     }
 
     /// Tuple of height when block got included into the blockchain and transaction identifier with
@@ -632,6 +632,15 @@ mod tests {
         );
     }
 
+    #[test]
+    fn script_bytes() {
+        let b = force_any_val::<ErgoBox>();
+        assert_eq!(
+            b.script_bytes(),
+            b.ergo_tree.sigma_serialize_bytes().as_vec_i8()
+        );
+    }
+
     #[test]
     fn creation_info() {
         let b = force_any_val::<ErgoBox>();
@@ -1,7 +1,7 @@
 //! ErgoBoxCandidate builder
 
 use std::collections::HashMap;
-use std::convert::{TryFrom, TryInto};
+use std::convert::TryFrom;
 
 use crate::chain::token::Token;
 
@@ -105,10 +105,13 @@ impl ErgoBoxCandidateBuilder {
     /// Calculate minimal box value for the current box serialized size(in bytes)
     pub fn calc_min_box_value(&self) -> Result<BoxValue, ErgoBoxCandidateBuilderError> {
         let box_size_bytes = self.calc_box_size_bytes()?;
-        Ok(
-            BoxValue::try_from(box_size_bytes as i64 * BoxValue::MIN_VALUE_PER_BOX_BYTE as i64)
-                .unwrap(),
-        )
+        Ok(self.min_box_value(box_size_bytes))
+    }
+
+    fn min_box_value(&self, box_size_bytes: usize) -> BoxValue {
+        // box value cannot be lower than the absolute minimum even with a lower value per byte
+        let raw = (box_size_bytes as u64 * self.min_value_per_byte as u64).max(BoxValue::MIN_RAW);
+        BoxValue::try_from(raw).unwrap()
     }
 
     /// Set register with a given id (R4-R9) to the given value
@@ -218,9 +221,7 @@ impl ErgoBoxCandidateBuilder {
             creation_height: self.creation_height,
         };
         let box_size_bytes = b.sigma_serialize_bytes().len();
-        let min_box_value: BoxValue = (box_size_bytes as i64 * self.min_value_per_byte as i64)
-            .try_into()
-            .unwrap();
+        let min_box_value = self.min_box_value(box_size_bytes);
         if self.value >= min_box_value {
             Ok(b)
         } else {
@@ -240,6 +241,8 @@ impl ErgoBoxCandidateBuilder {
 #[cfg(test)]
 mod tests {
 
+    use std::convert::TryInto;
+
     use sigma_test_util::force_any_val;
     use NonMandatoryRegisterId::*;
 
@@ -297,6 +300,21 @@ mod tests {
         assert!(builder.calc_min_box_value().unwrap() > BoxValue::MIN);
     }
 
+    #[test]
+    fn test_calc_min_box_value_custom_per_byte() {
+        let mut builder =
+            ErgoBoxCandidateBuilder::new(BoxValue::SAFE_USER_MIN, force_any_val::<ErgoTree>(), 1);
+        let box_size_bytes = builder.calc_box_size_bytes().unwrap() as u64;
+        builder.set_min_box_value_per_byte(1000);
+        assert_eq!(
+            *builder.calc_min_box_value().unwrap().as_u64(),
+            (box_size_bytes * 1000).max(BoxValue::MIN_RAW)
+        );
+        builder.set_min_box_value_per_byte(1);
+        assert_eq!(builder.calc_min_box_value().unwrap(), BoxValue::MIN);
+        assert!(builder.build().is_ok());
+    }
+
     #[test]
     fn test_build_fail_box_value_too_low() {
         let builder = ErgoBoxCandidateBuilder::new(BoxValue::MIN, force_any_val::<ErgoTree>(), 1);
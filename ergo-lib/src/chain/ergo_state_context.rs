@@ -1,19 +1,32 @@
 //! Blockchain state
 use ergotree_ir::mir::header::PreHeader;
 
+use super::parameters::Parameters;
+
 /// Blockchain state (last headers, etc.)
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub struct ErgoStateContext {
     /// Block header with the current `spendingTransaction`, that can be predicted
     /// by a miner before it's formation
     pub pre_header: PreHeader,
+    /// Current system parameters (changed by the miners voting)
+    pub parameters: Parameters,
 }
 
 impl ErgoStateContext {
+    /// Create context with the given pre-header and system parameters
+    pub fn new(pre_header: PreHeader, parameters: Parameters) -> ErgoStateContext {
+        ErgoStateContext {
+            pre_header,
+            parameters,
+        }
+    }
+
     /// Dummy instance intended for tests where actual values are not used
     pub fn dummy() -> ErgoStateContext {
         ErgoStateContext {
             pre_header: PreHeader::dummy(),
+            parameters: Parameters::default(),
         }
     }
 }
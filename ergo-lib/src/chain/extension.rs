@@ -0,0 +1,320 @@
+//! Block extension section: key-value fields with the system parameters, interlinks vector and
+//! soft-fork voting data
+
+#[cfg(feature = "json")]
+use std::convert::TryFrom;
+#[cfg(feature = "json")]
+use std::convert::TryInto;
+use std::io;
+
+use ergotree_ir::serialization::sigma_byte_reader::SigmaByteRead;
+use ergotree_ir::serialization::sigma_byte_writer::SigmaByteWrite;
+use ergotree_ir::serialization::SerializationError;
+use ergotree_ir::serialization::SigmaSerializable;
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::block_header::BlockId;
+#[cfg(feature = "json")]
+use super::json;
+use super::merkle_tree::MerkleTree;
+use super::Digest32;
+
+/// Extension field key
+pub type ExtensionKey = [u8; Extension::FIELD_KEY_SIZE];
+
+/// Extension errors
+#[derive(Error, PartialEq, Eq, Debug, Clone)]
+pub enum ExtensionError {
+    /// Field value is longer than [`Extension::FIELD_VALUE_MAX_SIZE`]
+    #[error("Extension field {key:?} value size {size} exceeds the limit")]
+    ValueTooLong {
+        /// Field key
+        key: ExtensionKey,
+        /// Value size
+        size: usize,
+    },
+    /// Field key is not [`Extension::FIELD_KEY_SIZE`] bytes long
+    #[error("Invalid extension field key size: {0}")]
+    InvalidKeySize(usize),
+    /// Digest in JSON differs from the Merkle root of the fields
+    #[error("Extension digest mismatch, expected: {expected:?}, actual: {actual:?}")]
+    DigestMismatch {
+        /// Digest from JSON
+        expected: Digest32,
+        /// Merkle root of the fields
+        actual: Digest32,
+    },
+}
+
+/// Block extension section
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "json",
+    serde(
+        try_from = "json::extension::ExtensionJson",
+        into = "json::extension::ExtensionJson"
+    )
+)]
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Extension {
+    header_id: BlockId,
+    fields: Vec<(ExtensionKey, Vec<u8>)>,
+}
+
+impl Extension {
+    /// Size of the field key
+    pub const FIELD_KEY_SIZE: usize = 2;
+    /// Maximum size of the field value
+    pub const FIELD_VALUE_MAX_SIZE: usize = 64;
+    /// First key byte of the system parameters fields
+    pub const SYSTEM_PARAMETERS_PREFIX: u8 = 0x00;
+    /// First key byte of the interlinks vector fields
+    pub const INTERLINKS_VECTOR_PREFIX: u8 = 0x01;
+    /// First key byte of the validation rules (soft-fork) fields
+    pub const VALIDATION_RULES_PREFIX: u8 = 0x02;
+
+    /// Create extension of the block with the given header id, checking the value sizes
+    pub fn new(
+        header_id: BlockId,
+        fields: Vec<(ExtensionKey, Vec<u8>)>,
+    ) -> Result<Extension, ExtensionError> {
+        if let Some((key, value)) = fields
+            .iter()
+            .find(|(_, value)| value.len() > Extension::FIELD_VALUE_MAX_SIZE)
+        {
+            return Err(ExtensionError::ValueTooLong {
+                key: *key,
+                size: value.len(),
+            });
+        }
+        Ok(Extension { header_id, fields })
+    }
+
+    /// Id of the block header
+    pub fn header_id(&self) -> &BlockId {
+        &self.header_id
+    }
+
+    /// Key-value fields
+    pub fn fields(&self) -> &[(ExtensionKey, Vec<u8>)] {
+        &self.fields
+    }
+
+    /// Fields with the first key byte equal to the given prefix
+    pub fn fields_with_prefix(&self, prefix: u8) -> impl Iterator<Item = &(ExtensionKey, Vec<u8>)> {
+        self.fields.iter().filter(move |(key, _)| key[0] == prefix)
+    }
+
+    /// Merkle tree leaf data of the field (key size byte, key and value)
+    pub fn field_leaf_data(key: &ExtensionKey, value: &[u8]) -> Vec<u8> {
+        let mut leaf = Vec::with_capacity(1 + key.len() + value.len());
+        leaf.push(key.len() as u8);
+        leaf.extend_from_slice(key);
+        leaf.extend_from_slice(value);
+        leaf
+    }
+
+    /// Merkle tree over the fields
+    pub fn merkle_tree(&self) -> MerkleTree {
+        MerkleTree::new(
+            self.fields
+                .iter()
+                .map(|(key, value)| Extension::field_leaf_data(key, value))
+                .collect(),
+        )
+    }
+
+    /// Merkle root of the fields (extension root in the block header)
+    pub fn digest(&self) -> Digest32 {
+        self.merkle_tree().root_hash()
+    }
+}
+
+impl SigmaSerializable for Extension {
+    fn sigma_serialize<W: SigmaByteWrite>(&self, w: &mut W) -> Result<(), io::Error> {
+        self.header_id.0.sigma_serialize(w)?;
+        w.put_usize_as_u16(self.fields.len())?;
+        for (key, value) in &self.fields {
+            w.write_all(key)?;
+            // value size is checked on creation
+            w.put_u8(value.len() as u8)?;
+            w.write_all(value)?;
+        }
+        Ok(())
+    }
+
+    fn sigma_parse<R: SigmaByteRead>(r: &mut R) -> Result<Self, SerializationError> {
+        let header_id = BlockId(Digest32::sigma_parse(r)?);
+        let fields_count = r.get_u16()?;
+        let mut fields = Vec::with_capacity(fields_count as usize);
+        for _ in 0..fields_count {
+            let mut key = [0u8; Extension::FIELD_KEY_SIZE];
+            r.read_exact(&mut key)?;
+            let value_size = r.get_u8()? as usize;
+            let mut value = vec![0u8; value_size];
+            r.read_exact(&mut value)?;
+            fields.push((key, value));
+        }
+        Extension::new(header_id, fields)
+            .map_err(|e| SerializationError::ValueOutOfBounds(e.to_string()))
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<Extension> for json::extension::ExtensionJson {
+    fn from(e: Extension) -> Self {
+        let digest = e.digest();
+        json::extension::ExtensionJson {
+            header_id: e.header_id,
+            digest: Some(digest),
+            fields: e
+                .fields
+                .into_iter()
+                .map(|(key, value)| {
+                    (
+                        super::Base16DecodedBytes(key.to_vec()),
+                        super::Base16DecodedBytes(value),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl TryFrom<json::extension::ExtensionJson> for Extension {
+    type Error = ExtensionError;
+
+    fn try_from(e: json::extension::ExtensionJson) -> Result<Self, Self::Error> {
+        let fields = e
+            .fields
+            .into_iter()
+            .map(|(key, value)| {
+                let key_size = key.0.len();
+                let key: ExtensionKey = key
+                    .0
+                    .try_into()
+                    .map_err(|_| ExtensionError::InvalidKeySize(key_size))?;
+                Ok((key, value.0))
+            })
+            .collect::<Result<Vec<_>, ExtensionError>>()?;
+        let extension = Extension::new(e.header_id, fields)?;
+        match e.digest {
+            Some(expected) if expected != extension.digest() => {
+                Err(ExtensionError::DigestMismatch {
+                    expected,
+                    actual: extension.digest(),
+                })
+            }
+            _ => Ok(extension),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::blake2b256_hash;
+    use ergotree_ir::serialization::sigma_serialize_roundtrip;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
+    impl Arbitrary for Extension {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Self>;
+
+        fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+            (
+                any::<[u8; 32]>(),
+                vec(
+                    (
+                        any::<ExtensionKey>(),
+                        vec(any::<u8>(), 0..=Extension::FIELD_VALUE_MAX_SIZE),
+                    ),
+                    0..10,
+                ),
+            )
+                .prop_map(|(id, fields)| Extension::new(BlockId(id.into()), fields).unwrap())
+                .boxed()
+        }
+    }
+
+    proptest! {
+
+        #[test]
+        fn ser_roundtrip(v in any::<Extension>()) {
+            prop_assert_eq![sigma_serialize_roundtrip(&v), v];
+        }
+    }
+
+    #[test]
+    fn value_too_long() {
+        let res = Extension::new(
+            BlockId(Digest32::zero()),
+            vec![([0, 1], vec![0u8; Extension::FIELD_VALUE_MAX_SIZE + 1])],
+        );
+        assert_eq!(
+            res,
+            Err(ExtensionError::ValueTooLong {
+                key: [0, 1],
+                size: Extension::FIELD_VALUE_MAX_SIZE + 1
+            })
+        );
+    }
+
+    #[test]
+    fn digest() {
+        let ext = Extension::new(BlockId(Digest32::zero()), vec![]).unwrap();
+        assert_eq!(ext.digest(), blake2b256_hash(&[]));
+
+        let ext = Extension::new(
+            BlockId(Digest32::zero()),
+            vec![([0, 1], vec![0, 0, 0, 1]), ([1, 0], vec![7u8; 33])],
+        )
+        .unwrap();
+        let leaves = vec![
+            vec![2, 0, 1, 0, 0, 0, 1],
+            [&[2, 1, 0][..], &[7u8; 33][..]].concat(),
+        ];
+        assert_eq!(ext.digest(), MerkleTree::new(leaves).root_hash());
+        assert_eq!(
+            ext.fields_with_prefix(Extension::INTERLINKS_VECTOR_PREFIX)
+                .count(),
+            1
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn parse_json() {
+        let json = r#"{
+            "headerId": "4caa17e62fe66ba7bd69597afdc996ae35b1ff12e0ba90c22ff288a4de10e91b",
+            "digest": "391c6d3137a8f7c9e2a35b4491e55c3057f780fac87acd955b94bec60194596b",
+            "fields": [
+                ["0001", "00000001"],
+                ["0100", "01b0244dfc267baca974a4caee06120321562784303a8a688976ae56170e4d175b"]
+            ]
+        }"#;
+        let ext: Extension = serde_json::from_str(json).unwrap();
+        assert_eq!(ext.fields().len(), 2);
+        assert_eq!(ext.fields()[1].0, [1, 0]);
+        let out: json::extension::ExtensionJson = ext.clone().into();
+        assert_eq!(out.digest, Some(ext.digest()));
+        let roundtrip: Extension =
+            serde_json::from_str(&serde_json::to_string(&ext).unwrap()).unwrap();
+        assert_eq!(roundtrip, ext);
+
+        let invalid_key = json.replace(r#"["0001""#, r#"["000102""#);
+        assert!(serde_json::from_str::<Extension>(&invalid_key).is_err());
+
+        let other_value = json.replace(r#""00000001""#, r#""00000002""#);
+        assert!(serde_json::from_str::<Extension>(&other_value).is_err());
+        let no_digest = json.replace(
+            r#""digest": "391c6d3137a8f7c9e2a35b4491e55c3057f780fac87acd955b94bec60194596b","#,
+            "",
+        );
+        assert_eq!(serde_json::from_str::<Extension>(&no_digest).unwrap(), ext);
+    }
+}
@@ -0,0 +1,402 @@
+//! Validation of a chain of block headers (light client)
+
+use std::collections::VecDeque;
+
+use num_bigint::BigInt;
+use thiserror::Error;
+
+use super::autolykos_pow_scheme::AutolykosPowScheme;
+use super::autolykos_pow_scheme::AutolykosPowSchemeError;
+use super::block_header::BlockHeader;
+use super::block_header::BlockId;
+use super::difficulty_adjustment::DifficultyAdjustment;
+use super::difficulty_adjustment::DifficultyAdjustmentError;
+use super::difficulty_adjustment::DifficultySettings;
+
+/// Header chain validation errors
+#[derive(Error, PartialEq, Eq, Debug, Clone)]
+pub enum HeaderChainError {
+    /// No trusted headers to start the chain from
+    #[error("Header chain is empty")]
+    EmptyChain,
+    /// Header id differs from calculated from serialized bytes
+    #[error("Header at height {0} has invalid id")]
+    InvalidId(u32),
+    /// Header does not reference the previous header as a parent
+    #[error("Header at height {height} has parent id {found:?}, expected {expected:?}")]
+    ParentIdMismatch {
+        /// Height of the header
+        height: u32,
+        /// Id of the previous header
+        expected: BlockId,
+        /// Parent id of the header
+        found: BlockId,
+    },
+    /// Header height is not the previous header height + 1
+    #[error("Expected header at height {expected}, got {found}")]
+    HeightMismatch {
+        /// Previous header height + 1
+        expected: u32,
+        /// Height of the header
+        found: u32,
+    },
+    /// Header timestamp is not greater than the parent's
+    #[error("Header at height {height} has timestamp {timestamp} not greater than the parent's {parent_timestamp}")]
+    TimestampNotIncreasing {
+        /// Height of the header
+        height: u32,
+        /// Timestamp of the parent
+        parent_timestamp: u64,
+        /// Timestamp of the header
+        timestamp: u64,
+    },
+    /// Header difficulty (`n_bits`) differs from the required one
+    #[error("Header at height {height} has difficulty {found}, expected {expected}")]
+    DifficultyMismatch {
+        /// Height of the header
+        height: u32,
+        /// Required difficulty
+        expected: BigInt,
+        /// Difficulty decoded from `n_bits` of the header
+        found: BigInt,
+    },
+    /// Header needed for the difficulty calculation is not in the window
+    #[error("Header at height {0} needed for the difficulty calculation is missing")]
+    MissingHeader(u32),
+    /// Required difficulty cannot be calculated from the headers in the window
+    #[error("Difficulty calculation error: {0}")]
+    Difficulty(#[from] DifficultyAdjustmentError),
+    /// Invalid PoW solution
+    #[error("Header at height {height} has invalid PoW: {error}")]
+    Pow {
+        /// Height of the header
+        height: u32,
+        /// PoW verification error
+        error: AutolykosPowSchemeError,
+    },
+}
+
+/// Validates headers appended to the chain against the sliding window of the last headers:
+/// parent linkage, height, timestamp, required difficulty and PoW
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct HeaderChainValidator {
+    difficulty_adjustment: DifficultyAdjustment,
+    pow_scheme: AutolykosPowScheme,
+    /// Headers before the tip (ascending by height)
+    window: VecDeque<BlockHeader>,
+    tip: BlockHeader,
+    max_window_size: usize,
+}
+
+impl HeaderChainValidator {
+    /// Create a validator starting from the trusted headers (ascending by height). Only the
+    /// ids and the linkage of the trusted headers are checked. To validate the difficulty of
+    /// the following headers they should include the last
+    /// `use_last_epochs * epoch_length` headers.
+    pub fn new(
+        settings: DifficultySettings,
+        trusted_headers: Vec<BlockHeader>,
+    ) -> Result<Self, HeaderChainError> {
+        let max_epoch_length = settings.epoch_length.max(settings.eip37_epoch_length);
+        let max_window_size = (settings.use_last_epochs * max_epoch_length) as usize + 1;
+        let mut window: VecDeque<BlockHeader> = VecDeque::with_capacity(max_window_size);
+        for header in trusted_headers {
+            if header.calc_id() != header.id {
+                return Err(HeaderChainError::InvalidId(header.height));
+            }
+            if let Some(parent) = window.back() {
+                check_linkage(parent, &header)?;
+            }
+            window.push_back(header);
+        }
+        let tip = window.pop_back().ok_or(HeaderChainError::EmptyChain)?;
+        let mut validator = HeaderChainValidator {
+            difficulty_adjustment: DifficultyAdjustment::new(settings),
+            pow_scheme: AutolykosPowScheme::default(),
+            window,
+            tip,
+            max_window_size,
+        };
+        validator.trim_window();
+        Ok(validator)
+    }
+
+    /// Last header of the chain
+    pub fn tip(&self) -> &BlockHeader {
+        &self.tip
+    }
+
+    /// Header at the given height if it's in the window
+    pub fn header_at(&self, height: u32) -> Option<&BlockHeader> {
+        if height == self.tip.height {
+            return Some(&self.tip);
+        }
+        let first_height = self.window.front()?.height;
+        height
+            .checked_sub(first_height)
+            .and_then(|idx| self.window.get(idx as usize))
+    }
+
+    /// Required difficulty of the header following the tip
+    pub fn required_difficulty_after_tip(&self) -> Result<BigInt, HeaderChainError> {
+        let parent = self.tip();
+        let height = parent.height + 1;
+        let epoch_length = self.difficulty_adjustment.epoch_length(height);
+        let previous_headers = self
+            .difficulty_adjustment
+            .previous_heights_required_for_recalculation(height, epoch_length)
+            .into_iter()
+            // there is no header before the genesis block (height 1)
+            .filter(|h| *h > 0)
+            .map(|h| {
+                self.header_at(h)
+                    .cloned()
+                    .ok_or(HeaderChainError::MissingHeader(h))
+            })
+            .collect::<Result<Vec<BlockHeader>, HeaderChainError>>()?;
+        Ok(self
+            .difficulty_adjustment
+            .required_difficulty_after(parent, &previous_headers)?)
+    }
+
+    /// Check that the header can be appended to the chain
+    pub fn validate_next(&self, header: &BlockHeader) -> Result<(), HeaderChainError> {
+        if header.calc_id() != header.id {
+            return Err(HeaderChainError::InvalidId(header.height));
+        }
+        let parent = self.tip();
+        check_linkage(parent, header)?;
+        if header.timestamp <= parent.timestamp {
+            return Err(HeaderChainError::TimestampNotIncreasing {
+                height: header.height,
+                parent_timestamp: parent.timestamp,
+                timestamp: header.timestamp,
+            });
+        }
+        let expected = self.required_difficulty_after_tip()?;
+        let found = header.required_difficulty();
+        if found != expected {
+            return Err(HeaderChainError::DifficultyMismatch {
+                height: header.height,
+                expected,
+                found,
+            });
+        }
+        self.pow_scheme
+            .validate(header)
+            .map_err(|error| HeaderChainError::Pow {
+                height: header.height,
+                error,
+            })
+    }
+
+    /// Validate the header and append it to the chain
+    pub fn append(&mut self, header: BlockHeader) -> Result<(), HeaderChainError> {
+        self.validate_next(&header)?;
+        let parent = std::mem::replace(&mut self.tip, header);
+        self.window.push_back(parent);
+        self.trim_window();
+        Ok(())
+    }
+
+    fn trim_window(&mut self) {
+        // the tip is not in the window
+        while self.window.len() + 1 > self.max_window_size {
+            self.window.pop_front();
+        }
+    }
+}
+
+fn check_linkage(parent: &BlockHeader, header: &BlockHeader) -> Result<(), HeaderChainError> {
+    if header.height != parent.height + 1 {
+        return Err(HeaderChainError::HeightMismatch {
+            expected: parent.height + 1,
+            found: header.height,
+        });
+    }
+    if header.parent_id != parent.id {
+        return Err(HeaderChainError::ParentIdMismatch {
+            height: header.height,
+            expected: parent.id.clone(),
+            found: header.parent_id.clone(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::block_header::encode_compact_bits;
+    use crate::chain::block_header::tests::difficulty_1_child;
+    use crate::chain::block_header::tests::difficulty_1_genesis;
+    use crate::chain::block_header::tests::MAINNET_HEADER_471746;
+
+    const EPOCH_LENGTH: u32 = 4;
+    const BLOCK_INTERVAL_MS: u64 = 120_000;
+
+    fn settings() -> DifficultySettings {
+        DifficultySettings {
+            epoch_length: EPOCH_LENGTH,
+            block_interval_ms: BLOCK_INTERVAL_MS,
+            use_last_epochs: 2,
+            initial_difficulty: BigInt::from(1),
+            eip37_activation_height: None,
+            eip37_epoch_length: EPOCH_LENGTH,
+        }
+    }
+
+    fn with_id(mut header: BlockHeader) -> BlockHeader {
+        header.id = header.calc_id();
+        header
+    }
+
+    /// Next header with the difficulty 1 (any v2 PoW solution is valid) mined at the desired
+    /// block interval, so the difficulty stays the same
+    fn next_header(parent: &BlockHeader) -> BlockHeader {
+        difficulty_1_child(parent, BLOCK_INTERVAL_MS)
+    }
+
+    fn chain(len: usize) -> Vec<BlockHeader> {
+        let mut headers = vec![difficulty_1_genesis()];
+        while headers.len() < len {
+            let next = next_header(headers.last().unwrap());
+            headers.push(next);
+        }
+        headers
+    }
+
+    #[test]
+    fn append_valid_chain() {
+        let headers = chain(30);
+        let mut validator = HeaderChainValidator::new(settings(), headers[..1].to_vec()).unwrap();
+        for h in headers[1..].iter() {
+            validator.append(h.clone()).unwrap();
+        }
+        assert_eq!(validator.tip(), headers.last().unwrap());
+        // use_last_epochs * epoch_length + 1 with the tip
+        assert_eq!(validator.window.len(), 8);
+        assert!(validator.header_at(21).is_none());
+        assert_eq!(validator.header_at(22), Some(&headers[21]));
+    }
+
+    #[test]
+    fn trusted_headers_are_checked() {
+        let mut headers = chain(3);
+        assert_eq!(
+            HeaderChainValidator::new(settings(), vec![]),
+            Err(HeaderChainError::EmptyChain)
+        );
+        headers.swap(1, 2);
+        assert_eq!(
+            HeaderChainValidator::new(settings(), headers),
+            Err(HeaderChainError::HeightMismatch {
+                expected: 2,
+                found: 3
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_headers() {
+        let headers = chain(10);
+        let validator = HeaderChainValidator::new(settings(), headers.clone()).unwrap();
+        let tip = validator.tip().clone();
+
+        let mut wrong_id = next_header(&tip);
+        wrong_id.timestamp += 1;
+        assert_eq!(
+            validator.validate_next(&wrong_id),
+            Err(HeaderChainError::InvalidId(11))
+        );
+
+        let mut wrong_parent = next_header(&tip);
+        wrong_parent.parent_id = headers[8].id.clone();
+        assert_eq!(
+            validator.validate_next(&with_id(wrong_parent)),
+            Err(HeaderChainError::ParentIdMismatch {
+                height: 11,
+                expected: tip.id.clone(),
+                found: headers[8].id.clone()
+            })
+        );
+
+        let mut wrong_height = next_header(&tip);
+        wrong_height.height = 12;
+        assert_eq!(
+            validator.validate_next(&with_id(wrong_height)),
+            Err(HeaderChainError::HeightMismatch {
+                expected: 11,
+                found: 12
+            })
+        );
+
+        let mut wrong_timestamp = next_header(&tip);
+        wrong_timestamp.timestamp = tip.timestamp;
+        assert_eq!(
+            validator.validate_next(&with_id(wrong_timestamp)),
+            Err(HeaderChainError::TimestampNotIncreasing {
+                height: 11,
+                parent_timestamp: tip.timestamp,
+                timestamp: tip.timestamp
+            })
+        );
+
+        let mut wrong_difficulty = next_header(&tip);
+        wrong_difficulty.n_bits = encode_compact_bits(&BigInt::from(2));
+        assert_eq!(
+            validator.validate_next(&with_id(wrong_difficulty)),
+            Err(HeaderChainError::DifficultyMismatch {
+                height: 11,
+                expected: BigInt::from(1),
+                found: BigInt::from(2)
+            })
+        );
+    }
+
+    #[test]
+    fn difficulty_is_recalculated_at_the_epoch_end() {
+        let mut headers = chain(12);
+        // the last epoch (heights 9-12) was mined twice as fast
+        for i in 8..12 {
+            let mut header = headers[i - 1].clone();
+            header.parent_id = headers[i - 1].id.clone();
+            header.height += 1;
+            header.timestamp += BLOCK_INTERVAL_MS / 2;
+            headers[i] = with_id(header);
+        }
+        let validator = HeaderChainValidator::new(settings(), headers).unwrap();
+        assert_eq!(validator.tip().height, 12);
+        // (8, 1) and (12, 2) extrapolated to the height 16
+        assert_eq!(
+            validator.required_difficulty_after_tip(),
+            Ok(BigInt::from(3))
+        );
+    }
+
+    #[test]
+    fn missing_headers_for_the_difficulty() {
+        let headers = chain(8);
+        let validator = HeaderChainValidator::new(settings(), headers[4..].to_vec()).unwrap();
+        assert_eq!(
+            validator.validate_next(&next_header(validator.tip())),
+            Err(HeaderChainError::MissingHeader(4))
+        );
+    }
+
+    #[test]
+    fn invalid_pow() {
+        let header: BlockHeader = serde_json::from_str(MAINNET_HEADER_471746).unwrap();
+        let validator =
+            HeaderChainValidator::new(DifficultySettings::mainnet(), vec![header.clone()]).unwrap();
+        // not the end of an epoch, the difficulty is the parent's one
+        assert_eq!(
+            validator.required_difficulty_after_tip(),
+            Ok(header.required_difficulty())
+        );
+        assert!(matches!(
+            validator.validate_next(&next_header(&header)),
+            Err(HeaderChainError::Pow { height: 471747, .. })
+        ));
+    }
+}
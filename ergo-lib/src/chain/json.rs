@@ -4,6 +4,8 @@ pub(crate) mod block_header;
 pub(crate) mod context_extension;
 pub(crate) mod ergo_box;
 pub(crate) mod ergo_tree;
+pub(crate) mod extension;
+pub(crate) mod merkle_proof;
 pub(crate) mod transaction;
 
 use ergotree_interpreter::sigma_protocol::prover::ProofBytes;
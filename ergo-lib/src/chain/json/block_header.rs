@@ -1,10 +1,15 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
 use std::convert::TryFrom;
 use std::convert::TryInto;
 
+use crate::chain::block_header::BlockId;
 use crate::chain::block_header::Votes;
 use crate::chain::block_header::VotesError;
+use crate::chain::ADDigest;
 use crate::chain::Base16DecodedBytes;
+use crate::chain::Digest32;
+use ergotree_ir::sigma_protocol::dlog_group::EcPoint;
 
 #[derive(Deserialize, Debug, Clone)]
 #[serde(untagged)]
@@ -24,68 +29,144 @@ impl TryFrom<VotesEncodingVariants> for Votes {
     }
 }
 
+/// Block header in the format of the node's `/blocks/{id}/header` API.
+/// The fields derived from the header (difficulty, size and the section ids) are written
+/// but ignored when parsing
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BlockHeaderJson {
+    pub id: BlockId,
+    pub version: u8,
+    #[serde(rename = "parentId")]
+    pub parent_id: BlockId,
+    #[serde(rename = "adProofsRoot")]
+    pub ad_proofs_root: Digest32,
+    #[serde(rename = "stateRoot")]
+    pub state_root: ADDigest,
+    #[serde(rename = "transactionsRoot")]
+    pub transaction_root: Digest32,
+    pub timestamp: u64,
+    #[serde(rename = "nBits")]
+    pub n_bits: u64,
+    pub height: u32,
+    #[serde(rename = "extensionHash")]
+    pub extension_root: Digest32,
+    #[serde(rename = "powSolutions")]
+    pub autolykos_solution: AutolykosSolutionJson,
+    pub votes: Votes,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub difficulty: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<usize>,
+    #[serde(
+        rename = "extensionId",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub extension_id: Option<Digest32>,
+    #[serde(
+        rename = "transactionsId",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub transactions_id: Option<Digest32>,
+    #[serde(
+        rename = "adProofsId",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub ad_proofs_id: Option<Digest32>,
+}
+
+/// Autolykos solution in the node's format (`d` is a JSON number of arbitrary size, so
+/// it is kept as raw JSON to avoid the loss of precision)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AutolykosSolutionJson {
+    #[serde(rename = "pk")]
+    pub miner_pk: EcPoint,
+    /// optional for v2, where it is not used
+    #[serde(rename = "w", default, skip_serializing_if = "Option::is_none")]
+    pub pow_onetime_pk: Option<EcPoint>,
+    #[serde(rename = "n")]
+    pub nonce: Base16DecodedBytes,
+    /// optional for v2, where it is not used
+    #[serde(rename = "d", default, skip_serializing_if = "Option::is_none")]
+    pub pow_distance: Option<Box<RawValue>>,
+}
+
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod tests {
+    use crate::chain::block_header::tests::MAINNET_HEADER_471746;
     use crate::chain::block_header::BlockHeader;
+    use crate::chain::block_header::BlockHeaderFromJsonError;
+    use crate::chain::Digest32;
+    use ergotree_ir::serialization::SigmaSerializable;
+    use proptest::prelude::*;
+    use std::convert::TryFrom;
+
+    proptest! {
+
+        #[test]
+        fn block_header_roundtrip(h in any::<BlockHeader>()) {
+            let j = serde_json::to_string(&h)?;
+            let h_parsed: BlockHeader = serde_json::from_str(&j)?;
+            prop_assert_eq![h, h_parsed];
+        }
+    }
 
     #[test]
     fn parse_block_header() {
-        let json = r#"{
-            "extensionId": "d16f25b14457186df4c5f6355579cc769261ce1aebc8209949ca6feadbac5a3f",
-            "difficulty": "626412390187008",
-            "votes": "040000",
-            "timestamp": 1618929697400,
-            "size": 221,
-            "stateRoot": "8ad868627ea4f7de6e2a2fe3f98fafe57f914e0f2ef3331c006def36c697f92713",
-            "height": 471746,
-            "nBits": 117586360,
-            "version": 2,
-            "id": "4caa17e62fe66ba7bd69597afdc996ae35b1ff12e0ba90c22ff288a4de10e91b",
-            "adProofsRoot": "d882aaf42e0a95eb95fcce5c3705adf758e591532f733efe790ac3c404730c39",
-            "transactionsRoot": "63eaa9aff76a1de3d71c81e4b2d92e8d97ae572a8e9ab9e66599ed0912dd2f8b",
-            "extensionHash": "3f91f3c680beb26615fdec251aee3f81aaf5a02740806c167c0f3c929471df44",
-            "powSolutions": {
-              "pk": "02b3a06d6eaa8671431ba1db4dd427a77f75a5c2acbd71bfb725d38adc2b55f669",
-              "w": "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
-              "n": "5939ecfee6b0d7f4",
-              "d": 0
-            },
-            "adProofsId": "86eaa41f328bee598e33e52c9e515952ad3b7874102f762847f17318a776a7ae",
-            "transactionsId": "ac80245714f25aa2fafe5494ad02a26d46e7955b8f5709f3659f1b9440797b3e",
-            "parentId": "6481752bace5fa5acba5d5ef7124d48826664742d46c974c98a2d60ace229a34"
-        }"#;
+        let json = MAINNET_HEADER_471746;
         let b: BlockHeader = serde_json::from_str(json).unwrap();
         assert_eq!(b.height, 471746);
+        assert_eq!(b.calc_id(), b.id);
+        assert_eq!(b.sigma_serialize_bytes().len(), 220);
+        assert_eq!(
+            b.extension_id(),
+            Digest32::try_from(
+                "d16f25b14457186df4c5f6355579cc769261ce1aebc8209949ca6feadbac5a3f".to_string()
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            b.ad_proofs_id(),
+            Digest32::try_from(
+                "86eaa41f328bee598e33e52c9e515952ad3b7874102f762847f17318a776a7ae".to_string()
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            b.transactions_id(),
+            Digest32::try_from(
+                "ac80245714f25aa2fafe5494ad02a26d46e7955b8f5709f3659f1b9440797b3e".to_string()
+            )
+            .unwrap()
+        );
+        // the node's output is reproduced
+        let expected: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(serde_json::to_value(&b).unwrap(), expected);
+        assert_eq!(
+            BlockHeader::sigma_parse_bytes(&b.sigma_serialize_bytes()).unwrap(),
+            b
+        );
+    }
+
+    #[test]
+    fn parse_block_header_invalid_id() {
+        let json = MAINNET_HEADER_471746.replace(r#""height": 471746"#, r#""height": 471747"#);
+        let res = serde_json::from_str::<BlockHeader>(&json);
+        assert!(res
+            .unwrap_err()
+            .to_string()
+            .contains(&BlockHeaderFromJsonError::InvalidBlockId.to_string()));
     }
 
     #[test]
     fn parse_block_header_explorer_v1() {
         // see https://api.ergoplatform.com/api/v1/blocks/de68a9cd727510d01eae3146f862261661f3bebdfd3c45c19d431b2ae81fb4b6
-        let json = r#"{
-            "extensionId": "d16f25b14457186df4c5f6355579cc769261ce1aebc8209949ca6feadbac5a3f",
-            "difficulty": "626412390187008",
-            "votes": [4,0,0],
-            "timestamp": 1618929697400,
-            "size": 221,
-            "stateRoot": "8ad868627ea4f7de6e2a2fe3f98fafe57f914e0f2ef3331c006def36c697f92713",
-            "height": 471746,
-            "nBits": 117586360,
-            "version": 2,
-            "id": "4caa17e62fe66ba7bd69597afdc996ae35b1ff12e0ba90c22ff288a4de10e91b",
-            "adProofsRoot": "d882aaf42e0a95eb95fcce5c3705adf758e591532f733efe790ac3c404730c39",
-            "transactionsRoot": "63eaa9aff76a1de3d71c81e4b2d92e8d97ae572a8e9ab9e66599ed0912dd2f8b",
-            "extensionHash": "3f91f3c680beb26615fdec251aee3f81aaf5a02740806c167c0f3c929471df44",
-            "powSolutions": {
-              "pk": "02b3a06d6eaa8671431ba1db4dd427a77f75a5c2acbd71bfb725d38adc2b55f669",
-              "w": "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
-              "n": "5939ecfee6b0d7f4",
-              "d": 0
-            },
-            "adProofsId": "86eaa41f328bee598e33e52c9e515952ad3b7874102f762847f17318a776a7ae",
-            "transactionsId": "ac80245714f25aa2fafe5494ad02a26d46e7955b8f5709f3659f1b9440797b3e",
-            "parentId": "6481752bace5fa5acba5d5ef7124d48826664742d46c974c98a2d60ace229a34"
-        }"#;
-        let b: BlockHeader = serde_json::from_str(json).unwrap();
+        let json = MAINNET_HEADER_471746.replace(r#""votes": "040000""#, r#""votes": [4,0,0]"#);
+        let b: BlockHeader = serde_json::from_str(&json).unwrap();
         assert_eq!(b.height, 471746);
+        assert_eq!(b.votes.0, [4, 0, 0]);
     }
 }
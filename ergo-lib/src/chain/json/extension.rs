@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+use crate::chain::block_header::BlockId;
+use crate::chain::Base16DecodedBytes;
+use crate::chain::Digest32;
+
+/// Block extension in the format of the node's `/blocks/{id}/extension` API.
+/// The digest is derived from the fields and checked against them when parsing (if present)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExtensionJson {
+    #[serde(rename = "headerId")]
+    pub header_id: BlockId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub digest: Option<Digest32>,
+    pub fields: Vec<(Base16DecodedBytes, Base16DecodedBytes)>,
+}
@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+use crate::chain::Base16DecodedBytes;
+use crate::chain::Digest32;
+
+/// Merkle proof in the format of the node's `/blocks/{headerId}/proofFor/{txId}` API.
+/// Each level is a pair of the sibling hash (empty string for an empty node) and the side byte
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MerkleProofJson {
+    #[serde(rename = "leafData")]
+    pub leaf_data: Base16DecodedBytes,
+    pub levels: Vec<(Base16DecodedBytes, u8)>,
+}
+
+/// Batch Merkle proof in the node's format (used for the interlinks proof of NiPoPoW headers)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BatchMerkleProofJson {
+    pub indices: Vec<BatchMerkleProofIndexJson>,
+    pub proofs: Vec<BatchMerkleProofLevelJson>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BatchMerkleProofIndexJson {
+    pub index: usize,
+    pub digest: Digest32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BatchMerkleProofLevelJson {
+    pub digest: Base16DecodedBytes,
+    pub side: u8,
+}
@@ -0,0 +1,658 @@
+//! Merkle tree over block transactions and Merkle inclusion proofs
+//!
+//! Follows the tree used by the node (scrypto's `MerkleTree`): leaves are hashed as
+//! `blake2b256(0x00 ++ data)`, internal nodes as `blake2b256(0x01 ++ left ++ right)`, and a
+//! node without a pair is joined with an empty node (an empty byte string in place of a hash).
+//! See <https://github.com/input-output-hk/scrypto/blob/master/src/main/scala/scorex/crypto/authds/merkle/MerkleTree.scala>
+
+use std::convert::TryFrom;
+#[cfg(feature = "json")]
+use std::convert::TryInto;
+
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::block_header::BlockHeader;
+#[cfg(feature = "json")]
+use super::json;
+use super::transaction::Transaction;
+use super::transaction::TxId;
+use super::{blake2b256_hash, Digest32};
+
+/// Prefix of the leaf data before hashing
+const LEAF_PREFIX: u8 = 0;
+/// Prefix of the concatenated child hashes before hashing
+const INTERNAL_NODE_PREFIX: u8 = 1;
+
+/// Hash of the leaf with the given data
+pub fn leaf_hash(data: &[u8]) -> Digest32 {
+    let mut bytes = Vec::with_capacity(data.len() + 1);
+    bytes.push(LEAF_PREFIX);
+    bytes.extend_from_slice(data);
+    blake2b256_hash(&bytes)
+}
+
+fn internal_node_hash(left: &[u8], right: &[u8]) -> Digest32 {
+    let mut bytes = Vec::with_capacity(left.len() + right.len() + 1);
+    bytes.push(INTERNAL_NODE_PREFIX);
+    bytes.extend_from_slice(left);
+    bytes.extend_from_slice(right);
+    blake2b256_hash(&bytes)
+}
+
+fn digest_bytes(digest: &Option<Digest32>) -> &[u8] {
+    match digest {
+        Some(d) => d.0.as_ref(),
+        None => &[],
+    }
+}
+
+/// Side of a node in a Merkle proof
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[repr(u8)]
+pub enum MerkleNodeSide {
+    /// Left child
+    Left = 0,
+    /// Right child
+    Right = 1,
+}
+
+/// Invalid side byte in a Merkle proof
+#[derive(Error, PartialEq, Eq, Debug, Clone)]
+#[error("Invalid Merkle proof node side: {0}")]
+pub struct InvalidMerkleNodeSide(pub u8);
+
+impl TryFrom<u8> for MerkleNodeSide {
+    type Error = InvalidMerkleNodeSide;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(MerkleNodeSide::Left),
+            1 => Ok(MerkleNodeSide::Right),
+            _ => Err(InvalidMerkleNodeSide(value)),
+        }
+    }
+}
+
+/// One level of a Merkle proof: the sibling hash and the side of the node being proven
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct MerkleProofLevel {
+    /// Hash of the sibling node, `None` for an empty node
+    pub hash: Option<Digest32>,
+    /// Side of the node being proven
+    pub side: MerkleNodeSide,
+}
+
+/// Merkle inclusion proof, as returned by the node's `/blocks/{headerId}/proofFor/{txId}`
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "json",
+    serde(
+        try_from = "json::merkle_proof::MerkleProofJson",
+        into = "json::merkle_proof::MerkleProofJson"
+    )
+)]
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct MerkleProof {
+    /// Data of the leaf (transaction id for block transactions)
+    pub leaf_data: Vec<u8>,
+    /// Levels from the leaf up to the root
+    pub levels: Vec<MerkleProofLevel>,
+}
+
+impl MerkleProof {
+    /// Root hash implied by the proof
+    pub fn root_hash(&self) -> Digest32 {
+        self.levels
+            .iter()
+            .fold(leaf_hash(&self.leaf_data), |prev, level| match level.side {
+                MerkleNodeSide::Left => {
+                    internal_node_hash(prev.0.as_ref(), digest_bytes(&level.hash))
+                }
+                MerkleNodeSide::Right => {
+                    internal_node_hash(digest_bytes(&level.hash), prev.0.as_ref())
+                }
+            })
+    }
+
+    /// Check the proof against the expected root hash (e.g. header's transactions root)
+    pub fn valid(&self, expected_root: &Digest32) -> bool {
+        &self.root_hash() == expected_root
+    }
+}
+
+/// Merkle tree
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct MerkleTree {
+    leaves: Vec<Vec<u8>>,
+    /// Node hashes by level, starting with the leaves and ending with the root
+    levels: Vec<Vec<Digest32>>,
+}
+
+impl MerkleTree {
+    /// Build a tree over the given leaf data
+    pub fn new(leaves: Vec<Vec<u8>>) -> MerkleTree {
+        let mut levels = Vec::new();
+        if !leaves.is_empty() {
+            let mut level: Vec<Digest32> = leaves.iter().map(|l| leaf_hash(l)).collect();
+            // a single leaf still gets an internal node (paired with the empty node)
+            loop {
+                let next: Vec<Digest32> = level
+                    .chunks(2)
+                    .map(|pair| {
+                        internal_node_hash(
+                            pair[0].0.as_ref(),
+                            pair.get(1).map(|d| &d.0[..]).unwrap_or(&[]),
+                        )
+                    })
+                    .collect();
+                levels.push(level);
+                if next.len() == 1 {
+                    levels.push(next);
+                    break;
+                }
+                level = next;
+            }
+        }
+        MerkleTree { leaves, levels }
+    }
+
+    /// Number of leaves
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    /// Returns true if the tree has no leaves
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    /// Root hash (hash of the empty input for an empty tree)
+    pub fn root_hash(&self) -> Digest32 {
+        self.levels
+            .last()
+            .and_then(|root| root.first())
+            .cloned()
+            .unwrap_or_else(|| blake2b256_hash(&[]))
+    }
+
+    /// Inclusion proof for the leaf at the given index
+    pub fn proof_by_index(&self, index: usize) -> Option<MerkleProof> {
+        let leaf_data = self.leaves.get(index)?.clone();
+        let mut idx = index;
+        let levels = self
+            .levels
+            .iter()
+            .take(self.levels.len() - 1)
+            .map(|level| {
+                let (sibling, side) = if idx & 1 == 0 {
+                    (idx + 1, MerkleNodeSide::Left)
+                } else {
+                    (idx - 1, MerkleNodeSide::Right)
+                };
+                idx /= 2;
+                MerkleProofLevel {
+                    hash: level.get(sibling).cloned(),
+                    side,
+                }
+            })
+            .collect();
+        Some(MerkleProof { leaf_data, levels })
+    }
+
+    /// Inclusion proof for the first leaf with the given data
+    pub fn proof_by_element(&self, data: &[u8]) -> Option<MerkleProof> {
+        let index = self.leaves.iter().position(|l| l.as_slice() == data)?;
+        self.proof_by_index(index)
+    }
+
+    /// Compact inclusion proof for the leaves at the given indices
+    pub fn proof_by_indices(&self, indices: &[usize]) -> Option<BatchMerkleProof> {
+        let mut a: Vec<usize> = indices.to_vec();
+        a.sort_unstable();
+        a.dedup();
+        if a.is_empty() || a.iter().any(|i| *i >= self.len()) {
+            return None;
+        }
+        let proven = a.iter().map(|i| (*i, self.levels[0][*i].clone())).collect();
+        let mut proofs = Vec::new();
+        for level in self.levels.iter().take(self.levels.len() - 1) {
+            for i in a.iter() {
+                let sibling = i ^ 1;
+                if a.binary_search(&sibling).is_err() {
+                    let side = if sibling & 1 == 0 {
+                        MerkleNodeSide::Left
+                    } else {
+                        MerkleNodeSide::Right
+                    };
+                    proofs.push(MerkleProofLevel {
+                        hash: level.get(sibling).cloned(),
+                        side,
+                    });
+                }
+            }
+            a = a.iter().map(|i| i / 2).collect();
+            a.dedup();
+        }
+        Some(BatchMerkleProof {
+            indices: proven,
+            proofs,
+        })
+    }
+
+    /// Compact inclusion proof for the first leaves with the given data
+    pub fn proof_by_elements(&self, elements: &[Vec<u8>]) -> Option<BatchMerkleProof> {
+        let indices = elements
+            .iter()
+            .map(|e| self.leaves.iter().position(|l| l == e))
+            .collect::<Option<Vec<usize>>>()?;
+        self.proof_by_indices(&indices)
+    }
+}
+
+/// Compact Merkle proof of inclusion of several leaves (scrypto's `BatchMerkleProof`)
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "json",
+    serde(
+        try_from = "json::merkle_proof::BatchMerkleProofJson",
+        into = "json::merkle_proof::BatchMerkleProofJson"
+    )
+)]
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct BatchMerkleProof {
+    /// Indices of the proven leaves with their leaf hashes
+    pub indices: Vec<(usize, Digest32)>,
+    /// Sibling hashes which cannot be computed from the proven leaves, level by level from the
+    /// leaves up. Unlike [`MerkleProof`], the side is the side of the sibling
+    pub proofs: Vec<MerkleProofLevel>,
+}
+
+impl BatchMerkleProof {
+    /// Check the proof against the expected root hash
+    pub fn valid(&self, expected_root: &Digest32) -> bool {
+        let mut e = self.indices.clone();
+        e.sort_by_key(|(idx, _)| *idx);
+        let mut proofs = self.proofs.iter();
+        while !e.is_empty() {
+            let mut next = Vec::with_capacity(e.len());
+            let mut j = 0;
+            while j < e.len() {
+                let (idx, hash) = &e[j];
+                match e.get(j + 1) {
+                    Some((right_idx, right)) if idx & 1 == 0 && *right_idx == idx + 1 => {
+                        next.push((
+                            idx / 2,
+                            internal_node_hash(hash.0.as_ref(), right.0.as_ref()),
+                        ));
+                        j += 2;
+                    }
+                    _ => {
+                        let sibling = match proofs.next() {
+                            Some(s) => s,
+                            None => return false,
+                        };
+                        let parent = match sibling.side {
+                            MerkleNodeSide::Left => {
+                                internal_node_hash(digest_bytes(&sibling.hash), hash.0.as_ref())
+                            }
+                            MerkleNodeSide::Right => {
+                                internal_node_hash(hash.0.as_ref(), digest_bytes(&sibling.hash))
+                            }
+                        };
+                        next.push((idx / 2, parent));
+                        j += 1;
+                    }
+                }
+            }
+            e = next;
+            if e.len() == 1 && proofs.len() == 0 {
+                return &e[0].1 == expected_root;
+            }
+        }
+        false
+    }
+}
+
+/// Merkle tree over the block transactions. For block version 1 the leaves are the transaction
+/// ids, since version 2 the witness ids (see [`Transaction::witness_id`]) follow the transaction
+/// ids.
+pub fn transactions_merkle_tree(txs: &[Transaction], block_version: u8) -> MerkleTree {
+    let ids = txs.iter().map(|tx| tx.id().0.into());
+    let leaves = if block_version == BlockHeader::INITIAL_VERSION {
+        ids.collect()
+    } else {
+        ids.chain(txs.iter().map(|tx| tx.witness_id().to_vec()))
+            .collect()
+    };
+    MerkleTree::new(leaves)
+}
+
+/// Transactions root of the block header for the given block transactions
+pub fn transactions_root(txs: &[Transaction], block_version: u8) -> Digest32 {
+    transactions_merkle_tree(txs, block_version).root_hash()
+}
+
+/// Inclusion proof for the transaction with the given id in the block transactions
+pub fn transaction_proof(
+    txs: &[Transaction],
+    block_version: u8,
+    tx_id: &TxId,
+) -> Option<MerkleProof> {
+    let id_bytes: Vec<u8> = tx_id.0.clone().into();
+    transactions_merkle_tree(txs, block_version).proof_by_element(&id_bytes)
+}
+
+#[cfg(feature = "json")]
+fn digest_from_json(
+    bytes: super::Base16DecodedBytes,
+) -> Result<Option<Digest32>, MerkleProofFromJsonError> {
+    if bytes.0.is_empty() {
+        Ok(None)
+    } else {
+        let len = bytes.0.len();
+        let arr: [u8; 32] = bytes
+            .0
+            .try_into()
+            .map_err(|_| MerkleProofFromJsonError::InvalidHashSize(len))?;
+        Ok(Some(arr.into()))
+    }
+}
+
+#[cfg(feature = "json")]
+fn digest_to_json(digest: Option<Digest32>) -> super::Base16DecodedBytes {
+    super::Base16DecodedBytes(digest.map(Vec::from).unwrap_or_default())
+}
+
+/// Errors on parsing MerkleProof from JSON
+#[cfg(feature = "json")]
+#[derive(Error, PartialEq, Eq, Debug, Clone)]
+pub enum MerkleProofFromJsonError {
+    /// Invalid sibling hash size
+    #[error("Invalid Merkle proof level hash size: {0}")]
+    InvalidHashSize(usize),
+    /// Invalid node side
+    #[error("{0}")]
+    InvalidSide(#[from] InvalidMerkleNodeSide),
+}
+
+#[cfg(feature = "json")]
+impl From<MerkleProof> for json::merkle_proof::MerkleProofJson {
+    fn from(p: MerkleProof) -> Self {
+        json::merkle_proof::MerkleProofJson {
+            leaf_data: super::Base16DecodedBytes(p.leaf_data),
+            levels: p
+                .levels
+                .into_iter()
+                .map(|l| (digest_to_json(l.hash), l.side as u8))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl TryFrom<json::merkle_proof::MerkleProofJson> for MerkleProof {
+    type Error = MerkleProofFromJsonError;
+
+    fn try_from(p: json::merkle_proof::MerkleProofJson) -> Result<Self, Self::Error> {
+        let levels = p
+            .levels
+            .into_iter()
+            .map(|(hash, side)| {
+                Ok(MerkleProofLevel {
+                    hash: digest_from_json(hash)?,
+                    side: side.try_into()?,
+                })
+            })
+            .collect::<Result<Vec<_>, MerkleProofFromJsonError>>()?;
+        Ok(MerkleProof {
+            leaf_data: p.leaf_data.0,
+            levels,
+        })
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<BatchMerkleProof> for json::merkle_proof::BatchMerkleProofJson {
+    fn from(p: BatchMerkleProof) -> Self {
+        json::merkle_proof::BatchMerkleProofJson {
+            indices: p
+                .indices
+                .into_iter()
+                .map(
+                    |(index, digest)| json::merkle_proof::BatchMerkleProofIndexJson {
+                        index,
+                        digest,
+                    },
+                )
+                .collect(),
+            proofs: p
+                .proofs
+                .into_iter()
+                .map(|l| json::merkle_proof::BatchMerkleProofLevelJson {
+                    digest: digest_to_json(l.hash),
+                    side: l.side as u8,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl TryFrom<json::merkle_proof::BatchMerkleProofJson> for BatchMerkleProof {
+    type Error = MerkleProofFromJsonError;
+
+    fn try_from(p: json::merkle_proof::BatchMerkleProofJson) -> Result<Self, Self::Error> {
+        let proofs = p
+            .proofs
+            .into_iter()
+            .map(|l| {
+                Ok(MerkleProofLevel {
+                    hash: digest_from_json(l.digest)?,
+                    side: l.side.try_into()?,
+                })
+            })
+            .collect::<Result<Vec<_>, MerkleProofFromJsonError>>()?;
+        Ok(BatchMerkleProof {
+            indices: p.indices.into_iter().map(|i| (i.index, i.digest)).collect(),
+            proofs,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
+    fn h(bytes: &[u8]) -> Digest32 {
+        blake2b256_hash(bytes)
+    }
+
+    #[test]
+    fn empty_tree_root_is_empty_input_hash() {
+        let tree = MerkleTree::new(vec![]);
+        assert_eq!(tree.root_hash(), h(&[]));
+        assert!(tree.proof_by_index(0).is_none());
+    }
+
+    #[test]
+    fn single_leaf_is_paired_with_empty_node() {
+        let leaf = vec![1u8; 32];
+        let tree = MerkleTree::new(vec![leaf.clone()]);
+        let leaf_hash = h(&[&[0u8][..], &leaf].concat());
+        let expected = h(&[&[1u8][..], leaf_hash.0.as_ref()].concat());
+        assert_eq!(tree.root_hash(), expected);
+        let proof = tree.proof_by_index(0).unwrap();
+        assert_eq!(
+            proof.levels,
+            vec![MerkleProofLevel {
+                hash: None,
+                side: MerkleNodeSide::Left
+            }]
+        );
+        assert!(proof.valid(&expected));
+    }
+
+    #[test]
+    fn three_leaves() {
+        let leaves: Vec<Vec<u8>> = (0u8..3).map(|i| vec![i; 32]).collect();
+        let l: Vec<Digest32> = leaves
+            .iter()
+            .map(|d| h(&[&[0u8][..], d].concat()))
+            .collect();
+        let n01 = h(&[&[1u8][..], l[0].0.as_ref(), l[1].0.as_ref()].concat());
+        let n2 = h(&[&[1u8][..], l[2].0.as_ref()].concat());
+        let root = h(&[&[1u8][..], n01.0.as_ref(), n2.0.as_ref()].concat());
+        let tree = MerkleTree::new(leaves.clone());
+        assert_eq!(tree.root_hash(), root);
+        let proof = tree.proof_by_element(&leaves[2]).unwrap();
+        assert_eq!(
+            proof.levels,
+            vec![
+                MerkleProofLevel {
+                    hash: None,
+                    side: MerkleNodeSide::Left
+                },
+                MerkleProofLevel {
+                    hash: Some(n01),
+                    side: MerkleNodeSide::Right
+                }
+            ]
+        );
+        assert!(proof.valid(&root));
+    }
+
+    #[test]
+    fn invalid_proof() {
+        let leaves: Vec<Vec<u8>> = (0u8..5).map(|i| vec![i; 32]).collect();
+        let tree = MerkleTree::new(leaves);
+        let mut proof = tree.proof_by_index(4).unwrap();
+        proof.leaf_data = vec![9u8; 32];
+        assert!(!proof.valid(&tree.root_hash()));
+        assert!(tree.proof_by_element(&[9u8; 32]).is_none());
+    }
+
+    #[test]
+    fn batch_proof_with_empty_node() {
+        let leaves: Vec<Vec<u8>> = (0u8..5).map(|i| vec![i; 32]).collect();
+        let tree = MerkleTree::new(leaves);
+        let proof = tree.proof_by_indices(&[4, 0]).unwrap();
+        assert_eq!(
+            proof.indices.iter().map(|i| i.0).collect::<Vec<_>>(),
+            vec![0, 4]
+        );
+        assert_eq!(
+            proof.proofs.iter().map(|l| l.side).collect::<Vec<_>>(),
+            vec![
+                MerkleNodeSide::Right,
+                MerkleNodeSide::Right,
+                MerkleNodeSide::Right,
+                MerkleNodeSide::Right
+            ]
+        );
+        // 4th leaf is paired with the empty node on the first two levels
+        assert_eq!(proof.proofs[1].hash, None);
+        assert_eq!(proof.proofs[3].hash, None);
+        assert!(proof.valid(&tree.root_hash()));
+        assert!(!proof.valid(&Digest32::zero()));
+        assert!(tree.proof_by_indices(&[5]).is_none());
+        assert!(tree.proof_by_indices(&[]).is_none());
+
+        let mut missing_proof = proof.clone();
+        missing_proof.proofs.pop();
+        assert!(!missing_proof.valid(&tree.root_hash()));
+        let mut extra_proof = proof;
+        extra_proof.proofs.push(extra_proof.proofs[0].clone());
+        assert!(!extra_proof.valid(&tree.root_hash()));
+    }
+
+    proptest! {
+
+        #[test]
+        fn batch_proofs_valid(leaves in vec(vec(any::<u8>(), 32), 1..40), seed in vec(any::<prop::sample::Index>(), 1..10)) {
+            let tree = MerkleTree::new(leaves.clone());
+            let indices: Vec<usize> = seed.iter().map(|i| i.index(leaves.len())).collect();
+            let proof = tree.proof_by_indices(&indices).unwrap();
+            prop_assert!(proof.valid(&tree.root_hash()));
+            let by_elements = tree.proof_by_elements(&[leaves[indices[0]].clone()]).unwrap();
+            prop_assert!(by_elements.valid(&tree.root_hash()));
+        }
+
+        #[test]
+        fn all_proofs_valid(leaves in vec(vec(any::<u8>(), 32), 1..40)) {
+            let tree = MerkleTree::new(leaves.clone());
+            let root = tree.root_hash();
+            for (i, leaf) in leaves.iter().enumerate() {
+                let proof = tree.proof_by_index(i).unwrap();
+                prop_assert_eq![&proof.leaf_data, leaf];
+                prop_assert!(proof.valid(&root));
+            }
+        }
+
+        #[test]
+        fn transactions_root_by_version(txs in vec(any::<Transaction>(), 1..5)) {
+            let v1_leaves: Vec<Vec<u8>> = txs.iter().map(|tx| tx.id().0.into()).collect();
+            prop_assert_eq![transactions_root(&txs, 1), MerkleTree::new(v1_leaves.clone()).root_hash()];
+            let mut v2_leaves = v1_leaves;
+            v2_leaves.extend(txs.iter().map(|tx| tx.witness_id().to_vec()));
+            let v2_root = MerkleTree::new(v2_leaves).root_hash();
+            prop_assert_eq![transactions_root(&txs, 2), v2_root.clone()];
+            for tx in &txs {
+                let proof = transaction_proof(&txs, 2, &tx.id()).unwrap();
+                prop_assert!(proof.valid(&v2_root));
+            }
+        }
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn proof_json_roundtrip() {
+        let leaves: Vec<Vec<u8>> = (0u8..3).map(|i| vec![i; 32]).collect();
+        let tree = MerkleTree::new(leaves);
+        let proof = tree.proof_by_index(2).unwrap();
+        let j = serde_json::to_string(&proof).unwrap();
+        let expected_levels = format!(
+            r#"[["",0],["{}",1]]"#,
+            base16::encode_lower(proof.levels[1].hash.clone().unwrap().0.as_ref())
+        );
+        assert_eq!(
+            j,
+            format!(
+                r#"{{"leafData":"{}","levels":{}}}"#,
+                base16::encode_lower(&[2u8; 32]),
+                expected_levels
+            )
+        );
+        let parsed: MerkleProof = serde_json::from_str(&j).unwrap();
+        assert_eq!(parsed, proof);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn batch_proof_json_roundtrip() {
+        let leaves: Vec<Vec<u8>> = (0u8..3).map(|i| vec![i; 32]).collect();
+        let tree = MerkleTree::new(leaves);
+        let proof = tree.proof_by_indices(&[2]).unwrap();
+        let j = serde_json::to_string(&proof).unwrap();
+        assert_eq!(
+            j,
+            format!(
+                r#"{{"indices":[{{"index":2,"digest":"{}"}}],"proofs":[{{"digest":"","side":1}},{{"digest":"{}","side":0}}]}}"#,
+                base16::encode_lower(proof.indices[0].1 .0.as_ref()),
+                base16::encode_lower(proof.proofs[1].hash.clone().unwrap().0.as_ref())
+            )
+        );
+        let parsed: BatchMerkleProof = serde_json::from_str(&j).unwrap();
+        assert_eq!(parsed, proof);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn proof_json_invalid_side() {
+        let j = r#"{"leafData":"00","levels":[["",2]]}"#;
+        assert!(serde_json::from_str::<MerkleProof>(j).is_err());
+    }
+}
@@ -0,0 +1,610 @@
+//! NiPoPoW (non-interactive proofs of proof-of-work, KMZ17): the interlinks vector stored in the
+//! block extension and the proofs served by the node's `/nipopow/proof/{m}/{k}` API, which let a
+//! light client bootstrap without downloading all the headers.
+//! See <https://eprint.iacr.org/2017/963.pdf> and `NipopowAlgos` in the node
+
+use num_bigint::BigUint;
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::autolykos_pow_scheme::group_order;
+use super::autolykos_pow_scheme::AutolykosPowScheme;
+use super::autolykos_pow_scheme::AutolykosPowSchemeError;
+use super::block_header::BlockHeader;
+use super::block_header::BlockId;
+use super::extension::Extension;
+use super::extension::ExtensionKey;
+use super::merkle_tree::leaf_hash;
+use super::merkle_tree::BatchMerkleProof;
+use super::Digest32;
+
+/// Height of the genesis block
+pub const GENESIS_HEIGHT: u32 = 1;
+
+/// NiPoPoW errors
+#[derive(Error, PartialEq, Eq, Debug, Clone)]
+pub enum NipopowError {
+    /// Interlinks field value is not a duplicates count followed by a block id
+    #[error("Interlinks improperly packed")]
+    InvalidInterlinksField,
+    /// Interlinks vector of a non-genesis header is empty
+    #[error("Interlinks vector is empty for the non-genesis header {0:?}")]
+    EmptyInterlinks(BlockId),
+    /// Header is not connected to the previous one via its parent id or interlinks
+    #[error("Header {0:?} is not connected to the previous header of the proof")]
+    NotConnected(BlockId),
+    /// Header height is not greater than the height of the previous one
+    #[error("Header {0:?} height is not greater than the previous header height")]
+    HeightNotIncreasing(BlockId),
+    /// Interlinks do not match the extension root of the header
+    #[error("Header {0:?} has invalid interlinks proof")]
+    InvalidInterlinksProof(BlockId),
+    /// Extension is not the one of the header (header id or the extension root differs)
+    #[error("Extension does not match the header {0:?}")]
+    ExtensionMismatch(BlockId),
+    /// Invalid PoW solution
+    #[error("Header {id:?} has invalid PoW: {error}")]
+    Pow {
+        /// Id of the header
+        id: BlockId,
+        /// PoW verification error
+        error: AutolykosPowSchemeError,
+    },
+}
+
+/// Pack the interlinks vector into the extension fields: each run of the same id is stored
+/// as `[INTERLINKS_VECTOR_PREFIX, index of the run start] -> [run length] ++ id`
+pub fn pack_interlinks(links: &[BlockId]) -> Vec<(ExtensionKey, Vec<u8>)> {
+    let mut fields = Vec::new();
+    let mut idx = 0;
+    while idx < links.len() {
+        let link = &links[idx];
+        let qty = links[idx..]
+            .iter()
+            .take(u8::MAX as usize)
+            .take_while(|l| *l == link)
+            .count();
+        let mut value = Vec::with_capacity(1 + Digest32::SIZE);
+        value.push(qty as u8);
+        value.extend_from_slice(link.0 .0.as_ref());
+        fields.push(([Extension::INTERLINKS_VECTOR_PREFIX, idx as u8], value));
+        idx += qty;
+    }
+    fields
+}
+
+/// Unpack the interlinks vector from the extension fields
+pub fn unpack_interlinks(extension: &Extension) -> Result<Vec<BlockId>, NipopowError> {
+    let mut links = Vec::new();
+    for (_, value) in extension.fields_with_prefix(Extension::INTERLINKS_VECTOR_PREFIX) {
+        if value.len() != 1 + Digest32::SIZE {
+            return Err(NipopowError::InvalidInterlinksField);
+        }
+        let mut id = [0u8; Digest32::SIZE];
+        id.copy_from_slice(&value[1..]);
+        let link = BlockId(id.into());
+        links.extend((0..value[0]).map(|_| link.clone()));
+    }
+    Ok(links)
+}
+
+/// Check the batch Merkle proof of the packed interlinks against the extension root
+pub fn check_interlinks_proof(
+    interlinks: &[BlockId],
+    extension_root: &Digest32,
+    proof: &BatchMerkleProof,
+) -> bool {
+    if interlinks.is_empty() {
+        // genesis header has no interlinks
+        return proof.indices.is_empty() && proof.proofs.is_empty();
+    }
+    let mut indices = proof.indices.clone();
+    indices.sort_by_key(|(idx, _)| *idx);
+    let fields = pack_interlinks(interlinks);
+    let leaves_match = fields.len() == indices.len()
+        && fields
+            .iter()
+            .zip(indices.iter())
+            .all(|((key, value), (_, hash))| {
+                leaf_hash(&Extension::field_leaf_data(key, value)) == *hash
+            });
+    leaves_match && proof.valid(extension_root)
+}
+
+/// Batch Merkle proof of the interlinks fields against the extension root
+pub fn interlinks_proof(extension: &Extension) -> Option<BatchMerkleProof> {
+    let leaves: Vec<Vec<u8>> = extension
+        .fields_with_prefix(Extension::INTERLINKS_VECTOR_PREFIX)
+        .map(|(key, value)| Extension::field_leaf_data(key, value))
+        .collect();
+    if leaves.is_empty() {
+        return Some(BatchMerkleProof {
+            indices: Vec::new(),
+            proofs: Vec::new(),
+        });
+    }
+    extension.merkle_tree().proof_by_elements(&leaves)
+}
+
+/// NiPoPoW algorithms parameterized by the PoW scheme
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct NipopowAlgos {
+    pow_scheme: AutolykosPowScheme,
+}
+
+impl NipopowAlgos {
+    /// Create with the given PoW scheme
+    pub fn new(pow_scheme: AutolykosPowScheme) -> Self {
+        NipopowAlgos { pow_scheme }
+    }
+
+    /// PoW scheme
+    pub fn pow_scheme(&self) -> &AutolykosPowScheme {
+        &self.pow_scheme
+    }
+
+    /// Level of the header: `log2(required target / real target)`, where the real target is the
+    /// PoW hit. Genesis header has the maximum level
+    pub fn max_level_of(&self, header: &BlockHeader) -> u32 {
+        if header.height == GENESIS_HEIGHT {
+            return u32::MAX;
+        }
+        let required_target = match header.required_difficulty().to_biguint() {
+            Some(difficulty) if difficulty > BigUint::from(0u8) => group_order() / difficulty,
+            _ => return 0,
+        };
+        let hit = self.pow_scheme.pow_hit(header);
+        if hit == BigUint::from(0u8) {
+            return u32::MAX;
+        }
+        if required_target < hit {
+            return 0;
+        }
+        // the largest level with hit * 2^level <= required target
+        let mut level = required_target.bits() - hit.bits();
+        if (&hit << level) > required_target {
+            level -= 1;
+        }
+        level as u32
+    }
+
+    /// Interlinks vector of the next header given the previous header and its interlinks:
+    /// the genesis id followed by the ids of the last headers with the level of at least
+    /// `n, n - 1, .., 1`
+    pub fn update_interlinks(
+        &self,
+        prev_header: &BlockHeader,
+        prev_interlinks: &[BlockId],
+    ) -> Result<Vec<BlockId>, NipopowError> {
+        if prev_header.height == GENESIS_HEIGHT {
+            return Ok(vec![prev_header.id.clone()]);
+        }
+        let (genesis, tail) = prev_interlinks
+            .split_first()
+            .ok_or_else(|| NipopowError::EmptyInterlinks(prev_header.id.clone()))?;
+        let prev_level = self.max_level_of(prev_header) as usize;
+        if prev_level == 0 {
+            return Ok(prev_interlinks.to_vec());
+        }
+        let kept = tail.len().saturating_sub(prev_level);
+        Ok(std::iter::once(genesis.clone())
+            .chain(tail[..kept].iter().cloned())
+            .chain((0..prev_level).map(|_| prev_header.id.clone()))
+            .collect())
+    }
+
+    /// Score of the chain: the maximum of `2^level * count of headers with at least this level`
+    /// over the levels with at least `m` headers
+    pub fn best_arg(&self, chain: &[&BlockHeader], m: u32) -> BigUint {
+        let levels: Vec<u32> = chain.iter().map(|h| self.max_level_of(h)).collect();
+        let mut best = BigUint::from(chain.len());
+        // a level can not exceed the target size in bits
+        for level in 1..=256u32 {
+            let count = levels.iter().filter(|l| **l >= level).count();
+            if count < m as usize || count == 0 {
+                break;
+            }
+            let score = BigUint::from(count) << level;
+            if score > best {
+                best = score;
+            }
+        }
+        best
+    }
+}
+
+/// Block header with its interlinks vector and the proof of the interlinks against the
+/// extension root
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct PoPowHeader {
+    /// Block header
+    pub header: BlockHeader,
+    /// Interlinks vector from the block extension
+    pub interlinks: Vec<BlockId>,
+    /// Proof of the interlinks fields against the extension root
+    #[cfg_attr(feature = "json", serde(rename = "interlinksProof"))]
+    pub interlinks_proof: BatchMerkleProof,
+}
+
+impl PoPowHeader {
+    /// Create from the header and its extension
+    pub fn new(header: BlockHeader, extension: &Extension) -> Result<PoPowHeader, NipopowError> {
+        if *extension.header_id() != header.id || extension.digest() != header.extension_root {
+            return Err(NipopowError::ExtensionMismatch(header.id));
+        }
+        let interlinks = unpack_interlinks(extension)?;
+        let interlinks_proof = interlinks_proof(extension)
+            .ok_or_else(|| NipopowError::InvalidInterlinksProof(header.id.clone()))?;
+        Ok(PoPowHeader {
+            header,
+            interlinks,
+            interlinks_proof,
+        })
+    }
+
+    /// Block id
+    pub fn id(&self) -> &BlockId {
+        &self.header.id
+    }
+
+    /// Check the interlinks against the extension root of the header
+    pub fn check_interlinks_proof(&self) -> bool {
+        check_interlinks_proof(
+            &self.interlinks,
+            &self.header.extension_root,
+            &self.interlinks_proof,
+        )
+    }
+}
+
+/// NiPoPoW proof: the prefix of superblocks (`m` headers of each level) followed by the suffix of
+/// the last `k` headers
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct NipopowProof {
+    /// Security parameter (minimum number of superblocks of a level)
+    pub m: u32,
+    /// Security parameter (suffix length)
+    pub k: u32,
+    /// Superblocks preceding the suffix
+    pub prefix: Vec<PoPowHeader>,
+    /// First header of the suffix
+    #[cfg_attr(feature = "json", serde(rename = "suffixHead"))]
+    pub suffix_head: PoPowHeader,
+    /// The rest of the suffix
+    #[cfg_attr(feature = "json", serde(rename = "suffixTail"))]
+    pub suffix_tail: Vec<BlockHeader>,
+    /// Whether the proof is for the continuous chain (contains the headers for the difficulty
+    /// recalculation)
+    #[cfg_attr(feature = "json", serde(default))]
+    pub continuous: bool,
+}
+
+impl NipopowProof {
+    /// Headers of the suffix
+    pub fn suffix_headers(&self) -> Vec<&BlockHeader> {
+        std::iter::once(&self.suffix_head.header)
+            .chain(self.suffix_tail.iter())
+            .collect()
+    }
+
+    /// All headers of the proof
+    pub fn headers_chain(&self) -> Vec<&BlockHeader> {
+        self.prefix
+            .iter()
+            .map(|h| &h.header)
+            .chain(self.suffix_headers())
+            .collect()
+    }
+
+    /// Validate the proof: prefix headers are connected via interlinks (or parent ids), suffix
+    /// headers via parent ids, heights are increasing, interlinks match the extension roots and
+    /// every header has a valid PoW solution
+    pub fn validate(&self, algos: &NipopowAlgos) -> Result<(), NipopowError> {
+        let prefix_next = self.prefix.iter().skip(1).chain(Some(&self.suffix_head));
+        for (prev, next) in self.prefix.iter().zip(prefix_next) {
+            if !(next.interlinks.contains(prev.id()) || next.header.parent_id == *prev.id()) {
+                return Err(NipopowError::NotConnected(next.id().clone()));
+            }
+        }
+        let suffix = self.suffix_headers();
+        for (prev, next) in suffix.iter().zip(suffix.iter().skip(1)) {
+            if next.parent_id != prev.id {
+                return Err(NipopowError::NotConnected(next.id.clone()));
+            }
+        }
+        let chain = self.headers_chain();
+        for (prev, next) in chain.iter().zip(chain.iter().skip(1)) {
+            if next.height <= prev.height {
+                return Err(NipopowError::HeightNotIncreasing(next.id.clone()));
+            }
+        }
+        if let Some(h) = self
+            .prefix
+            .iter()
+            .chain(Some(&self.suffix_head))
+            .find(|h| !h.check_interlinks_proof())
+        {
+            return Err(NipopowError::InvalidInterlinksProof(h.id().clone()));
+        }
+        chain.iter().try_for_each(|h| {
+            algos
+                .pow_scheme()
+                .validate(h)
+                .map_err(|error| NipopowError::Pow {
+                    id: h.id.clone(),
+                    error,
+                })
+        })
+    }
+
+    /// Returns true if the proof is valid
+    pub fn is_valid(&self, algos: &NipopowAlgos) -> bool {
+        self.validate(algos).is_ok()
+    }
+
+    /// Compare with the proof of another (possibly forked) chain: a valid proof is better than an
+    /// invalid one, otherwise the proof with the higher score of the headers after the last common
+    /// header wins
+    pub fn is_better_than(&self, that: &NipopowProof, algos: &NipopowAlgos) -> bool {
+        let this_valid = self.is_valid(algos);
+        if !(this_valid && that.is_valid(algos)) {
+            return this_valid;
+        }
+        let this_chain = self.headers_chain();
+        let that_chain = that.headers_chain();
+        match lowest_common_ancestor(&this_chain, &that_chain) {
+            Some(lca) => {
+                algos.best_arg(&diverging_headers(&this_chain, lca), self.m)
+                    > algos.best_arg(&diverging_headers(&that_chain, lca), self.m)
+            }
+            None => false,
+        }
+    }
+}
+
+/// Headers of the chain after the common ancestor
+fn diverging_headers<'a>(chain: &[&'a BlockHeader], lca: &BlockHeader) -> Vec<&'a BlockHeader> {
+    chain
+        .iter()
+        .filter(|h| h.height > lca.height)
+        .cloned()
+        .collect()
+}
+
+/// The last header of the common prefix of the chains
+fn lowest_common_ancestor<'a>(
+    left: &[&'a BlockHeader],
+    right: &[&BlockHeader],
+) -> Option<&'a BlockHeader> {
+    left.iter()
+        .zip(right.iter())
+        .take_while(|(l, r)| l.id == r.id)
+        .last()
+        .map(|(l, _)| *l)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::block_header::encode_compact_bits;
+    use crate::chain::block_header::tests::difficulty_1_child;
+    use crate::chain::block_header::tests::difficulty_1_genesis;
+    use num_bigint::BigInt;
+
+    /// Chain of difficulty 1 (any v2 PoW solution is valid) with the interlinks stored in the
+    /// extensions, header levels vary with the PoW hits
+    fn chain(len: usize) -> Vec<(BlockHeader, Extension)> {
+        let algos = NipopowAlgos::default();
+        let mut genesis = difficulty_1_genesis();
+        genesis.extension_root = Extension::new(genesis.id.clone(), vec![]).unwrap().digest();
+        genesis.id = genesis.calc_id();
+        let genesis_ext = Extension::new(genesis.id.clone(), vec![]).unwrap();
+        let mut res = vec![(genesis, genesis_ext)];
+        while res.len() < len {
+            let (prev, prev_ext) = res.last().unwrap();
+            let links = algos
+                .update_interlinks(prev, &unpack_interlinks(prev_ext).unwrap())
+                .unwrap();
+            let mut fields = vec![([Extension::SYSTEM_PARAMETERS_PREFIX, 1], vec![0, 0, 0, 1])];
+            fields.append(&mut pack_interlinks(&links));
+            let mut header = difficulty_1_child(prev, 1);
+            header.extension_root = Extension::new(header.id.clone(), fields.clone())
+                .unwrap()
+                .digest();
+            header.id = header.calc_id();
+            let ext = Extension::new(header.id.clone(), fields).unwrap();
+            res.push((header, ext));
+        }
+        res
+    }
+
+    fn popow_header(h: &(BlockHeader, Extension)) -> PoPowHeader {
+        PoPowHeader::new(h.0.clone(), &h.1).unwrap()
+    }
+
+    /// Proof with the prefix of the genesis and all the superblocks (level >= 1) before the suffix
+    /// of the last `k` headers
+    fn proof(chain: &[(BlockHeader, Extension)], m: u32, k: usize) -> NipopowProof {
+        let algos = NipopowAlgos::default();
+        let suffix_start = chain.len() - k;
+        let prefix = chain[..suffix_start]
+            .iter()
+            .filter(|h| algos.max_level_of(&h.0) >= 1)
+            .map(popow_header)
+            .collect();
+        NipopowProof {
+            m,
+            k: k as u32,
+            prefix,
+            suffix_head: popow_header(&chain[suffix_start]),
+            suffix_tail: chain[suffix_start + 1..]
+                .iter()
+                .map(|h| h.0.clone())
+                .collect(),
+            continuous: false,
+        }
+    }
+
+    #[test]
+    fn pack_unpack_interlinks() {
+        let a = BlockId(Digest32::zero());
+        let b = BlockId([1u8; 32].into());
+        let links = vec![a.clone(), b.clone(), b.clone(), b, a];
+        let fields = pack_interlinks(&links);
+        assert_eq!(
+            fields.iter().map(|(k, v)| (*k, v[0])).collect::<Vec<_>>(),
+            vec![([1, 0], 1), ([1, 1], 3), ([1, 4], 1)]
+        );
+        let ext = Extension::new(BlockId(Digest32::zero()), fields).unwrap();
+        assert_eq!(unpack_interlinks(&ext).unwrap(), links);
+
+        let invalid =
+            Extension::new(BlockId(Digest32::zero()), vec![([1, 0], vec![1, 2, 3])]).unwrap();
+        assert_eq!(
+            unpack_interlinks(&invalid),
+            Err(NipopowError::InvalidInterlinksField)
+        );
+    }
+
+    #[test]
+    fn interlinks_point_to_the_last_superblocks() {
+        let algos = NipopowAlgos::default();
+        let chain = chain(32);
+        let levels: Vec<u32> = chain.iter().map(|(h, _)| algos.max_level_of(h)).collect();
+        let genesis_id = chain[0].0.id.clone();
+        for (i, (header, ext)) in chain.iter().enumerate().skip(1) {
+            let links = unpack_interlinks(ext).unwrap();
+            assert_eq!(links[0], genesis_id);
+            // link for the level `l` is at `links.len() - l`
+            for level in 1..links.len() {
+                let expected = (0..i)
+                    .rev()
+                    .find(|j| levels[*j] >= level as u32)
+                    .map(|j| chain[j].0.id.clone())
+                    .unwrap();
+                assert_eq!(links[links.len() - level], expected);
+            }
+            let popow = popow_header(&(header.clone(), ext.clone()));
+            assert!(popow.check_interlinks_proof());
+        }
+        assert!(levels.iter().skip(1).any(|l| *l > 0));
+    }
+
+    #[test]
+    fn max_level_of_v1() {
+        let algos = NipopowAlgos::default();
+        let mut header = chain(2)[1].0.clone();
+        header.version = BlockHeader::INITIAL_VERSION;
+        let q = group_order();
+        header.autolykos_solution.pow_distance = &q >> 3;
+        assert_eq!(algos.max_level_of(&header), 3);
+        header.autolykos_solution.pow_distance = (&q >> 3) + 1u8;
+        assert_eq!(algos.max_level_of(&header), 2);
+        header.autolykos_solution.pow_distance = q;
+        assert_eq!(algos.max_level_of(&header), 0);
+    }
+
+    #[test]
+    fn extension_of_other_header() {
+        let chain = chain(3);
+        for (header, ext) in &chain {
+            assert_eq!(ext.digest(), header.extension_root);
+        }
+        assert_eq!(
+            PoPowHeader::new(chain[2].0.clone(), &chain[1].1),
+            Err(NipopowError::ExtensionMismatch(chain[2].0.id.clone()))
+        );
+        let mut other_root = chain[2].0.clone();
+        other_root.extension_root = chain[1].0.extension_root.clone();
+        let other_root_ext =
+            Extension::new(other_root.id.clone(), chain[2].1.fields().to_vec()).unwrap();
+        assert_eq!(
+            PoPowHeader::new(other_root.clone(), &other_root_ext),
+            Err(NipopowError::ExtensionMismatch(other_root.id))
+        );
+    }
+
+    #[test]
+    fn valid_proofs() {
+        let algos = NipopowAlgos::default();
+        let chain = chain(48);
+        let sparse = proof(&chain, 1, 6);
+        assert!(sparse.prefix.len() < 42);
+        assert_eq!(sparse.validate(&algos), Ok(()));
+
+        let full = NipopowProof {
+            prefix: chain[..42].iter().map(popow_header).collect(),
+            ..sparse.clone()
+        };
+        assert_eq!(full.validate(&algos), Ok(()));
+    }
+
+    #[test]
+    fn invalid_proofs() {
+        let algos = NipopowAlgos::default();
+        let chain = chain(48);
+        let valid = proof(&chain, 1, 6);
+
+        let mut missing_suffix_header = valid.clone();
+        missing_suffix_header.suffix_tail.remove(1);
+        assert_eq!(
+            missing_suffix_header.validate(&algos),
+            Err(NipopowError::NotConnected(chain[45].0.id.clone()))
+        );
+
+        let mut wrong_interlinks = valid.clone();
+        wrong_interlinks.suffix_head.interlinks[0] = BlockId(Digest32::zero());
+        assert_eq!(
+            wrong_interlinks.validate(&algos),
+            Err(NipopowError::InvalidInterlinksProof(chain[42].0.id.clone()))
+        );
+
+        let mut unordered = valid.clone();
+        unordered.prefix.swap(0, 1);
+        assert!(unordered.validate(&algos).is_err());
+
+        let mut higher_difficulty = valid;
+        let mut header = chain[44].0.clone();
+        header.n_bits = encode_compact_bits(&BigInt::from(1u64 << 40));
+        header.id = header.calc_id();
+        higher_difficulty.suffix_tail[1] = header;
+        higher_difficulty.suffix_tail.truncate(2);
+        assert!(matches!(
+            higher_difficulty.validate(&algos),
+            Err(NipopowError::Pow { .. })
+        ));
+    }
+
+    #[test]
+    fn better_proof() {
+        let algos = NipopowAlgos::default();
+        let chain = chain(48);
+        // with `m` above any superblocks count only the headers count is compared, so the
+        // result does not depend on the levels of the random chain
+        let longer = proof(&chain, 100, 6);
+        let shorter = proof(&chain[..24], 100, 6);
+        assert!(longer.is_better_than(&shorter, &algos));
+        assert!(!shorter.is_better_than(&longer, &algos));
+
+        let mut invalid = longer.clone();
+        invalid.suffix_tail.remove(1);
+        assert!(shorter.is_better_than(&invalid, &algos));
+        assert!(!invalid.is_better_than(&shorter, &algos));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn proof_json_roundtrip() {
+        let chain = chain(16);
+        let proof = proof(&chain, 1, 4);
+        let j = serde_json::to_value(&proof).unwrap();
+        assert_eq!(j["suffixTail"].as_array().unwrap().len(), 3);
+        assert!(j["suffixHead"]["interlinksProof"]["indices"].is_array());
+        let parsed: NipopowProof = serde_json::from_value(j).unwrap();
+        assert_eq!(parsed, proof);
+        assert!(parsed.is_valid(&NipopowAlgos::default()));
+    }
+}
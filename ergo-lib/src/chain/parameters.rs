@@ -0,0 +1,558 @@
+//! System parameters (storage fee factor, min box value per byte, costs, etc.) adjustable by
+//! the miners voting, and the votes encoding in block headers
+
+use std::collections::BTreeMap;
+use std::convert::TryInto;
+
+use thiserror::Error;
+
+use super::block_header::BlockHeader;
+use super::block_header::Votes;
+use super::extension::Extension;
+use super::extension::ExtensionKey;
+
+/// System parameter (the id is used in the block extension keys and votes)
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+#[repr(u8)]
+pub enum Parameter {
+    /// Storage fee factor (per byte per storage period)
+    StorageFeeFactor = 1,
+    /// Minimum monetary value of a box per byte of its serialized size
+    MinValuePerByte = 2,
+    /// Maximum block size
+    MaxBlockSize = 3,
+    /// Maximum cumulative computational cost of a block
+    MaxBlockCost = 4,
+    /// Token access cost
+    TokenAccessCost = 5,
+    /// Cost per one transaction input
+    InputCost = 6,
+    /// Cost per one data input
+    DataInputCost = 7,
+    /// Cost per one transaction output
+    OutputCost = 8,
+    /// Number of votes collected for the soft-fork
+    SoftForkVotesCollected = 121,
+    /// Height the soft-fork voting started at
+    SoftForkStartingHeight = 122,
+    /// Protocol (block) version
+    BlockVersion = 123,
+}
+
+impl Parameter {
+    /// Parameters which values are changed by the miners voting
+    pub const VOTABLE: [Parameter; 8] = [
+        Parameter::StorageFeeFactor,
+        Parameter::MinValuePerByte,
+        Parameter::MaxBlockSize,
+        Parameter::MaxBlockCost,
+        Parameter::TokenAccessCost,
+        Parameter::InputCost,
+        Parameter::DataInputCost,
+        Parameter::OutputCost,
+    ];
+
+    /// Parameter with the given id
+    pub fn from_id(id: u8) -> Option<Parameter> {
+        match id {
+            1 => Some(Parameter::StorageFeeFactor),
+            2 => Some(Parameter::MinValuePerByte),
+            3 => Some(Parameter::MaxBlockSize),
+            4 => Some(Parameter::MaxBlockCost),
+            5 => Some(Parameter::TokenAccessCost),
+            6 => Some(Parameter::InputCost),
+            7 => Some(Parameter::DataInputCost),
+            8 => Some(Parameter::OutputCost),
+            121 => Some(Parameter::SoftForkVotesCollected),
+            122 => Some(Parameter::SoftForkStartingHeight),
+            123 => Some(Parameter::BlockVersion),
+            _ => None,
+        }
+    }
+
+    /// Parameter id
+    pub fn id(self) -> u8 {
+        self as u8
+    }
+
+    /// Value set on the mainnet launch
+    pub fn launch_value(self) -> i32 {
+        match self {
+            Parameter::StorageFeeFactor => 1_250_000,
+            Parameter::MinValuePerByte => 360,
+            Parameter::MaxBlockSize => 512 * 1024,
+            Parameter::MaxBlockCost => 1_000_000,
+            Parameter::TokenAccessCost => 100,
+            Parameter::InputCost => 2000,
+            Parameter::DataInputCost => 100,
+            Parameter::OutputCost => 100,
+            Parameter::SoftForkVotesCollected => 0,
+            Parameter::SoftForkStartingHeight => 0,
+            Parameter::BlockVersion => 1,
+        }
+    }
+
+    /// Minimal value the parameter can be voted down to
+    pub fn min_value(self) -> i32 {
+        match self {
+            Parameter::MaxBlockSize | Parameter::MaxBlockCost => 16 * 1024,
+            _ => 0,
+        }
+    }
+
+    /// Maximal value the parameter can be voted up to
+    pub fn max_value(self) -> i32 {
+        match self {
+            Parameter::StorageFeeFactor => 2_500_000,
+            Parameter::MinValuePerByte => 10_000,
+            _ => i32::MAX / 2,
+        }
+    }
+
+    /// Change of the parameter with the current value on an approved vote
+    pub fn step(self, current_value: i32) -> i32 {
+        match self {
+            Parameter::StorageFeeFactor => 25_000,
+            Parameter::MinValuePerByte => 10,
+            _ => (current_value / 100).max(1),
+        }
+    }
+}
+
+/// Vote for the parameter change (encoded as a byte in the block header [`Votes`])
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+pub enum Vote {
+    /// Increase the parameter value by its step
+    Increase(Parameter),
+    /// Decrease the parameter value by its step
+    Decrease(Parameter),
+    /// Vote for the soft-fork
+    SoftFork,
+}
+
+impl Vote {
+    /// Byte encoding of the soft-fork vote
+    pub const SOFT_FORK_BYTE: u8 = 120;
+    /// Byte encoding of the empty vote slot
+    pub const NO_VOTE_BYTE: u8 = 0;
+
+    /// Decode the vote from a byte (parameter id for the increase, negated parameter id for the
+    /// decrease). Returns `None` for the empty slot and unknown parameter ids
+    pub fn from_byte(b: u8) -> Option<Vote> {
+        if b == Vote::SOFT_FORK_BYTE {
+            Some(Vote::SoftFork)
+        } else if (b as i8) > 0 {
+            Parameter::from_id(b).map(Vote::Increase)
+        } else if (b as i8) < 0 {
+            Parameter::from_id((b as i8).wrapping_neg() as u8).map(Vote::Decrease)
+        } else {
+            None
+        }
+    }
+
+    /// Encode the vote as a byte
+    pub fn to_byte(self) -> u8 {
+        match self {
+            Vote::Increase(p) => p.id(),
+            Vote::Decrease(p) => (-(p.id() as i8)) as u8,
+            Vote::SoftFork => Vote::SOFT_FORK_BYTE,
+        }
+    }
+}
+
+/// Decode the votes of the block header (empty slots and unknown parameters are skipped)
+pub fn decode_votes(votes: &Votes) -> Vec<Vote> {
+    votes.0.iter().filter_map(|b| Vote::from_byte(*b)).collect()
+}
+
+/// Encode up to 3 votes for the block header (the rest of the slots are left empty)
+pub fn encode_votes(votes: &[Vote]) -> Votes {
+    let mut bytes = [Vote::NO_VOTE_BYTE; 3];
+    bytes
+        .iter_mut()
+        .zip(votes.iter())
+        .for_each(|(b, v)| *b = v.to_byte());
+    Votes(bytes)
+}
+
+/// Count the votes of the voting epoch headers (in the chain order). As in the node, only the
+/// changes proposed in the first block of the epoch are voted for in the rest of the epoch
+pub fn count_epoch_votes<'a, I: IntoIterator<Item = &'a BlockHeader>>(
+    epoch_headers: I,
+) -> BTreeMap<Vote, u32> {
+    let mut headers = epoch_headers.into_iter();
+    let mut counts: BTreeMap<Vote, u32> = match headers.next() {
+        Some(first) => decode_votes(&first.votes)
+            .into_iter()
+            .map(|v| (v, 1))
+            .collect(),
+        None => return BTreeMap::new(),
+    };
+    for header in headers {
+        for vote in decode_votes(&header.votes) {
+            if let Some(count) = counts.get_mut(&vote) {
+                *count += 1;
+            }
+        }
+    }
+    counts
+}
+
+/// Voting settings of the network
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct VotingSettings {
+    /// Number of blocks in the voting epoch
+    pub voting_length: u32,
+    /// Number of the voting epochs for the soft-fork
+    pub soft_fork_epochs: u32,
+    /// Number of epochs between the approved soft-fork and its activation
+    pub activation_epochs: u32,
+}
+
+impl VotingSettings {
+    /// Mainnet settings
+    pub fn mainnet() -> Self {
+        VotingSettings {
+            voting_length: 1024,
+            soft_fork_epochs: 32,
+            activation_epochs: 32,
+        }
+    }
+
+    /// Whether the change with the given number of votes in the epoch is approved
+    pub fn change_approved(&self, votes_count: u32) -> bool {
+        votes_count > self.voting_length / 2
+    }
+}
+
+/// Parameters errors
+#[derive(Error, PartialEq, Eq, Debug, Clone)]
+pub enum ParametersError {
+    /// Parameter value in the extension is not a 4-byte integer
+    #[error("Invalid size {size} of the parameter {id} value")]
+    InvalidValueSize {
+        /// Parameter id
+        id: u8,
+        /// Value size
+        size: usize,
+    },
+}
+
+/// System parameters at the given height
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Parameters {
+    height: u32,
+    table: BTreeMap<Parameter, i32>,
+}
+
+impl Default for Parameters {
+    /// Mainnet launch parameters
+    fn default() -> Self {
+        let table = Parameter::VOTABLE
+            .iter()
+            .chain(std::iter::once(&Parameter::BlockVersion))
+            .map(|p| (*p, p.launch_value()))
+            .collect();
+        Parameters { height: 0, table }
+    }
+}
+
+impl Parameters {
+    /// Key of the soft-fork disabling rules field (in the system parameters extension fields)
+    /// which is not a parameter value
+    const SOFT_FORK_DISABLING_RULES_KEY: u8 = 124;
+
+    /// Create parameters with the given values
+    pub fn new(height: u32, table: BTreeMap<Parameter, i32>) -> Self {
+        Parameters { height, table }
+    }
+
+    /// Parse the parameters from the system parameters fields of the extension of the block at the
+    /// given height (the first block of the voting epoch). Unknown parameter ids are skipped
+    pub fn parse_extension(height: u32, extension: &Extension) -> Result<Self, ParametersError> {
+        let table =
+            extension
+                .fields_with_prefix(Extension::SYSTEM_PARAMETERS_PREFIX)
+                .filter(|(key, _)| key[1] != Parameters::SOFT_FORK_DISABLING_RULES_KEY)
+                .filter_map(|(key, value)| Parameter::from_id(key[1]).map(|p| (p, value)))
+                .map(|(p, value)| {
+                    let bytes: [u8; 4] = value.as_slice().try_into().map_err(|_| {
+                        ParametersError::InvalidValueSize {
+                            id: p.id(),
+                            size: value.len(),
+                        }
+                    })?;
+                    Ok((p, i32::from_be_bytes(bytes)))
+                })
+                .collect::<Result<BTreeMap<_, _>, ParametersError>>()?;
+        Ok(Parameters { height, table })
+    }
+
+    /// System parameters fields for the extension of the first block of the voting epoch
+    pub fn to_extension_fields(&self) -> Vec<(ExtensionKey, Vec<u8>)> {
+        self.table
+            .iter()
+            .map(|(p, value)| {
+                (
+                    [Extension::SYSTEM_PARAMETERS_PREFIX, p.id()],
+                    value.to_be_bytes().to_vec(),
+                )
+            })
+            .collect()
+    }
+
+    /// Height of the block the parameters were set at
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Parameter values
+    pub fn table(&self) -> &BTreeMap<Parameter, i32> {
+        &self.table
+    }
+
+    /// Value of the parameter (the launch value if it's not in the table)
+    pub fn get(&self, parameter: Parameter) -> i32 {
+        self.table
+            .get(&parameter)
+            .copied()
+            .unwrap_or_else(|| parameter.launch_value())
+    }
+
+    /// Storage fee factor (per byte per storage period)
+    pub fn storage_fee_factor(&self) -> i32 {
+        self.get(Parameter::StorageFeeFactor)
+    }
+
+    /// Minimum monetary value of a box per byte of its serialized size
+    pub fn min_value_per_byte(&self) -> i32 {
+        self.get(Parameter::MinValuePerByte)
+    }
+
+    /// Maximum block size
+    pub fn max_block_size(&self) -> i32 {
+        self.get(Parameter::MaxBlockSize)
+    }
+
+    /// Maximum cumulative computational cost of a block
+    pub fn max_block_cost(&self) -> i32 {
+        self.get(Parameter::MaxBlockCost)
+    }
+
+    /// Token access cost
+    pub fn token_access_cost(&self) -> i32 {
+        self.get(Parameter::TokenAccessCost)
+    }
+
+    /// Cost per one transaction input
+    pub fn input_cost(&self) -> i32 {
+        self.get(Parameter::InputCost)
+    }
+
+    /// Cost per one data input
+    pub fn data_input_cost(&self) -> i32 {
+        self.get(Parameter::DataInputCost)
+    }
+
+    /// Cost per one transaction output
+    pub fn output_cost(&self) -> i32 {
+        self.get(Parameter::OutputCost)
+    }
+
+    /// Protocol (block) version
+    pub fn block_version(&self) -> i32 {
+        self.get(Parameter::BlockVersion)
+    }
+
+    /// Parameters for the first block of the next voting epoch at the given height, with the
+    /// changes approved by the given epoch votes applied (soft-fork voting is not handled).
+    /// As in the node, a parameter is changed by the whole step if it is below the maximum (for
+    /// an increase) or above the minimum (for a decrease), so it may end up past the limit
+    pub fn update(
+        &self,
+        height: u32,
+        epoch_votes: &BTreeMap<Vote, u32>,
+        settings: &VotingSettings,
+    ) -> Parameters {
+        let mut table = self.table.clone();
+        for (vote, count) in epoch_votes {
+            if !settings.change_approved(*count) {
+                continue;
+            }
+            match vote {
+                Vote::Increase(p) => {
+                    let value = self.get(*p);
+                    if value < p.max_value() {
+                        table.insert(*p, value.saturating_add(p.step(value)));
+                    }
+                }
+                Vote::Decrease(p) => {
+                    let value = self.get(*p);
+                    if value > p.min_value() {
+                        table.insert(*p, value.saturating_sub(p.step(value)));
+                    }
+                }
+                Vote::SoftFork => (),
+            }
+        }
+        Parameters { height, table }
+    }
+
+    /// Cost of the interpreter initialization (added to every transaction validation cost)
+    pub const INTERPRETER_INIT_COST: u64 = 10_000;
+
+    /// Initial cost of the transaction validation (before the scripts evaluation) for the given
+    /// number of inputs, data inputs and outputs
+    pub fn tx_initial_cost(&self, inputs: usize, data_inputs: usize, outputs: usize) -> u64 {
+        Parameters::INTERPRETER_INIT_COST
+            + inputs as u64 * self.input_cost() as u64
+            + data_inputs as u64 * self.data_input_cost() as u64
+            + outputs as u64 * self.output_cost() as u64
+    }
+
+    /// Cost of the transaction tokens access for the given total number of tokens and number of
+    /// distinct token ids in inputs and outputs
+    pub fn tx_token_access_cost(
+        &self,
+        input_tokens: usize,
+        input_distinct_tokens: usize,
+        output_tokens: usize,
+        output_distinct_tokens: usize,
+    ) -> u64 {
+        (input_tokens + input_distinct_tokens + output_tokens + output_distinct_tokens) as u64
+            * self.token_access_cost() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::block_header::BlockId;
+    use crate::chain::Digest32;
+    use proptest::prelude::*;
+    use sigma_test_util::force_any_val;
+
+    impl Arbitrary for Parameter {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Self>;
+
+        fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+            prop::sample::select(Parameter::VOTABLE.to_vec()).boxed()
+        }
+    }
+
+    proptest! {
+
+        #[test]
+        fn vote_byte_roundtrip(p in any::<Parameter>()) {
+            prop_assert_eq![Vote::from_byte(Vote::Increase(p).to_byte()), Some(Vote::Increase(p))];
+            prop_assert_eq![Vote::from_byte(Vote::Decrease(p).to_byte()), Some(Vote::Decrease(p))];
+        }
+
+        #[test]
+        fn extension_roundtrip(values in proptest::collection::vec(any::<i32>(), 8)) {
+            let table = Parameter::VOTABLE.iter().copied().zip(values).collect();
+            let params = Parameters::new(1024, table);
+            let ext = Extension::new(BlockId(Digest32::zero()), params.to_extension_fields()).unwrap();
+            prop_assert_eq![Parameters::parse_extension(1024, &ext), Ok(params)];
+        }
+    }
+
+    #[test]
+    fn decode_header_votes() {
+        let votes = Votes([2, 0xfc, 120]);
+        assert_eq!(
+            decode_votes(&votes),
+            vec![
+                Vote::Increase(Parameter::MinValuePerByte),
+                Vote::Decrease(Parameter::MaxBlockCost),
+                Vote::SoftFork
+            ]
+        );
+        assert_eq!(encode_votes(&decode_votes(&votes)), votes);
+        assert!(decode_votes(&Votes([0, 0, 0])).is_empty());
+        assert!(decode_votes(&Votes([99, 0, 0])).is_empty());
+    }
+
+    #[test]
+    fn parse_extension() {
+        let ext = Extension::new(
+            BlockId(Digest32::zero()),
+            vec![
+                ([0, 1], vec![0, 0x13, 0x12, 0xd0]),
+                ([0, 2], vec![0, 0, 1, 0x68]),
+                ([0, 124], vec![0, 1, 2]),
+                ([0, 99], vec![0, 0, 0, 1]),
+                ([1, 0], vec![7u8; 33]),
+            ],
+        )
+        .unwrap();
+        let params = Parameters::parse_extension(1024, &ext).unwrap();
+        assert_eq!(params.height(), 1024);
+        assert_eq!(params.table().len(), 2);
+        assert_eq!(params.storage_fee_factor(), 1_250_000);
+        assert_eq!(params.min_value_per_byte(), 360);
+
+        let invalid =
+            Extension::new(BlockId(Digest32::zero()), vec![([0, 4], vec![0, 1])]).unwrap();
+        assert_eq!(
+            Parameters::parse_extension(1024, &invalid),
+            Err(ParametersError::InvalidValueSize { id: 4, size: 2 })
+        );
+    }
+
+    #[test]
+    fn update() {
+        let settings = VotingSettings::mainnet();
+        let params = Parameters::default();
+        let mut votes = BTreeMap::new();
+        votes.insert(Vote::Increase(Parameter::MaxBlockCost), 513);
+        votes.insert(Vote::Decrease(Parameter::MinValuePerByte), 600);
+        votes.insert(Vote::Increase(Parameter::StorageFeeFactor), 512);
+        votes.insert(Vote::SoftFork, 1000);
+        let updated = params.update(1024, &votes, &settings);
+        assert_eq!(updated.height(), 1024);
+        assert_eq!(updated.max_block_cost(), 1_010_000);
+        assert_eq!(updated.min_value_per_byte(), 350);
+        assert_eq!(updated.storage_fee_factor(), 1_250_000);
+        assert_eq!(updated.block_version(), 1);
+
+        let mut table = BTreeMap::new();
+        table.insert(Parameter::StorageFeeFactor, 2_490_000);
+        table.insert(Parameter::MaxBlockSize, 16 * 1024 + 1);
+        let params = Parameters::new(0, table);
+        let mut votes = BTreeMap::new();
+        votes.insert(Vote::Increase(Parameter::StorageFeeFactor), 1024);
+        votes.insert(Vote::Decrease(Parameter::MaxBlockSize), 1024);
+        let updated = params.update(1024, &votes, &settings);
+        // not clamped to the limits
+        assert_eq!(updated.storage_fee_factor(), 2_515_000);
+        assert_eq!(updated.max_block_size(), 16 * 1024 + 1 - 163);
+        // already past the limits
+        let updated = updated.update(2048, &votes, &settings);
+        assert_eq!(updated.storage_fee_factor(), 2_515_000);
+        assert_eq!(updated.max_block_size(), 16 * 1024 + 1 - 163);
+    }
+
+    #[test]
+    fn epoch_votes() {
+        let mut headers: Vec<BlockHeader> =
+            (0..4).map(|_| force_any_val::<BlockHeader>()).collect();
+        headers[0].votes = encode_votes(&[Vote::Increase(Parameter::InputCost)]);
+        headers[1].votes = encode_votes(&[
+            Vote::Increase(Parameter::InputCost),
+            Vote::Decrease(Parameter::OutputCost),
+        ]);
+        headers[3].votes = encode_votes(&[Vote::Increase(Parameter::InputCost)]);
+        let counts = count_epoch_votes(&headers);
+        assert_eq!(counts.len(), 1);
+        assert_eq!(counts[&Vote::Increase(Parameter::InputCost)], 3);
+    }
+
+    #[test]
+    fn tx_costs() {
+        let params = Parameters::default();
+        assert_eq!(params.tx_initial_cost(2, 1, 3), 10_000 + 4000 + 100 + 300);
+        assert_eq!(params.tx_token_access_cost(3, 2, 2, 1), 800);
+    }
+}
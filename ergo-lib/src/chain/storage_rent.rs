@@ -0,0 +1,204 @@
+//! Storage rent (demurrage): boxes older than [`STORAGE_PERIOD`] can be spent by anyone who
+//! recreates them with the storage fee deducted (or collects the whole box value if it does not
+//! cover the fee)
+
+use ergotree_interpreter::sigma_protocol::prover::ContextExtension;
+use ergotree_ir::mir::constant::TryExtractInto;
+use ergotree_ir::serialization::SigmaSerializable;
+
+use super::ergo_box::ErgoBox;
+use super::ergo_box::ErgoBoxCandidate;
+use super::parameters::Parameters;
+
+/// Number of blocks (4 years) after the box creation when the storage fee can be charged
+pub const STORAGE_PERIOD: u32 = 4 * 365 * 24 * 30;
+
+/// Id of the context extension variable with the index of the output the expired box is recreated
+/// in (`Short`)
+pub const STORAGE_INDEX_VAR_ID: u8 = 127;
+
+/// Whether the storage fee can be charged for the box at the given height
+pub fn is_expired(b: &ErgoBox, current_height: u32) -> bool {
+    current_height.saturating_sub(b.creation_height) >= STORAGE_PERIOD
+}
+
+/// Storage fee for the box (its serialized size times the storage fee factor)
+pub fn storage_fee(b: &ErgoBox, parameters: &Parameters) -> u64 {
+    b.sigma_serialize_bytes().len() as u64 * parameters.storage_fee_factor().max(0) as u64
+}
+
+/// Whether the box value does not cover the storage fee (the box can be spent as a whole)
+pub fn storage_fee_not_covered(b: &ErgoBox, parameters: &Parameters) -> bool {
+    *b.value.as_u64() <= storage_fee(b, parameters)
+}
+
+/// Index of the output the expired box is recreated in, from the spending input context extension
+pub fn recreated_output_index(extension: &ContextExtension) -> Option<usize> {
+    extension
+        .values
+        .get(&STORAGE_INDEX_VAR_ID)
+        .cloned()
+        .and_then(|c| c.try_extract_into::<i16>().ok())
+        .filter(|idx| *idx >= 0)
+        .map(|idx| idx as usize)
+}
+
+/// Check the output recreating the expired box: created at the current height, with the value not
+/// less than the box value minus the storage fee, the same script, tokens and registers.
+/// Any output passes if the box value does not cover the storage fee
+pub fn check_expired_box(
+    b: &ErgoBox,
+    output: &ErgoBoxCandidate,
+    current_height: u32,
+    parameters: &Parameters,
+) -> bool {
+    if storage_fee_not_covered(b, parameters) {
+        return true;
+    }
+    let min_output_value = b.value.as_u64() - storage_fee(b, parameters);
+    output.creation_height == current_height
+        && *output.value.as_u64() >= min_output_value
+        && output.ergo_tree == b.ergo_tree
+        && output.tokens == b.tokens
+        && output.additional_registers == b.additional_registers
+}
+
+/// Whether the input spends the expired box via the storage rent path: the box is expired, the
+/// proof is empty and the context extension points to the valid recreated output
+pub fn is_valid_storage_rent_spending(
+    b: &ErgoBox,
+    extension: &ContextExtension,
+    outputs: &[ErgoBoxCandidate],
+    current_height: u32,
+    parameters: &Parameters,
+) -> bool {
+    is_expired(b, current_height)
+        && recreated_output_index(extension)
+            .and_then(|idx| outputs.get(idx))
+            .map(|output| check_expired_box(b, output, current_height, parameters))
+            .unwrap_or(false)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::chain::ergo_box::box_builder::ErgoBoxCandidateBuilder;
+    use crate::chain::ergo_box::BoxValue;
+    use crate::chain::ergo_box::NonMandatoryRegisters;
+    use crate::chain::token::Token;
+    use crate::chain::transaction::TxId;
+    use ergotree_interpreter::sigma_protocol::private_input::DlogProverInput;
+    use ergotree_ir::ergo_tree::ErgoTree;
+    use ergotree_ir::mir::expr::Expr;
+    use ergotree_ir::sigma_protocol::sigma_boolean::ProveDlog;
+    use sigma_test_util::force_any_val;
+    use std::convert::TryInto;
+
+    /// P2PK box with the given value created at the given height
+    pub(crate) fn new_box(value: u64, creation_height: u32) -> ErgoBox {
+        new_box_with(
+            value,
+            creation_height,
+            force_any_val::<DlogProverInput>().public_image(),
+            vec![],
+        )
+    }
+
+    /// Box of the given public key with the given value and tokens created at the given height
+    pub(crate) fn new_box_with(
+        value: u64,
+        creation_height: u32,
+        pk: ProveDlog,
+        tokens: Vec<Token>,
+    ) -> ErgoBox {
+        ErgoBox::new(
+            value.try_into().unwrap(),
+            ErgoTree::from(Expr::Const(pk.into())),
+            tokens,
+            NonMandatoryRegisters::empty(),
+            creation_height,
+            TxId::zero(),
+            0,
+        )
+    }
+
+    fn recreated(b: &ErgoBox, value: u64, height: u32) -> ErgoBoxCandidate {
+        ErgoBoxCandidateBuilder::new(value.try_into().unwrap(), b.ergo_tree.clone(), height)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn expiration() {
+        let b = new_box(BoxValue::SAFE_USER_MIN.as_u64() * 1000, 1);
+        assert!(!is_expired(&b, STORAGE_PERIOD));
+        assert!(is_expired(&b, STORAGE_PERIOD + 1));
+        assert!(!is_expired(&b, 0));
+    }
+
+    #[test]
+    fn fee() {
+        let b = new_box(BoxValue::SAFE_USER_MIN.as_u64() * 1000, 1);
+        let params = Parameters::default();
+        let fee = storage_fee(&b, &params);
+        assert_eq!(fee, b.sigma_serialize_bytes().len() as u64 * 1_250_000);
+        assert!(!storage_fee_not_covered(&b, &params));
+        assert!(storage_fee_not_covered(
+            &new_box(BoxValue::MIN_RAW, 1),
+            &params
+        ));
+    }
+
+    #[test]
+    fn recreated_box_check() {
+        let height = STORAGE_PERIOD + 1;
+        let b = new_box(BoxValue::SAFE_USER_MIN.as_u64() * 1000, 1);
+        let params = Parameters::default();
+        let value = b.value.as_u64() - storage_fee(&b, &params);
+        assert!(check_expired_box(
+            &b,
+            &recreated(&b, value, height),
+            height,
+            &params
+        ));
+        assert!(!check_expired_box(
+            &b,
+            &recreated(&b, value - 1, height),
+            height,
+            &params
+        ));
+        assert!(!check_expired_box(
+            &b,
+            &recreated(&b, value, height - 1),
+            height,
+            &params
+        ));
+        let mut other_script = recreated(&b, value, height);
+        other_script.ergo_tree = force_any_val::<ErgoTree>();
+        assert_eq!(
+            check_expired_box(&b, &other_script, height, &params),
+            other_script.ergo_tree == b.ergo_tree
+        );
+
+        let mut extension = ContextExtension::empty();
+        let outputs = vec![recreated(&b, value, height)];
+        assert!(!is_valid_storage_rent_spending(
+            &b, &extension, &outputs, height, &params
+        ));
+        extension.values.insert(STORAGE_INDEX_VAR_ID, 0i16.into());
+        assert!(is_valid_storage_rent_spending(
+            &b, &extension, &outputs, height, &params
+        ));
+        assert!(!is_valid_storage_rent_spending(
+            &b,
+            &extension,
+            &outputs,
+            STORAGE_PERIOD,
+            &params
+        ));
+        extension.values.insert(STORAGE_INDEX_VAR_ID, 1i16.into());
+        assert!(!is_valid_storage_rent_spending(
+            &b, &extension, &outputs, height, &params
+        ));
+    }
+}
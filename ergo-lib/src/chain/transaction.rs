@@ -145,6 +145,20 @@ impl Transaction {
     pub fn id(&self) -> TxId {
         self.tx_id.clone()
     }
+
+    /// Hash of the concatenated spending proofs of the inputs without the first byte
+    /// (`witnessSerializedId` in the node), included in the transactions Merkle tree since block
+    /// version 2
+    pub fn witness_id(&self) -> [u8; 31] {
+        let proofs: Vec<u8> = self
+            .inputs
+            .iter()
+            .flat_map(|i| Vec::from(i.spending_proof.proof.clone()))
+            .collect();
+        let mut id = [0u8; 31];
+        id.copy_from_slice(&blake2b256_hash(&proofs).0[1..]);
+        id
+    }
 }
 
 impl SigmaSerializable for Transaction {
@@ -347,6 +361,11 @@ pub mod tests {
     }"#;
         let res = serde_json::from_str(json);
         let t: Transaction = res.unwrap();
+        // the only input has an empty proof, blake2b256("") without the first byte (0e)
+        assert_eq!(
+            base16::encode_lower(&t.witness_id()),
+            "5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8"
+        );
         let tx_id_str: String = t.tx_id.into();
         assert_eq!(
             "9148408c04c2e38a6402a7950d6157730fa7d49e9ab3b9cadec481d7769918e9",
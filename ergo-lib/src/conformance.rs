@@ -0,0 +1,398 @@
+//! Conformance test vectors
+//!
+//! Data-driven harness checking the interpreter against the expected results (e.g. exported from
+//! the reference implementation, sigmastate). A suite is a JSON file with the cases (ErgoTree
+//! bytes, context, expected reduction result, cost, proof and verification result) run through
+//! [`Evaluator`] and [`Verifier`]. The bundled regression corpus is in the `conformance` folder of
+//! this crate (see `conformance/README.md`).
+
+use std::sync::Arc;
+
+use ergotree_interpreter::eval::context::Context;
+use ergotree_interpreter::eval::env::Env;
+use ergotree_interpreter::eval::EvalError;
+use ergotree_interpreter::eval::Evaluator;
+use ergotree_interpreter::sigma_protocol::prover::ContextExtension;
+use ergotree_interpreter::sigma_protocol::prover::ProofBytes;
+use ergotree_interpreter::sigma_protocol::verifier::TestVerifier;
+use ergotree_interpreter::sigma_protocol::verifier::Verifier;
+use ergotree_interpreter::sigma_protocol::verifier::VerifierError;
+use ergotree_ir::ergo_tree::ErgoTree;
+use ergotree_ir::ergo_tree::ErgoTreeParsingError;
+use ergotree_ir::mir::header::PreHeader;
+use ergotree_ir::serialization::SerializationError;
+use ergotree_ir::serialization::SigmaSerializable;
+use ergotree_ir::sigma_protocol::sigma_boolean::SigmaBoolean;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::chain::ergo_box::ErgoBox;
+use crate::chain::Base16DecodedBytes;
+use crate::wallet::signing::ErgoBoxArena;
+
+/// Set of conformance cases
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConformanceSuite {
+    /// What the suite covers and where the vectors come from
+    pub description: String,
+    /// IR nodes and methods the cases are meant to cover (`Expr::node_name`, `BinOp::<kind>`,
+    /// `GlobalVars::<var>` or `<type>.<method>`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub covers: Vec<String>,
+    /// Context for the cases that do not define their own
+    pub context: ConformanceContext,
+    /// Cases
+    pub cases: Vec<ConformanceCase>,
+}
+
+/// Context the script is evaluated in
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConformanceContext {
+    /// Current height
+    pub height: u32,
+    /// Index of the SELF box in `boxes_to_spend`
+    #[serde(default)]
+    pub self_index: usize,
+    /// Spending transaction inputs
+    pub boxes_to_spend: Vec<ErgoBox>,
+    /// Spending transaction data inputs
+    #[serde(default)]
+    pub data_boxes: Vec<ErgoBox>,
+    /// Spending transaction outputs
+    #[serde(default)]
+    pub outputs: Vec<ErgoBox>,
+    /// Context variables
+    #[serde(
+        default = "ContextExtension::empty",
+        with = "crate::chain::json::context_extension::ContextExtensionSerde"
+    )]
+    pub extension: ContextExtension,
+}
+
+/// Single conformance test vector
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConformanceCase {
+    /// Name (unique in the suite)
+    pub name: String,
+    /// Source of the script (for reference only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script: Option<String>,
+    /// Serialized ErgoTree
+    pub tree: Base16DecodedBytes,
+    /// Context, the suite context is used if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<ConformanceContext>,
+    /// Expected result of the reduction (serialized `SigmaBoolean`), `None` if the reduction is
+    /// expected to fail
+    pub reduced: Option<Base16DecodedBytes>,
+    /// Expected cost of the reduction (checked if set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<u64>,
+    /// Proof and the expected verification result (checked if set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification: Option<ConformanceVerification>,
+}
+
+/// Proof verification vector
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConformanceVerification {
+    /// Proof
+    pub proof: Base16DecodedBytes,
+    /// Signed message
+    pub message: Base16DecodedBytes,
+    /// Expected verification result
+    pub result: bool,
+}
+
+/// Conformance case failure
+#[derive(Error, PartialEq, Debug, Clone)]
+pub enum ConformanceError {
+    /// Failed to parse ErgoTree
+    #[error("ErgoTree parsing error: {0}")]
+    TreeParsing(SerializationError),
+    /// Failed to parse the root expression of ErgoTree
+    #[error("ErgoTree root expression parsing error: {0:?}")]
+    RootParsing(ErgoTreeParsingError),
+    /// ErgoTree is serialized to different bytes
+    #[error("ErgoTree is serialized to different bytes: {0}")]
+    TreeRoundtrip(String),
+    /// Failed to parse the expected reduction result
+    #[error("Invalid expected SigmaBoolean: {0}")]
+    InvalidExpectedReduced(SerializationError),
+    /// Invalid context
+    #[error("Invalid context: {0}")]
+    InvalidContext(String),
+    /// Reduction result differs from the expected one
+    #[error("Reduction result mismatch, expected: {expected:?}, actual: {actual:?}")]
+    Reduction {
+        /// Expected result (`None` if failure is expected)
+        expected: Option<SigmaBoolean>,
+        /// Actual result
+        actual: Result<SigmaBoolean, EvalError>,
+    },
+    /// Cost differs from the expected one
+    #[error("Cost mismatch, expected: {expected}, actual: {actual:?}")]
+    Cost {
+        /// Expected cost
+        expected: u64,
+        /// Actual cost (`None` if the reduction failed)
+        actual: Option<u64>,
+    },
+    /// Verifier error
+    #[error("Verifier error: {0}")]
+    Verifier(VerifierError),
+    /// Verification result differs from the expected one
+    #[error("Verification result mismatch, expected: {expected}, actual: {actual}")]
+    Verification {
+        /// Expected verification result
+        expected: bool,
+        /// Actual verification result
+        actual: bool,
+    },
+}
+
+impl ConformanceSuite {
+    /// Run all cases, returns the names of the failed cases with the failures
+    pub fn run(&self) -> Vec<(String, ConformanceError)> {
+        self.cases
+            .iter()
+            .filter_map(|case| {
+                case.run(&self.context)
+                    .err()
+                    .map(|e| (case.name.clone(), e))
+            })
+            .collect()
+    }
+}
+
+impl ConformanceContext {
+    /// Interpreter context
+    pub fn to_context(&self) -> Result<Context, ConformanceError> {
+        let self_box = self
+            .boxes_to_spend
+            .get(self.self_index)
+            .cloned()
+            .ok_or_else(|| {
+                ConformanceError::InvalidContext(format!(
+                    "self index {} is out of bounds",
+                    self.self_index
+                ))
+            })?;
+        let ids = |boxes: &[ErgoBox]| boxes.iter().map(|b| b.box_id().into()).collect();
+        let pre_header = PreHeader {
+            height: self.height,
+            ..PreHeader::dummy()
+        };
+        Ok(Context {
+            height: self.height,
+            self_box: self_box.box_id().into(),
+            outputs: ids(&self.outputs),
+            data_inputs: ids(&self.data_boxes),
+            inputs: ids(&self.boxes_to_spend),
+            box_arena: Arc::new(ErgoBoxArena::new(
+                self_box,
+                self.outputs.clone(),
+                self.data_boxes.clone(),
+                self.boxes_to_spend.clone(),
+            )),
+            pre_header,
+            extension: self.extension.clone(),
+        })
+    }
+}
+
+impl ConformanceCase {
+    /// Check the case, `default_context` is used if the case does not have its own context
+    pub fn run(&self, default_context: &ConformanceContext) -> Result<(), ConformanceError> {
+        let tree =
+            ErgoTree::sigma_parse_bytes(&self.tree.0).map_err(ConformanceError::TreeParsing)?;
+        let tree_bytes = tree.sigma_serialize_bytes();
+        if tree_bytes != self.tree.0 {
+            return Err(ConformanceError::TreeRoundtrip(base16::encode_lower(
+                &tree_bytes,
+            )));
+        }
+        let expr = tree.proposition().map_err(ConformanceError::RootParsing)?;
+        let expected = self
+            .reduced
+            .as_ref()
+            .map(|bytes| SigmaBoolean::sigma_parse_bytes(&bytes.0))
+            .transpose()
+            .map_err(ConformanceError::InvalidExpectedReduced)?;
+        let ctx = Arc::new(
+            self.context
+                .as_ref()
+                .unwrap_or(default_context)
+                .to_context()?,
+        );
+        let verifier = TestVerifier;
+        let res = verifier.reduce_to_crypto(expr.as_ref(), &Env::empty(), ctx.clone());
+        let reduced = res.as_ref().map(|r| r.sigma_prop.clone()).ok();
+        if reduced != expected {
+            return Err(ConformanceError::Reduction {
+                expected,
+                actual: res.map(|r| r.sigma_prop),
+            });
+        }
+        if let Some(expected) = self.cost {
+            let actual = res.as_ref().map(|r| r.cost).ok();
+            if actual != Some(expected) {
+                return Err(ConformanceError::Cost { expected, actual });
+            }
+        }
+        if let Some(v) = &self.verification {
+            let proof = if v.proof.0.is_empty() {
+                ProofBytes::Empty
+            } else {
+                ProofBytes::Some(v.proof.0.clone())
+            };
+            let res = verifier
+                .verify(&tree, &Env::empty(), ctx, proof, &v.message.0)
+                .map_err(ConformanceError::Verifier)?;
+            if res.result != v.result {
+                return Err(ConformanceError::Verification {
+                    expected: v.result,
+                    actual: res.result,
+                });
+            }
+            if let Some(expected) = self.cost {
+                if res.cost != expected {
+                    return Err(ConformanceError::Cost {
+                        expected,
+                        actual: Some(res.cost),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use ergotree_ir::mir::expr::Expr;
+    use ergotree_ir::mir::visitor;
+    use std::collections::BTreeSet;
+
+    fn bundled_suites() -> Vec<(&'static str, ConformanceSuite)> {
+        vec![
+            ("opcodes", include_str!("../conformance/opcodes.json")),
+            ("methods", include_str!("../conformance/methods.json")),
+            ("signatures", include_str!("../conformance/signatures.json")),
+        ]
+        .into_iter()
+        .map(|(name, json)| (name, serde_json::from_str(json).unwrap()))
+        .collect()
+    }
+
+    fn node_key(e: &Expr) -> String {
+        match e {
+            Expr::BinOp(op) => format!("BinOp::{:?}", op.kind),
+            Expr::GlobalVars(op) => format!("GlobalVars::{:?}", op),
+            Expr::MethodCall(mc) => {
+                format!("{}.{}", mc.method.obj_type.type_name(), mc.method.name())
+            }
+            Expr::ProperyCall(pc) => {
+                format!("{}.{}", pc.method.obj_type.type_name(), pc.method.name())
+            }
+            _ => e.node_name().to_string(),
+        }
+    }
+
+    #[test]
+    fn bundled_suites_pass() {
+        for (name, suite) in bundled_suites() {
+            let failures = suite.run();
+            assert!(
+                failures.is_empty(),
+                "{}: {}",
+                name,
+                failures
+                    .iter()
+                    .map(|(case, e)| format!("{}: {}", case, e))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            );
+        }
+    }
+
+    #[test]
+    fn cost_mismatch_fails() {
+        let (_, suite) = bundled_suites().into_iter().next().unwrap();
+        let passing = suite
+            .cases
+            .iter()
+            .find(|case| case.reduced.is_some())
+            .unwrap();
+        let case = ConformanceCase {
+            cost: Some(u64::MAX),
+            ..passing.clone()
+        };
+        assert!(matches!(
+            case.run(&suite.context),
+            Err(ConformanceError::Cost {
+                expected: u64::MAX,
+                actual: Some(_)
+            })
+        ));
+        let failing = suite
+            .cases
+            .iter()
+            .find(|case| case.reduced.is_none())
+            .unwrap();
+        let case = ConformanceCase {
+            cost: Some(1),
+            ..failing.clone()
+        };
+        assert_eq!(
+            case.run(&suite.context),
+            Err(ConformanceError::Cost {
+                expected: 1,
+                actual: None
+            })
+        );
+    }
+
+    #[test]
+    fn bundled_suites_json_roundtrip() {
+        for (name, suite) in bundled_suites() {
+            let json = serde_json::to_string(&suite).unwrap();
+            let suite_parsed: ConformanceSuite = serde_json::from_str(&json).unwrap();
+            assert_eq!(suite_parsed, suite, "{}", name);
+        }
+    }
+
+    #[test]
+    fn bundled_suites_cover_declared_nodes() {
+        for (name, suite) in bundled_suites() {
+            assert!(!suite.covers.is_empty(), "{}", name);
+            let covered: BTreeSet<String> = suite
+                .cases
+                .iter()
+                .map(|case| {
+                    ErgoTree::sigma_parse_bytes(&case.tree.0)
+                        .unwrap()
+                        .proposition()
+                        .unwrap()
+                })
+                .fold(BTreeSet::new(), |acc, expr| {
+                    visitor::fold(&expr, acc, |mut acc, e| {
+                        acc.insert(node_key(e));
+                        acc
+                    })
+                });
+            let missing: Vec<&String> = suite
+                .covers
+                .iter()
+                .filter(|key| !covered.contains(*key))
+                .collect();
+            assert!(missing.is_empty(), "{}: not covered: {:?}", name, missing);
+        }
+    }
+}
@@ -14,6 +14,8 @@
 #![deny(broken_intra_doc_links)]
 
 pub mod chain;
+#[cfg(feature = "json")]
+pub mod conformance;
 pub mod constants;
 pub mod wallet;
 
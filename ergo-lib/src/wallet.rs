@@ -3,6 +3,7 @@
 pub mod box_selector;
 pub mod secret_key;
 pub mod signing;
+pub mod storage_rent;
 pub mod tx_builder;
 
 use ergotree_interpreter::sigma_protocol::private_input::PrivateInput;
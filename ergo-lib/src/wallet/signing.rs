@@ -2,10 +2,13 @@
 
 use ergotree_interpreter::sigma_protocol::prover::hint::HintsBag;
 use std::collections::HashMap;
-use std::rc::Rc;
+use std::sync::Arc;
 
 use crate::chain::ergo_box::BoxId;
+use crate::chain::storage_rent::is_valid_storage_rent_spending;
+use crate::chain::transaction::input::prover_result::ProverResult;
 use crate::chain::transaction::Input;
+use crate::chain::transaction::UnsignedInput;
 use crate::chain::{
     ergo_box::ErgoBox,
     ergo_state_context::ErgoStateContext,
@@ -14,8 +17,11 @@ use crate::chain::{
 
 use ergotree_interpreter::eval::context::Context;
 use ergotree_interpreter::eval::env::Env;
+use ergotree_interpreter::sigma_protocol::prover::ProofBytes;
 use ergotree_interpreter::sigma_protocol::prover::ProverError;
 use ergotree_interpreter::sigma_protocol::prover::{ContextExtension, Prover};
+use ergotree_interpreter::sigma_protocol::verifier::Verifier;
+use ergotree_interpreter::sigma_protocol::verifier::VerifierError;
 use ergotree_ir::ir_ergo_box::IrBoxId;
 use ergotree_ir::ir_ergo_box::IrErgoBox;
 use ergotree_ir::ir_ergo_box::IrErgoBoxArena;
@@ -36,6 +42,23 @@ pub enum TxSigningError {
     ContextError(String),
 }
 
+/// Errors on transaction input verification
+#[derive(Error, PartialEq, Eq, Debug, Clone)]
+pub enum TxVerifyError {
+    /// input index is out of bounds
+    #[error("Input not found (index {0})")]
+    InputNotFound(usize),
+    /// failed to find an input in boxes_to_spend
+    #[error("Input box not found (index {0})")]
+    InputBoxNotFound(usize),
+    /// Context creation error
+    #[error("Context error: {0}")]
+    ContextError(String),
+    /// error on verifying the input proof
+    #[error("Verifier error: {0}")]
+    VerifierError(VerifierError),
+}
+
 /// Transaction and an additional info required for signing
 #[derive(PartialEq, Debug, Clone)]
 pub struct TransactionContext {
@@ -75,12 +98,12 @@ impl ErgoBoxArena {
 }
 
 impl IrErgoBoxArena for ErgoBoxArena {
-    fn get(&self, id: &IrBoxId) -> Result<Rc<dyn IrErgoBox>, IrErgoBoxArenaError> {
+    fn get(&self, id: &IrBoxId) -> Result<Arc<dyn IrErgoBox>, IrErgoBoxArenaError> {
         self.0
             .get(&id.into())
             .cloned()
             .ok_or_else(|| IrErgoBoxArenaError(format!("ErgoBox with id {0:?} not found", id)))
-            .map(|b| Rc::new(b) as Rc<dyn IrErgoBox>)
+            .map(|b| Arc::new(b) as Arc<dyn IrErgoBox>)
     }
 }
 
@@ -103,6 +126,13 @@ pub fn make_context(
         .enumerate()
         .map(|(idx, b)| ErgoBox::from_box_candidate(b, tx_ctx.spending_tx.id(), idx as u16))
         .collect();
+    let extension = tx_ctx
+        .spending_tx
+        .inputs
+        .iter()
+        .find(|i| i.box_id == self_box.box_id())
+        .map(|i| i.extension.clone())
+        .unwrap_or_else(ContextExtension::empty);
     let data_inputs: Vec<ErgoBox> = tx_ctx.data_boxes.clone();
     let self_box_ir = self_box.box_id().into();
     let outputs_ir = outputs.iter().map(|b| b.box_id().into()).collect();
@@ -112,7 +142,7 @@ pub fn make_context(
         .map(|b| b.box_id().into())
         .collect();
     let data_inputs_ir = data_inputs.iter().map(|b| b.box_id().into()).collect();
-    let box_arena = Rc::new(ErgoBoxArena::new(
+    let box_arena = Arc::new(ErgoBoxArena::new(
         self_box,
         outputs,
         data_inputs,
@@ -126,11 +156,13 @@ pub fn make_context(
         data_inputs: data_inputs_ir,
         inputs: inputs_ir,
         pre_header: state_ctx.pre_header.clone(),
-        extension: ContextExtension::empty(),
+        extension,
     })
 }
 
-/// Signs a transaction (generating proofs for inputs)
+/// Signs a transaction (generating proofs for inputs).
+/// Inputs spending expired boxes via the storage rent path (see [`crate::chain::storage_rent`])
+/// get an empty proof
 pub fn sign_transaction(
     prover: &dyn Prover,
     tx_context: TransactionContext,
@@ -140,12 +172,27 @@ pub fn sign_transaction(
     let message_to_sign = tx.bytes_to_sign();
     let mut signed_inputs: Vec<Input> = vec![];
     tx.inputs.iter().enumerate().try_for_each(|(idx, input)| {
-        if let Some(input_box) = tx_context
+        if let Some((box_idx, input_box)) = tx_context
             .boxes_to_spend
             .iter()
-            .find(|b| b.box_id() == input.box_id)
+            .enumerate()
+            .find(|(_, b)| b.box_id() == input.box_id)
         {
-            let ctx = Rc::new(make_context(state_context, &tx_context, idx)?);
+            if is_valid_storage_rent_spending(
+                input_box,
+                &input.extension,
+                &tx.output_candidates,
+                state_context.pre_header.height,
+                &state_context.parameters,
+            ) {
+                let proof = ProverResult {
+                    proof: ProofBytes::Empty,
+                    extension: input.extension.clone(),
+                };
+                signed_inputs.push(Input::new(input.box_id.clone(), proof));
+                return Ok(());
+            }
+            let ctx = Arc::new(make_context(state_context, &tx_context, box_idx)?);
             prover
                 .prove(
                     &input_box.ergo_tree,
@@ -170,6 +217,64 @@ pub fn sign_transaction(
     ))
 }
 
+/// Verifies the spending proof of the transaction input with the given index.
+/// An empty proof of the expired box is accepted if the box is spent via the storage rent path
+/// (see [`crate::chain::storage_rent`]), otherwise the box script is verified with `verifier`
+pub fn verify_tx_input_proof(
+    verifier: &dyn Verifier,
+    tx: &Transaction,
+    boxes_to_spend: &[ErgoBox],
+    data_boxes: &[ErgoBox],
+    state_context: &ErgoStateContext,
+    input_idx: usize,
+) -> Result<bool, TxVerifyError> {
+    let input = tx
+        .inputs
+        .get(input_idx)
+        .ok_or(TxVerifyError::InputNotFound(input_idx))?;
+    let (box_idx, input_box) = boxes_to_spend
+        .iter()
+        .enumerate()
+        .find(|(_, b)| b.box_id() == input.box_id)
+        .ok_or(TxVerifyError::InputBoxNotFound(input_idx))?;
+    let proof = &input.spending_proof;
+    if proof.proof == ProofBytes::Empty
+        && is_valid_storage_rent_spending(
+            input_box,
+            &proof.extension,
+            &tx.output_candidates,
+            state_context.pre_header.height,
+            &state_context.parameters,
+        )
+    {
+        return Ok(true);
+    }
+    let tx_context = TransactionContext {
+        spending_tx: UnsignedTransaction::new(
+            tx.inputs
+                .iter()
+                .map(|i| UnsignedInput::new(i.box_id.clone(), i.spending_proof.extension.clone()))
+                .collect(),
+            tx.data_inputs.clone(),
+            tx.output_candidates.clone(),
+        ),
+        boxes_to_spend: boxes_to_spend.to_vec(),
+        data_boxes: data_boxes.to_vec(),
+    };
+    let ctx = make_context(state_context, &tx_context, box_idx)
+        .map_err(|e| TxVerifyError::ContextError(e.to_string()))?;
+    verifier
+        .verify(
+            &input_box.ergo_tree,
+            &Env::empty(),
+            Arc::new(ctx),
+            proof.proof.clone(),
+            &tx.bytes_to_sign(),
+        )
+        .map(|res| res.result)
+        .map_err(TxVerifyError::VerifierError)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -177,8 +282,7 @@ mod tests {
     use ergotree_interpreter::sigma_protocol::private_input::PrivateInput;
     use ergotree_interpreter::sigma_protocol::prover::TestProver;
     use ergotree_interpreter::sigma_protocol::verifier::TestVerifier;
-    use ergotree_interpreter::sigma_protocol::verifier::Verifier;
-    use ergotree_interpreter::sigma_protocol::verifier::VerifierError;
+    use ergotree_ir::address::Address;
     use ergotree_ir::address::AddressEncoder;
     use ergotree_ir::address::NetworkPrefix;
     use proptest::collection::vec;
@@ -193,7 +297,7 @@ mod tests {
     };
     use ergotree_ir::ergo_tree::ErgoTree;
     use ergotree_ir::mir::expr::Expr;
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     fn verify_tx_proofs(
         tx: &Transaction,
@@ -206,10 +310,12 @@ mod tests {
                 .iter()
                 .find(|b| b.box_id() == input.box_id)
                 .unwrap();
+            let mut ctx = force_any_val::<Context>();
+            ctx.extension = input.spending_proof.extension.clone();
             let res = verifier.verify(
                 &b.ergo_tree,
                 &Env::empty(),
-                Rc::new(force_any_val::<Context>()),
+                Arc::new(ctx),
                 input.spending_proof.proof.clone(),
                 &message,
             )?;
@@ -255,6 +361,121 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_tx_signing_p2sh() {
+        let secret = force_any_val::<DlogProverInput>();
+        let script = ErgoTree::from(Expr::Const(secret.public_image().into()));
+        let address = Address::p2sh_from_script(&script).unwrap();
+        let box_to_spend = ErgoBox::new(
+            BoxValue::SAFE_USER_MIN,
+            address.script().unwrap(),
+            vec![],
+            NonMandatoryRegisters::empty(),
+            0,
+            TxId::zero(),
+            0,
+        );
+        let mut extension = ContextExtension::empty();
+        extension.values.insert(
+            Address::P2SH_SCRIPT_VAR_ID,
+            Address::p2sh_script_bytes(&script).unwrap().into(),
+        );
+        let candidate = ErgoBoxCandidateBuilder::new(BoxValue::SAFE_USER_MIN, script, 0)
+            .build()
+            .unwrap();
+        let tx = UnsignedTransaction::new(
+            vec![UnsignedInput::new(box_to_spend.box_id(), extension)],
+            vec![],
+            vec![candidate],
+        );
+        let tx_context = TransactionContext {
+            spending_tx: tx,
+            boxes_to_spend: vec![box_to_spend.clone()],
+            data_boxes: vec![],
+        };
+        let prover = TestProver {
+            secrets: vec![PrivateInput::DlogProverInput(secret)],
+        };
+        let signed_tx =
+            sign_transaction(&prover, tx_context.clone(), &ErgoStateContext::dummy()).unwrap();
+        assert!(verify_tx_proofs(&signed_tx, &[box_to_spend]).unwrap());
+
+        // the script is not revealed
+        let mut tx_context = tx_context;
+        tx_context.spending_tx.inputs[0].extension = ContextExtension::empty();
+        assert!(sign_transaction(&prover, tx_context, &ErgoStateContext::dummy()).is_err());
+    }
+
+    #[test]
+    fn test_tx_signing_storage_rent() {
+        use crate::chain::storage_rent::tests::new_box_with;
+        use crate::chain::storage_rent::STORAGE_PERIOD;
+        use crate::wallet::storage_rent::build_storage_rent_claim;
+        use ergotree_ir::mir::header::PreHeader;
+
+        let secret = force_any_val::<DlogProverInput>();
+        let expired_box = new_box_with(
+            *BoxValue::SAFE_USER_MIN.as_u64() * 1000,
+            1,
+            secret.public_image(),
+            vec![],
+        );
+        let tree = expired_box.ergo_tree.clone();
+        let state_context = ErgoStateContext {
+            pre_header: PreHeader {
+                height: STORAGE_PERIOD + 1,
+                ..PreHeader::dummy()
+            },
+            ..ErgoStateContext::dummy()
+        };
+        let tx = build_storage_rent_claim(
+            std::slice::from_ref(&expired_box),
+            &state_context,
+            BoxValue::SAFE_USER_MIN,
+            &force_any_val::<Address>(),
+        )
+        .unwrap();
+        let tx_context = TransactionContext {
+            spending_tx: tx,
+            boxes_to_spend: vec![expired_box.clone()],
+            data_boxes: vec![],
+        };
+        // anyone can spend the expired box
+        let prover = TestProver { secrets: vec![] };
+        let signed_tx = sign_transaction(&prover, tx_context.clone(), &state_context).unwrap();
+        assert_eq!(signed_tx.inputs[0].spending_proof.proof, ProofBytes::Empty);
+        assert!(verify_tx_input_proof(
+            &TestVerifier,
+            &signed_tx,
+            std::slice::from_ref(&expired_box),
+            &[],
+            &state_context,
+            0
+        )
+        .unwrap());
+
+        // the box is not recreated, so the owner's proof is required
+        let mut tampered = tx_context;
+        tampered.spending_tx.output_candidates[0].tokens = vec![];
+        tampered.spending_tx.output_candidates[0].ergo_tree = tree.clone();
+        tampered.spending_tx.output_candidates[0].value = BoxValue::SAFE_USER_MIN;
+        assert!(sign_transaction(&prover, tampered.clone(), &state_context).is_err());
+        let owner = TestProver {
+            secrets: vec![PrivateInput::DlogProverInput(secret)],
+        };
+        let signed_tx = sign_transaction(&owner, tampered, &state_context).unwrap();
+        assert_ne!(signed_tx.inputs[0].spending_proof.proof, ProofBytes::Empty);
+        assert!(verify_tx_input_proof(
+            &TestVerifier,
+            &signed_tx,
+            &[expired_box],
+            &[],
+            &state_context,
+            0
+        )
+        .unwrap());
+    }
+
     #[test]
     fn test_proof_from_mainnet() {
         use crate::chain::transaction::Transaction;
@@ -347,7 +568,7 @@ mod tests {
         let ver_res = verifier.verify(
             &ergo_tree,
             &Env::empty(),
-            Rc::new(force_any_val::<Context>()),
+            Arc::new(force_any_val::<Context>()),
             tx.inputs.get(1).unwrap().spending_proof.proof.clone(),
             message.as_slice(),
         );
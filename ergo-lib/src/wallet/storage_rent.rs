@@ -0,0 +1,327 @@
+//! Storage rent claim transaction builder
+
+use std::convert::TryFrom;
+
+use ergotree_interpreter::sigma_protocol::prover::ContextExtension;
+use ergotree_ir::address::Address;
+use ergotree_ir::serialization::SerializationError;
+use ergotree_ir::serialization::SigmaSerializable;
+use thiserror::Error;
+
+use crate::chain::contract::Contract;
+use crate::chain::ergo_box::box_builder::{ErgoBoxCandidateBuilder, ErgoBoxCandidateBuilderError};
+use crate::chain::ergo_box::{BoxId, BoxValue, BoxValueError, ErgoBox, ErgoBoxCandidate};
+use crate::chain::ergo_state_context::ErgoStateContext;
+use crate::chain::storage_rent::{is_expired, storage_fee, STORAGE_INDEX_VAR_ID};
+use crate::chain::token::{Token, TokenAmountError};
+use crate::chain::transaction::unsigned::UnsignedTransaction;
+use crate::chain::transaction::{Transaction, UnsignedInput};
+
+use super::tx_builder::miner_fee_box_builder;
+
+/// Errors of the storage rent claim transaction building
+#[derive(Error, PartialEq, Eq, Debug, Clone)]
+pub enum StorageRentClaimError {
+    /// No boxes to claim the storage fee from
+    #[error("No boxes to claim")]
+    NoBoxes,
+    /// Too many boxes to claim in one transaction
+    #[error("Too many boxes to claim ({0})")]
+    TooManyBoxes(usize),
+    /// Storage fee cannot be charged for the box yet
+    #[error("Box {0:?} is not expired")]
+    BoxNotExpired(BoxId),
+    /// Box value is lower than the minimal value of the recreated box
+    #[error("Box {0:?} value is too low to recreate it")]
+    BoxValueTooLow(BoxId),
+    /// Collected storage fees do not cover the miner's fee
+    #[error(
+        "Collected storage fees ({collected} nanoERGs) do not cover the miner's fee {fee_amount:?}"
+    )]
+    NotEnoughCollected {
+        /// Total collected storage fees
+        collected: u64,
+        /// Miner's fee
+        fee_amount: BoxValue,
+    },
+    /// Too many distinct tokens of the boxes collected as a whole to move them to the reward box
+    #[error("Too many tokens to move to the reward box ({0})")]
+    TooManyTokens(usize),
+    /// Box value error
+    #[error("Box value error: {0}")]
+    BoxValueError(#[from] BoxValueError),
+    /// Token amount error
+    #[error("Token amount error: {0}")]
+    TokenAmountError(#[from] TokenAmountError),
+    /// Serialization error
+    #[error("Serialization error: {0}")]
+    SerializationError(#[from] SerializationError),
+    /// ErgoBoxCandidate error
+    #[error("ErgoBoxCandidateBuilder error: {0}")]
+    ErgoBoxCandidateBuilderError(#[from] ErgoBoxCandidateBuilderError),
+}
+
+/// Build the transaction claiming the storage fee from the expired boxes at the current height
+/// (from `state_context`). Each box with the value covering the storage fee is recreated with the
+/// fee deducted (outputs in the order of the boxes), the rest of the boxes are collected as a whole.
+/// The collected fees minus `fee_amount` (miner's fee) and the tokens of the boxes collected as a
+/// whole are sent to `reward_address`.
+/// Inputs have empty proofs, the recreated output index is in the context extension variable
+/// [`STORAGE_INDEX_VAR_ID`] (see [`super::signing::sign_transaction`])
+pub fn build_storage_rent_claim(
+    expired_boxes: &[ErgoBox],
+    state_context: &ErgoStateContext,
+    fee_amount: BoxValue,
+    reward_address: &Address,
+) -> Result<UnsignedTransaction, StorageRentClaimError> {
+    if expired_boxes.is_empty() {
+        return Err(StorageRentClaimError::NoBoxes);
+    }
+    if expired_boxes.len() + 2 > Transaction::MAX_OUTPUTS_COUNT
+        || expired_boxes.len() > i16::MAX as usize
+    {
+        return Err(StorageRentClaimError::TooManyBoxes(expired_boxes.len()));
+    }
+    let height = state_context.pre_header.height;
+    let parameters = &state_context.parameters;
+    let min_value_per_byte = parameters.min_value_per_byte().max(0) as u64;
+    let mut collected: u64 = 0;
+    let mut recreated: Vec<ErgoBoxCandidate> = vec![];
+    // output indices, `None` for the boxes collected as a whole (pointing to the reward box)
+    let mut output_indices: Vec<Option<usize>> = vec![];
+    // tokens of the boxes collected as a whole (in the order of appearance)
+    let mut collected_tokens: Vec<Token> = vec![];
+    for b in expired_boxes {
+        if !is_expired(b, height) {
+            return Err(StorageRentClaimError::BoxNotExpired(b.box_id()));
+        }
+        let fee = storage_fee(b, parameters);
+        let value = *b.value.as_u64();
+        if value <= fee {
+            collected += value;
+            output_indices.push(None);
+            for token in &b.tokens {
+                match collected_tokens
+                    .iter_mut()
+                    .find(|t| t.token_id == token.token_id)
+                {
+                    Some(t) => t.amount = t.amount.checked_add(&token.amount)?,
+                    None => collected_tokens.push(token.clone()),
+                }
+            }
+            continue;
+        }
+        let mut candidate = ErgoBoxCandidate::from(b.clone());
+        candidate.creation_height = height;
+        let min_value = (candidate.sigma_serialize_bytes().len() as u64 * min_value_per_byte)
+            .max(BoxValue::MIN_RAW);
+        if value < min_value {
+            return Err(StorageRentClaimError::BoxValueTooLow(b.box_id()));
+        }
+        let recreated_value = (value - fee).max(min_value);
+        candidate.value = BoxValue::try_from(recreated_value)?;
+        collected += value - recreated_value;
+        output_indices.push(Some(recreated.len()));
+        recreated.push(candidate);
+    }
+    let reward_value = collected
+        .checked_sub(*fee_amount.as_u64())
+        .ok_or(StorageRentClaimError::NotEnoughCollected {
+            collected,
+            fee_amount,
+        })
+        .and_then(|v| {
+            BoxValue::try_from(v).map_err(|_| StorageRentClaimError::NotEnoughCollected {
+                collected,
+                fee_amount,
+            })
+        })?;
+    let reward_index = recreated.len();
+    let mut reward_box = ErgoBoxCandidateBuilder::new(
+        reward_value,
+        Contract::pay_to_address(reward_address)?.ergo_tree(),
+        height,
+    );
+    reward_box.set_min_box_value_per_byte(min_value_per_byte as u32);
+    if collected_tokens.len() > ErgoBox::MAX_TOKENS_COUNT {
+        return Err(StorageRentClaimError::TooManyTokens(collected_tokens.len()));
+    }
+    collected_tokens
+        .into_iter()
+        .for_each(|t| reward_box.add_token(t));
+    let mut output_candidates = recreated;
+    output_candidates.push(reward_box.build()?);
+    let mut fee_box = miner_fee_box_builder(fee_amount, height);
+    fee_box.set_min_box_value_per_byte(min_value_per_byte as u32);
+    output_candidates.push(fee_box.build()?);
+    let inputs = expired_boxes
+        .iter()
+        .zip(output_indices)
+        .map(|(b, idx)| {
+            let mut extension = ContextExtension::empty();
+            extension.values.insert(
+                STORAGE_INDEX_VAR_ID,
+                (idx.unwrap_or(reward_index) as i16).into(),
+            );
+            UnsignedInput::new(b.box_id(), extension)
+        })
+        .collect();
+    Ok(UnsignedTransaction::new(inputs, vec![], output_candidates))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::parameters::{Parameter, Parameters};
+    use crate::chain::storage_rent::recreated_output_index;
+    use crate::chain::storage_rent::tests::{new_box, new_box_with};
+    use crate::chain::storage_rent::{check_expired_box, STORAGE_PERIOD};
+    use ergotree_interpreter::sigma_protocol::private_input::DlogProverInput;
+    use ergotree_ir::mir::header::PreHeader;
+    use sigma_test_util::force_any_val;
+    use std::collections::BTreeMap;
+
+    fn state_context(height: u32) -> ErgoStateContext {
+        let mut ctx = ErgoStateContext::dummy();
+        ctx.pre_header = PreHeader {
+            height,
+            ..PreHeader::dummy()
+        };
+        ctx
+    }
+
+    #[test]
+    fn test_claim() {
+        let height = STORAGE_PERIOD + 10;
+        let ctx = state_context(height);
+        let rich = new_box(BoxValue::UNITS_PER_ERGO as u64 * 10, 1);
+        let dust = new_box(BoxValue::MIN_RAW * 3, 5);
+        let reward_address = force_any_val::<Address>();
+        let tx = build_storage_rent_claim(
+            &[rich.clone(), dust.clone()],
+            &ctx,
+            BoxValue::SAFE_USER_MIN,
+            &reward_address,
+        )
+        .unwrap();
+        assert_eq!(tx.output_candidates.len(), 3);
+        assert_eq!(recreated_output_index(&tx.inputs[0].extension), Some(0));
+        assert_eq!(recreated_output_index(&tx.inputs[1].extension), Some(1));
+        assert!(check_expired_box(
+            &rich,
+            &tx.output_candidates[0],
+            height,
+            &ctx.parameters
+        ));
+        assert!(check_expired_box(
+            &dust,
+            &tx.output_candidates[1],
+            height,
+            &ctx.parameters
+        ));
+        let fee = storage_fee(&rich, &ctx.parameters);
+        assert_eq!(
+            *tx.output_candidates[1].value.as_u64(),
+            fee + dust.value.as_u64() - BoxValue::SAFE_USER_MIN.as_u64()
+        );
+        assert_eq!(
+            tx.output_candidates[1].ergo_tree,
+            reward_address.script().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_claim_dust_tokens() {
+        let height = STORAGE_PERIOD + 10;
+        let ctx = state_context(height);
+        let rich = new_box(BoxValue::UNITS_PER_ERGO as u64 * 10, 1);
+        let token = force_any_val::<Token>();
+        let other_token = force_any_val::<Token>();
+        let dust = new_box_with(
+            BoxValue::MIN_RAW * 3,
+            5,
+            force_any_val::<DlogProverInput>().public_image(),
+            vec![token.clone(), other_token.clone()],
+        );
+        let dust_2 = new_box_with(
+            BoxValue::MIN_RAW * 3,
+            5,
+            force_any_val::<DlogProverInput>().public_image(),
+            vec![token.clone()],
+        );
+        let reward_address = force_any_val::<Address>();
+        let tx = build_storage_rent_claim(
+            &[rich, dust, dust_2],
+            &ctx,
+            BoxValue::SAFE_USER_MIN,
+            &reward_address,
+        )
+        .unwrap();
+        assert_eq!(recreated_output_index(&tx.inputs[1].extension), Some(1));
+        assert_eq!(recreated_output_index(&tx.inputs[2].extension), Some(1));
+        let amount = token.amount.checked_add(&token.amount).unwrap();
+        assert_eq!(
+            tx.output_candidates[1].tokens,
+            vec![
+                Token {
+                    token_id: token.token_id,
+                    amount
+                },
+                other_token
+            ]
+        );
+        assert!(tx.output_candidates[0].tokens.is_empty());
+        assert!(tx.output_candidates[2].tokens.is_empty());
+    }
+
+    #[test]
+    fn test_claim_errors() {
+        let ctx = state_context(STORAGE_PERIOD + 10);
+        let address = force_any_val::<Address>();
+        assert_eq!(
+            build_storage_rent_claim(&[], &ctx, BoxValue::SAFE_USER_MIN, &address),
+            Err(StorageRentClaimError::NoBoxes)
+        );
+        let fresh = new_box(BoxValue::UNITS_PER_ERGO as u64, 11);
+        assert_eq!(
+            build_storage_rent_claim(
+                std::slice::from_ref(&fresh),
+                &ctx,
+                BoxValue::SAFE_USER_MIN,
+                &address
+            ),
+            Err(StorageRentClaimError::BoxNotExpired(fresh.box_id()))
+        );
+        let dust = new_box(BoxValue::MIN_RAW, 1);
+        assert!(matches!(
+            build_storage_rent_claim(&[dust], &ctx, BoxValue::SAFE_USER_MIN, &address),
+            Err(StorageRentClaimError::NotEnoughCollected { .. })
+        ));
+    }
+
+    #[test]
+    fn test_claim_fee_box_min_value() {
+        let height = STORAGE_PERIOD + 10;
+        let mut ctx = state_context(height);
+        let mut table: BTreeMap<Parameter, i32> = ctx.parameters.table().clone();
+        table.insert(Parameter::MinValuePerByte, 100_000);
+        ctx.parameters = Parameters::new(height, table);
+        let rich = new_box(BoxValue::UNITS_PER_ERGO as u64 * 10, 1);
+        let address = force_any_val::<Address>();
+        // the miner's fee box is about 100 bytes
+        assert!(matches!(
+            build_storage_rent_claim(
+                std::slice::from_ref(&rich),
+                &ctx,
+                BoxValue::SAFE_USER_MIN,
+                &address
+            ),
+            Err(StorageRentClaimError::ErgoBoxCandidateBuilderError(
+                ErgoBoxCandidateBuilderError::BoxValueTooLow { .. }
+            ))
+        ));
+        let fee_amount = BoxValue::try_from(BoxValue::SAFE_USER_MIN.as_u64() * 20).unwrap();
+        let tx = build_storage_rent_claim(&[rich], &ctx, fee_amount, &address).unwrap();
+        assert_eq!(tx.output_candidates[2].value, fee_amount);
+    }
+}
@@ -1,10 +1,14 @@
 //! Builder for an UnsignedTransaction
 
+use std::collections::HashMap;
 use std::collections::HashSet;
 
 use ergotree_interpreter::sigma_protocol;
+use ergotree_interpreter::sigma_protocol::prover::ContextExtension;
 use ergotree_interpreter::sigma_protocol::prover::ProofBytes;
 use ergotree_ir::address::{Address, AddressEncoder, NetworkPrefix};
+use ergotree_ir::ergo_tree::ErgoTree;
+use ergotree_ir::ergo_tree::ErgoTreeParsingError;
 use ergotree_ir::serialization::SerializationError;
 use ergotree_ir::serialization::SigmaSerializable;
 use thiserror::Error;
@@ -12,6 +16,7 @@ use thiserror::Error;
 use crate::chain::contract::Contract;
 use crate::chain::ergo_box::box_builder::{ErgoBoxCandidateBuilder, ErgoBoxCandidateBuilderError};
 use crate::chain::ergo_box::{sum_tokens_from_boxes, sum_value, BoxId, BoxValue, BoxValueError};
+use crate::chain::parameters::Parameters;
 use crate::chain::token::{Token, TokenId};
 use crate::chain::transaction::{DataInput, Input, Transaction, UnsignedInput};
 use crate::chain::{
@@ -31,10 +36,12 @@ pub struct TxBuilder<S: ErgoBoxAssets> {
     box_selection: BoxSelection<S>,
     data_inputs: Vec<DataInput>,
     output_candidates: Vec<ErgoBoxCandidate>,
+    context_extensions: HashMap<BoxId, ContextExtension>,
     current_height: u32,
     fee_amount: BoxValue,
     change_address: Address,
     min_change_value: BoxValue,
+    parameters: Parameters,
 }
 
 impl<S: ErgoBoxAssets + ErgoBoxId + Clone> TxBuilder<S> {
@@ -45,7 +52,8 @@ impl<S: ErgoBoxAssets + ErgoBoxId + Clone> TxBuilder<S> {
     /// `fee_amount` - miner's fee (higher values will speed up inclusion in blocks),
     /// `change_address` - change (inputs - outputs) will be sent to this address,
     /// `min_change_value` - minimal value of the change to be sent to `change_address`, value less than that
-    /// will be given to miners,
+    /// will be given to miners.
+    /// The mainnet launch system parameters are used (see [`TxBuilder::set_parameters`])
     pub fn new(
         box_selection: BoxSelection<S>,
         output_candidates: Vec<ErgoBoxCandidate>,
@@ -58,10 +66,12 @@ impl<S: ErgoBoxAssets + ErgoBoxId + Clone> TxBuilder<S> {
             box_selection,
             data_inputs: vec![],
             output_candidates,
+            context_extensions: HashMap::new(),
             current_height,
             fee_amount,
             change_address,
             min_change_value,
+            parameters: Parameters::default(),
         }
     }
 
@@ -100,11 +110,43 @@ impl<S: ErgoBoxAssets + ErgoBoxId + Clone> TxBuilder<S> {
         self.min_change_value
     }
 
+    /// Get system parameters
+    pub fn parameters(&self) -> &Parameters {
+        &self.parameters
+    }
+
+    /// Set system parameters (min box value per byte for the change and fee boxes, cost limit)
+    pub fn set_parameters(&mut self, parameters: Parameters) {
+        self.parameters = parameters;
+    }
+
     /// Set transaction's data inputs
     pub fn set_data_inputs(&mut self, data_inputs: Vec<DataInput>) {
         self.data_inputs = data_inputs;
     }
 
+    /// Set context extension for the input with the given box id
+    pub fn set_context_extension(&mut self, box_id: BoxId, context_extension: ContextExtension) {
+        self.context_extensions.insert(box_id, context_extension);
+    }
+
+    /// Reveal the script for the input with the given box id protected by P2SH address (made from
+    /// this script). The serialized script is put into the input's context extension
+    /// (variable [`Address::P2SH_SCRIPT_VAR_ID`]).
+    pub fn set_p2sh_script(
+        &mut self,
+        box_id: BoxId,
+        script: &ErgoTree,
+    ) -> Result<(), TxBuilderError> {
+        let script_bytes = Address::p2sh_script_bytes(script)?;
+        self.context_extensions
+            .entry(box_id)
+            .or_insert_with(ContextExtension::empty)
+            .values
+            .insert(Address::P2SH_SCRIPT_VAR_ID, script_bytes.into());
+        Ok(())
+    }
+
     /// Estimated serialized transaction size in bytes after signing (assuming P2PK box spending)
     pub fn estimate_tx_size_bytes(&self) -> Result<usize, TxBuilderError> {
         let tx = self.build_tx()?;
@@ -168,6 +210,7 @@ impl<S: ErgoBoxAssets + ErgoBoxId + Clone> TxBuilder<S> {
                     change_address_ergo_tree.clone(),
                     self.current_height,
                 );
+                candidate.set_min_box_value_per_byte(self.min_value_per_byte());
                 for token in &b.tokens() {
                     candidate.add_token(token.clone());
                 }
@@ -184,7 +227,9 @@ impl<S: ErgoBoxAssets + ErgoBoxId + Clone> TxBuilder<S> {
             ));
         }
         // add miner's fee
-        let miner_fee_box = new_miner_fee_box(self.fee_amount, self.current_height)?;
+        let mut miner_fee_box = miner_fee_box_builder(self.fee_amount, self.current_height);
+        miner_fee_box.set_min_box_value_per_byte(self.min_value_per_byte());
+        let miner_fee_box = miner_fee_box.build()?;
         output_candidates.push(miner_fee_box);
         if output_candidates.len() > Transaction::MAX_OUTPUTS_COUNT {
             return Err(TxBuilderError::InvalidArgs("too many outputs".to_string()));
@@ -220,13 +265,46 @@ impl<S: ErgoBoxAssets + ErgoBoxId + Clone> TxBuilder<S> {
                     _ => Err(TxBuilderError::NotEnoughTokens(vec![output_token.clone()])),
                 }
             })?;
+        // check the cost which is known before the scripts evaluation
+        let all_input_tokens = self
+            .box_selection
+            .boxes
+            .iter()
+            .flat_map(|b| b.tokens())
+            .collect::<Vec<Token>>();
+        let all_output_tokens = output_candidates
+            .iter()
+            .flat_map(|b| b.tokens())
+            .collect::<Vec<Token>>();
+        let cost = self.parameters.tx_initial_cost(
+            self.box_selection.boxes.len(),
+            self.data_inputs.len(),
+            output_candidates.len(),
+        ) + self.parameters.tx_token_access_cost(
+            all_input_tokens.len(),
+            distinct_token_ids_count(&all_input_tokens),
+            all_output_tokens.len(),
+            distinct_token_ids_count(&all_output_tokens),
+        );
+        let cost_limit = self.parameters.max_block_cost().max(0) as u64;
+        if cost > cost_limit {
+            return Err(TxBuilderError::CostLimitExceeded { cost, cost_limit });
+        }
 
         Ok(UnsignedTransaction::new(
             self.box_selection
                 .boxes
                 .clone()
-                .into_iter()
-                .map(UnsignedInput::from)
+                .iter()
+                .map(|b| {
+                    let box_id = b.box_id();
+                    let extension = self
+                        .context_extensions
+                        .get(&box_id)
+                        .cloned()
+                        .unwrap_or_else(ContextExtension::empty);
+                    UnsignedInput::new(box_id, extension)
+                })
                 .collect(),
             self.data_inputs.clone(),
             output_candidates,
@@ -237,6 +315,18 @@ impl<S: ErgoBoxAssets + ErgoBoxId + Clone> TxBuilder<S> {
     pub fn build(self) -> Result<UnsignedTransaction, TxBuilderError> {
         self.build_tx()
     }
+
+    fn min_value_per_byte(&self) -> u32 {
+        self.parameters.min_value_per_byte().max(0) as u32
+    }
+}
+
+fn distinct_token_ids_count(tokens: &[Token]) -> usize {
+    tokens
+        .iter()
+        .map(|t| t.token_id.clone())
+        .collect::<HashSet<TokenId>>()
+        .len()
 }
 
 /// Create a box with miner's contract and a given value
@@ -244,12 +334,19 @@ pub fn new_miner_fee_box(
     fee_amount: BoxValue,
     creation_height: u32,
 ) -> Result<ErgoBoxCandidate, ErgoBoxCandidateBuilderError> {
+    miner_fee_box_builder(fee_amount, creation_height).build()
+}
+
+pub(crate) fn miner_fee_box_builder(
+    fee_amount: BoxValue,
+    creation_height: u32,
+) -> ErgoBoxCandidateBuilder {
     let address_encoder = AddressEncoder::new(NetworkPrefix::Mainnet);
     let miner_fee_address = address_encoder
         .parse_address_from_str(MINERS_FEE_MAINNET_ADDRESS)
         .unwrap();
     let ergo_tree = miner_fee_address.script().unwrap();
-    ErgoBoxCandidateBuilder::new(fee_amount, ergo_tree, creation_height).build()
+    ErgoBoxCandidateBuilder::new(fee_amount, ergo_tree, creation_height)
 }
 
 /// Errors of TxBuilder
@@ -264,6 +361,9 @@ pub enum TxBuilderError {
     /// Serialization error
     #[error("Serialization error")]
     SerializationError(#[from] SerializationError),
+    /// ErgoTree parsing error
+    #[error("ErgoTree parsing error: {0}")]
+    ErgoTreeParsingError(#[from] ErgoTreeParsingError),
     /// Invalid arguments
     #[error("Invalid arguments: {0}")]
     InvalidArgs(String),
@@ -276,6 +376,14 @@ pub enum TxBuilderError {
     /// Not enough coins
     #[error("Not enough coins({0} nanoERGs are missing)")]
     NotEnoughCoins(u64),
+    /// Transaction cost exceeds the limit
+    #[error("Transaction cost {cost} exceeds the limit {cost_limit}")]
+    CostLimitExceeded {
+        /// Transaction cost (before the scripts evaluation)
+        cost: u64,
+        /// Maximum block cost
+        cost_limit: u64,
+    },
 }
 
 #[cfg(test)]
@@ -547,6 +655,104 @@ mod tests {
         assert!(tx_builder.estimate_tx_size_bytes().unwrap() > 0);
     }
 
+    #[test]
+    fn test_parameters() {
+        use crate::chain::parameters::Parameter;
+        use std::collections::BTreeMap;
+
+        let input = ErgoBox::new(
+            10000000i64.try_into().unwrap(),
+            force_any_val::<ErgoTree>(),
+            vec![],
+            NonMandatoryRegisters::empty(),
+            1,
+            force_any_val::<TxId>(),
+            0,
+        );
+        let tx_fee = BoxValue::SAFE_USER_MIN;
+        let out_box_value = input.value.checked_sub(&tx_fee).unwrap();
+        let out_box = ErgoBoxCandidateBuilder::new(out_box_value, force_any_val::<ErgoTree>(), 0)
+            .build()
+            .unwrap();
+        let mut tx_builder = TxBuilder::new(
+            BoxSelection {
+                boxes: vec![input],
+                change_boxes: vec![],
+            },
+            vec![out_box],
+            0,
+            tx_fee,
+            force_any_val::<Address>(),
+            BoxValue::SAFE_USER_MIN,
+        );
+        assert_eq!(tx_builder.parameters(), &Parameters::default());
+        assert!(tx_builder.clone().build().is_ok());
+
+        let mut table = BTreeMap::new();
+        table.insert(Parameter::MaxBlockCost, 16 * 1024);
+        table.insert(Parameter::InputCost, 20000);
+        tx_builder.set_parameters(Parameters::new(0, table));
+        assert_eq!(
+            tx_builder.clone().build(),
+            Err(TxBuilderError::CostLimitExceeded {
+                cost: 10000 + 20000 + 2 * 100,
+                cost_limit: 16 * 1024
+            })
+        );
+
+        // the miner's fee box is larger than 100 bytes
+        let mut table = BTreeMap::new();
+        table.insert(Parameter::MinValuePerByte, 10000);
+        tx_builder.set_parameters(Parameters::new(0, table));
+        assert!(matches!(
+            tx_builder.build(),
+            Err(TxBuilderError::ErgoBoxCandidateBuilderError(
+                ErgoBoxCandidateBuilderError::BoxValueTooLow { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_p2sh_script() {
+        let script = force_any_val::<ErgoTree>();
+        let address = Address::p2sh_from_script(&script).unwrap();
+        let input = ErgoBox::new(
+            10000000i64.try_into().unwrap(),
+            address.script().unwrap(),
+            vec![],
+            NonMandatoryRegisters::empty(),
+            1,
+            force_any_val::<TxId>(),
+            0,
+        );
+        let tx_fee = super::SUGGESTED_TX_FEE;
+        let out_box_value = input.value.checked_sub(&tx_fee).unwrap();
+        let out_box = ErgoBoxCandidateBuilder::new(out_box_value, force_any_val::<ErgoTree>(), 0)
+            .build()
+            .unwrap();
+        let mut tx_builder = TxBuilder::new(
+            BoxSelection {
+                boxes: vec![input.clone()],
+                change_boxes: vec![],
+            },
+            vec![out_box],
+            0,
+            tx_fee,
+            force_any_val::<Address>(),
+            BoxValue::SAFE_USER_MIN,
+        );
+        tx_builder.set_p2sh_script(input.box_id(), &script).unwrap();
+        let tx = tx_builder.build().unwrap();
+        assert_eq!(
+            tx.inputs[0]
+                .extension
+                .values
+                .get(&Address::P2SH_SCRIPT_VAR_ID)
+                .cloned(),
+            Some(Address::p2sh_script_bytes(&script).unwrap().into())
+        );
+    }
+
     proptest! {
 
         #![proptest_config(ProptestConfig::with_cases(16))]
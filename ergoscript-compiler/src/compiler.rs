@@ -107,6 +107,22 @@ mod tests {
         );
     }
 
+    fn check_recompiles(source: &str, expected: expect_test::Expect) {
+        let expr = compile_expr(source, ScriptEnv::new()).unwrap();
+        let printed = ergotree_ir::pretty_printer::print_expr(&expr);
+        expected.assert_eq(&printed);
+        assert_eq!(compile_expr(&printed, ScriptEnv::new()).unwrap(), expr);
+    }
+
+    #[test]
+    fn test_pretty_printed_recompiles() {
+        check_recompiles("HEIGHT + 1", expect!["HEIGHT + 1"]);
+        check_recompiles("42", expect!["42"]);
+        check_recompiles("HEIGHT + HEIGHT", expect!["HEIGHT + HEIGHT"]);
+        check_recompiles("1 + 2 + 3", expect!["1 + 2 + 3"]);
+        check_recompiles("1L + 4294967296L", expect!["1L + 4294967296L"]);
+    }
+
     #[test]
     fn test_parser_error() {
         check(
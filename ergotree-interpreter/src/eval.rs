@@ -1,10 +1,12 @@
 //! Interpreter
-use std::rc::Rc;
+use std::sync::Arc;
 
 use ergotree_ir::ir_ergo_box::IrErgoBoxArenaError;
 use ergotree_ir::mir::constant::TryExtractFromError;
 use ergotree_ir::mir::expr::Expr;
+use ergotree_ir::mir::val_def::ValId;
 use ergotree_ir::mir::value::Value;
+use ergotree_ir::mir::visitor::children;
 use ergotree_ir::sigma_protocol::sigma_boolean::SigmaBoolean;
 
 use cost_accum::CostAccumulator;
@@ -14,11 +16,20 @@ use thiserror::Error;
 use self::context::Context;
 use self::cost_accum::CostError;
 use self::env::Env;
+use self::limits::EvalLimitError;
+use self::limits::EvalLimits;
+use self::limits::EvalLimitsTracker;
+use self::trace::EvalHook;
+use self::trace::EvalTrace;
+use self::trace::Tracer;
 
 /// Context(blockchain) for the interpreter
 pub mod context;
 /// Environment for
 pub mod env;
+pub mod limits;
+pub mod partial_eval;
+pub mod trace;
 
 pub(crate) mod and;
 pub(crate) mod apply;
@@ -91,6 +102,9 @@ pub enum EvalError {
     /// Error on cost calculation
     #[error("Error on cost calculation: {0:?}")]
     CostError(#[from] CostError),
+    /// Evaluation limit exceeded
+    #[error("{0}")]
+    LimitExceeded(#[from] EvalLimitError),
     /// Unexpected value type
     #[error("Unexpected value type: {0:?}")]
     TryExtractFrom(#[from] TryExtractFromError),
@@ -112,6 +126,125 @@ pub enum EvalError {
     /// Misc error
     #[error("error: {0}")]
     Misc(String),
+    /// Evaluation aborted by the [`EvalHook`] on the given node
+    #[error("Evaluation aborted on {0}")]
+    Aborted(String),
+    /// Evaluation of the node is not yet implemented
+    #[error("Evaluation is not yet implemented: {0}")]
+    NotImplementedYet(String),
+}
+
+/// Location of the node which evaluation failed
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct ErrorLocation {
+    /// Path from the root to the failed node, as indices of the children (in the order of
+    /// [`ergotree_ir::mir::visitor::children`]). If the failed node is in the body of a lambda
+    /// evaluated outside of its definition, the path points to the node applying the lambda.
+    pub path: Vec<usize>,
+    /// Path description with node names (i.e. "BoolToSigmaProp[0]/BinOp[1]/BinOp")
+    pub path_desc: String,
+    /// Name of the failed node kind
+    pub node_name: &'static str,
+    /// Op code of the failed node (`None` for constants)
+    pub op_code: Option<u8>,
+    /// Id of the innermost `ValDef` with the failed node
+    pub val_def_id: Option<ValId>,
+}
+
+impl std::fmt::Display for ErrorLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.path_desc)?;
+        if let Some(op_code) = self.op_code {
+            write!(f, ", op code 0x{:02x}", op_code)?;
+        }
+        if let Some(id) = self.val_def_id {
+            write!(f, ", in ValDef {}", id.0)?;
+        }
+        Ok(())
+    }
+}
+
+/// Builds the [`ErrorLocation`] while the error is propagated from the failed node to the root
+#[derive(Debug, Default)]
+pub(crate) struct ErrorLocator {
+    /// Depth of the currently evaluated node
+    depth: usize,
+    failed: Option<FailedNode>,
+}
+
+#[derive(Debug)]
+struct FailedNode {
+    /// Depth of the last node on the path
+    depth: usize,
+    /// Last node on the path, to find its index among the children of its parent
+    node: Expr,
+    location: ErrorLocation,
+}
+
+impl ErrorLocator {
+    pub(crate) fn enter(&mut self) {
+        self.depth += 1;
+    }
+
+    /// Called on leaving the node evaluation, `failed` is set if the evaluation failed
+    pub(crate) fn exit(&mut self, node: &Expr, failed: bool) {
+        self.depth = self.depth.saturating_sub(1);
+        let depth = self.depth;
+        self.failed = match self.failed.take() {
+            Some(mut f) if failed && f.depth == depth + 1 => {
+                f.extend_path(node);
+                f.depth = depth;
+                Some(f)
+            }
+            // the error of the nested node was handled and the evaluation went on
+            Some(_) if !failed => None,
+            _ if failed => Some(FailedNode {
+                depth,
+                node: node.clone(),
+                location: ErrorLocation {
+                    path: Vec::new(),
+                    path_desc: node.node_name().to_string(),
+                    node_name: node.node_name(),
+                    op_code: node.op_code_value(),
+                    val_def_id: None,
+                },
+            }),
+            _ => None,
+        };
+    }
+
+    pub(crate) fn into_location(self) -> Option<ErrorLocation> {
+        self.failed.map(|f| f.location)
+    }
+}
+
+impl FailedNode {
+    fn extend_path(&mut self, parent: &Expr) {
+        let node = &self.node;
+        let loc = &mut self.location;
+        let children = children(parent);
+        if let Some(idx) = children.iter().position(|c| *c == node) {
+            loc.path.insert(0, idx);
+            loc.path_desc = format!("{}[{}]/{}", parent.node_name(), idx, loc.path_desc);
+        } else if let Some((idx, vd)) = children.iter().enumerate().find_map(|(idx, c)| match c {
+            Expr::ValDef(vd) if *vd.rhs == *node => Some((idx, vd)),
+            _ => None,
+        }) {
+            loc.path.splice(0..0, vec![idx, 0]);
+            loc.path_desc = format!(
+                "{}[{}]/ValDef[0]/{}",
+                parent.node_name(),
+                idx,
+                loc.path_desc
+            );
+            loc.val_def_id.get_or_insert(vd.id);
+        } else {
+            // failed node is in the lambda body evaluated by `parent`
+            loc.path.clear();
+            loc.path_desc = format!("{}/.../{}", parent.node_name(), loc.node_name);
+        }
+        self.node = parent.clone();
+    }
 }
 
 /// Result of expression reduction procedure (see `reduce_to_crypto`).
@@ -123,42 +256,115 @@ pub struct ReductionResult {
 }
 
 /// Interpreter
-pub trait Evaluator {
+pub trait Evaluator: Send + Sync {
     /// Evaluate the given expression by reducing it to SigmaBoolean value.
     fn reduce_to_crypto(
         &self,
         expr: &Expr,
         env: &Env,
-        ctx: Rc<Context>,
+        ctx: Arc<Context>,
+    ) -> Result<ReductionResult, EvalError> {
+        self.reduce_to_crypto_with_limits(expr, env, ctx, EvalLimits::default())
+    }
+
+    /// Same as [`Evaluator::reduce_to_crypto`], but with the given evaluation limits
+    /// (call depth, collection size, etc.), none of them is enforced by default.
+    fn reduce_to_crypto_with_limits(
+        &self,
+        expr: &Expr,
+        env: &Env,
+        ctx: Arc<Context>,
+        limits: EvalLimits,
     ) -> Result<ReductionResult, EvalError> {
+        let cost_accum = CostAccumulator::new(0, None);
+        let mut ectx = EvalContext::new(ctx, cost_accum).with_limits(limits);
+        expr.eval(env, &mut ectx).and_then(to_reduction_result)
+    }
+
+    /// Same as [`Evaluator::reduce_to_crypto`], but on failure also returns the location of the
+    /// node which evaluation failed
+    fn reduce_to_crypto_located(
+        &self,
+        expr: &Expr,
+        env: &Env,
+        ctx: Arc<Context>,
+    ) -> (Result<ReductionResult, EvalError>, Option<ErrorLocation>) {
         let cost_accum = CostAccumulator::new(0, None);
         let mut ectx = EvalContext::new(ctx, cost_accum);
-        expr.eval(env, &mut ectx)
-            .and_then(|v| -> Result<ReductionResult, EvalError> {
-                match v {
-                    Value::Boolean(b) => Ok(ReductionResult {
-                        sigma_prop: SigmaBoolean::TrivialProp(b),
-                        cost: 0,
-                    }),
-                    Value::SigmaProp(sp) => Ok(ReductionResult {
-                        sigma_prop: sp.value().clone(),
-                        cost: 0,
-                    }),
-                    _ => Err(EvalError::InvalidResultType),
-                }
-            })
+        ectx.error_locator = Some(ErrorLocator::default());
+        let res = expr.eval(env, &mut ectx).and_then(to_reduction_result);
+        let location = match res {
+            Ok(_) => None,
+            Err(_) => ectx
+                .error_locator
+                .take()
+                .and_then(ErrorLocator::into_location),
+        };
+        (res, location)
+    }
+
+    /// Same as [`Evaluator::reduce_to_crypto`], but records every evaluated node into
+    /// [`EvalTrace`] and calls the given hook on breakpoints and evaluation steps.
+    /// The trace is returned even if the evaluation fails.
+    fn reduce_to_crypto_traced(
+        &self,
+        expr: &Expr,
+        env: &Env,
+        ctx: Arc<Context>,
+        hook: Option<Box<dyn EvalHook>>,
+    ) -> (Result<ReductionResult, EvalError>, EvalTrace) {
+        let cost_accum = CostAccumulator::new(0, None);
+        let mut ectx = EvalContext::new(ctx, cost_accum);
+        ectx.tracer = Some(Tracer::new(hook));
+        let res = expr.eval(env, &mut ectx).and_then(to_reduction_result);
+        let trace = ectx
+            .tracer
+            .take()
+            .map(Tracer::into_trace)
+            .unwrap_or_default();
+        (res, trace)
+    }
+}
+
+fn to_reduction_result(v: Value) -> Result<ReductionResult, EvalError> {
+    match v {
+        Value::Boolean(b) => Ok(ReductionResult {
+            sigma_prop: SigmaBoolean::TrivialProp(b),
+            cost: 0,
+        }),
+        Value::SigmaProp(sp) => Ok(ReductionResult {
+            sigma_prop: sp.value().clone(),
+            cost: 0,
+        }),
+        _ => Err(EvalError::InvalidResultType),
     }
 }
 
 #[derive(Debug)]
 pub(crate) struct EvalContext {
-    pub(crate) ctx: Rc<Context>,
+    pub(crate) ctx: Arc<Context>,
     pub(crate) cost_accum: CostAccumulator,
+    pub(crate) tracer: Option<Tracer>,
+    pub(crate) limits: EvalLimitsTracker,
+    pub(crate) error_locator: Option<ErrorLocator>,
 }
 
 impl EvalContext {
-    pub fn new(ctx: Rc<Context>, cost_accum: CostAccumulator) -> Self {
-        EvalContext { ctx, cost_accum }
+    pub fn new(ctx: Arc<Context>, cost_accum: CostAccumulator) -> Self {
+        EvalContext {
+            ctx,
+            cost_accum,
+            tracer: None,
+            limits: EvalLimitsTracker::new(EvalLimits::default()),
+            error_locator: None,
+        }
+    }
+
+    pub fn with_limits(self, limits: EvalLimits) -> Self {
+        EvalContext {
+            limits: EvalLimitsTracker::new(limits),
+            ..self
+        }
     }
 }
 
@@ -171,9 +377,9 @@ pub(crate) trait Evaluable {
 
 type EvalFn = fn(env: &Env, ctx: &mut EvalContext, Value, Vec<Value>) -> Result<Value, EvalError>;
 
-fn smethod_eval_fn(method: &SMethod) -> EvalFn {
+fn smethod_eval_fn(method: &SMethod) -> Result<EvalFn, EvalError> {
     use ergotree_ir::types::*;
-    match method.obj_type.type_id() {
+    Ok(match method.obj_type.type_id() {
         scontext::TYPE_ID if method.method_id() == scontext::DATA_INPUTS_PROPERTY_METHOD_ID => {
             self::scontext::DATA_INPUTS_EVAL_FN
         }
@@ -181,15 +387,30 @@ fn smethod_eval_fn(method: &SMethod) -> EvalFn {
             sbox::VALUE_METHOD_ID => self::sbox::VALUE_EVAL_FN,
             sbox::GET_REG_METHOD_ID => self::sbox::GET_REG_EVAL_FN,
             sbox::TOKENS_METHOD_ID => self::sbox::TOKENS_EVAL_FN,
-            method_id => panic!("Eval fn: unknown method id in SBox: {:?}", method_id),
+            method_id => {
+                return Err(EvalError::NotFound(format!(
+                    "Eval fn: unknown method id in SBox: {:?}",
+                    method_id
+                )))
+            }
         },
         scoll::TYPE_ID => match method.method_id() {
             scoll::INDEX_OF_METHOD_ID => self::scoll::INDEX_OF_EVAL_FN,
             scoll::FLATMAP_METHOD_ID => self::scoll::FLATMAP_EVAL_FN,
-            method_id => panic!("Eval fn: unknown method id in SCollection: {:?}", method_id),
+            method_id => {
+                return Err(EvalError::NotFound(format!(
+                    "Eval fn: unknown method id in SCollection: {:?}",
+                    method_id
+                )))
+            }
         },
-        type_id => todo!("Eval fn: unknown type id {:?}", type_id),
-    }
+        type_id => {
+            return Err(EvalError::NotFound(format!(
+                "Eval fn: unknown type id {:?}",
+                type_id
+            )))
+        }
+    })
 }
 
 #[cfg(test)]
@@ -201,16 +422,17 @@ pub(crate) mod tests {
 
     use super::env::Env;
     use super::*;
+    use crate::sigma_protocol::verifier::TestVerifier;
     use ergotree_ir::mir::constant::TryExtractFrom;
     use ergotree_ir::mir::constant::TryExtractInto;
     use sigma_test_util::force_any_val;
 
     pub fn eval_out_wo_ctx<T: TryExtractFrom<Value>>(expr: &Expr) -> T {
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         eval_out(expr, ctx)
     }
 
-    pub fn eval_out<T: TryExtractFrom<Value>>(expr: &Expr, ctx: Rc<Context>) -> T {
+    pub fn eval_out<T: TryExtractFrom<Value>>(expr: &Expr, ctx: Arc<Context>) -> T {
         let cost_accum = CostAccumulator::new(0, None);
         let mut ectx = EvalContext::new(ctx, cost_accum);
         expr.eval(&Env::empty(), &mut ectx)
@@ -221,7 +443,7 @@ pub(crate) mod tests {
 
     pub fn try_eval_out<T: TryExtractFrom<Value>>(
         expr: &Expr,
-        ctx: Rc<Context>,
+        ctx: Arc<Context>,
     ) -> Result<T, EvalError> {
         let cost_accum = CostAccumulator::new(0, None);
         let mut ectx = EvalContext::new(ctx, cost_accum);
@@ -230,7 +452,125 @@ pub(crate) mod tests {
     }
 
     pub fn try_eval_out_wo_ctx<T: TryExtractFrom<Value>>(expr: &Expr) -> Result<T, EvalError> {
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         try_eval_out(expr, ctx)
     }
+
+    #[test]
+    fn error_location() {
+        use ergotree_ir::mir::bin_op::ArithOp;
+        use ergotree_ir::mir::bin_op::BinOp;
+        use ergotree_ir::mir::bin_op::RelationOp;
+        use ergotree_ir::mir::block::BlockValue;
+        use ergotree_ir::mir::val_def::ValDef;
+        use ergotree_ir::mir::val_use::ValUse;
+        use ergotree_ir::types::stype::SType;
+
+        // { val x = Int.MaxValue + 1; x } > 0
+        let overflow: Expr = BinOp {
+            kind: ArithOp::Plus.into(),
+            left: Box::new(i32::MAX.into()),
+            right: Box::new(1i32.into()),
+        }
+        .into();
+        let block: Expr = BlockValue {
+            items: vec![ValDef {
+                id: 1.into(),
+                rhs: Box::new(overflow.clone()),
+            }
+            .into()],
+            result: Box::new(
+                ValUse {
+                    val_id: 1.into(),
+                    tpe: SType::SInt,
+                }
+                .into(),
+            ),
+        }
+        .into();
+        let expr: Expr = BinOp {
+            kind: RelationOp::Gt.into(),
+            left: Box::new(block),
+            right: Box::new(0i32.into()),
+        }
+        .into();
+        let ctx = Arc::new(force_any_val::<Context>());
+        let (res, loc) = TestVerifier.reduce_to_crypto_located(&expr, &Env::empty(), ctx);
+        assert!(matches!(res, Err(EvalError::ArithmeticException(_))));
+        let loc = loc.unwrap();
+        assert_eq!(loc.path, vec![0, 0, 0]);
+        assert_eq!(loc.path_desc, "BinOp[0]/BlockValue[0]/ValDef[0]/BinOp");
+        assert_eq!(loc.node_name, "BinOp");
+        assert_eq!(loc.op_code, overflow.op_code_value());
+        assert_eq!(loc.val_def_id, Some(1.into()));
+        assert_eq!(
+            loc.to_string(),
+            "BinOp[0]/BlockValue[0]/ValDef[0]/BinOp, op code 0x9a, in ValDef 1"
+        );
+    }
+
+    #[test]
+    fn unsupported_nodes_fail_with_error() {
+        use ergotree_ir::mir::constant::ConstantPlaceholder;
+        use ergotree_ir::mir::val_def::ValDef;
+        use ergotree_ir::types::stype::SType;
+
+        let placeholder: Expr = ConstantPlaceholder {
+            id: 0,
+            tpe: SType::SInt,
+        }
+        .into();
+        let val_def: Expr = ValDef {
+            id: 1.into(),
+            rhs: Box::new(1i32.into()),
+        }
+        .into();
+        for expr in [placeholder, val_def].iter() {
+            let ctx = Arc::new(force_any_val::<Context>());
+            let (res, loc) = TestVerifier.reduce_to_crypto_located(expr, &Env::empty(), ctx);
+            assert!(matches!(res, Err(EvalError::UnexpectedExpr(_))));
+            assert_eq!(loc.unwrap().path, Vec::<usize>::new());
+        }
+    }
+
+    mod no_panic {
+        use super::*;
+        use crate::eval::partial_eval::partial_eval;
+        use crate::eval::partial_eval::ContextVar;
+        use crate::eval::partial_eval::PartialContext;
+        use crate::sigma_protocol::verifier::TestVerifier;
+        use ergotree_ir::mir::expr::well_typed::any_well_typed_expr;
+        use ergotree_ir::mir::expr::well_typed::well_typed_expr;
+        use ergotree_ir::types::stype::SType;
+        use proptest::prelude::*;
+        use std::panic::catch_unwind;
+        use std::panic::AssertUnwindSafe;
+
+        proptest! {
+
+            #![proptest_config(ProptestConfig::with_cases(64))]
+
+            #[test]
+            fn eval_does_not_panic(expr in any_well_typed_expr(4), ctx in any::<Context>()) {
+                let res = catch_unwind(AssertUnwindSafe(|| {
+                    try_eval_out::<Value>(&expr, Arc::new(ctx))
+                }));
+                prop_assert!(res.is_ok(), "panic on {:?}", expr);
+            }
+
+            #[test]
+            fn reduce_does_not_panic(
+                expr in well_typed_expr(SType::SSigmaProp, 4),
+                ctx in any::<Context>()
+            ) {
+                let ctx = Arc::new(ctx);
+                let res = catch_unwind(AssertUnwindSafe(|| {
+                    let _ = TestVerifier.reduce_to_crypto(&expr, &Env::empty(), ctx.clone());
+                    let pctx = PartialContext::new(ctx, vec![ContextVar::Height]);
+                    partial_eval(&expr, &pctx)
+                }));
+                prop_assert!(res.is_ok(), "panic on {:?}", expr);
+            }
+        }
+    }
 }
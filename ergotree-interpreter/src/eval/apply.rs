@@ -32,7 +32,7 @@ impl Evaluable for Apply {
 
 #[cfg(test)]
 mod tests {
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     use ergotree_ir::mir::bin_op::BinOp;
     use ergotree_ir::mir::bin_op::RelationOp;
@@ -92,7 +92,7 @@ mod tests {
             args: vec![arg],
         }
         .into();
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         assert!(eval_out::<bool>(&apply, ctx));
     }
 }
@@ -313,7 +313,7 @@ mod tests {
     use num_bigint::ToBigInt;
     use proptest::prelude::*;
     use sigma_test_util::force_any_val;
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     fn check_eq_neq(left: Constant, right: Constant) -> bool {
         let eq_op: Expr = BinOp {
@@ -322,14 +322,14 @@ mod tests {
             right: Box::new(right.clone().into()),
         }
         .into();
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         let neq_op: Expr = BinOp {
             kind: BinOpKind::Relation(RelationOp::NEq),
             left: Box::new(left.into()),
             right: Box::new(right.into()),
         }
         .into();
-        let ctx1 = Rc::new(force_any_val::<Context>());
+        let ctx1 = Arc::new(force_any_val::<Context>());
         eval_out::<bool>(&eq_op, ctx) && !eval_out::<bool>(&neq_op, ctx1)
     }
 
@@ -398,7 +398,7 @@ mod tests {
             ),
         }
         .into();
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         assert!(eval_out::<bool>(&e, ctx));
     }
 
@@ -418,7 +418,7 @@ mod tests {
             ),
         }
         .into();
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         assert!(!eval_out::<bool>(&e, ctx));
     }
 
@@ -433,7 +433,7 @@ mod tests {
             right: Box::new(right.into().into()),
         }
         .into();
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         try_eval_out::<T>(&expr, ctx)
     }
 
@@ -444,7 +444,7 @@ mod tests {
             right: Box::new(right.into().into()),
         }
         .into();
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         eval_out::<bool>(&expr, ctx)
     }
 
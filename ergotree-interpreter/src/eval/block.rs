@@ -1,6 +1,5 @@
 use ergotree_ir::mir::block::BlockValue;
-use ergotree_ir::mir::constant::TryExtractInto;
-use ergotree_ir::mir::val_def::ValDef;
+use ergotree_ir::mir::expr::Expr;
 use ergotree_ir::mir::value::Value;
 
 use crate::eval::env::Env;
@@ -12,7 +11,15 @@ impl Evaluable for BlockValue {
     fn eval(&self, env: &Env, ctx: &mut EvalContext) -> Result<Value, EvalError> {
         let mut cur_env = env.clone();
         for i in &self.items {
-            let val_def = i.clone().try_extract_into::<ValDef>()?;
+            let val_def = match i {
+                Expr::ValDef(vd) => vd,
+                _ => {
+                    return Err(EvalError::UnexpectedExpr(format!(
+                        "BlockValue: expected ValDef item, got: {0:?}",
+                        i
+                    )))
+                }
+            };
             let v: Value = val_def.rhs.eval(&cur_env, ctx)?;
             cur_env.insert(val_def.id, v);
         }
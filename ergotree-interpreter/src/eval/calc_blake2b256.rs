@@ -16,7 +16,7 @@ impl Evaluable for CalcBlake2b256 {
         match input_v.clone() {
             Value::Coll(CollKind::NativeColl(NativeColl::CollByte(coll_byte))) => {
                 let expected_hash: Vec<u8> =
-                    blake2b256_hash(coll_byte.as_vec_u8().as_slice()).to_vec();
+                    blake2b256_hash(coll_byte.to_vec().as_vec_u8().as_slice()).to_vec();
                 Ok(expected_hash.into())
             }
             _ => Err(EvalError::UnexpectedValue(format!(
@@ -35,7 +35,7 @@ mod tests {
     use ergotree_ir::mir::expr::Expr;
     use proptest::prelude::*;
     use sigma_test_util::force_any_val;
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     proptest! {
 
@@ -46,7 +46,7 @@ mod tests {
                 input: Box::new(Expr::Const(byte_array.into())),
             }
             .into();
-            let ctx = Rc::new(force_any_val::<Context>());
+            let ctx = Arc::new(force_any_val::<Context>());
             assert_eq!(eval_out::<Vec<i8>>(&expr, ctx).as_vec_u8(), expected_hash);
         }
 
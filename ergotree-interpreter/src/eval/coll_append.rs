@@ -8,14 +8,9 @@ use crate::eval::EvalContext;
 use crate::eval::EvalError;
 use crate::eval::Evaluable;
 
-fn concat<T>(mut e1: Vec<T>, e2: Vec<T>) -> Vec<T> {
-    e1.extend(e2);
-    e1
-}
-
-fn extract_vecval(inp: Value) -> Result<Vec<Value>, EvalError> {
+fn extract_coll(inp: Value) -> Result<CollKind, EvalError> {
     match inp {
-        Value::Coll(coll) => Ok(coll.as_vec()),
+        Value::Coll(coll) => Ok(coll),
         _ => Err(EvalError::UnexpectedValue(format!(
             "Append: expected Value to be Value::Coll, got: {0:?}",
             inp
@@ -25,10 +20,13 @@ fn extract_vecval(inp: Value) -> Result<Vec<Value>, EvalError> {
 
 impl Evaluable for Append {
     fn eval(&self, env: &Env, ctx: &mut EvalContext) -> Result<Value, EvalError> {
-        let input_vecval: Vec<Value> = extract_vecval(self.input.eval(env, ctx)?)?;
-        let col_2_vecval: Vec<Value> = extract_vecval(self.col_2.eval(env, ctx)?)?;
-        let concat_vecval: Vec<Value> = concat(input_vecval, col_2_vecval);
-        Ok(Value::Coll(CollKind::from_vec(self.tpe(), concat_vecval)?))
+        let input_coll = extract_coll(self.input.eval(env, ctx)?)?;
+        let col_2_coll = extract_coll(self.col_2.eval(env, ctx)?)?;
+        ctx.limits.alloc_coll(
+            input_coll.elem_tpe(),
+            input_coll.len().saturating_add(col_2_coll.len()),
+        )?;
+        Ok(Value::Coll(input_coll.append(col_2_coll)?))
     }
 }
 
@@ -27,7 +27,7 @@ impl Evaluable for Exists {
                 input_v_clone
             ))),
         };
-        let normalized_input_vals: Vec<Value> = match input_v {
+        let input_coll = match input_v {
             Value::Coll(coll) => {
                 if *coll.elem_tpe() != self.elem_tpe {
                     return Err(EvalError::UnexpectedValue(format!(
@@ -36,7 +36,7 @@ impl Evaluable for Exists {
                         coll.elem_tpe()
                     )));
                 };
-                Ok(coll.as_vec())
+                Ok(coll)
             }
             _ => Err(EvalError::UnexpectedValue(format!(
                 "expected Map input to be Value::Coll, got: {0:?}",
@@ -44,7 +44,7 @@ impl Evaluable for Exists {
             ))),
         }?;
 
-        for item in normalized_input_vals {
+        for item in input_coll.iter() {
             let res = condition_call(item)?.try_extract_into::<bool>()?;
             if res {
                 return Ok(true.into());
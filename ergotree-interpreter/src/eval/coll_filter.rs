@@ -1,6 +1,8 @@
 use ergotree_ir::mir::coll_filter::Filter;
 use ergotree_ir::mir::constant::TryExtractInto;
 use ergotree_ir::mir::value::CollKind;
+use ergotree_ir::mir::value::NativeColl;
+use ergotree_ir::mir::value::SharedSlice;
 use ergotree_ir::mir::value::Value;
 
 use crate::eval::env::Env;
@@ -28,7 +30,7 @@ impl Evaluable for Filter {
                 input_v_clone
             ))),
         };
-        let normalized_input_vals: Vec<Value> = match input_v {
+        let input_coll = match input_v {
             Value::Coll(coll) => {
                 if *coll.elem_tpe() != self.elem_tpe {
                     return Err(EvalError::UnexpectedValue(format!(
@@ -37,7 +39,7 @@ impl Evaluable for Filter {
                         coll.elem_tpe()
                     )));
                 };
-                Ok(coll.as_vec())
+                Ok(coll)
             }
             _ => Err(EvalError::UnexpectedValue(format!(
                 "expected Map input to be Value::Coll, got: {0:?}",
@@ -45,36 +47,53 @@ impl Evaluable for Filter {
             ))),
         }?;
 
-        let items_conditions: Vec<bool> = normalized_input_vals
-            .clone()
-            .into_iter()
-            .map(|item| {
-                condition_call(item).and_then(|res| {
-                    res.try_extract_into::<bool>()
-                        .map_err(EvalError::TryExtractFrom)
-                })
-            })
-            .collect::<Result<Vec<bool>, EvalError>>()?;
-        let filtered_items = normalized_input_vals
-            .into_iter()
-            .zip(items_conditions)
-            .filter(|(_, condition)| *condition)
-            .map(|(item, _)| item)
-            .collect();
-        Ok(Value::Coll(CollKind::from_vec(
-            self.elem_tpe.clone(),
-            filtered_items,
-        )?))
+        let filtered_coll = match input_coll {
+            CollKind::NativeColl(NativeColl::CollByte(items)) => CollKind::NativeColl(
+                NativeColl::CollByte(filter_items(&items, &mut condition_call)?),
+            ),
+            CollKind::NativeColl(NativeColl::CollBoolean(items)) => CollKind::NativeColl(
+                NativeColl::CollBoolean(filter_items(&items, &mut condition_call)?),
+            ),
+            CollKind::NativeColl(NativeColl::CollShort(items)) => CollKind::NativeColl(
+                NativeColl::CollShort(filter_items(&items, &mut condition_call)?),
+            ),
+            CollKind::NativeColl(NativeColl::CollInt(items)) => CollKind::NativeColl(
+                NativeColl::CollInt(filter_items(&items, &mut condition_call)?),
+            ),
+            CollKind::NativeColl(NativeColl::CollLong(items)) => CollKind::NativeColl(
+                NativeColl::CollLong(filter_items(&items, &mut condition_call)?),
+            ),
+            CollKind::WrappedColl { elem_tpe, items } => CollKind::WrappedColl {
+                elem_tpe,
+                items: filter_items(&items, &mut condition_call)?,
+            },
+        };
+        ctx.limits.alloc_coll(&self.elem_tpe, filtered_coll.len())?;
+        Ok(Value::Coll(filtered_coll))
+    }
+}
+
+fn filter_items<T: Clone + Into<Value>>(
+    items: &[T],
+    condition_call: &mut impl FnMut(Value) -> Result<Value, EvalError>,
+) -> Result<SharedSlice<T>, EvalError> {
+    let mut filtered_items = Vec::new();
+    for item in items {
+        if condition_call(item.clone().into())?.try_extract_into::<bool>()? {
+            filtered_items.push(item.clone());
+        }
     }
+    Ok(filtered_items.into())
 }
 
 #[allow(clippy::unwrap_used)]
 #[cfg(test)]
 mod tests {
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     use crate::eval::context::Context;
     use crate::eval::tests::eval_out;
+    use crate::eval::tests::eval_out_wo_ctx;
 
     use super::*;
 
@@ -127,7 +146,7 @@ mod tests {
             )
             .unwrap()
             .into();
-            let ctx = Rc::new(ctx);
+            let ctx = Arc::new(ctx);
             assert_eq!(
                 eval_out::<Vec<IrBoxId>>(&expr, ctx.clone()),
                 ctx.data_inputs.clone()
@@ -136,4 +155,38 @@ mod tests {
             );
         }
     }
+    #[test]
+    fn eval_native_coll() {
+        let body: Expr = BinOp {
+            kind: RelationOp::Gt.into(),
+            left: Box::new(
+                ValUse {
+                    val_id: 1.into(),
+                    tpe: SType::SInt,
+                }
+                .into(),
+            ),
+            right: Box::new(Expr::Const(1i32.into())),
+        }
+        .into();
+        let expr: Expr = Filter::new(
+            Expr::Const(vec![1i32, 2, 3].into()),
+            FuncValue::new(
+                vec![FuncArg {
+                    idx: 1.into(),
+                    tpe: SType::SInt,
+                }],
+                body,
+            )
+            .into(),
+        )
+        .unwrap()
+        .into();
+        match eval_out_wo_ctx::<Value>(&expr) {
+            Value::Coll(CollKind::NativeColl(NativeColl::CollInt(items))) => {
+                assert_eq!(items.as_slice(), &[2, 3])
+            }
+            v => panic!("expected native Coll[Int], got {:?}", v),
+        }
+    }
 }
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use ergotree_ir::mir::coll_fold::Fold;
 use ergotree_ir::mir::value::CollKind;
 use ergotree_ir::mir::value::NativeColl;
@@ -32,15 +34,12 @@ impl Evaluable for Fold {
             Value::Coll(coll) => match coll {
                 CollKind::NativeColl(NativeColl::CollByte(coll_byte)) => {
                     coll_byte.iter().try_fold(zero_v, |acc, byte| {
-                        let tup_arg = Value::Tup([acc, Value::Byte(*byte)].into());
+                        let tup_arg = Value::Tup(Arc::new([acc, Value::Byte(*byte)].into()));
                         fold_op_call(tup_arg)
                     })
                 }
-                CollKind::WrappedColl {
-                    elem_tpe: _,
-                    items: v,
-                } => v.iter().try_fold(zero_v, |acc, item| {
-                    let tup_arg = Value::Tup([acc, item.clone()].into());
+                coll => coll.iter().try_fold(zero_v, |acc, item| {
+                    let tup_arg = Value::Tup(Arc::new([acc, item].into()));
                     fold_op_call(tup_arg)
                 }),
             },
@@ -56,7 +55,7 @@ impl Evaluable for Fold {
 #[cfg(test)]
 mod tests {
     use std::convert::TryInto;
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     use crate::eval::context::Context;
     use crate::eval::tests::eval_out;
@@ -122,7 +121,7 @@ mod tests {
             )
             .unwrap()
             .into();
-            let ctx = Rc::new(ctx);
+            let ctx = Arc::new(ctx);
             assert_eq!(
                 eval_out::<i64>(&expr, ctx.clone()),
                 ctx.data_inputs
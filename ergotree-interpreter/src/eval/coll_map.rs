@@ -37,7 +37,7 @@ impl Evaluable for Map {
                 )
             })?
             .clone();
-        let normalized_input_vals: Vec<Value> = match input_v {
+        let input_coll = match input_v {
             Value::Coll(coll) => {
                 if *coll.elem_tpe() != mapper_input_tpe {
                     return Err(EvalError::UnexpectedValue(format!(
@@ -46,21 +46,20 @@ impl Evaluable for Map {
                         coll.elem_tpe()
                     )));
                 };
-                Ok(coll.as_vec())
+                Ok(coll)
             }
             _ => Err(EvalError::UnexpectedValue(format!(
                 "expected Map input to be Value::Coll, got: {0:?}",
                 input_v
             ))),
         }?;
-        normalized_input_vals
+        let values = input_coll
             .iter()
-            .map(|item| mapper_call(item.clone()))
-            .collect::<Result<Vec<Value>, EvalError>>()
-            .map(|values| {
-                CollKind::from_vec(self.out_elem_tpe(), values).map_err(EvalError::TryExtractFrom)
-            })
-            .and_then(|v| v) // flatten <Result<Result<Value, _>, _>
+            .map(&mut mapper_call)
+            .collect::<Result<Vec<Value>, EvalError>>()?;
+        ctx.limits.alloc_coll(&self.out_elem_tpe(), values.len())?;
+        CollKind::from_vec(self.out_elem_tpe(), values)
+            .map_err(EvalError::TryExtractFrom)
             .map(Value::Coll)
     }
 }
@@ -68,7 +67,7 @@ impl Evaluable for Map {
 #[allow(clippy::unwrap_used)]
 #[cfg(test)]
 mod tests {
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     use crate::eval::context::Context;
     use crate::eval::tests::eval_out;
@@ -123,7 +122,7 @@ mod tests {
             )
             .unwrap()
             .into();
-            let ctx = Rc::new(ctx);
+            let ctx = Arc::new(ctx);
             assert_eq!(
                 eval_out::<Vec<i64>>(&expr, ctx.clone()),
                 ctx.data_inputs
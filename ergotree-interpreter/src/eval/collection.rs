@@ -1,8 +1,5 @@
 use ergotree_ir::mir::collection::Collection;
-use ergotree_ir::mir::constant::TryExtractFromError;
-use ergotree_ir::mir::constant::TryExtractInto;
 use ergotree_ir::mir::value::CollKind;
-use ergotree_ir::mir::value::NativeColl;
 use ergotree_ir::mir::value::Value;
 use ergotree_ir::types::stype::SType;
 
@@ -14,23 +11,15 @@ use crate::eval::Evaluable;
 impl Evaluable for Collection {
     fn eval(&self, env: &Env, ctx: &mut EvalContext) -> Result<Value, EvalError> {
         Ok(match self {
-            Collection::BoolConstants(bools) => bools.clone().into(),
+            Collection::BoolConstants(bools) => {
+                ctx.limits.alloc_coll(&SType::SBoolean, bools.len())?;
+                bools.clone().into()
+            }
             Collection::Exprs { elem_tpe, items } => {
+                ctx.limits.alloc_coll(elem_tpe, items.len())?;
                 let items_v: Result<Vec<Value>, EvalError> =
                     items.iter().map(|i| i.eval(env, ctx)).collect();
-                match elem_tpe {
-                    SType::SByte => {
-                        let bytes: Result<Vec<i8>, TryExtractFromError> = items_v?
-                            .into_iter()
-                            .map(|i| i.try_extract_into::<i8>())
-                            .collect();
-                        Value::Coll(CollKind::NativeColl(NativeColl::CollByte(bytes?)))
-                    }
-                    _ => Value::Coll(CollKind::WrappedColl {
-                        elem_tpe: elem_tpe.clone(),
-                        items: items_v?,
-                    }),
-                }
+                Value::Coll(CollKind::from_vec(elem_tpe.clone(), items_v?)?)
             }
         })
     }
@@ -42,6 +31,7 @@ mod tests {
     use super::*;
     use crate::eval::tests::eval_out_wo_ctx;
     use ergotree_ir::mir::expr::Expr;
+    use ergotree_ir::types::stype::SType;
     use proptest::prelude::*;
 
     proptest! {
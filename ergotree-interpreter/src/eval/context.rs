@@ -1,6 +1,6 @@
 mod ir_ergo_box_dummy;
 
-use std::rc::Rc;
+use std::sync::Arc;
 
 use crate::sigma_protocol::prover::ContextExtension;
 use ergotree_ir::ir_ergo_box::IrBoxId;
@@ -11,7 +11,7 @@ use ergotree_ir::mir::header::PreHeader;
 #[derive(Debug)]
 pub struct Context {
     /// Arena with all boxes (from self, inputs, outputs, data_inputs)
-    pub box_arena: Rc<dyn IrErgoBoxArena>,
+    pub box_arena: Arc<dyn IrErgoBoxArena>,
     /// Current height
     pub height: u32,
     /// Box that contains the script we're evaluating (from spending transaction inputs)
@@ -70,7 +70,7 @@ mod arbitrary {
                         });
                         let box_arena = IrErgoBoxDummyArena(m);
                         Self {
-                            box_arena: Rc::new(box_arena) as Rc<dyn IrErgoBoxArena>,
+                            box_arena: Arc::new(box_arena) as Arc<dyn IrErgoBoxArena>,
                             height,
                             self_box: self_box_id,
                             outputs: outputs_ids,
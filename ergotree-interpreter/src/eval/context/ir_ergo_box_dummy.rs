@@ -1,5 +1,5 @@
 use std::collections::HashMap;
-use std::rc::Rc;
+use std::sync::Arc;
 
 use ergotree_ir::ir_ergo_box::IrBoxId;
 use ergotree_ir::ir_ergo_box::IrErgoBox;
@@ -11,12 +11,12 @@ use ergotree_ir::mir::constant::Constant;
 pub(crate) struct IrErgoBoxDummyArena(pub HashMap<IrBoxId, IrErgoBoxDummy>);
 
 impl IrErgoBoxArena for IrErgoBoxDummyArena {
-    fn get(&self, id: &IrBoxId) -> Result<Rc<dyn IrErgoBox>, IrErgoBoxArenaError> {
+    fn get(&self, id: &IrBoxId) -> Result<Arc<dyn IrErgoBox>, IrErgoBoxArenaError> {
         self.0
             .get(id)
             .cloned()
             .ok_or_else(|| IrErgoBoxArenaError(format!("IrErgoBox with id {0:?} not found", id)))
-            .map(|b| Rc::new(b) as Rc<dyn IrErgoBox>)
+            .map(|b| Arc::new(b) as Arc<dyn IrErgoBox>)
     }
 }
 
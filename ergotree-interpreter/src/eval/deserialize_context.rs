@@ -1,15 +1,152 @@
+use ergotree_ir::mir::constant::TryExtractInto;
 use ergotree_ir::mir::deserialize_context::DeserializeContext;
+use ergotree_ir::mir::expr::Expr;
 use ergotree_ir::mir::value::Value;
+use ergotree_ir::mir::visitor;
+use ergotree_ir::serialization::SigmaSerializable;
+use ergotree_ir::types::stype::SType;
 
 use crate::eval::env::Env;
 use crate::eval::EvalContext;
 use crate::eval::EvalError;
 use crate::eval::Evaluable;
-// use ergotree_ir::mir::constant::TryExtractInto;
-// use ergotree_ir::serialization::SigmaSerializable;
 
 impl Evaluable for DeserializeContext {
-    fn eval(&self, _env: &Env, _ctx: &mut EvalContext) -> Result<Value, EvalError> {
-        todo!()
+    fn eval(&self, _env: &Env, ctx: &mut EvalContext) -> Result<Value, EvalError> {
+        let bytes: Vec<u8> = match ctx.ctx.extension.values.get(&self.id) {
+            None => {
+                return Err(EvalError::NotFound(format!(
+                    "DeserializeContext: context variable {} not found",
+                    self.id
+                )))
+            }
+            Some(c) if c.tpe == SType::SColl(SType::SByte.into()) => {
+                c.v.clone().try_extract_into::<Vec<u8>>()?
+            }
+            Some(c) => {
+                return Err(EvalError::UnexpectedValue(format!(
+                    "DeserializeContext: expected context variable {} to be Coll[Byte], got {:?}",
+                    self.id, c.tpe
+                )))
+            }
+        };
+        let expr = Expr::sigma_parse_bytes(&bytes).map_err(|e| {
+            EvalError::Misc(format!(
+                "DeserializeContext: failed to parse context variable {}: {}",
+                self.id, e
+            ))
+        })?;
+        if expr.tpe() != self.tpe {
+            return Err(EvalError::UnexpectedExpr(format!(
+                "DeserializeContext: expected the deserialized expression to be of type {:?}, got {:?}",
+                self.tpe,
+                expr.tpe()
+            )));
+        }
+        // sigmastate substitutes the deserialized expressions once, before the evaluation
+        let has_nested = visitor::fold(&expr, false, |acc, e| {
+            acc || matches!(
+                e,
+                Expr::DeserializeContext(_) | Expr::DeserializeRegister(_)
+            )
+        });
+        if has_nested {
+            return Err(EvalError::UnexpectedExpr(
+                "DeserializeContext: the deserialized expression cannot be deserialized further"
+                    .to_string(),
+            ));
+        }
+        expr.eval(&Env::empty(), ctx)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::eval::context::Context;
+    use crate::eval::tests::{eval_out, try_eval_out};
+    use ergotree_ir::mir::bin_op::ArithOp;
+    use ergotree_ir::mir::bin_op::BinOp;
+    use ergotree_ir::mir::constant::Constant;
+    use sigma_test_util::force_any_val;
+    use std::sync::Arc;
+
+    const VAR_IDX: u8 = 1;
+
+    fn prepare_context(var: Constant) -> Arc<Context> {
+        let mut ctx = force_any_val::<Context>();
+        ctx.extension.values.clear();
+        ctx.extension.values.insert(VAR_IDX, var);
+        Arc::new(ctx)
+    }
+
+    fn plus(left: Expr, right: Expr) -> Expr {
+        BinOp {
+            kind: ArithOp::Plus.into(),
+            left: left.into(),
+            right: right.into(),
+        }
+        .into()
+    }
+
+    #[test]
+    fn eval_success() {
+        let script = plus(2i32.into(), 3i32.into());
+        let ctx = prepare_context(script.sigma_serialize_bytes().into());
+        let expr: Expr = plus(
+            DeserializeContext {
+                tpe: SType::SInt,
+                id: VAR_IDX,
+            }
+            .into(),
+            1i32.into(),
+        );
+        assert_eq!(eval_out::<i32>(&expr, ctx), 6);
+    }
+
+    #[test]
+    fn eval_missing_var() {
+        let ctx = prepare_context(1i32.into());
+        let expr: Expr = DeserializeContext {
+            tpe: SType::SInt,
+            id: VAR_IDX + 1,
+        }
+        .into();
+        assert!(try_eval_out::<i32>(&expr, ctx).is_err());
+    }
+
+    #[test]
+    fn eval_wrong_var_type() {
+        let ctx = prepare_context(1i32.into());
+        let expr: Expr = DeserializeContext {
+            tpe: SType::SInt,
+            id: VAR_IDX,
+        }
+        .into();
+        assert!(try_eval_out::<i32>(&expr, ctx).is_err());
+    }
+
+    #[test]
+    fn eval_wrong_expr_type() {
+        let script: Expr = true.into();
+        let ctx = prepare_context(script.sigma_serialize_bytes().into());
+        let expr: Expr = DeserializeContext {
+            tpe: SType::SInt,
+            id: VAR_IDX,
+        }
+        .into();
+        assert!(try_eval_out::<i32>(&expr, ctx).is_err());
+    }
+
+    #[test]
+    fn eval_nested() {
+        let nested: Expr = DeserializeContext {
+            tpe: SType::SInt,
+            id: VAR_IDX,
+        }
+        .into();
+        let ctx = prepare_context(nested.sigma_serialize_bytes().into());
+        assert!(try_eval_out::<i32>(&nested, ctx).is_err());
     }
 }
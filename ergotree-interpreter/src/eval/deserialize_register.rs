@@ -10,6 +10,8 @@ use crate::eval::Evaluable;
 
 impl Evaluable for DeserializeRegister {
     fn eval(&self, _env: &Env, _ctx: &mut EvalContext) -> Result<Value, EvalError> {
-        todo!()
+        Err(EvalError::NotImplementedYet(
+            "DeserializeRegister".to_string(),
+        ))
     }
 }
@@ -1,4 +1,5 @@
 use ergotree_ir::ir_ergo_box::IrBoxId;
+use ergotree_ir::mir::constant::TryExtractFromError;
 use ergotree_ir::mir::constant::TryExtractInto;
 use ergotree_ir::mir::extract_reg_as::ExtractRegisterAs;
 use ergotree_ir::mir::value::Value;
@@ -11,13 +12,20 @@ use crate::eval::Evaluable;
 impl Evaluable for ExtractRegisterAs {
     fn eval(&self, env: &Env, ctx: &mut EvalContext) -> Result<Value, EvalError> {
         let ir_box_id = self.input.eval(env, ctx)?.try_extract_into::<IrBoxId>()?;
-        Ok(Value::Opt(Box::new(
-            ctx.ctx
-                .box_arena
-                .get(&ir_box_id)?
-                .get_register(self.register_id)
-                .map(|c| c.v),
-        )))
+        match ctx
+            .ctx
+            .box_arena
+            .get(&ir_box_id)?
+            .get_register(self.register_id)
+        {
+            None => Ok(Value::Opt(None.into())),
+            Some(c) if c.tpe == self.elem_tpe => Ok(Value::Opt(Some(c.v).into())),
+            Some(c) => Err(TryExtractFromError(format!(
+                "ExtractRegisterAs: expected {:?}, found {:?}",
+                self.elem_tpe, c.tpe
+            ))
+            .into()),
+        }
     }
 }
 
@@ -27,13 +35,14 @@ mod tests {
     use super::*;
     use crate::eval::context::Context;
     use crate::eval::tests::eval_out;
+    use crate::eval::tests::try_eval_out;
     use ergotree_ir::mir::expr::Expr;
     use ergotree_ir::mir::global_vars::GlobalVars;
     use ergotree_ir::mir::option_get::OptionGet;
     use ergotree_ir::mir::unary_op::UnaryOpTryBuild;
     use ergotree_ir::types::stype::SType;
     use sigma_test_util::force_any_val;
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     #[test]
     fn eval_box_get_reg_r0() {
@@ -45,8 +54,21 @@ mod tests {
         .unwrap()
         .into();
         let option_get_expr: Expr = OptionGet::try_build(get_reg_expr).unwrap().into();
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         let v = eval_out::<i64>(&option_get_expr, ctx.clone());
         assert_eq!(v, ctx.self_box.get_box(&ctx.box_arena).unwrap().value());
     }
+
+    #[test]
+    fn eval_box_get_reg_wrong_type() {
+        let get_reg_expr: Expr = ExtractRegisterAs::new(
+            GlobalVars::SelfBox.into(),
+            0,
+            SType::SOption(SType::SInt.into()),
+        )
+        .unwrap()
+        .into();
+        let ctx = Arc::new(force_any_val::<Context>());
+        assert!(try_eval_out::<Value>(&get_reg_expr, ctx).is_err());
+    }
 }
@@ -24,7 +24,7 @@ impl Evaluable for GlobalVars {
 #[allow(clippy::unwrap_used)]
 #[cfg(test)]
 mod tests {
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     use crate::eval::context::Context;
     use crate::eval::tests::eval_out;
@@ -37,14 +37,14 @@ mod tests {
 
     #[test]
     fn eval_height() {
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         let expr = compile_expr("HEIGHT", ScriptEnv::new()).unwrap();
         assert_eq!(eval_out::<i32>(&expr, ctx.clone()), ctx.height as i32);
     }
 
     #[test]
     fn eval_self_box() {
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         assert_eq!(
             eval_out::<IrBoxId>(&GlobalVars::SelfBox.into(), ctx.clone()),
             ctx.self_box
@@ -53,7 +53,7 @@ mod tests {
 
     #[test]
     fn eval_outputs() {
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         assert_eq!(
             eval_out::<Vec<IrBoxId>>(&GlobalVars::Outputs.into(), ctx.clone()),
             ctx.outputs
@@ -62,7 +62,7 @@ mod tests {
 
     #[test]
     fn eval_inputs() {
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         assert_eq!(
             eval_out::<Vec<IrBoxId>>(&GlobalVars::Inputs.into(), ctx.clone()),
             ctx.inputs
@@ -0,0 +1,324 @@
+//! Resource limits enforced during the evaluation
+
+use ergotree_ir::mir::value::Value;
+use ergotree_ir::types::stype::SType;
+use thiserror::Error;
+
+/// Limits enforced during the evaluation to guard against scripts building huge intermediate
+/// values or recursing too deep (on the Rust stack). Checked on top of the cost limit since
+/// the cost of a node does not depend on the size of the values it creates.
+///
+/// The reference implementation (sigmastate) has no such limits, so none of them is enforced by
+/// default (a script accepted by the node must not be rejected here). Set them explicitly to
+/// bound the resources spent on untrusted scripts.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct EvalLimits {
+    /// Max depth of the nested node evaluations (including the lambda bodies evaluated by
+    /// `Apply` and collection operations)
+    pub max_call_depth: Option<usize>,
+    /// Max number of elements in a collection created during the evaluation
+    pub max_coll_size: Option<usize>,
+    /// Max total size (in bytes) of the collections created during the evaluation
+    pub max_allocated_bytes: Option<usize>,
+}
+
+/// Evaluation limit violation
+#[derive(Error, PartialEq, Eq, Debug, Clone)]
+pub enum EvalLimitError {
+    /// Max depth of the nested node evaluations exceeded
+    #[error("Call depth limit ({0}) exceeded")]
+    CallDepthLimitExceeded(usize),
+    /// Created collection is too big
+    #[error("Collection size limit ({limit}) exceeded: {size}")]
+    CollSizeLimitExceeded {
+        /// Limit
+        limit: usize,
+        /// Size of the collection
+        size: usize,
+    },
+    /// Total size of the created collections is too big
+    #[error("Allocated size limit ({0} bytes) exceeded")]
+    AllocationLimitExceeded(usize),
+}
+
+/// Tracks the resources used by the evaluation
+#[derive(Debug, Clone)]
+pub(crate) struct EvalLimitsTracker {
+    limits: EvalLimits,
+    call_depth: usize,
+    allocated_bytes: usize,
+}
+
+impl EvalLimitsTracker {
+    pub(crate) fn new(limits: EvalLimits) -> Self {
+        EvalLimitsTracker {
+            limits,
+            call_depth: 0,
+            allocated_bytes: 0,
+        }
+    }
+
+    /// Enter the evaluation of a node
+    pub(crate) fn enter(&mut self) -> Result<(), EvalLimitError> {
+        if let Some(limit) = self.limits.max_call_depth {
+            if self.call_depth >= limit {
+                return Err(EvalLimitError::CallDepthLimitExceeded(limit));
+            }
+        }
+        self.call_depth += 1;
+        Ok(())
+    }
+
+    /// Exit the evaluation of a node (entered with [`EvalLimitsTracker::enter`])
+    pub(crate) fn exit(&mut self) {
+        self.call_depth = self.call_depth.saturating_sub(1);
+    }
+
+    /// Check the size of a collection created by the evaluation and account for it in the total
+    /// allocated size
+    pub(crate) fn alloc_coll(
+        &mut self,
+        elem_tpe: &SType,
+        size: usize,
+    ) -> Result<(), EvalLimitError> {
+        if let Some(limit) = self.limits.max_coll_size {
+            if size > limit {
+                return Err(EvalLimitError::CollSizeLimitExceeded { limit, size });
+            }
+        }
+        if let Some(limit) = self.limits.max_allocated_bytes {
+            self.allocated_bytes = self
+                .allocated_bytes
+                .saturating_add(size.saturating_mul(coll_elem_size(elem_tpe)));
+            if self.allocated_bytes > limit {
+                return Err(EvalLimitError::AllocationLimitExceeded(limit));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Size of the collection element in memory (elements of the types with the native collection
+/// representation are stored unboxed)
+fn coll_elem_size(elem_tpe: &SType) -> usize {
+    match elem_tpe {
+        SType::SByte | SType::SBoolean => 1,
+        SType::SShort => 2,
+        SType::SInt => 4,
+        SType::SLong => 8,
+        _ => std::mem::size_of::<Value>(),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use std::convert::TryInto;
+
+    #[test]
+    fn call_depth() {
+        let mut tracker = EvalLimitsTracker::new(EvalLimits {
+            max_call_depth: Some(2),
+            ..Default::default()
+        });
+        tracker.enter().unwrap();
+        tracker.enter().unwrap();
+        assert_eq!(
+            tracker.enter(),
+            Err(EvalLimitError::CallDepthLimitExceeded(2))
+        );
+        tracker.exit();
+        tracker.enter().unwrap();
+    }
+
+    #[test]
+    fn allocated_bytes() {
+        let mut tracker = EvalLimitsTracker::new(EvalLimits {
+            max_allocated_bytes: Some(100),
+            ..Default::default()
+        });
+        tracker.alloc_coll(&SType::SByte, 60).unwrap();
+        tracker.alloc_coll(&SType::SInt, 10).unwrap();
+        assert_eq!(
+            tracker.alloc_coll(&SType::SByte, 1),
+            Err(EvalLimitError::AllocationLimitExceeded(100))
+        );
+    }
+
+    #[cfg(feature = "arbitrary")]
+    mod eval {
+        use std::sync::Arc;
+
+        use super::*;
+        use crate::eval::context::Context;
+        use crate::eval::env::Env;
+        use crate::eval::EvalError;
+        use crate::eval::Evaluator;
+        use crate::sigma_protocol::prover::hint::HintsBag;
+        use crate::sigma_protocol::prover::ProofBytes;
+        use crate::sigma_protocol::prover::Prover;
+        use crate::sigma_protocol::prover::ProverError;
+        use crate::sigma_protocol::prover::TestProver;
+        use crate::sigma_protocol::verifier::TestVerifier;
+        use crate::sigma_protocol::verifier::Verifier;
+        use crate::sigma_protocol::verifier::VerifierError;
+        use ergotree_ir::ergo_tree::ErgoTree;
+        use ergotree_ir::mir::bin_op::BinOp;
+        use ergotree_ir::mir::bin_op::RelationOp;
+        use ergotree_ir::mir::coll_append::Append;
+        use ergotree_ir::mir::coll_fold::Fold;
+        use ergotree_ir::mir::coll_size::SizeOf;
+        use ergotree_ir::mir::expr::Expr;
+        use ergotree_ir::mir::func_value::FuncArg;
+        use ergotree_ir::mir::func_value::FuncValue;
+        use ergotree_ir::mir::logical_not::LogicalNot;
+        use ergotree_ir::mir::select_field::SelectField;
+        use ergotree_ir::mir::unary_op::UnaryOpTryBuild;
+        use ergotree_ir::mir::val_use::ValUse;
+        use ergotree_ir::serialization::parser_limits::ParserLimits;
+        use ergotree_ir::types::stuple::STuple;
+        use sigma_test_util::force_any_val;
+
+        fn reduce(expr: &Expr, limits: EvalLimits) -> Result<(), EvalError> {
+            let ctx = Arc::new(force_any_val::<Context>());
+            TestVerifier
+                .reduce_to_crypto_with_limits(expr, &Env::empty(), ctx, limits)
+                .map(|_| ())
+        }
+
+        fn nested_not(depth: usize) -> Expr {
+            (0..depth).fold(true.into(), |acc, _| {
+                LogicalNot::try_build(acc).unwrap().into()
+            })
+        }
+
+        /// `Coll(1, 2, ...).fold(Coll[Byte]())({ (acc, _) => acc ++ chunk }).size > 0`
+        fn fold_append(count: usize, chunk: Vec<i8>) -> Expr {
+            let acc_tpe = SType::SColl(SType::SByte.into());
+            let arg_tpe: SType = STuple::pair(acc_tpe, SType::SInt).into();
+            let arg: Expr = ValUse {
+                val_id: 1.into(),
+                tpe: arg_tpe.clone(),
+            }
+            .into();
+            let body: Expr = Append::new(
+                SelectField::new(arg, 1.try_into().unwrap()).unwrap().into(),
+                chunk.into(),
+            )
+            .unwrap()
+            .into();
+            let fold: Expr = Fold::new(
+                (0..count as i32).collect::<Vec<i32>>().into(),
+                Vec::<i8>::new().into(),
+                FuncValue::new(
+                    vec![FuncArg {
+                        idx: 1.into(),
+                        tpe: arg_tpe,
+                    }],
+                    body,
+                )
+                .into(),
+            )
+            .unwrap()
+            .into();
+            BinOp {
+                kind: RelationOp::Gt.into(),
+                left: Box::new(SizeOf::try_build(fold).unwrap().into()),
+                right: Box::new(0i32.into()),
+            }
+            .into()
+        }
+
+        #[test]
+        fn no_limits_by_default() {
+            assert_eq!(
+                reduce(
+                    &nested_not(ParserLimits::MAX_TREE_DEPTH),
+                    EvalLimits::default()
+                ),
+                Ok(())
+            );
+            // the result is longer than `u16::MAX`
+            assert_eq!(
+                reduce(&fold_append(100, vec![1; 1000]), EvalLimits::default()),
+                Ok(())
+            );
+        }
+
+        #[test]
+        fn call_depth_limit() {
+            let limits = EvalLimits {
+                max_call_depth: Some(10),
+                ..Default::default()
+            };
+            assert_eq!(reduce(&nested_not(9), limits.clone()), Ok(()));
+            assert_eq!(
+                reduce(&nested_not(20), limits).unwrap_err(),
+                EvalError::LimitExceeded(EvalLimitError::CallDepthLimitExceeded(10))
+            );
+        }
+
+        #[test]
+        fn verify_and_prove_with_limits() {
+            let tree = ErgoTree::from(nested_not(20));
+            let limits = EvalLimits {
+                max_call_depth: Some(10),
+                ..Default::default()
+            };
+            let ctx = Arc::new(force_any_val::<Context>());
+            let err = EvalError::LimitExceeded(EvalLimitError::CallDepthLimitExceeded(10));
+            assert_eq!(
+                TestVerifier
+                    .verify_with_limits(
+                        &tree,
+                        &Env::empty(),
+                        ctx.clone(),
+                        ProofBytes::Empty,
+                        &[],
+                        limits.clone()
+                    )
+                    .err(),
+                Some(VerifierError::EvalError(err.clone()))
+            );
+            assert_eq!(
+                TestProver { secrets: vec![] }
+                    .prove_with_limits(&tree, &Env::empty(), ctx, &[], &HintsBag::empty(), limits)
+                    .err(),
+                Some(ProverError::EvalError(err))
+            );
+        }
+
+        #[test]
+        fn coll_size_limit() {
+            let limits = EvalLimits {
+                max_coll_size: Some(1000),
+                ..Default::default()
+            };
+            assert_eq!(
+                reduce(&fold_append(100, vec![1; 10]), limits.clone()),
+                Ok(())
+            );
+            assert_eq!(
+                reduce(&fold_append(101, vec![1; 10]), limits).unwrap_err(),
+                EvalError::LimitExceeded(EvalLimitError::CollSizeLimitExceeded {
+                    limit: 1000,
+                    size: 1010
+                })
+            );
+        }
+
+        #[test]
+        fn allocation_limit() {
+            let limits = EvalLimits {
+                max_allocated_bytes: Some(100_000),
+                ..Default::default()
+            };
+            // every step copies the accumulator, 1 + 2 + ... + 100 chunks are allocated
+            assert_eq!(
+                reduce(&fold_append(100, vec![1; 100]), limits).unwrap_err(),
+                EvalError::LimitExceeded(EvalLimitError::AllocationLimitExceeded(100_000))
+            );
+        }
+    }
+}
@@ -12,7 +12,7 @@ impl Evaluable for MethodCall {
         let ov = self.obj.eval(env, ectx)?;
         let argsv: Result<Vec<Value>, EvalError> =
             self.args.iter().map(|arg| arg.eval(env, ectx)).collect();
-        smethod_eval_fn(&self.method)(env, ectx, ov, argsv?)
+        smethod_eval_fn(&self.method)?(env, ectx, ov, argsv?)
     }
 }
 
@@ -20,7 +20,7 @@ impl Evaluable for MethodCall {
 #[cfg(test)]
 #[cfg(feature = "arbitrary")]
 mod tests {
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     use ergotree_ir::mir::constant::Constant;
     use ergotree_ir::mir::expr::Expr;
@@ -45,7 +45,7 @@ mod tests {
         .unwrap()
         .into();
         let option_get_expr: Expr = OptionGet::try_build(mc).unwrap().into();
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         assert_eq!(
             eval_out::<i64>(&option_get_expr, ctx.clone()),
             ctx.self_box.get_box(&ctx.box_arena).unwrap().value()
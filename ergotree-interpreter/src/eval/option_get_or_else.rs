@@ -32,7 +32,7 @@ mod tests {
     use ergotree_ir::mir::global_vars::GlobalVars;
     use ergotree_ir::types::stype::SType;
     use sigma_test_util::force_any_val;
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     #[test]
     fn eval_non_empty() {
@@ -47,7 +47,7 @@ mod tests {
         let option_get_expr: Expr = OptionGetOrElse::new(get_reg_expr, default_expr.into())
             .unwrap()
             .into();
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         let v = eval_out::<i64>(&option_get_expr, ctx.clone());
         assert_eq!(v, ctx.self_box.get_box(&ctx.box_arena).unwrap().value());
     }
@@ -65,7 +65,7 @@ mod tests {
         let option_get_expr: Expr = OptionGetOrElse::new(get_reg_expr, default_expr.into())
             .unwrap()
             .into();
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         let v = eval_out::<i64>(&option_get_expr, ctx);
         assert_eq!(v, 1i64);
     }
@@ -0,0 +1,504 @@
+//! Partial evaluation (constant folding) of expressions with the partially known context
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use ergotree_ir::mir::bin_op::BinOp;
+use ergotree_ir::mir::bin_op::BinOpKind;
+use ergotree_ir::mir::bin_op::RelationOp;
+use ergotree_ir::mir::block::BlockValue;
+use ergotree_ir::mir::constant::Constant;
+use ergotree_ir::mir::expr::Expr;
+use ergotree_ir::mir::global_vars::GlobalVars;
+use ergotree_ir::mir::if_op::If;
+use ergotree_ir::mir::sigma_and::SigmaAnd;
+use ergotree_ir::mir::sigma_or::SigmaOr;
+use ergotree_ir::mir::val_def::ValDef;
+use ergotree_ir::mir::val_def::ValId;
+use ergotree_ir::mir::value::Value;
+use ergotree_ir::mir::visitor;
+use ergotree_ir::sigma_protocol::sigma_boolean::SigmaBoolean;
+use ergotree_ir::sigma_protocol::sigma_boolean::SigmaProp;
+
+use super::context::Context;
+use super::cost_accum::CostAccumulator;
+use super::env::Env;
+use super::EvalContext;
+use super::Evaluable;
+
+/// Part of the context that can be unknown during the partial evaluation
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash)]
+pub enum ContextVar {
+    /// `HEIGHT`
+    Height,
+    /// `SELF`
+    SelfBox,
+    /// `INPUTS`
+    Inputs,
+    /// `OUTPUTS`
+    Outputs,
+    /// `CONTEXT.dataInputs`
+    DataInputs,
+    /// Pre header of the current block (including `minerPubKey`)
+    PreHeader,
+    /// Context extension variables (`getVar`)
+    Extension,
+}
+
+/// Context with some of its parts unknown
+#[derive(Debug)]
+pub struct PartialContext {
+    /// Context with the known parts (unknown parts are ignored)
+    pub ctx: Arc<Context>,
+    /// Parts of the context that are unknown
+    pub unknown: HashSet<ContextVar>,
+}
+
+impl PartialContext {
+    /// Create new partial context, `unknown` parts of `ctx` are not used in the evaluation
+    pub fn new<I: IntoIterator<Item = ContextVar>>(ctx: Arc<Context>, unknown: I) -> Self {
+        PartialContext {
+            ctx,
+            unknown: unknown.into_iter().collect(),
+        }
+    }
+
+    fn is_known(&self, var: ContextVar) -> bool {
+        !self.unknown.contains(&var)
+    }
+}
+
+/// Result of the partial evaluation
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct PartialEvalResult {
+    /// Expression with all sub-expressions with known inputs replaced by their values
+    pub residual: Expr,
+}
+
+impl PartialEvalResult {
+    /// Proposition if the expression was fully reduced (to a boolean or a sigma proposition)
+    pub fn reduced(&self) -> Option<SigmaBoolean> {
+        match &self.residual {
+            Expr::Const(Constant {
+                v: Value::Boolean(b),
+                ..
+            }) => Some(SigmaBoolean::TrivialProp(*b)),
+            Expr::Const(Constant {
+                v: Value::SigmaProp(sp),
+                ..
+            }) => Some(sp.value().clone()),
+            _ => None,
+        }
+    }
+
+    /// Known non-trivial sigma propositions left in the residual expression (e.g. public keys
+    /// required to sign the spending transaction if the unknown conditions hold)
+    pub fn sigma_conditions(&self) -> Vec<SigmaBoolean> {
+        visitor::fold(&self.residual, Vec::new(), |mut acc, e| {
+            if let Expr::Const(Constant {
+                v: Value::SigmaProp(sp),
+                ..
+            }) = e
+            {
+                if !matches!(sp.value(), SigmaBoolean::TrivialProp(_)) {
+                    acc.push(sp.value().clone());
+                }
+            }
+            acc
+        })
+    }
+}
+
+/// Folds the sub-expressions of `expr` which depend only on the known parts of the context
+/// (evaluating them with the interpreter) and simplifies the boolean and sigma connectives with
+/// the known operands. Sub-expressions which depend on the unknown parts of the context or fail
+/// to evaluate are left in the residual expression.
+pub fn partial_eval(expr: &Expr, pctx: &PartialContext) -> PartialEvalResult {
+    let mut pe = PartialEvaluator {
+        pctx,
+        env: Env::empty(),
+        known_vals: HashSet::new(),
+        ectx: EvalContext::new(pctx.ctx.clone(), CostAccumulator::new(0, None)),
+    };
+    PartialEvalResult {
+        residual: pe.peval(expr.clone()),
+    }
+}
+
+struct PartialEvaluator<'a> {
+    pctx: &'a PartialContext,
+    /// Values of the variables (`ValDef`) evaluated so far
+    env: Env,
+    known_vals: HashSet<ValId>,
+    ectx: EvalContext,
+}
+
+impl<'a> PartialEvaluator<'a> {
+    fn peval(&mut self, e: Expr) -> Expr {
+        if let Expr::Const(_) = e {
+            return e;
+        }
+        if let Some(folded) = self.try_fold(&e) {
+            return folded;
+        }
+        let e = match e {
+            Expr::BlockValue(block) => self.block(block),
+            _ => match visitor::map_children(e, &mut |c| Ok::<Expr, Infallible>(self.peval(c))) {
+                Ok(e) => e,
+                Err(never) => match never {},
+            },
+        };
+        // children might get folded
+        match self.try_fold(&e) {
+            Some(folded) => folded,
+            None => simplify(e),
+        }
+    }
+
+    fn block(&mut self, block: BlockValue) -> Expr {
+        let mut items = Vec::new();
+        for item in block.items {
+            match item {
+                Expr::ValDef(ValDef { id, rhs }) => {
+                    let value = if self.is_known(&rhs) {
+                        rhs.eval(&self.env, &mut self.ectx).ok()
+                    } else {
+                        None
+                    };
+                    match value {
+                        Some(v) => {
+                            self.env.insert(id, v.clone());
+                            self.known_vals.insert(id);
+                            if !is_constant_value(&v) {
+                                // value can't be inlined, keep the definition
+                                items.push(ValDef { id, rhs }.into());
+                            }
+                        }
+                        None => items.push(
+                            ValDef {
+                                id,
+                                rhs: Box::new(self.peval(*rhs)),
+                            }
+                            .into(),
+                        ),
+                    }
+                }
+                item => items.push(self.peval(item)),
+            }
+        }
+        let result = self.peval(*block.result);
+        if items.is_empty() {
+            result
+        } else {
+            BlockValue {
+                items,
+                result: Box::new(result),
+            }
+            .into()
+        }
+    }
+
+    fn try_fold(&mut self, e: &Expr) -> Option<Expr> {
+        if !self.is_known(e) {
+            return None;
+        }
+        let v = e.eval(&self.env, &mut self.ectx).ok()?;
+        if is_constant_value(&v) {
+            Some(Expr::Const(Constant {
+                tpe: e.post_eval_tpe(),
+                v,
+            }))
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if the expression can be evaluated using only the known inputs
+    fn is_known(&self, e: &Expr) -> bool {
+        let bound = visitor::fold(e, HashSet::new(), |mut acc, node| {
+            match node {
+                Expr::FuncValue(f) => acc.extend(f.args().iter().map(|a| a.idx)),
+                Expr::ValDef(vd) => {
+                    acc.insert(vd.id);
+                }
+                _ => (),
+            };
+            acc
+        });
+        visitor::fold(e, true, |acc, node| {
+            acc && match node {
+                Expr::ValUse(vu) => {
+                    self.known_vals.contains(&vu.val_id) || bound.contains(&vu.val_id)
+                }
+                Expr::GlobalVars(GlobalVars::Height) => self.pctx.is_known(ContextVar::Height),
+                Expr::GlobalVars(GlobalVars::SelfBox) => self.pctx.is_known(ContextVar::SelfBox),
+                Expr::GlobalVars(GlobalVars::Inputs) => self.pctx.is_known(ContextVar::Inputs),
+                Expr::GlobalVars(GlobalVars::Outputs) => self.pctx.is_known(ContextVar::Outputs),
+                Expr::GlobalVars(GlobalVars::MinerPubKey) => {
+                    self.pctx.is_known(ContextVar::PreHeader)
+                }
+                Expr::Context => self.pctx.unknown.is_empty(),
+                Expr::GetVar(_) | Expr::DeserializeContext(_) => {
+                    self.pctx.is_known(ContextVar::Extension)
+                }
+                Expr::DeserializeRegister(_) => self.pctx.is_known(ContextVar::SelfBox),
+                // not supported by the interpreter
+                Expr::ConstPlaceholder(_) | Expr::SubstConstants(_) | Expr::Atleast(_) => false,
+                _ => true,
+            }
+        })
+    }
+}
+
+/// Returns true if the value can be put in a constant
+fn is_constant_value(v: &Value) -> bool {
+    match v {
+        Value::Boolean(_)
+        | Value::Byte(_)
+        | Value::Short(_)
+        | Value::Int(_)
+        | Value::Long(_)
+        | Value::BigInt(_)
+        | Value::GroupElement(_)
+        | Value::SigmaProp(_) => true,
+        Value::Coll(coll) => coll.iter().all(|item| is_constant_value(&item)),
+        Value::Tup(items) => items.iter().all(is_constant_value),
+        Value::Opt(opt) => match opt.as_ref() {
+            Some(v) => is_constant_value(v),
+            None => true,
+        },
+        Value::CBox(_) | Value::AvlTree | Value::Context | Value::Global | Value::Lambda(_) => {
+            false
+        }
+    }
+}
+
+fn const_bool(e: &Expr) -> Option<bool> {
+    match e {
+        Expr::Const(Constant {
+            v: Value::Boolean(b),
+            ..
+        }) => Some(*b),
+        _ => None,
+    }
+}
+
+fn const_sigma_bool(e: &Expr) -> Option<bool> {
+    match e {
+        Expr::Const(Constant {
+            v: Value::SigmaProp(sp),
+            ..
+        }) => match sp.value() {
+            SigmaBoolean::TrivialProp(b) => Some(*b),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn trivial_prop(b: bool) -> Expr {
+    Expr::Const(SigmaProp::new(SigmaBoolean::TrivialProp(b)).into())
+}
+
+/// Simplifies the boolean and sigma connectives with the known operands
+fn simplify(e: Expr) -> Expr {
+    match e {
+        Expr::BinOp(BinOp {
+            kind: BinOpKind::Relation(RelationOp::And),
+            left,
+            right,
+        }) => match (const_bool(&left), const_bool(&right)) {
+            (Some(false), _) => false.into(),
+            (Some(true), _) => *right,
+            (_, Some(true)) => *left,
+            _ => BinOp {
+                kind: RelationOp::And.into(),
+                left,
+                right,
+            }
+            .into(),
+        },
+        Expr::BinOp(BinOp {
+            kind: BinOpKind::Relation(RelationOp::Or),
+            left,
+            right,
+        }) => match (const_bool(&left), const_bool(&right)) {
+            (Some(true), _) => true.into(),
+            (Some(false), _) => *right,
+            (_, Some(false)) => *left,
+            _ => BinOp {
+                kind: RelationOp::Or.into(),
+                left,
+                right,
+            }
+            .into(),
+        },
+        Expr::If(If {
+            condition,
+            true_branch,
+            false_branch,
+        }) => match const_bool(&condition) {
+            Some(true) => *true_branch,
+            Some(false) => *false_branch,
+            None => If {
+                condition,
+                true_branch,
+                false_branch,
+            }
+            .into(),
+        },
+        Expr::SigmaAnd(op) => simplify_sigma_conj(op.items.as_vec(), true, |items| {
+            SigmaAnd::new(items).map(Expr::from).ok()
+        })
+        .unwrap_or_else(|| op.into()),
+        Expr::SigmaOr(op) => simplify_sigma_conj(op.items.as_vec(), false, |items| {
+            SigmaOr::new(items).map(Expr::from).ok()
+        })
+        .unwrap_or_else(|| op.into()),
+        e => e,
+    }
+}
+
+/// Simplifies sigma AND (`neutral` is `true`) or OR (`neutral` is `false`) with the known
+/// trivial items. Returns `None` if there is nothing to simplify.
+fn simplify_sigma_conj<F: FnOnce(Vec<Expr>) -> Option<Expr>>(
+    items: &[Expr],
+    neutral: bool,
+    build: F,
+) -> Option<Expr> {
+    if items
+        .iter()
+        .any(|it| const_sigma_bool(it) == Some(!neutral))
+    {
+        return Some(trivial_prop(!neutral));
+    }
+    let mut rest: Vec<Expr> = items
+        .iter()
+        .filter(|it| const_sigma_bool(it) != Some(neutral))
+        .cloned()
+        .collect();
+    match rest.len() {
+        0 => Some(trivial_prop(neutral)),
+        1 => Some(rest.remove(0)),
+        n if n == items.len() => None,
+        _ => build(rest),
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "arbitrary")]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use ergotree_ir::mir::bin_op::ArithOp;
+    use ergotree_ir::mir::bool_to_sigma::BoolToSigmaProp;
+    use ergotree_ir::mir::coll_size::SizeOf;
+    use ergotree_ir::mir::val_use::ValUse;
+    use ergotree_ir::pretty_printer::print_expr;
+    use ergotree_ir::sigma_protocol::sigma_boolean::ProveDlog;
+    use ergotree_ir::types::stype::SType;
+    use sigma_test_util::force_any_val;
+
+    fn bin_op<K: Into<BinOpKind>>(kind: K, left: Expr, right: Expr) -> Expr {
+        BinOp {
+            kind: kind.into(),
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+        .into()
+    }
+
+    fn sigma_prop(input: Expr) -> Expr {
+        BoolToSigmaProp {
+            input: Box::new(input),
+        }
+        .into()
+    }
+
+    fn outputs_size() -> Expr {
+        SizeOf {
+            input: Box::new(GlobalVars::Outputs.into()),
+        }
+        .into()
+    }
+
+    /// HEIGHT > 100 && OUTPUTS.size > 1
+    fn height_and_outputs() -> Expr {
+        bin_op(
+            RelationOp::And,
+            bin_op(RelationOp::Gt, GlobalVars::Height.into(), 100i32.into()),
+            bin_op(RelationOp::Gt, outputs_size(), 1i32.into()),
+        )
+    }
+
+    fn pctx_with_height(height: u32) -> PartialContext {
+        let mut ctx = force_any_val::<Context>();
+        ctx.height = height;
+        PartialContext::new(Arc::new(ctx), vec![ContextVar::Outputs])
+    }
+
+    #[test]
+    fn fold_known_false() {
+        let res = partial_eval(&sigma_prop(height_and_outputs()), &pctx_with_height(50));
+        assert_eq!(res.reduced(), Some(SigmaBoolean::TrivialProp(false)));
+    }
+
+    #[test]
+    fn residual_for_unknown() {
+        let res = partial_eval(&sigma_prop(height_and_outputs()), &pctx_with_height(200));
+        assert_eq!(res.reduced(), None);
+        assert_eq!(print_expr(&res.residual), "sigmaProp(OUTPUTS.size > 1)");
+    }
+
+    #[test]
+    fn residual_sigma_conditions() {
+        let pk = force_any_val::<ProveDlog>();
+        let expr: Expr = SigmaAnd::new(vec![
+            Expr::Const(pk.clone().into()),
+            sigma_prop(height_and_outputs()),
+        ])
+        .unwrap()
+        .into();
+        let res = partial_eval(&expr, &pctx_with_height(200));
+        assert_eq!(res.reduced(), None);
+        assert_eq!(res.sigma_conditions(), vec![pk.clone().into()]);
+        let res = partial_eval(&expr, &pctx_with_height(50));
+        assert_eq!(res.reduced(), Some(SigmaBoolean::TrivialProp(false)));
+        let expr: Expr = SigmaAnd::new(vec![
+            Expr::Const(pk.clone().into()),
+            sigma_prop(bin_op(
+                RelationOp::Gt,
+                GlobalVars::Height.into(),
+                100i32.into(),
+            )),
+        ])
+        .unwrap()
+        .into();
+        let res = partial_eval(&expr, &pctx_with_height(200));
+        assert_eq!(res.reduced(), Some(pk.into()));
+    }
+
+    #[test]
+    fn inline_known_val() {
+        // { val h = HEIGHT + 1; OUTPUTS.size < h }
+        let h = ValUse {
+            val_id: 1.into(),
+            tpe: SType::SInt,
+        };
+        let expr: Expr = BlockValue {
+            items: vec![ValDef {
+                id: 1.into(),
+                rhs: Box::new(bin_op(
+                    ArithOp::Plus,
+                    GlobalVars::Height.into(),
+                    1i32.into(),
+                )),
+            }
+            .into()],
+            result: Box::new(bin_op(RelationOp::Lt, outputs_size(), h.into())),
+        }
+        .into();
+        let res = partial_eval(&expr, &pctx_with_height(200));
+        assert_eq!(print_expr(&res.residual), "OUTPUTS.size < 201");
+    }
+}
@@ -4,13 +4,14 @@ use crate::eval::Evaluable;
 use ergotree_ir::mir::constant::TryExtractInto;
 use ergotree_ir::mir::value::CollKind;
 use ergotree_ir::mir::value::Value;
+use ergotree_ir::types::stype::SType;
 
 use super::EvalFn;
 
 pub(crate) static INDEX_OF_EVAL_FN: EvalFn = |_env, _ctx, obj, args| {
     Ok(Value::Int({
-        let normalized_input_vals: Vec<Value> = match obj {
-            Value::Coll(coll) => Ok(coll.as_vec()),
+        let input_coll = match obj {
+            Value::Coll(coll) => Ok(coll),
             _ => Err(EvalError::UnexpectedValue(format!(
                 "expected obj to be Value::Coll, got: {0:?}",
                 obj
@@ -20,15 +21,20 @@ pub(crate) static INDEX_OF_EVAL_FN: EvalFn = |_env, _ctx, obj, args| {
             .get(0)
             .cloned()
             .ok_or_else(|| EvalError::NotFound("indexOf: missing first arg".to_string()))?;
-        let fallback_index = args
+        let from = args
             .get(1)
             .cloned()
-            .ok_or_else(|| EvalError::NotFound("indexOf: missing second arg".to_string()))?;
-        let index_of = normalized_input_vals
-            .into_iter()
+            .ok_or_else(|| EvalError::NotFound("indexOf: missing second arg".to_string()))?
+            .try_extract_into::<i32>()?
+            .max(0) as usize;
+        // same as Scala's `indexOf(elem, from)`, -1 if not found
+        let index_of = input_coll
+            .iter()
+            .skip(from)
             .position(|it| it == target_element)
-            .unwrap_or(fallback_index.try_extract_into::<i32>()? as usize);
-        index_of as i32
+            .map(|pos| (pos + from) as i32)
+            .unwrap_or(-1);
+        index_of
     }))
 };
 
@@ -79,14 +85,24 @@ pub(crate) static FLATMAP_EVAL_FN: EvalFn = |env, ctx, obj, args| {
             input_v
         ))),
     }?;
-    normalized_input_vals
+    let out_elem_tpe = match lambda.body.tpe() {
+        SType::SColl(elem_tpe) => Ok(*elem_tpe),
+        tpe => Err(EvalError::UnexpectedValue(format!(
+            "expected Flatmap lambda to return a collection, got: {0:?}",
+            tpe
+        ))),
+    }?;
+    let values = normalized_input_vals
         .iter()
         .map(|item| lambda_call(item.clone()))
-        .collect::<Result<Vec<Value>, EvalError>>()
-        .map(|values| {
-            CollKind::from_vec_vec(lambda.body.tpe(), values).map_err(EvalError::TryExtractFrom)
-        })
-        .and_then(|v| v) // flatten <Result<Result<Value, _>, _>
+        .collect::<Result<Vec<Value>, EvalError>>()?;
+    let size = values.iter().fold(0usize, |acc, v| match v {
+        Value::Coll(coll) => acc.saturating_add(coll.len()),
+        _ => acc,
+    });
+    ctx.limits.alloc_coll(&out_elem_tpe, size)?;
+    CollKind::from_vec_vec(out_elem_tpe, values)
+        .map_err(EvalError::TryExtractFrom)
         .map(Value::Coll)
 };
 
@@ -103,6 +119,7 @@ mod tests {
     use ergotree_ir::mir::func_value::FuncValue;
     use ergotree_ir::mir::method_call::MethodCall;
     use ergotree_ir::mir::val_use::ValUse;
+    use ergotree_ir::mir::value::Value;
     use ergotree_ir::types::scoll;
     use ergotree_ir::types::stype::SType;
     use ergotree_ir::types::stype_param::STypeVar;
@@ -127,7 +144,7 @@ mod tests {
     }
 
     #[test]
-    fn eval_index_of_default() {
+    fn eval_index_of_not_found() {
         let coll_const: Constant = vec![1i64, 2i64].into();
         let expr: Expr = MethodCall::new(
             coll_const.into(),
@@ -139,6 +156,38 @@ mod tests {
         .unwrap()
         .into();
         let res = eval_out_wo_ctx::<i32>(&expr);
+        assert_eq!(res, -1);
+    }
+
+    #[test]
+    fn eval_index_of_from() {
+        let coll_const: Constant = vec![2i64, 1i64, 2i64].into();
+        let expr: Expr = MethodCall::new(
+            coll_const.into(),
+            scoll::INDEX_OF_METHOD
+                .clone()
+                .with_concrete_types(&[(STypeVar::t(), SType::SLong)].iter().cloned().collect()),
+            vec![2i64.into(), 1i32.into()],
+        )
+        .unwrap()
+        .into();
+        let res = eval_out_wo_ctx::<i32>(&expr);
+        assert_eq!(res, 2);
+    }
+
+    #[test]
+    fn eval_index_of_negative_from() {
+        let coll_const: Constant = vec![2i64, 1i64].into();
+        let expr: Expr = MethodCall::new(
+            coll_const.into(),
+            scoll::INDEX_OF_METHOD
+                .clone()
+                .with_concrete_types(&[(STypeVar::t(), SType::SLong)].iter().cloned().collect()),
+            vec![2i64.into(), (-5i32).into()],
+        )
+        .unwrap()
+        .into();
+        let res = eval_out_wo_ctx::<i32>(&expr);
         assert_eq!(res, 0);
     }
 
@@ -183,7 +232,58 @@ mod tests {
         )
         .unwrap()
         .into();
-        let res = eval_out_wo_ctx::<Vec<bool>>(&expr);
-        assert_eq!(res, vec![true, false]);
+        let res = eval_out_wo_ctx::<Value>(&expr);
+        let expected: Value = vec![true, false].into();
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn eval_flatmap_elem_tpe() {
+        let coll_const: Constant = vec![1i64, 2i64].into();
+        let arg: Expr = ValUse {
+            val_id: 1.into(),
+            tpe: SType::SLong,
+        }
+        .into();
+        let body: Expr = Collection::Exprs {
+            elem_tpe: SType::SLong,
+            items: vec![arg.clone(), arg],
+        }
+        .into();
+        let expr: Expr = MethodCall::new(
+            coll_const.into(),
+            scoll::FLATMAP_METHOD.clone().with_concrete_types(
+                &[
+                    (STypeVar::iv(), SType::SLong),
+                    (STypeVar::ov(), SType::SLong),
+                ]
+                .iter()
+                .cloned()
+                .collect(),
+            ),
+            vec![FuncValue::new(
+                vec![FuncArg {
+                    idx: 1.into(),
+                    tpe: SType::SLong,
+                }],
+                body,
+            )
+            .into()],
+        )
+        .unwrap()
+        .into();
+        match eval_out_wo_ctx::<Value>(&expr) {
+            Value::Coll(coll) => {
+                assert_eq!(*coll.elem_tpe(), SType::SLong);
+                assert_eq!(
+                    coll.as_vec(),
+                    vec![1i64, 1, 2, 2]
+                        .into_iter()
+                        .map(Value::from)
+                        .collect::<Vec<_>>()
+                );
+            }
+            v => panic!("expected collection, got {:?}", v),
+        }
     }
 }
@@ -27,7 +27,7 @@ mod tests {
     use ergotree_ir::sigma_protocol::sigma_boolean::SigmaBoolean;
     use ergotree_ir::sigma_protocol::sigma_boolean::SigmaConjecture;
     use std::convert::TryInto;
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     use crate::eval::context::Context;
     use crate::eval::tests::eval_out;
@@ -47,7 +47,7 @@ mod tests {
         fn eval(sigmaprops in collection::vec(any::<SigmaProp>(), 2..10)) {
             let items = sigmaprops.clone().into_iter().map(|sp| Expr::Const(sp.into())).collect();
             let expr: Expr = SigmaOr::new(items).unwrap().into();
-            let ctx = Rc::new(force_any_val::<Context>());
+            let ctx = Arc::new(force_any_val::<Context>());
             let res = eval_out::<SigmaProp>(&expr, ctx);
             let expected_sb: Vec<SigmaBoolean> = sigmaprops.into_iter().map(|sp| sp.into()).collect();
             prop_assert!(matches!(res.clone().into(), SigmaBoolean::SigmaConjecture(SigmaConjecture::Cor(_))));
@@ -21,13 +21,61 @@ impl Evaluable for SigmaPropBytes {
 
 #[cfg(feature = "arbitrary")]
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
     use crate::eval::tests::eval_out_wo_ctx;
     use ergotree_ir::mir::constant::Constant;
     use ergotree_ir::mir::expr::Expr;
+    use ergotree_ir::sigma_protocol::dlog_group::EcPoint;
+    use ergotree_ir::sigma_protocol::sigma_boolean::cand::Cand;
+    use ergotree_ir::sigma_protocol::sigma_boolean::cor::Cor;
+    use ergotree_ir::sigma_protocol::sigma_boolean::ProveDhTuple;
+    use ergotree_ir::sigma_protocol::sigma_boolean::ProveDlog;
+    use ergotree_ir::sigma_protocol::sigma_boolean::SigmaBoolean;
+    use ergotree_ir::sigma_protocol::sigma_boolean::SigmaConjecture;
+    use ergotree_ir::sigma_protocol::sigma_boolean::SigmaProofOfKnowledgeTree;
     use ergotree_ir::sigma_protocol::sigma_boolean::SigmaProp;
     use proptest::prelude::*;
+    use sigma_test_util::force_any_val;
+    use std::convert::TryInto;
+
+    fn eval_prop_bytes(sb: SigmaBoolean) -> Vec<u8> {
+        let input: Constant = SigmaProp::new(sb).into();
+        let e: Expr = SigmaPropBytes {
+            input: Box::new(input.into()),
+        }
+        .into();
+        eval_out_wo_ctx::<Vec<u8>>(&e)
+    }
+
+    #[test]
+    fn eval_all_kinds() {
+        let pk = || -> SigmaBoolean { force_any_val::<ProveDlog>().into() };
+        let dht = SigmaBoolean::ProofOfKnowledge(SigmaProofOfKnowledgeTree::ProveDhTuple(
+            ProveDhTuple::new(
+                force_any_val::<EcPoint>(),
+                force_any_val::<EcPoint>(),
+                force_any_val::<EcPoint>(),
+                force_any_val::<EcPoint>(),
+            ),
+        ));
+        let cand = SigmaBoolean::SigmaConjecture(SigmaConjecture::Cand(Cand {
+            items: vec![pk(), dht.clone()].try_into().unwrap(),
+        }));
+        let cor = SigmaBoolean::SigmaConjecture(SigmaConjecture::Cor(Cor {
+            items: vec![cand.clone(), pk()].try_into().unwrap(),
+        }));
+        for sb in [
+            dht,
+            cand,
+            cor,
+            SigmaBoolean::TrivialProp(true),
+            SigmaBoolean::TrivialProp(false),
+        ] {
+            assert_eq!(eval_prop_bytes(sb.clone()), SigmaProp::new(sb).prop_bytes());
+        }
+    }
 
     proptest! {
 
@@ -0,0 +1,311 @@
+//! Evaluation tracing
+use std::fmt;
+use std::fmt::Write;
+
+use ergotree_ir::mir::expr::Expr;
+use ergotree_ir::mir::value::Value;
+use ergotree_ir::pretty_printer::print_expr;
+use ergotree_ir::pretty_printer::print_value;
+use ergotree_ir::types::stype::SType;
+
+use super::env::Env;
+use super::EvalError;
+
+/// Max length of the node source printed in the rendered trace
+const MAX_SOURCE_LEN: usize = 80;
+
+/// Evaluated node
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct EvalTraceNode {
+    /// Name of the node kind (i.e. "BinOp")
+    pub node_name: &'static str,
+    /// Op code of the node (`None` for constants)
+    pub op_code: Option<u8>,
+    /// Node printed as ErgoScript
+    pub source: String,
+    /// Type of the node value
+    pub tpe: SType,
+    /// Result of the node evaluation
+    pub result: Result<Value, EvalError>,
+    /// Cost of the node evaluation (including the evaluated children)
+    pub cost: u64,
+    /// Nodes evaluated while evaluating this node (i.e. inputs), in the order of evaluation
+    pub children: Vec<EvalTraceNode>,
+}
+
+impl EvalTraceNode {
+    /// Values of the evaluated children
+    pub fn inputs(&self) -> Vec<&Value> {
+        self.children
+            .iter()
+            .filter_map(|c| c.result.as_ref().ok())
+            .collect()
+    }
+
+    /// Find the first node (depth first) with `false` result value among this node and its
+    /// children, i.e. the failed condition
+    pub fn find_false(&self) -> Option<&EvalTraceNode> {
+        match self.children.iter().find_map(|c| c.find_false()) {
+            Some(node) => Some(node),
+            None if self.result == Ok(Value::Boolean(false)) => Some(self),
+            None => None,
+        }
+    }
+
+    fn render(&self, depth: usize, out: &mut String) -> fmt::Result {
+        let source = self.source.split_whitespace().collect::<Vec<_>>().join(" ");
+        let source = if source.chars().count() > MAX_SOURCE_LEN {
+            format!(
+                "{}...",
+                source.chars().take(MAX_SOURCE_LEN).collect::<String>()
+            )
+        } else {
+            source
+        };
+        let result = match &self.result {
+            Ok(v) => print_value(v, &self.tpe),
+            Err(e) => format!("error: {}", e),
+        };
+        write!(
+            out,
+            "{:indent$}{} => {}",
+            "",
+            source,
+            result,
+            indent = depth * 2
+        )?;
+        match self.op_code {
+            Some(op_code) => writeln!(
+                out,
+                "  // {} (0x{:02x}), cost {}",
+                self.node_name, op_code, self.cost
+            )?,
+            None => writeln!(out, "  // {}, cost {}", self.node_name, self.cost)?,
+        };
+        self.children
+            .iter()
+            .try_for_each(|c| c.render(depth + 1, out))
+    }
+}
+
+/// Tree of the evaluated nodes
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct EvalTrace {
+    /// Top level evaluated nodes
+    pub roots: Vec<EvalTraceNode>,
+}
+
+impl EvalTrace {
+    /// Renders the trace as the evaluated nodes printed as ErgoScript and annotated with the
+    /// result value, node kind, op code and cost. Children are indented under the parent node.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        #[allow(clippy::unwrap_used)]
+        // writing into String never fails
+        self.roots
+            .iter()
+            .try_for_each(|n| n.render(0, &mut out))
+            .unwrap();
+        out
+    }
+}
+
+/// What to do after the hook call
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum StepAction {
+    /// Continue evaluation
+    Continue,
+    /// Stop evaluation with [`EvalError::Aborted`]
+    Abort,
+}
+
+/// Callbacks for breakpoints and evaluation steps
+pub trait EvalHook: Send {
+    /// Returns true if the evaluation should break on the given node (before evaluating it)
+    fn is_breakpoint(&self, _expr: &Expr) -> bool {
+        false
+    }
+
+    /// Called before evaluation of the node for which [`EvalHook::is_breakpoint`] returned true
+    fn on_breakpoint(&mut self, _expr: &Expr, _env: &Env) -> StepAction {
+        StepAction::Continue
+    }
+
+    /// Called after each node evaluation, `depth` is the nesting level of the node
+    fn on_step(&mut self, _node: &EvalTraceNode, _depth: usize) -> StepAction {
+        StepAction::Continue
+    }
+}
+
+/// Records the evaluated nodes into [`EvalTrace`]
+pub(crate) struct Tracer {
+    /// Children of the nodes being evaluated (the first one is for the top level nodes)
+    stack: Vec<Vec<EvalTraceNode>>,
+    hook: Option<Box<dyn EvalHook>>,
+}
+
+impl Tracer {
+    pub(crate) fn new(hook: Option<Box<dyn EvalHook>>) -> Self {
+        Tracer {
+            stack: vec![Vec::new()],
+            hook,
+        }
+    }
+
+    pub(crate) fn enter(&mut self, expr: &Expr, env: &Env) -> Result<(), EvalError> {
+        if let Some(hook) = self.hook.as_mut() {
+            if hook.is_breakpoint(expr) && hook.on_breakpoint(expr, env) == StepAction::Abort {
+                return Err(EvalError::Aborted(expr.node_name().to_string()));
+            }
+        }
+        self.stack.push(Vec::new());
+        Ok(())
+    }
+
+    pub(crate) fn exit(
+        &mut self,
+        expr: &Expr,
+        result: Result<Value, EvalError>,
+        cost: u64,
+    ) -> Result<Value, EvalError> {
+        let children = self.stack.pop().unwrap_or_default();
+        let node = EvalTraceNode {
+            node_name: expr.node_name(),
+            op_code: expr.op_code_value(),
+            source: print_expr(expr),
+            tpe: expr.post_eval_tpe(),
+            result: result.clone(),
+            cost,
+            children,
+        };
+        let depth = self.stack.len().saturating_sub(1);
+        let action = self
+            .hook
+            .as_mut()
+            .map(|hook| hook.on_step(&node, depth))
+            .unwrap_or(StepAction::Continue);
+        if let Some(parent) = self.stack.last_mut() {
+            parent.push(node);
+        }
+        match action {
+            StepAction::Continue => result,
+            StepAction::Abort => Err(EvalError::Aborted(expr.node_name().to_string())),
+        }
+    }
+
+    pub(crate) fn into_trace(mut self) -> EvalTrace {
+        // nodes left on the stack if the evaluation was interrupted
+        while self.stack.len() > 1 {
+            let orphans = self.stack.pop().unwrap_or_default();
+            if let Some(parent) = self.stack.last_mut() {
+                parent.extend(orphans);
+            }
+        }
+        EvalTrace {
+            roots: self.stack.pop().unwrap_or_default(),
+        }
+    }
+}
+
+impl fmt::Debug for Tracer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tracer")
+            .field("stack", &self.stack)
+            .field("hook", &self.hook.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "arbitrary")]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::eval::context::Context;
+    use crate::eval::Evaluator;
+    use crate::sigma_protocol::verifier::TestVerifier;
+    use ergotree_ir::mir::bin_op::BinOp;
+    use ergotree_ir::mir::bin_op::RelationOp;
+    use ergotree_ir::mir::bool_to_sigma::BoolToSigmaProp;
+    use ergotree_ir::mir::global_vars::GlobalVars;
+    use ergotree_ir::sigma_protocol::sigma_boolean::SigmaBoolean;
+    use sigma_test_util::force_any_val;
+
+    fn rel(kind: RelationOp, left: Expr, right: Expr) -> Expr {
+        BinOp {
+            kind: kind.into(),
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+        .into()
+    }
+
+    /// sigmaProp(HEIGHT > 100 && HEIGHT < 1000)
+    fn height_in_range() -> Expr {
+        BoolToSigmaProp {
+            input: Box::new(rel(
+                RelationOp::And,
+                rel(RelationOp::Gt, GlobalVars::Height.into(), 100i32.into()),
+                rel(RelationOp::Lt, GlobalVars::Height.into(), 1000i32.into()),
+            )),
+        }
+        .into()
+    }
+
+    fn ctx_with_height(height: u32) -> Arc<Context> {
+        let mut ctx = force_any_val::<Context>();
+        ctx.height = height;
+        Arc::new(ctx)
+    }
+
+    #[test]
+    fn trace_false_condition() {
+        let (res, trace) = TestVerifier.reduce_to_crypto_traced(
+            &height_in_range(),
+            &Env::empty(),
+            ctx_with_height(50),
+            None,
+        );
+        assert_eq!(res.unwrap().sigma_prop, SigmaBoolean::TrivialProp(false));
+        assert_eq!(trace.roots.len(), 1);
+        let failed = trace.roots[0].find_false().unwrap();
+        assert_eq!(failed.source, "HEIGHT > 100");
+        assert_eq!(failed.inputs(), vec![&Value::Int(50), &Value::Int(100)]);
+        assert!(trace
+            .render()
+            .contains("  HEIGHT > 100 && HEIGHT < 1000 => false"));
+    }
+
+    #[test]
+    fn hook_abort_on_breakpoint() {
+        struct BreakOnHeight {
+            steps: usize,
+        }
+        impl EvalHook for BreakOnHeight {
+            fn is_breakpoint(&self, expr: &Expr) -> bool {
+                *expr == GlobalVars::Height.into()
+            }
+            fn on_breakpoint(&mut self, _expr: &Expr, _env: &Env) -> StepAction {
+                StepAction::Abort
+            }
+            fn on_step(&mut self, _node: &EvalTraceNode, _depth: usize) -> StepAction {
+                self.steps += 1;
+                StepAction::Continue
+            }
+        }
+        let (res, trace) = TestVerifier.reduce_to_crypto_traced(
+            &height_in_range(),
+            &Env::empty(),
+            ctx_with_height(50),
+            Some(Box::new(BreakOnHeight { steps: 0 })),
+        );
+        assert_eq!(
+            res.err(),
+            Some(EvalError::Aborted("GlobalVars".to_string()))
+        );
+        assert_eq!(trace.roots.len(), 1);
+        assert_eq!(trace.roots[0].node_name, "BoolToSigmaProp");
+    }
+}
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use ergotree_ir::mir::tuple::Tuple;
 use ergotree_ir::mir::value::Value;
 
@@ -9,7 +11,7 @@ use crate::eval::Evaluable;
 impl Evaluable for Tuple {
     fn eval(&self, env: &Env, ctx: &mut EvalContext) -> Result<Value, EvalError> {
         let items_v = self.items.try_mapped_ref(|i| i.eval(env, ctx));
-        Ok(Value::Tup(items_v?))
+        Ok(Value::Tup(Arc::new(items_v?)))
     }
 }
 
@@ -1,20 +1,19 @@
 use ergotree_ir::mir::upcast::Upcast;
 use ergotree_ir::mir::value::Value;
 use ergotree_ir::types::stype::SType;
-use num_bigint::ToBigInt;
+use num_bigint::BigInt;
 
 use crate::eval::env::Env;
 use crate::eval::EvalContext;
 use crate::eval::EvalError;
 use crate::eval::Evaluable;
 
-#[allow(clippy::unwrap_used)]
 fn upcast_to_bigint(in_v: Value) -> Result<Value, EvalError> {
     match in_v {
-        Value::Byte(v) => Ok(v.to_bigint().unwrap().into()),
-        Value::Short(v) => Ok(v.to_bigint().unwrap().into()),
-        Value::Int(v) => Ok(v.to_bigint().unwrap().into()),
-        Value::Long(v) => Ok(v.to_bigint().unwrap().into()),
+        Value::Byte(v) => Ok(BigInt::from(v).into()),
+        Value::Short(v) => Ok(BigInt::from(v).into()),
+        Value::Int(v) => Ok(BigInt::from(v).into()),
+        Value::Long(v) => Ok(BigInt::from(v).into()),
         Value::BigInt(_) => Ok(in_v),
         _ => Err(EvalError::UnexpectedValue(format!(
             "Upcast: cannot upcast {0:?} to Long",
@@ -91,6 +90,7 @@ impl Evaluable for Upcast {
 mod tests {
     use ergotree_ir::mir::constant::Constant;
     use num_bigint::BigInt;
+    use num_bigint::ToBigInt;
     use sigma_test_util::force_any_val;
 
     use crate::eval::tests::eval_out_wo_ctx;
@@ -8,7 +8,7 @@ use crate::eval::EvalContext;
 use crate::eval::EvalError;
 use crate::eval::Evaluable;
 
-fn helper_xor(mut x: Vec<i8>, y: Vec<i8>) -> Vec<i8> {
+fn helper_xor(mut x: Vec<i8>, y: &[i8]) -> Vec<i8> {
     x.iter_mut().zip(y.iter()).for_each(|(x1, x2)| *x1 ^= *x2);
     x
 }
@@ -23,7 +23,7 @@ impl Evaluable for Xor {
                 Value::Coll(CollKind::NativeColl(NativeColl::CollByte(l_byte))),
                 Value::Coll(CollKind::NativeColl(NativeColl::CollByte(r_byte))),
             ) => {
-                let xor = helper_xor(l_byte, r_byte);
+                let xor = helper_xor(l_byte.to_vec(), &r_byte);
                 Ok(xor.into())
             }
             _ => Err(EvalError::UnexpectedValue(format!(
@@ -43,7 +43,7 @@ mod tests {
     use ergotree_ir::mir::expr::Expr;
     use proptest::prelude::*;
     use sigma_test_util::force_any_val;
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     #[test]
     fn eval_1_xor_0() {
@@ -57,7 +57,7 @@ mod tests {
         }
         .into();
 
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         assert_eq!(eval_out::<Vec<i8>>(&expr, ctx), expected_xor);
     }
 
@@ -73,7 +73,7 @@ mod tests {
         }
         .into();
 
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         assert_eq!(eval_out::<Vec<i8>>(&expr, ctx), expected_xor);
     }
 
@@ -89,7 +89,7 @@ mod tests {
         }
         .into();
 
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         assert_eq!(eval_out::<Vec<i8>>(&expr, ctx), expected_xor);
     }
 
@@ -105,7 +105,7 @@ mod tests {
         }
         .into();
 
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         assert_eq!(eval_out::<Vec<i8>>(&expr, ctx), expected_xor);
     }
 
@@ -121,7 +121,7 @@ mod tests {
         }
         .into();
 
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         assert_eq!(eval_out::<Vec<i8>>(&expr, ctx), expected_xor);
     }
 
@@ -130,7 +130,7 @@ mod tests {
         #[test]
         fn eval_any(left_bytes in any::<Vec<i8>>(), right_bytes in any::<Vec<i8>>()) {
 
-            let expected_xor = helper_xor(left_bytes.clone(), right_bytes.clone());
+            let expected_xor = helper_xor(left_bytes.clone(), &right_bytes);
 
             let expr: Expr = Xor {
                 left: Box::new(Expr::Const(left_bytes.into())),
@@ -138,7 +138,7 @@ mod tests {
             }
             .into();
 
-            let ctx = Rc::new(force_any_val::<Context>());
+            let ctx = Arc::new(force_any_val::<Context>());
             assert_eq!(eval_out::<Vec<i8>>(&expr, ctx), expected_xor);
         }
     }
@@ -18,7 +18,7 @@ use crate::sigma_protocol::UnprovenLeaf;
 use ergotree_ir::sigma_protocol::sigma_boolean::SigmaBoolean;
 use ergotree_ir::sigma_protocol::sigma_boolean::SigmaConjectureItems;
 use std::convert::TryInto;
-use std::rc::Rc;
+use std::sync::Arc;
 
 pub use context_extension::*;
 use ergotree_ir::ergo_tree::ErgoTree;
@@ -44,6 +44,7 @@ use super::unproven_tree::UnprovenTree;
 
 use crate::eval::context::Context;
 use crate::eval::env::Env;
+use crate::eval::limits::EvalLimits;
 use crate::eval::{EvalError, Evaluator};
 
 use derive_more::From;
@@ -95,13 +96,28 @@ pub trait Prover: Evaluator {
         &self,
         tree: &ErgoTree,
         env: &Env,
-        ctx: Rc<Context>,
+        ctx: Arc<Context>,
         message: &[u8],
         hints_bag: &HintsBag,
+    ) -> Result<ProverResult, ProverError> {
+        self.prove_with_limits(tree, env, ctx, message, hints_bag, EvalLimits::default())
+    }
+
+    /// Same as [`Prover::prove`], but the script is evaluated with the given evaluation limits
+    /// (see [`Evaluator::reduce_to_crypto_with_limits`])
+    fn prove_with_limits(
+        &self,
+        tree: &ErgoTree,
+        env: &Env,
+        ctx: Arc<Context>,
+        message: &[u8],
+        hints_bag: &HintsBag,
+        limits: EvalLimits,
     ) -> Result<ProverResult, ProverError> {
         let expr = tree.proposition()?;
+        let extension = ctx.extension.clone();
         let proof = self
-            .reduce_to_crypto(expr.as_ref(), env, ctx)
+            .reduce_to_crypto_with_limits(expr.as_ref(), env, ctx, limits)
             .map_err(ProverError::EvalError)
             .and_then(|v| match v.sigma_prop {
                 SigmaBoolean::TrivialProp(true) => Ok(UncheckedTree::NoProof),
@@ -114,7 +130,7 @@ pub trait Prover: Evaluator {
             });
         proof.map(|v| ProverResult {
             proof: serialize_sig(v),
-            extension: ContextExtension::empty(),
+            extension,
         })
     }
 }
@@ -779,7 +795,7 @@ mod tests {
     use ergotree_ir::mir::value::Value;
     use ergotree_ir::types::stype::SType;
     use sigma_test_util::force_any_val;
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     #[test]
     fn test_prove_true_prop() {
@@ -793,7 +809,7 @@ mod tests {
         let res = prover.prove(
             &bool_true_tree,
             &Env::empty(),
-            Rc::new(force_any_val::<Context>()),
+            Arc::new(force_any_val::<Context>()),
             message.as_slice(),
             &HintsBag::empty(),
         );
@@ -813,7 +829,7 @@ mod tests {
         let res = prover.prove(
             &bool_false_tree,
             &Env::empty(),
-            Rc::new(force_any_val::<Context>()),
+            Arc::new(force_any_val::<Context>()),
             message.as_slice(),
             &HintsBag::empty(),
         );
@@ -834,7 +850,7 @@ mod tests {
         let res = prover.prove(
             &tree,
             &Env::empty(),
-            Rc::new(force_any_val::<Context>()),
+            Arc::new(force_any_val::<Context>()),
             message.as_slice(),
             &HintsBag::empty(),
         );
@@ -860,7 +876,7 @@ mod tests {
         let res = prover.prove(
             &tree,
             &Env::empty(),
-            Rc::new(force_any_val::<Context>()),
+            Arc::new(force_any_val::<Context>()),
             message.as_slice(),
             &HintsBag::empty(),
         );
@@ -892,7 +908,7 @@ mod tests {
         let res = prover.prove(
             &tree,
             &Env::empty(),
-            Rc::new(force_any_val::<Context>()),
+            Arc::new(force_any_val::<Context>()),
             message.as_slice(),
             &HintsBag::empty(),
         );
@@ -917,7 +933,7 @@ mod tests {
         let res = prover.prove(
             &tree,
             &Env::empty(),
-            Rc::new(force_any_val::<Context>()),
+            Arc::new(force_any_val::<Context>()),
             message.as_slice(),
             &HintsBag::empty(),
         );
@@ -949,7 +965,7 @@ mod tests {
         let res = prover.prove(
             &tree,
             &Env::empty(),
-            Rc::new(force_any_val::<Context>()),
+            Arc::new(force_any_val::<Context>()),
             message.as_slice(),
             &HintsBag::empty(),
         );
@@ -122,9 +122,7 @@ fn parse_sig_compute_challnges_reader<R: SigmaByteRead>(
     };
 
     match exp {
-        SigmaBoolean::TrivialProp(_) => {
-            panic!("TrivialProp should be handled before this call")
-        }
+        SigmaBoolean::TrivialProp(_) => Err(SigParsingError::TrivialPropFound),
         SigmaBoolean::ProofOfKnowledge(tree) => match tree {
             SigmaProofOfKnowledgeTree::ProveDlog(dl) => {
                 // Verifier Step 3: For every leaf node, read the response z provided in the proof.
@@ -139,7 +137,9 @@ fn parse_sig_compute_challnges_reader<R: SigmaByteRead>(
                 }
                 .into())
             }
-            SigmaProofOfKnowledgeTree::ProveDhTuple(_) => todo!("DHT is not yet supported"),
+            SigmaProofOfKnowledgeTree::ProveDhTuple(_) => Err(SigParsingError::NotImplementedYet(
+                "ProveDhTuple".to_string(),
+            )),
         },
         SigmaBoolean::SigmaConjecture(conj) => match conj {
             SigmaConjecture::Cand(cand) => {
@@ -182,7 +182,9 @@ fn parse_sig_compute_challnges_reader<R: SigmaByteRead>(
                 }
                 .into())
             }
-            SigmaConjecture::Cthreshold(_) => todo!(),
+            SigmaConjecture::Cthreshold(_) => {
+                Err(SigParsingError::NotImplementedYet("Cthreshold".to_string()))
+            }
         },
     }
 }
@@ -196,6 +198,14 @@ pub enum SigParsingError {
     /// Serialization error
     #[error("Serialization error: {0}")]
     SerializationError(SerializationError),
+    /// Trivial proposition inside the tree (should be eliminated on reduction)
+    #[error("Unexpected TrivialProp in the proposition")]
+    #[from(ignore)]
+    TrivialPropFound,
+    /// Proposition is not supported yet
+    #[error("Not implemented yet: {0}")]
+    #[from(ignore)]
+    NotImplementedYet(String),
 }
 
 impl From<std::io::Error> for SigParsingError {
@@ -1,6 +1,6 @@
 //! Verifier
 
-use std::rc::Rc;
+use std::sync::Arc;
 
 use super::fiat_shamir::FiatShamirTreeSerializationError;
 use super::prover::ProofBytes;
@@ -14,6 +14,7 @@ use super::{
 };
 use crate::eval::context::Context;
 use crate::eval::env::Env;
+use crate::eval::limits::EvalLimits;
 use crate::eval::{EvalError, Evaluator};
 use dlog_protocol::FirstDlogProverMessage;
 use ergotree_ir::ergo_tree::ErgoTree;
@@ -60,12 +61,28 @@ pub trait Verifier: Evaluator {
         &self,
         tree: &ErgoTree,
         env: &Env,
-        ctx: Rc<Context>,
+        ctx: Arc<Context>,
         proof: ProofBytes,
         message: &[u8],
+    ) -> Result<VerificationResult, VerifierError> {
+        self.verify_with_limits(tree, env, ctx, proof, message, EvalLimits::default())
+    }
+
+    /// Same as [`Verifier::verify`], but the script is evaluated with the given evaluation limits
+    /// (see [`Evaluator::reduce_to_crypto_with_limits`])
+    fn verify_with_limits(
+        &self,
+        tree: &ErgoTree,
+        env: &Env,
+        ctx: Arc<Context>,
+        proof: ProofBytes,
+        message: &[u8],
+        limits: EvalLimits,
     ) -> Result<VerificationResult, VerifierError> {
         let expr = tree.proposition()?;
-        let cprop = self.reduce_to_crypto(expr.as_ref(), env, ctx)?.sigma_prop;
+        let cprop = self
+            .reduce_to_crypto_with_limits(expr.as_ref(), env, ctx, limits)?
+            .sigma_prop;
         let res: bool = match cprop {
             SigmaBoolean::TrivialProp(b) => b,
             sb => {
@@ -86,6 +103,41 @@ pub trait Verifier: Evaluator {
     }
 }
 
+/// Script, context and proof of an input to be verified with [`verify_par`]
+#[derive(Debug, Clone)]
+pub struct VerifierInput<'a> {
+    /// Script guarding the input box
+    pub tree: &'a ErgoTree,
+    /// Context of the script evaluation
+    pub ctx: Arc<Context>,
+    /// Proof for the script
+    pub proof: ProofBytes,
+    /// Message the proof was made for
+    pub message: &'a [u8],
+}
+
+/// Verifies the given inputs in parallel, returning the results in the same order as `inputs`
+#[cfg(feature = "rayon")]
+pub fn verify_par<V: Verifier + ?Sized>(
+    verifier: &V,
+    env: &Env,
+    inputs: &[VerifierInput],
+) -> Vec<Result<VerificationResult, VerifierError>> {
+    use rayon::prelude::*;
+    inputs
+        .par_iter()
+        .map(|input| {
+            verifier.verify(
+                input.tree,
+                env,
+                input.ctx.clone(),
+                input.proof.clone(),
+                input.message,
+            )
+        })
+        .collect()
+}
+
 /// Perform Verifier Steps 4-6
 fn check_commitments(sp: UncheckedSigmaTree, message: &[u8]) -> Result<bool, VerifierError> {
     // Perform Verifier Step 4
@@ -150,7 +202,7 @@ mod tests {
     use proptest::collection::vec;
     use proptest::prelude::*;
     use sigma_test_util::force_any_val;
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     fn proof_append_byte(proof: &ProofBytes) -> ProofBytes {
         match proof {
@@ -163,6 +215,60 @@ mod tests {
         }
     }
 
+    #[test]
+    fn interpreter_is_send_sync() {
+        fn assert_send_sync<T: Send + Sync + ?Sized>() {}
+        assert_send_sync::<Context>();
+        assert_send_sync::<ErgoTree>();
+        assert_send_sync::<TestVerifier>();
+        assert_send_sync::<dyn Verifier>();
+        assert_send_sync::<TestProver>();
+        assert_send_sync::<dyn Prover>();
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_verify_par() {
+        let secrets: Vec<DlogProverInput> = (0..4).map(|_| force_any_val()).collect();
+        let trees: Vec<ErgoTree> = secrets
+            .iter()
+            .map(|s| ErgoTree::from(Expr::Const(s.public_image().into())))
+            .collect();
+        let message = vec![1u8; 100];
+        let prover = TestProver {
+            secrets: secrets
+                .into_iter()
+                .map(PrivateInput::DlogProverInput)
+                .collect(),
+        };
+        let ctx = Arc::new(force_any_val::<Context>());
+        let mut inputs: Vec<VerifierInput> = trees
+            .iter()
+            .map(|tree| VerifierInput {
+                tree,
+                ctx: ctx.clone(),
+                proof: prover
+                    .prove(
+                        tree,
+                        &Env::empty(),
+                        ctx.clone(),
+                        message.as_slice(),
+                        &HintsBag::empty(),
+                    )
+                    .unwrap()
+                    .proof,
+                message: message.as_slice(),
+            })
+            .collect();
+        // proof for another input
+        inputs[3].proof = inputs[0].proof.clone();
+        let results: Vec<bool> = verify_par(&TestVerifier, &Env::empty(), &inputs)
+            .into_iter()
+            .map(|r| r.unwrap().result)
+            .collect();
+        assert_eq!(results, vec![true, true, true, false]);
+    }
+
     proptest! {
 
         #![proptest_config(ProptestConfig::with_cases(4))]
@@ -177,14 +283,14 @@ mod tests {
             };
             let res = prover.prove(&tree,
                 &Env::empty(),
-                Rc::new(force_any_val::<Context>()),
+                Arc::new(force_any_val::<Context>()),
                 message.as_slice(),
                 &HintsBag::empty());
             let proof = res.unwrap().proof;
             let verifier = TestVerifier;
             prop_assert_eq!(verifier.verify(&tree,
                                             &Env::empty(),
-                                            Rc::new(force_any_val::<Context>()),
+                                            Arc::new(force_any_val::<Context>()),
                                             proof.clone(),
                                             message.as_slice())
                             .unwrap().result,
@@ -193,7 +299,7 @@ mod tests {
             // possible to append bytes
             prop_assert_eq!(verifier.verify(&tree,
                                             &Env::empty(),
-                                            Rc::new(force_any_val::<Context>()),
+                                            Arc::new(force_any_val::<Context>()),
                                             proof_append_byte(&proof),
                                             message.as_slice())
                             .unwrap().result,
@@ -202,7 +308,7 @@ mod tests {
             // wrong message
             prop_assert_eq!(verifier.verify(&tree,
                                             &Env::empty(),
-                                            Rc::new(force_any_val::<Context>()),
+                                            Arc::new(force_any_val::<Context>()),
                                             proof,
                                             vec![1u8; 100].as_slice())
                             .unwrap().result,
@@ -224,14 +330,14 @@ mod tests {
             };
             let res = prover.prove(&tree,
                 &Env::empty(),
-                Rc::new(force_any_val::<Context>()),
+                Arc::new(force_any_val::<Context>()),
                 message.as_slice(),
                 &HintsBag::empty());
             let proof = res.unwrap().proof;
             let verifier = TestVerifier;
             let ver_res = verifier.verify(&tree,
                                           &Env::empty(),
-                                          Rc::new(force_any_val::<Context>()),
+                                          Arc::new(force_any_val::<Context>()),
                                           proof,
                                           message.as_slice());
             prop_assert_eq!(ver_res.unwrap().result, true);
@@ -260,14 +366,14 @@ mod tests {
             };
             let res = prover.prove(&tree,
                 &Env::empty(),
-                Rc::new(force_any_val::<Context>()),
+                Arc::new(force_any_val::<Context>()),
                 message.as_slice(),
                 &HintsBag::empty());
             let proof = res.unwrap().proof;
             let verifier = TestVerifier;
             let ver_res = verifier.verify(&tree,
                                           &Env::empty(),
-                                          Rc::new(force_any_val::<Context>()),
+                                          Arc::new(force_any_val::<Context>()),
                                           proof,
                                           message.as_slice());
             prop_assert_eq!(ver_res.unwrap().result, true);
@@ -291,14 +397,14 @@ mod tests {
                 };
                 let res = prover.prove(&tree,
                     &Env::empty(),
-                    Rc::new(force_any_val::<Context>()),
+                    Arc::new(force_any_val::<Context>()),
                     message.as_slice(),
                     &HintsBag::empty());
                 let proof = res.unwrap_or_else(|_| panic!("proof failed for secret: {:?}", secret)).proof;
                 let verifier = TestVerifier;
                 let ver_res = verifier.verify(&tree,
                                               &Env::empty(),
-                                              Rc::new(force_any_val::<Context>()),
+                                              Arc::new(force_any_val::<Context>()),
                                               proof,
                                               message.as_slice());
                 prop_assert_eq!(ver_res.unwrap().result, true, "verify failed on secret: {:?}", &secret);
@@ -332,14 +438,14 @@ mod tests {
                 };
                 let res = prover.prove(&tree,
                     &Env::empty(),
-                    Rc::new(force_any_val::<Context>()),
+                    Arc::new(force_any_val::<Context>()),
                     message.as_slice(),
                     &HintsBag::empty());
                 let proof = res.unwrap_or_else(|_| panic!("proof failed for secret: {:?}", secret)).proof;
                 let verifier = TestVerifier;
                 let ver_res = verifier.verify(&tree,
                                               &Env::empty(),
-                                              Rc::new(force_any_val::<Context>()),
+                                              Arc::new(force_any_val::<Context>()),
                                               proof,
                                               message.as_slice());
                 prop_assert_eq!(ver_res.unwrap().result, true, "verify failed on secret: {:?}", &secret);
@@ -375,7 +481,7 @@ mod tests {
         let ver_res = verifier.verify(
             &expr.into(),
             &Env::empty(),
-            Rc::new(force_any_val::<Context>()),
+            Arc::new(force_any_val::<Context>()),
             signature.into(),
             msg.as_slice(),
         );
@@ -422,7 +528,7 @@ mod tests {
         // let res = prover.prove(
         //     &tree,
         //     &Env::empty(),
-        //     Rc::new(force_any_val::<Context>()),
+        //     Arc::new(force_any_val::<Context>()),
         //     msg.as_slice(),
         //     &HintsBag::empty(),
         // );
@@ -433,7 +539,7 @@ mod tests {
         let ver_res = verifier.verify(
             &tree,
             &Env::empty(),
-            Rc::new(force_any_val::<Context>()),
+            Arc::new(force_any_val::<Context>()),
             signature.into(),
             msg.as_slice(),
         );
@@ -480,7 +586,7 @@ mod tests {
         // let res = prover.prove(
         //     &tree,
         //     &Env::empty(),
-        //     Rc::new(force_any_val::<Context>()),
+        //     Arc::new(force_any_val::<Context>()),
         //     msg.as_slice(),
         //     &HintsBag::empty(),
         // );
@@ -491,7 +597,7 @@ mod tests {
         let ver_res = verifier.verify(
             &tree,
             &Env::empty(),
-            Rc::new(force_any_val::<Context>()),
+            Arc::new(force_any_val::<Context>()),
             signature.into(),
             msg.as_slice(),
         );
@@ -552,7 +658,7 @@ mod tests {
         // let res = prover.prove(
         //     &tree,
         //     &Env::empty(),
-        //     Rc::new(force_any_val::<Context>()),
+        //     Arc::new(force_any_val::<Context>()),
         //     msg.as_slice(),
         //     &HintsBag::empty(),
         // );
@@ -563,7 +669,7 @@ mod tests {
         let ver_res = verifier.verify(
             &tree,
             &Env::empty(),
-            Rc::new(force_any_val::<Context>()),
+            Arc::new(force_any_val::<Context>()),
             signature.into(),
             msg.as_slice(),
         );
@@ -624,7 +730,7 @@ mod tests {
         // let res = prover.prove(
         //     &tree,
         //     &Env::empty(),
-        //     Rc::new(force_any_val::<Context>()),
+        //     Arc::new(force_any_val::<Context>()),
         //     msg.as_slice(),
         //     &HintsBag::empty(),
         // );
@@ -635,7 +741,7 @@ mod tests {
         let ver_res = verifier.verify(
             &tree,
             &Env::empty(),
-            Rc::new(force_any_val::<Context>()),
+            Arc::new(force_any_val::<Context>()),
             signature.into(),
             msg.as_slice(),
         );
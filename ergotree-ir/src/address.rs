@@ -1,9 +1,22 @@
 //! Address types
 
 use crate::ergo_tree::ErgoTree;
+use crate::ergo_tree::ErgoTreeHeader;
 use crate::ergo_tree::ErgoTreeParsingError;
+use crate::mir::bin_op::BinOp;
+use crate::mir::bin_op::RelationOp;
+use crate::mir::bool_to_sigma::BoolToSigmaProp;
+use crate::mir::calc_blake2b256::CalcBlake2b256;
+use crate::mir::coll_slice::Slice;
 use crate::mir::constant::Constant;
+use crate::mir::deserialize_context::DeserializeContext;
 use crate::mir::expr::Expr;
+use crate::mir::expr::InvalidArgumentError;
+use crate::mir::get_var::GetVar;
+use crate::mir::option_get::OptionGet;
+use crate::mir::sigma_and::SigmaAnd;
+use crate::mir::unary_op::UnaryOpTryBuild;
+use crate::script_kind::ScriptKind;
 use crate::serialization::SerializationError;
 use crate::serialization::SigmaSerializable;
 use crate::sigma_protocol::dlog_group::EcPoint;
@@ -12,8 +25,14 @@ use crate::sigma_protocol::sigma_boolean::SigmaBoolean;
 use crate::sigma_protocol::sigma_boolean::SigmaProofOfKnowledgeTree;
 use crate::sigma_protocol::sigma_boolean::SigmaProp;
 use crate::types::stype::SType;
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
 use sigma_util::hash::blake2b256_hash;
 use std::convert::{TryFrom, TryInto};
+#[cfg(feature = "json")]
+use std::fmt::Display;
+#[cfg(feature = "json")]
+use std::str::FromStr;
 use thiserror::Error;
 
 /**
@@ -65,10 +84,17 @@ pub enum Address {
     P2Pk(ProveDlog),
     /// serialized script
     P2S(Vec<u8>),
-    // P2SH([u8; 24]),
+    /// first 192 bits of the Blake2b256 hash of serialized script bytes
+    P2SH([u8; Address::P2SH_HASH_LENGTH]),
 }
 
 impl Address {
+    /// Length of the script hash in P2SH address
+    pub const P2SH_HASH_LENGTH: usize = 24;
+    /// Id of the context variable holding the serialized script when spending a box protected by
+    /// P2SH address
+    pub const P2SH_SCRIPT_VAR_ID: u8 = 1;
+
     /// Create a P2PK address from serialized PK bytes(EcPoint/GroupElement)
     pub fn p2pk_from_pk_bytes(bytes: &[u8]) -> Result<Address, SerializationError> {
         EcPoint::sigma_parse_bytes(bytes)
@@ -76,24 +102,72 @@ impl Address {
             .map(Address::P2Pk)
     }
 
+    /// Create a P2SH address from the script (hash of the serialized proposition of the tree)
+    pub fn p2sh_from_script(script: &ErgoTree) -> Result<Address, ErgoTreeParsingError> {
+        let script_bytes = Address::p2sh_script_bytes(script)?;
+        let mut hash = [0u8; Address::P2SH_HASH_LENGTH];
+        hash.copy_from_slice(&blake2b256_hash(&script_bytes)[..Address::P2SH_HASH_LENGTH]);
+        Ok(Address::P2SH(hash))
+    }
+
+    /// Serialized script to be revealed (in the context variable with
+    /// [`Address::P2SH_SCRIPT_VAR_ID`] id) when spending a box protected by P2SH address made
+    /// from this script
+    pub fn p2sh_script_bytes(script: &ErgoTree) -> Result<Vec<u8>, ErgoTreeParsingError> {
+        Ok(script.proposition()?.sigma_serialize_bytes())
+    }
+
     /// Re-create the address from ErgoTree that was built from the address
     ///
     /// At some point in the past a user entered an address from which the ErgoTree was built.
     /// Re-create the address from this ErgoTree.
     /// `tree` - ErgoTree that was created from an Address
     pub fn recreate_from_ergo_tree(tree: &ErgoTree) -> Result<Address, AddressError> {
-        match tree.proposition() {
-            Ok(expr) => Ok(match &*expr {
-                Expr::Const(Constant {
-                    tpe: SType::SSigmaProp,
-                    v,
-                }) => ProveDlog::try_from(v.clone())
-                    .map(Address::P2Pk)
-                    .unwrap_or_else(|_| Address::P2S(tree.sigma_serialize_bytes())),
-                _ => Address::P2S(tree.sigma_serialize_bytes()),
-            }),
-            Err(_) => Ok(Address::P2S(tree.sigma_serialize_bytes())),
+        Ok(match ScriptKind::classify(tree) {
+            Some(ScriptKind::P2Pk(prove_dlog)) => Address::P2Pk(prove_dlog),
+            Some(ScriptKind::P2SH(hash)) => Address::P2SH(hash),
+            _ => Address::P2S(tree.sigma_serialize_bytes()),
+        })
+    }
+
+    /// `sigmaProp(blake2b256(getVar[Coll[Byte]](1).get).slice(0, 24) == hash) &&
+    /// deserializeContext[SigmaProp](1)`
+    fn p2sh_wrapper(hash: [u8; Address::P2SH_HASH_LENGTH]) -> Result<Expr, InvalidArgumentError> {
+        let script_var: Expr = OptionGet::try_build(
+            GetVar {
+                var_id: Address::P2SH_SCRIPT_VAR_ID,
+                var_tpe: SType::SColl(SType::SByte.into()),
+            }
+            .into(),
+        )?
+        .into();
+        let script_hash: Expr = Slice::new(
+            CalcBlake2b256 {
+                input: script_var.into(),
+            }
+            .into(),
+            0i32.into(),
+            (Address::P2SH_HASH_LENGTH as i32).into(),
+        )?
+        .into();
+        let hash_eq: Expr = BinOp {
+            kind: RelationOp::Eq.into(),
+            left: script_hash.into(),
+            right: Box::new(Constant::from(hash.to_vec()).into()),
         }
+        .into();
+        Ok(SigmaAnd::new(vec![
+            BoolToSigmaProp {
+                input: hash_eq.into(),
+            }
+            .into(),
+            DeserializeContext {
+                tpe: SType::SSigmaProp,
+                id: Address::P2SH_SCRIPT_VAR_ID,
+            }
+            .into(),
+        ])?
+        .into())
     }
 
     /// address type prefix (for encoding)
@@ -101,7 +175,7 @@ impl Address {
         match self {
             Address::P2Pk(_) => AddressTypePrefix::P2Pk,
             Address::P2S(_) => AddressTypePrefix::Pay2S,
-            //Address::P2SH(_) => AddressTypePrefix::P2SH,
+            Address::P2SH(_) => AddressTypePrefix::Pay2Sh,
         }
     }
 
@@ -110,10 +184,14 @@ impl Address {
         match self {
             Address::P2Pk(prove_dlog) => prove_dlog.h.sigma_serialize_bytes(),
             Address::P2S(bytes) => bytes.clone(),
+            Address::P2SH(hash) => hash.to_vec(),
         }
     }
 
     /// script encoded in the address
+    ///
+    /// For P2SH address it's the tree checking that the hash of the script revealed in the context
+    /// variable [`Address::P2SH_SCRIPT_VAR_ID`] matches the address and evaluating this script
     pub fn script(&self) -> Result<ErgoTree, SerializationError> {
         match self {
             Address::P2Pk(prove_dlog) => Ok(ErgoTree::from(Expr::Const(
@@ -123,6 +201,10 @@ impl Address {
                 .into(),
             ))),
             Address::P2S(bytes) => ErgoTree::sigma_parse_bytes(bytes),
+            Address::P2SH(hash) => Ok(ErgoTree::new(
+                ErgoTreeHeader::v0(false),
+                &Address::p2sh_wrapper(*hash)?,
+            )),
         }
     }
 }
@@ -130,6 +212,11 @@ impl Address {
 /// Combination of an Address with a network
 /// These two combined together form a base58 encoding
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(
+    feature = "json",
+    derive(Serialize, Deserialize),
+    serde(into = "String", try_from = "String")
+)]
 pub struct NetworkAddress {
     network: NetworkPrefix,
     address: Address,
@@ -160,6 +247,38 @@ impl NetworkAddress {
     }
 }
 
+#[cfg(feature = "json")]
+impl FromStr for NetworkAddress {
+    type Err = AddressValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        AddressEncoder::validate_network_address(s)
+    }
+}
+
+#[cfg(feature = "json")]
+impl Display for NetworkAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_base58())
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<NetworkAddress> for String {
+    fn from(a: NetworkAddress) -> Self {
+        a.to_base58()
+    }
+}
+
+#[cfg(feature = "json")]
+impl TryFrom<String> for NetworkAddress {
+    type Error = AddressValidationError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
 /// Errors for Address processing
 #[derive(Error, Eq, PartialEq, Debug, Clone)]
 pub enum AddressError {
@@ -255,6 +374,100 @@ impl From<SerializationError> for AddressEncoderError {
     }
 }
 
+/// Reason why the address is not valid
+#[derive(Error, Eq, PartialEq, Debug, Clone)]
+pub enum AddressValidationError {
+    /// Character that is not in the Base58 alphabet
+    #[error("Invalid Base58 character {character:?} at position {index}")]
+    InvalidBase58Character {
+        /// Invalid character
+        character: char,
+        /// Position (byte index) of the character in the string
+        index: usize,
+    },
+    /// Other Base58 decoding error
+    #[error("Base58 decoding error: {0}")]
+    Base58DecodingError(String),
+    /// Decoded address is shorter than the prefix, checksum and at least one content byte
+    #[error("Address is too short ({0} bytes)")]
+    TooShort(usize),
+    /// Checksum does not match the prefix and content bytes
+    #[error(
+        "Invalid checksum: expected {}, found {}",
+        base16::encode_lower(expected),
+        base16::encode_lower(found)
+    )]
+    ChecksumMismatch {
+        /// Checksum calculated from the prefix and content bytes
+        expected: [u8; AddressEncoder::CHECKSUM_LENGTH],
+        /// Checksum found in the address
+        found: [u8; AddressEncoder::CHECKSUM_LENGTH],
+    },
+    /// Unknown network in the prefix byte
+    #[error("Unknown network prefix {0}")]
+    UnknownNetwork(u8),
+    /// Unknown address type in the prefix byte
+    #[error("Unknown address type prefix {0}")]
+    UnknownAddressType(u8),
+    /// Address is for the other network
+    #[error("Expected {expected:?} address, found {found:?}")]
+    NetworkMismatch {
+        /// Expected network
+        expected: NetworkPrefix,
+        /// Network of the address
+        found: NetworkPrefix,
+    },
+    /// Public key of P2PK address is not a valid point
+    #[error("Invalid public key in P2PK address: {0}")]
+    InvalidPublicKey(SerializationError),
+    /// Script of P2S address cannot be parsed
+    #[error("Invalid script in P2S address: {0}")]
+    InvalidScript(String),
+    /// Script hash of P2SH address has invalid length
+    #[error("Invalid script hash length in P2SH address: {0}")]
+    InvalidScriptHashLength(usize),
+}
+
+impl From<bs58::decode::Error> for AddressValidationError {
+    fn from(err: bs58::decode::Error) -> Self {
+        match err {
+            bs58::decode::Error::InvalidCharacter { character, index } => {
+                AddressValidationError::InvalidBase58Character { character, index }
+            }
+            e => AddressValidationError::Base58DecodingError(e.to_string()),
+        }
+    }
+}
+
+impl From<AddressValidationError> for AddressEncoderError {
+    fn from(err: AddressValidationError) -> Self {
+        match err {
+            AddressValidationError::InvalidBase58Character { .. }
+            | AddressValidationError::Base58DecodingError(_) => {
+                AddressEncoderError::Base58DecodingError(err.to_string())
+            }
+            AddressValidationError::TooShort(_)
+            | AddressValidationError::InvalidScriptHashLength(_) => {
+                AddressEncoderError::InvalidSize
+            }
+            AddressValidationError::ChecksumMismatch { .. } => AddressEncoderError::InvalidChecksum,
+            AddressValidationError::UnknownNetwork(_)
+            | AddressValidationError::NetworkMismatch { .. } => {
+                AddressEncoderError::InvalidNetwork(err.to_string())
+            }
+            AddressValidationError::UnknownAddressType(t) => {
+                AddressEncoderError::InvalidAddressType(t)
+            }
+            AddressValidationError::InvalidPublicKey(e) => {
+                AddressEncoderError::DeserializationFailed(e)
+            }
+            AddressValidationError::InvalidScript(e) => {
+                AddressEncoderError::DeserializationFailed(SerializationError::Misc(e))
+            }
+        }
+    }
+}
+
 /// Encodes/Decodes address to/from string
 #[derive(PartialEq, Eq, Debug)]
 pub struct AddressEncoder {
@@ -327,15 +540,7 @@ impl AddressEncoder {
     pub fn unchecked_parse_network_address_from_bytes(
         bytes: &[u8],
     ) -> Result<NetworkAddress, AddressEncoderError> {
-        if bytes.len() < AddressEncoder::MIN_ADDRESS_LENGTH {
-            return Err(AddressEncoderError::InvalidSize);
-        };
-
-        let network_prefix = (bytes[0] & 0xF0).try_into()?;
-        AddressEncoder::unchecked_parse_address_from_bytes(&bytes).map(|addr| NetworkAddress {
-            address: addr,
-            network: network_prefix,
-        })
+        Ok(AddressEncoder::parse_network_address_from_bytes(bytes)?)
     }
 
     /// parse address from Base58 encoded string
@@ -348,25 +553,73 @@ impl AddressEncoder {
     pub fn unchecked_parse_address_from_bytes(
         bytes: &[u8],
     ) -> Result<Address, AddressEncoderError> {
+        AddressEncoder::unchecked_parse_network_address_from_bytes(bytes).map(|a| a.address)
+    }
+
+    fn parse_network_address_from_bytes(
+        bytes: &[u8],
+    ) -> Result<NetworkAddress, AddressValidationError> {
         if bytes.len() < AddressEncoder::MIN_ADDRESS_LENGTH {
-            return Err(AddressEncoderError::InvalidSize);
-        };
+            return Err(AddressValidationError::TooShort(bytes.len()));
+        }
         let (without_checksum, checksum) =
             bytes.split_at(bytes.len() - AddressEncoder::CHECKSUM_LENGTH);
-        let calculated_checksum = AddressEncoder::calc_checksum(without_checksum);
-        if checksum != calculated_checksum {
-            return Err(AddressEncoderError::InvalidChecksum);
+        let expected = AddressEncoder::calc_checksum(without_checksum);
+        if checksum != expected {
+            let mut found = [0u8; AddressEncoder::CHECKSUM_LENGTH];
+            found.copy_from_slice(checksum);
+            return Err(AddressValidationError::ChecksumMismatch { expected, found });
+        }
+        let head_byte = bytes[0];
+        let network = NetworkPrefix::try_from(head_byte & 0xF0)
+            .map_err(|_| AddressValidationError::UnknownNetwork(head_byte & 0xF0))?;
+        let address_type = AddressTypePrefix::try_from(head_byte & 0x0F)
+            .map_err(|_| AddressValidationError::UnknownAddressType(head_byte & 0x0F))?;
+        let content_bytes = &without_checksum[1..];
+        let address = match address_type {
+            AddressTypePrefix::P2Pk => EcPoint::sigma_parse_bytes(content_bytes)
+                .map(|point| Address::P2Pk(ProveDlog::new(point)))
+                .map_err(AddressValidationError::InvalidPublicKey)?,
+            AddressTypePrefix::Pay2S => Address::P2S(content_bytes.to_vec()),
+            AddressTypePrefix::Pay2Sh => Address::P2SH(content_bytes.try_into().map_err(|_| {
+                AddressValidationError::InvalidScriptHashLength(content_bytes.len())
+            })?),
         };
+        Ok(NetworkAddress { network, address })
+    }
 
-        let content_bytes: Vec<u8> = without_checksum[1..].to_vec(); // without head_byte
-        let address_type = AddressTypePrefix::try_from(bytes[0] & 0xF_u8)?;
-        Ok(match address_type {
-            AddressTypePrefix::P2Pk => {
-                Address::P2Pk(ProveDlog::new(EcPoint::sigma_parse_bytes(&content_bytes)?))
-            }
-            AddressTypePrefix::Pay2S => Address::P2S(content_bytes),
-            AddressTypePrefix::Pay2Sh => todo!(),
-        })
+    /// Validate Base58 encoded address for the network of this encoder, returns the precise
+    /// reason if the address is not valid. The public key of P2PK address must be a valid point,
+    /// the script of P2S address is not checked (see [`AddressEncoder::validate_script`])
+    pub fn validate_address(&self, str: &str) -> Result<Address, AddressValidationError> {
+        let network_address = AddressEncoder::validate_network_address(str)?;
+        if network_address.network != self.network_prefix {
+            return Err(AddressValidationError::NetworkMismatch {
+                expected: self.network_prefix,
+                found: network_address.network,
+            });
+        }
+        Ok(network_address.address)
+    }
+
+    /// Validate Base58 encoded address (for any network), returns the precise reason if the
+    /// address is not valid (see [`AddressEncoder::validate_address`])
+    pub fn validate_network_address(str: &str) -> Result<NetworkAddress, AddressValidationError> {
+        let bytes = bs58::decode(str).into_vec()?;
+        AddressEncoder::parse_network_address_from_bytes(&bytes)
+    }
+
+    /// Check that the script of P2S address can be parsed (always `Ok` for the other address
+    /// types). Not done by the address validation since a P2S address can hold any bytes.
+    pub fn validate_script(address: &Address) -> Result<(), AddressValidationError> {
+        match address {
+            Address::P2S(bytes) => ErgoTree::sigma_parse_bytes(bytes)
+                .map_err(|e| AddressValidationError::InvalidScript(e.to_string()))?
+                .proposition()
+                .map(|_| ())
+                .map_err(|e| AddressValidationError::InvalidScript(e.to_string())),
+            _ => Ok(()),
+        }
     }
 
     /// encode address as Base58 encoded string
@@ -412,7 +665,8 @@ pub(crate) mod arbitrary {
                     Ok(dlog) => Address::P2Pk(dlog),
                     Err(_) => Address::P2S(t.sigma_serialize_bytes()),
                 }),
-                Just(Address::P2S(base16::decode(non_parseable_tree).unwrap()))
+                Just(Address::P2S(base16::decode(non_parseable_tree).unwrap())),
+                any::<[u8; Address::P2SH_HASH_LENGTH]>().prop_map(Address::P2SH),
             ]
             .boxed()
         }
@@ -425,6 +679,7 @@ mod tests {
 
     use super::*;
     use proptest::prelude::*;
+    use sigma_test_util::force_any_val;
 
     proptest! {
 
@@ -443,10 +698,245 @@ mod tests {
             prop_assert_eq![recreated, v];
         }
 
+        #[test]
+        fn p2sh_from_script_recreate_roundtrip(tree in any::<ErgoTree>()) {
+            let address = Address::p2sh_from_script(&tree).unwrap();
+            let recreated = Address::recreate_from_ergo_tree(&address.script().unwrap()).unwrap();
+            prop_assert_eq![recreated, address];
+        }
+
         #[test]
         fn doesnt_crash_on_invalid_input(s in "\\w+") {
             let encoder = AddressEncoder::new(NetworkPrefix::Testnet);
             prop_assert![encoder.parse_address_from_str(&s).is_err()];
         }
     }
+
+    #[test]
+    fn p2sh_parse_encode() {
+        for (network, addr_str) in &[
+            (
+                NetworkPrefix::Testnet,
+                "rbcrmKEYduUvADj9Ts3dSVSG27h54pgrq5fPuwB",
+            ),
+            (
+                NetworkPrefix::Mainnet,
+                "8UApt8czfFVuTgQmMwtsRBZ4nfWquNiSwCWUjMg",
+            ),
+        ] {
+            let encoder = AddressEncoder::new(*network);
+            let addr = encoder.parse_address_from_str(addr_str).unwrap();
+            assert!(matches!(addr, Address::P2SH(_)));
+            assert_eq!(encoder.address_to_str(&addr), *addr_str);
+        }
+    }
+
+    #[test]
+    fn p2sh_invalid_size() {
+        let bytes = AddressEncoder::encode_address_as_bytes(
+            NetworkPrefix::Mainnet,
+            &Address::P2S(vec![1; Address::P2SH_HASH_LENGTH - 1]),
+        );
+        let mut bytes = bytes[..bytes.len() - AddressEncoder::CHECKSUM_LENGTH].to_vec();
+        bytes[0] = NetworkPrefix::Mainnet as u8 + AddressTypePrefix::Pay2Sh as u8;
+        let mut checksum = AddressEncoder::calc_checksum(&bytes).to_vec();
+        bytes.append(&mut checksum);
+        assert_eq!(
+            AddressEncoder::unchecked_parse_address_from_bytes(&bytes),
+            Err(AddressEncoderError::InvalidSize)
+        );
+    }
+
+    #[test]
+    fn recreate_p2pk_header_variants() {
+        let pk = force_any_val::<ProveDlog>();
+        let expr = Expr::Const(pk.clone().into());
+        for header in &[
+            ErgoTreeHeader::v0(false),
+            ErgoTreeHeader::v0(true),
+            ErgoTreeHeader::v1(false),
+            ErgoTreeHeader::v1(true),
+        ] {
+            let tree = ErgoTree::new(header.clone(), &expr);
+            assert_eq!(
+                Address::recreate_from_ergo_tree(&tree).unwrap(),
+                Address::P2Pk(pk.clone())
+            );
+        }
+    }
+
+    #[test]
+    fn p2sh_script() {
+        let encoder = AddressEncoder::new(NetworkPrefix::Mainnet);
+        let addr = encoder
+            .parse_address_from_str("8UApt8czfFVuTgQmMwtsRBZ4nfWquNiSwCWUjMg")
+            .unwrap();
+        // sigmastate Pay2SHAddress.script: ErgoTree.withoutSegregation(SigmaAnd(
+        // BoolToSigmaProp(EQ(Slice(CalcBlake2b256(OptionGet(GetVar(1, SByteArray))),
+        // IntConstant(0), IntConstant(24)), ByteArrayConstant(hash))),
+        // DeserializeContext(1, SSigmaProp)))
+        let expected = base16::decode(
+            "00ea02d193b4cbe4e3010e040004300e18\
+             d62151f990f191c102a6fe995b89ed3d0f343a96f13789a3\
+             d40801",
+        )
+        .unwrap();
+        assert_eq!(addr.script().unwrap().sigma_serialize_bytes(), expected);
+    }
+
+    fn encode_raw(head_byte: u8, content_bytes: &[u8]) -> String {
+        let mut bytes = vec![head_byte];
+        bytes.extend_from_slice(content_bytes);
+        let checksum = AddressEncoder::calc_checksum(&bytes);
+        bytes.extend_from_slice(&checksum);
+        bs58::encode(bytes).into_string()
+    }
+
+    #[test]
+    fn validate_valid() {
+        let encoder = AddressEncoder::new(NetworkPrefix::Mainnet);
+        for addr_str in &[
+            "9fRAWhdxEsTcdb8PhGNrZfwqa65zfkuYHAMmkQLcic1gdLSV5vA",
+            "8UApt8czfFVuTgQmMwtsRBZ4nfWquNiSwCWUjMg",
+            "4MQyML64GnzMxZgm",
+        ] {
+            assert_eq!(
+                encoder.validate_address(addr_str),
+                Ok(encoder.parse_address_from_str(addr_str).unwrap())
+            );
+        }
+    }
+
+    #[test]
+    fn validate_invalid_base58_char() {
+        assert_eq!(
+            AddressEncoder::validate_network_address("9fRAWhdxEsTcdb8Ph0NrZfwqa65"),
+            Err(AddressValidationError::InvalidBase58Character {
+                character: '0',
+                index: 17
+            })
+        );
+    }
+
+    #[test]
+    fn validate_too_short() {
+        assert_eq!(
+            AddressEncoder::validate_network_address("9fRAW"),
+            Err(AddressValidationError::TooShort(4))
+        );
+    }
+
+    #[test]
+    fn validate_checksum_mismatch() {
+        let mut bytes = bs58::decode("4MQyML64GnzMxZgm").into_vec().unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        let found: [u8; 4] = bytes[last - 3..].try_into().unwrap();
+        let mut expected = found;
+        expected[3] ^= 1;
+        assert_eq!(
+            AddressEncoder::validate_network_address(&bs58::encode(bytes).into_string()),
+            Err(AddressValidationError::ChecksumMismatch { expected, found })
+        );
+    }
+
+    #[test]
+    fn validate_unknown_prefix() {
+        assert_eq!(
+            AddressEncoder::validate_network_address(&encode_raw(0x20 + 3, &[1])),
+            Err(AddressValidationError::UnknownNetwork(0x20))
+        );
+        assert_eq!(
+            AddressEncoder::validate_network_address(&encode_raw(4, &[1])),
+            Err(AddressValidationError::UnknownAddressType(4))
+        );
+    }
+
+    #[test]
+    fn unchecked_parse_errors() {
+        let mut bytes = bs58::decode("4MQyML64GnzMxZgm").into_vec().unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        assert_eq!(
+            AddressEncoder::unchecked_parse_address_from_bytes(&bytes),
+            Err(AddressEncoderError::InvalidChecksum)
+        );
+        assert!(matches!(
+            AddressEncoder::unchecked_parse_address_from_str(&encode_raw(0x20 + 3, &[1])),
+            Err(AddressEncoderError::InvalidNetwork(_))
+        ));
+        assert_eq!(
+            AddressEncoder::unchecked_parse_address_from_str(&encode_raw(4, &[1])),
+            Err(AddressEncoderError::InvalidAddressType(4))
+        );
+    }
+
+    #[test]
+    fn validate_network_mismatch() {
+        let encoder = AddressEncoder::new(NetworkPrefix::Mainnet);
+        assert_eq!(
+            encoder.validate_address("3WvsT2Gm4EpsM9Pg18PdY6XyhNNMqXDsvJTbbf6ihLvAmSb7u5RN"),
+            Err(AddressValidationError::NetworkMismatch {
+                expected: NetworkPrefix::Mainnet,
+                found: NetworkPrefix::Testnet
+            })
+        );
+    }
+
+    #[test]
+    fn validate_invalid_content() {
+        assert!(matches!(
+            AddressEncoder::validate_network_address(&encode_raw(1, &[7; 33])),
+            Err(AddressValidationError::InvalidPublicKey(_))
+        ));
+        assert_eq!(
+            AddressEncoder::validate_network_address(&encode_raw(2, &[1; 23])),
+            Err(AddressValidationError::InvalidScriptHashLength(23))
+        );
+    }
+
+    #[test]
+    fn validate_script() {
+        // ErgoTree v0 with an unknown opcode as the root
+        let non_parseable_tree = [0u8, 0xff];
+        let addr =
+            AddressEncoder::validate_network_address(&encode_raw(3, &non_parseable_tree)).unwrap();
+        assert_eq!(addr.address(), Address::P2S(non_parseable_tree.to_vec()));
+        assert!(matches!(
+            AddressEncoder::validate_script(&addr.address()),
+            Err(AddressValidationError::InvalidScript(_))
+        ));
+        let encoder = AddressEncoder::new(NetworkPrefix::Mainnet);
+        for addr_str in &[
+            "9fRAWhdxEsTcdb8PhGNrZfwqa65zfkuYHAMmkQLcic1gdLSV5vA",
+            "8UApt8czfFVuTgQmMwtsRBZ4nfWquNiSwCWUjMg",
+            "4MQyML64GnzMxZgm",
+        ] {
+            let addr = encoder.validate_address(addr_str).unwrap();
+            assert_eq!(AddressEncoder::validate_script(&addr), Ok(()));
+        }
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn network_address_str_roundtrip() {
+        let addr_str = "3WvsT2Gm4EpsM9Pg18PdY6XyhNNMqXDsvJTbbf6ihLvAmSb7u5RN";
+        let addr: NetworkAddress = addr_str.parse().unwrap();
+        assert_eq!(addr.network(), NetworkPrefix::Testnet);
+        assert_eq!(addr.to_string(), addr_str);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn network_address_json() {
+        let addr_str = "9fRAWhdxEsTcdb8PhGNrZfwqa65zfkuYHAMmkQLcic1gdLSV5vA";
+        let json = format!("\"{}\"", addr_str);
+        let addr: NetworkAddress = serde_json::from_str(&json).unwrap();
+        assert_eq!(addr, addr_str.parse().unwrap());
+        assert_eq!(serde_json::to_string(&addr).unwrap(), json);
+        assert!(serde_json::from_str::<NetworkAddress>("\"9fRAW\"").is_err());
+        // the script of P2S address is not checked
+        let p2s_json = format!("\"{}\"", encode_raw(3, &[0u8, 0xff]));
+        assert!(serde_json::from_str::<NetworkAddress>(&p2s_json).is_ok());
+    }
 }
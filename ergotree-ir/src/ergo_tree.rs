@@ -2,6 +2,8 @@
 use crate::mir::constant::Constant;
 use crate::mir::constant::TryExtractFromError;
 use crate::mir::expr::Expr;
+use crate::mir::visitor::rewrite_bottom_up;
+use crate::pretty_printer::print_expr;
 use crate::serialization::{
     sigma_byte_reader::{SigmaByteRead, SigmaByteReader},
     sigma_byte_writer::{SigmaByteWrite, SigmaByteWriter},
@@ -14,19 +16,21 @@ use sigma_ser::vlq_encode::ReadSigmaVlqExt;
 use sigma_ser::vlq_encode::WriteSigmaVlqExt;
 
 use crate::serialization::constant_store::ConstantStore;
+use crate::serialization::parser_limits::ParserLimits;
 use derive_more::From;
 use derive_more::Into;
+use std::convert::Infallible;
 use std::convert::TryFrom;
 use std::io;
 use std::io::Read;
 use std::io::Write;
-use std::rc::Rc;
+use std::sync::Arc;
 use thiserror::Error;
 
 #[derive(PartialEq, Eq, Debug, Clone)]
 struct ParsedTree {
     constants: Vec<Constant>,
-    root: Result<Rc<Expr>, ErgoTreeRootParsingError>,
+    root: Result<Arc<Expr>, ErgoTreeRootParsingError>,
 }
 
 impl ParsedTree {
@@ -201,60 +205,73 @@ impl ErgoTree {
         header: ErgoTreeHeader,
         size: u32,
     ) -> Result<Self, SerializationError> {
+        let limits = r.limits().clone();
+        if size as usize > limits.max_tree_bytes {
+            return Err(SerializationError::TreeSizeLimitExceeded(
+                size as usize,
+                limits.max_tree_bytes,
+            ));
+        }
         let mut buf = vec![0u8; size as usize];
         r.read_exact(buf.as_mut_slice())?;
-        if let Ok((constants, mut tree_bytes)) =
-            ErgoTree::sigma_parse_tree_bytes(buf.as_mut_slice(), header.is_constant_segregation())
-        {
-            let tree_bytes_copy = tree_bytes.clone();
-            let mut tree_reader = SigmaByteReader::new(
-                Cursor::new(&mut tree_bytes[..]),
-                ConstantStore::new(constants.clone()),
-            );
-            match Expr::sigma_parse(&mut tree_reader) {
-                Ok(parsed) => Ok(ErgoTree {
-                    header,
-                    tree: Ok(ParsedTree {
-                        constants,
-                        root: Ok(Rc::new(parsed)),
+        match ErgoTree::sigma_parse_tree_bytes(
+            buf.as_mut_slice(),
+            header.is_constant_segregation(),
+            limits.clone(),
+        ) {
+            Ok((constants, mut tree_bytes)) => {
+                let tree_bytes_copy = tree_bytes.clone();
+                let mut tree_reader = SigmaByteReader::new(
+                    Cursor::new(&mut tree_bytes[..]),
+                    ConstantStore::new(constants.clone()),
+                )
+                .with_limits(limits);
+                match Expr::sigma_parse(&mut tree_reader) {
+                    Ok(parsed) => Ok(ErgoTree {
+                        header,
+                        tree: Ok(ParsedTree {
+                            constants,
+                            root: Ok(Arc::new(parsed)),
+                        }),
                     }),
-                }),
-                Err(err) => Ok(ErgoTree {
-                    header,
-                    tree: Ok(ParsedTree {
-                        constants,
-                        root: Err(ErgoTreeRootParsingError {
-                            root_expr_bytes: tree_bytes_copy,
-                            error: err,
+                    Err(err) => Ok(ErgoTree {
+                        header,
+                        tree: Ok(ParsedTree {
+                            constants,
+                            root: Err(ErgoTreeRootParsingError {
+                                root_expr_bytes: tree_bytes_copy,
+                                error: err,
+                            }),
                         }),
                     }),
-                }),
+                }
             }
-        } else {
-            let mut whole_tree_bytes = Vec::new();
-            let mut w = SigmaByteWriter::new(&mut whole_tree_bytes, None);
-            header.sigma_serialize(&mut w)?;
-            if header.has_size() {
-                w.put_u32(size)?;
+            Err(error) => {
+                let mut whole_tree_bytes = Vec::new();
+                let mut w = SigmaByteWriter::new(&mut whole_tree_bytes, None);
+                header.sigma_serialize(&mut w)?;
+                if header.has_size() {
+                    w.put_u32(size)?;
+                }
+                w.write_all(&buf)?;
+                Ok(ErgoTree {
+                    header,
+                    tree: Err(ErgoTreeConstantsParsingError {
+                        bytes: whole_tree_bytes,
+                        error,
+                    }),
+                })
             }
-            w.write_all(&buf)?;
-            Ok(ErgoTree {
-                header,
-                tree: Err(ErgoTreeConstantsParsingError {
-                    bytes: whole_tree_bytes,
-                    error: SerializationError::NotImplementedYet(
-                        "not all constant types serialization is supported".to_string(),
-                    ),
-                }),
-            })
         }
     }
 
     fn sigma_parse_tree_bytes(
         bytes: &mut [u8],
         is_constant_segregation: bool,
+        limits: ParserLimits,
     ) -> Result<(Vec<Constant>, Vec<u8>), SerializationError> {
-        let mut r = SigmaByteReader::new(Cursor::new(&bytes), ConstantStore::empty());
+        let mut r =
+            SigmaByteReader::new(Cursor::new(&bytes), ConstantStore::empty()).with_limits(limits);
         let constants = if is_constant_segregation {
             ErgoTree::sigma_parse_constants(&mut r)?
         } else {
@@ -269,9 +286,10 @@ impl ErgoTree {
         r: &mut R,
     ) -> Result<Vec<Constant>, SerializationError> {
         let constants_len = r.get_u32()?;
-        if constants_len as usize > ErgoTree::MAX_CONSTANTS_COUNT {
-            return Err(SerializationError::ValueOutOfBounds(
-                "too many constants".to_string(),
+        if constants_len as usize > r.limits().max_constants {
+            return Err(SerializationError::ConstantsCountLimitExceeded(
+                constants_len as usize,
+                r.limits().max_constants,
             ));
         }
         let mut constants = Vec::with_capacity(constants_len as usize);
@@ -285,24 +303,19 @@ impl ErgoTree {
     /// Creates a tree using provided header and root expression
     pub fn new(header: ErgoTreeHeader, expr: &Expr) -> Self {
         if header.is_constant_segregation() {
-            let mut data = Vec::new();
-            let cs = ConstantStore::empty();
-            let mut w = SigmaByteWriter::new(&mut data, Some(cs));
-            #[allow(clippy::unwrap_used)]
-            expr.sigma_serialize(&mut w).unwrap();
-            #[allow(clippy::unwrap_used)]
-            let constants = w.constant_store_mut_ref().unwrap().get_all();
-            let cursor = Cursor::new(&mut data[..]);
-            let new_cs = ConstantStore::new(constants.clone());
-            let mut sr = SigmaByteReader::new(cursor, new_cs);
-            #[allow(clippy::unwrap_used)]
-            // if it was serialized, then we should deserialize it without error
-            let parsed_expr = Expr::sigma_parse(&mut sr).unwrap();
+            let mut cs = ConstantStore::empty();
+            let segregated_expr = rewrite_bottom_up(expr.clone(), |e| {
+                Ok::<_, Infallible>(match e {
+                    Expr::Const(c) => Some(cs.put(c.clone()).into()),
+                    _ => None,
+                })
+            })
+            .unwrap_or_else(|never| match never {});
             ErgoTree {
                 header: ErgoTreeHeader(ErgoTreeHeader::CONSTANT_SEGREGATION_FLAG | header.0),
                 tree: Ok(ParsedTree {
-                    constants,
-                    root: Ok(Rc::new(parsed_expr)),
+                    constants: cs.get_all(),
+                    root: Ok(Arc::new(segregated_expr)),
                 }),
             }
         } else {
@@ -310,7 +323,7 @@ impl ErgoTree {
                 header,
                 tree: Ok(ParsedTree {
                     constants: Vec::new(),
-                    root: Ok(Rc::new(expr.clone())),
+                    root: Ok(Arc::new(expr.clone())),
                 }),
             }
         }
@@ -320,30 +333,32 @@ impl ErgoTree {
     pub const MAX_CONSTANTS_COUNT: usize = 4096;
 
     /// get Expr out of ErgoTree
-    pub fn proposition(&self) -> Result<Rc<Expr>, ErgoTreeParsingError> {
+    pub fn proposition(&self) -> Result<Arc<Expr>, ErgoTreeParsingError> {
         let tree = self
             .tree
             .clone()
             .map_err(ErgoTreeParsingError::TreeParsingError)?;
         // This tree has ConstantPlaceholder nodes instead of Constant nodes.
         // We need to substitute placeholders with constant values.
-        // So far the easiest way to do it is during deserialization (after the serialization)
         let root = tree.root.map_err(ErgoTreeParsingError::RootParsingError)?;
         if self.header.is_constant_segregation() {
-            let mut data = Vec::new();
-            let cs = ConstantStore::empty();
-            let mut w = SigmaByteWriter::new(&mut data, Some(cs));
-            #[allow(clippy::unwrap_used)]
-            root.sigma_serialize(&mut w).unwrap();
-            let cursor = Cursor::new(&mut data[..]);
-            let mut sr = SigmaByteReader::new_with_substitute_placeholders(
-                cursor,
-                ConstantStore::new(tree.constants),
-            );
-            #[allow(clippy::unwrap_used)]
-            // if it was serialized, then we should deserialize it without error
-            let parsed_expr = Expr::sigma_parse(&mut sr).unwrap();
-            Ok(Rc::new(parsed_expr))
+            let constants = tree.constants;
+            let substituted_expr = rewrite_bottom_up((*root).clone(), |e| match e {
+                Expr::ConstPlaceholder(cp) => match constants.get(cp.id as usize) {
+                    Some(c) => Ok(Some(Expr::Const(c.clone()))),
+                    None => Err(ErgoTreeParsingError::RootParsingError(
+                        ErgoTreeRootParsingError {
+                            root_expr_bytes: root.sigma_serialize_bytes(),
+                            error: SerializationError::ValueOutOfBounds(format!(
+                                "no constant for the placeholder with id {}",
+                                cp.id
+                            )),
+                        },
+                    )),
+                },
+                _ => Ok(None),
+            })?;
+            Ok(Arc::new(substituted_expr))
         } else {
             Ok(root)
         }
@@ -355,6 +370,25 @@ impl ErgoTree {
         tree
     }
 
+    /// Prints the root expression as ErgoScript-like source code (constants are inlined)
+    pub fn pretty_print(&self) -> Result<String, ErgoTreeParsingError> {
+        Ok(print_expr(&*self.proposition()?))
+    }
+
+    /// Prints the root expression as ErgoScript-like source code, with constants in segregated
+    /// trees shown as placeholders
+    pub fn pretty_print_template(&self) -> Result<String, ErgoTreeParsingError> {
+        let tree = self
+            .tree
+            .as_ref()
+            .map_err(|e| ErgoTreeParsingError::TreeParsingError(e.clone()))?;
+        let root = tree
+            .root
+            .as_ref()
+            .map_err(|e| ErgoTreeParsingError::RootParsingError(e.clone()))?;
+        Ok(print_expr(root))
+    }
+
     /// Returns Base16-encoded serialized bytes
     pub fn to_base16_bytes(&self) -> String {
         let bytes = self.sigma_serialize_bytes();
@@ -447,6 +481,7 @@ impl SigmaSerializable for ErgoTree {
     }
 
     fn sigma_parse<R: SigmaByteRead>(r: &mut R) -> Result<Self, SerializationError> {
+        let start_pos = r.position();
         let header = ErgoTreeHeader::sigma_parse(r)?;
         if header.has_size() {
             let tree_size_bytes = r.get_u32()?;
@@ -459,19 +494,32 @@ impl SigmaSerializable for ErgoTree {
             };
             r.set_constant_store(ConstantStore::new(constants.clone()));
             let root = Expr::sigma_parse(r)?;
+            let tree_size = r.position() - start_pos;
+            if tree_size > r.limits().max_tree_bytes {
+                return Err(SerializationError::TreeSizeLimitExceeded(
+                    tree_size,
+                    r.limits().max_tree_bytes,
+                ));
+            }
             Ok(ErgoTree {
                 header,
                 tree: Ok(ParsedTree {
                     constants,
-                    root: Ok(Rc::new(root)),
+                    root: Ok(Arc::new(root)),
                 }),
             })
         }
     }
 
-    fn sigma_parse_bytes(bytes: &[u8]) -> Result<Self, SerializationError> {
+    fn sigma_parse_bytes_with_limits(
+        bytes: &[u8],
+        limits: ParserLimits,
+    ) -> Result<Self, SerializationError> {
+        if bytes.len() > limits.max_bytes {
+            return Err(SerializationError::InputSizeLimitExceeded(limits.max_bytes));
+        }
         let cursor = Cursor::new(bytes);
-        let mut r = SigmaByteReader::new(cursor, ConstantStore::empty());
+        let mut r = SigmaByteReader::new(cursor, ConstantStore::empty()).with_limits(limits);
         let header = ErgoTreeHeader::sigma_parse(&mut r)?;
         let rest_of_the_bytes_len = if header.has_size() {
             r.get_u32()?
@@ -567,6 +615,14 @@ mod tests {
             prop_assert_eq!(&res.template_bytes().unwrap(), &v.template_bytes().unwrap());
             prop_assert_eq![res, v];
         }
+
+        #[test]
+        fn constant_segregation_roundtrip(e in any::<Expr>()) {
+            let tree = ErgoTree::new(ErgoTreeHeader::v0(true), &e);
+            prop_assert_eq![tree.proposition().unwrap(), Arc::new(e)];
+            let parsed = ErgoTree::sigma_parse_bytes(&tree.sigma_serialize_bytes()).unwrap();
+            prop_assert_eq![parsed, tree];
+        }
     }
 
     #[test]
@@ -1,12 +1,15 @@
 //! ErgoBox representation in IR
 use crate::mir::constant::Constant;
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
 use sigma_util::DIGEST32_SIZE;
 use std::fmt::Debug;
-use std::rc::Rc;
+use std::sync::Arc;
 use thiserror::Error;
 
 /// Ergo box id
 #[derive(PartialEq, Eq, Debug, Clone, Hash)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct IrBoxId(pub [i8; DIGEST32_SIZE]);
 
 impl IrBoxId {
@@ -18,8 +21,8 @@ impl IrBoxId {
     /// Gets box with this id from box arena
     pub fn get_box(
         &self,
-        arena: &Rc<dyn IrErgoBoxArena>,
-    ) -> Result<Rc<dyn IrErgoBox>, IrErgoBoxArenaError> {
+        arena: &Arc<dyn IrErgoBoxArena>,
+    ) -> Result<Arc<dyn IrErgoBox>, IrErgoBoxArenaError> {
         arena.get(self)
     }
 
@@ -30,9 +33,9 @@ impl IrBoxId {
 }
 
 /// Arena (store) for boxes
-pub trait IrErgoBoxArena: Debug {
+pub trait IrErgoBoxArena: Debug + Send + Sync {
     /// Returns a box with the given id
-    fn get(&self, id: &IrBoxId) -> Result<Rc<dyn IrErgoBox>, IrErgoBoxArenaError>;
+    fn get(&self, id: &IrBoxId) -> Result<Arc<dyn IrErgoBox>, IrErgoBoxArenaError>;
 }
 
 /// Box arena error
@@ -41,7 +44,7 @@ pub trait IrErgoBoxArena: Debug {
 pub struct IrErgoBoxArenaError(pub String);
 
 /// Ergo box properties
-pub trait IrErgoBox: Debug {
+pub trait IrErgoBox: Debug + Send + Sync {
     /// Box id
     fn id(&self) -> IrBoxId;
     /// Box value
@@ -0,0 +1,573 @@
+//! JSON encoding of ErgoTree IR
+//!
+//! Expressions are encoded as `{"kind": "<node name>", "tpe": <node type>, "node": <node fields>}`.
+//! The `tpe` field is derived from the node and is not read back on decoding. Nodes with the
+//! validating constructors are decoded through them.
+
+use serde::de::Error;
+use serde::ser::SerializeStruct;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+
+use crate::mir::apply::Apply;
+use crate::mir::bool_to_sigma::BoolToSigmaProp;
+use crate::mir::byte_array_to_bigint::ByteArrayToBigInt;
+use crate::mir::byte_array_to_long::ByteArrayToLong;
+use crate::mir::calc_blake2b256::CalcBlake2b256;
+use crate::mir::calc_sha256::CalcSha256;
+use crate::mir::coll_append::Append;
+use crate::mir::coll_by_index::ByIndex;
+use crate::mir::coll_exists::Exists;
+use crate::mir::coll_filter::Filter;
+use crate::mir::coll_fold::Fold;
+use crate::mir::coll_forall::ForAll;
+use crate::mir::coll_map::Map;
+use crate::mir::coll_size::SizeOf;
+use crate::mir::coll_slice::Slice;
+use crate::mir::collection::Collection;
+use crate::mir::create_prove_dh_tuple::CreateProveDhTuple;
+use crate::mir::create_provedlog::CreateProveDlog;
+use crate::mir::decode_point::DecodePoint;
+use crate::mir::expr::Expr;
+use crate::mir::expr::InvalidArgumentError;
+use crate::mir::extract_amount::ExtractAmount;
+use crate::mir::extract_creation_info::ExtractCreationInfo;
+use crate::mir::extract_id::ExtractId;
+use crate::mir::extract_reg_as::ExtractRegisterAs;
+use crate::mir::extract_script_bytes::ExtractScriptBytes;
+use crate::mir::logical_not::LogicalNot;
+use crate::mir::long_to_byte_array::LongToByteArray;
+use crate::mir::method_call::MethodCall;
+use crate::mir::multiply_group::MultiplyGroup;
+use crate::mir::negation::Negation;
+use crate::mir::option_get::OptionGet;
+use crate::mir::option_get_or_else::OptionGetOrElse;
+use crate::mir::option_is_defined::OptionIsDefined;
+use crate::mir::property_call::PropertyCall;
+use crate::mir::select_field::SelectField;
+use crate::mir::select_field::TupleFieldIndex;
+use crate::mir::sigma_and::SigmaAnd;
+use crate::mir::sigma_or::SigmaOr;
+use crate::mir::sigma_prop_bytes::SigmaPropBytes;
+use crate::mir::tuple::Tuple;
+use crate::mir::unary_op::UnaryOpTryBuild;
+use crate::mir::upcast::Upcast;
+use crate::mir::xor::Xor;
+use crate::serialization::types::TypeCode;
+use crate::serialization::SigmaSerializable;
+use crate::sigma_protocol::dlog_group::EcPoint;
+use crate::types::sfunc::SFunc;
+use crate::types::smethod::MethodId;
+use crate::types::smethod::SMethod;
+use crate::types::stype::SType;
+use crate::types::stype_companion::STypeCompanion;
+
+impl Serialize for Expr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Expr::Append(node) => serialize_node(self, "Append", node, serializer),
+            Expr::Const(node) => serialize_node(self, "Const", node, serializer),
+            Expr::ConstPlaceholder(node) => {
+                serialize_node(self, "ConstPlaceholder", node, serializer)
+            }
+            Expr::SubstConstants(node) => serialize_node(self, "SubstConstants", node, serializer),
+            Expr::ByteArrayToLong(node) => {
+                serialize_node(self, "ByteArrayToLong", node, serializer)
+            }
+            Expr::ByteArrayToBigInt(node) => {
+                serialize_node(self, "ByteArrayToBigInt", node, serializer)
+            }
+            Expr::LongToByteArray(node) => {
+                serialize_node(self, "LongToByteArray", node, serializer)
+            }
+            Expr::Collection(node) => serialize_node(self, "Collection", node, serializer),
+            Expr::Tuple(node) => serialize_node(self, "Tuple", node, serializer),
+            Expr::CalcBlake2b256(node) => serialize_node(self, "CalcBlake2b256", node, serializer),
+            Expr::CalcSha256(node) => serialize_node(self, "CalcSha256", node, serializer),
+            Expr::Context => serialize_unit_node(self, "Context", serializer),
+            Expr::Global => serialize_unit_node(self, "Global", serializer),
+            Expr::GlobalVars(node) => serialize_node(self, "GlobalVars", node, serializer),
+            Expr::FuncValue(node) => serialize_node(self, "FuncValue", node, serializer),
+            Expr::Apply(node) => serialize_node(self, "Apply", node, serializer),
+            Expr::MethodCall(node) => serialize_node(self, "MethodCall", node, serializer),
+            Expr::ProperyCall(node) => serialize_node(self, "PropertyCall", node, serializer),
+            Expr::BlockValue(node) => serialize_node(self, "BlockValue", node, serializer),
+            Expr::ValDef(node) => serialize_node(self, "ValDef", node, serializer),
+            Expr::ValUse(node) => serialize_node(self, "ValUse", node, serializer),
+            Expr::If(node) => serialize_node(self, "If", node, serializer),
+            Expr::BinOp(node) => serialize_node(self, "BinOp", node, serializer),
+            Expr::And(node) => serialize_node(self, "And", node, serializer),
+            Expr::Or(node) => serialize_node(self, "Or", node, serializer),
+            Expr::Xor(node) => serialize_node(self, "Xor", node, serializer),
+            Expr::Atleast(node) => serialize_node(self, "Atleast", node, serializer),
+            Expr::LogicalNot(node) => serialize_node(self, "LogicalNot", node, serializer),
+            Expr::Negation(node) => serialize_node(self, "Negation", node, serializer),
+            Expr::OptionGet(node) => serialize_node(self, "OptionGet", node, serializer),
+            Expr::OptionIsDefined(node) => {
+                serialize_node(self, "OptionIsDefined", node, serializer)
+            }
+            Expr::OptionGetOrElse(node) => {
+                serialize_node(self, "OptionGetOrElse", node, serializer)
+            }
+            Expr::ExtractAmount(node) => serialize_node(self, "ExtractAmount", node, serializer),
+            Expr::ExtractRegisterAs(node) => {
+                serialize_node(self, "ExtractRegisterAs", node, serializer)
+            }
+            Expr::ExtractScriptBytes(node) => {
+                serialize_node(self, "ExtractScriptBytes", node, serializer)
+            }
+            Expr::ExtractCreationInfo(node) => {
+                serialize_node(self, "ExtractCreationInfo", node, serializer)
+            }
+            Expr::ExtractId(node) => serialize_node(self, "ExtractId", node, serializer),
+            Expr::ByIndex(node) => serialize_node(self, "ByIndex", node, serializer),
+            Expr::SizeOf(node) => serialize_node(self, "SizeOf", node, serializer),
+            Expr::Slice(node) => serialize_node(self, "Slice", node, serializer),
+            Expr::Fold(node) => serialize_node(self, "Fold", node, serializer),
+            Expr::Map(node) => serialize_node(self, "Map", node, serializer),
+            Expr::Filter(node) => serialize_node(self, "Filter", node, serializer),
+            Expr::Exists(node) => serialize_node(self, "Exists", node, serializer),
+            Expr::ForAll(node) => serialize_node(self, "ForAll", node, serializer),
+            Expr::SelectField(node) => serialize_node(self, "SelectField", node, serializer),
+            Expr::BoolToSigmaProp(node) => {
+                serialize_node(self, "BoolToSigmaProp", node, serializer)
+            }
+            Expr::Upcast(node) => serialize_node(self, "Upcast", node, serializer),
+            Expr::CreateProveDlog(node) => {
+                serialize_node(self, "CreateProveDlog", node, serializer)
+            }
+            Expr::CreateProveDhTuple(node) => {
+                serialize_node(self, "CreateProveDhTuple", node, serializer)
+            }
+            Expr::SigmaPropBytes(node) => serialize_node(self, "SigmaPropBytes", node, serializer),
+            Expr::DecodePoint(node) => serialize_node(self, "DecodePoint", node, serializer),
+            Expr::SigmaAnd(node) => serialize_node(self, "SigmaAnd", node, serializer),
+            Expr::SigmaOr(node) => serialize_node(self, "SigmaOr", node, serializer),
+            Expr::GetVar(node) => serialize_node(self, "GetVar", node, serializer),
+            Expr::DeserializeRegister(node) => {
+                serialize_node(self, "DeserializeRegister", node, serializer)
+            }
+            Expr::DeserializeContext(node) => {
+                serialize_node(self, "DeserializeContext", node, serializer)
+            }
+            Expr::MultiplyGroup(node) => serialize_node(self, "MultiplyGroup", node, serializer),
+        }
+    }
+}
+
+fn serialize_node<T: Serialize, S: Serializer>(
+    expr: &Expr,
+    kind: &'static str,
+    node: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut s = serializer.serialize_struct("Expr", 3)?;
+    s.serialize_field("kind", kind)?;
+    s.serialize_field("tpe", &expr.tpe())?;
+    s.serialize_field("node", node)?;
+    s.end()
+}
+
+fn serialize_unit_node<S: Serializer>(
+    expr: &Expr,
+    kind: &'static str,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut s = serializer.serialize_struct("Expr", 2)?;
+    s.serialize_field("kind", kind)?;
+    s.serialize_field("tpe", &expr.tpe())?;
+    s.end()
+}
+
+/// Deserialize the IR node from its fields through the validating constructor
+macro_rules! deserialize_via_constructor {
+    ($node:ty, $ctor:expr, $($field:ident: $tpe:ty),+) => {
+        impl<'de> Deserialize<'de> for $node {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                #[derive(Deserialize)]
+                struct Fields {
+                    $($field: $tpe),+
+                }
+                let Fields { $($field),+ } = Fields::deserialize(deserializer)?;
+                $ctor($($field),+).map_err(|e: InvalidArgumentError| D::Error::custom(e.0))
+            }
+        }
+    };
+}
+
+/// Deserialize the unary IR node through [`UnaryOpTryBuild::try_build`]
+macro_rules! deserialize_unary_op {
+    ($($node:ty),+) => {
+        $(deserialize_via_constructor!($node, <$node as UnaryOpTryBuild>::try_build, input: Expr);)+
+    };
+}
+
+deserialize_unary_op!(
+    BoolToSigmaProp,
+    ByteArrayToBigInt,
+    ByteArrayToLong,
+    CalcBlake2b256,
+    CalcSha256,
+    SizeOf,
+    CreateProveDlog,
+    DecodePoint,
+    ExtractAmount,
+    ExtractCreationInfo,
+    ExtractId,
+    ExtractScriptBytes,
+    LogicalNot,
+    LongToByteArray,
+    Negation,
+    OptionGet,
+    OptionIsDefined,
+    SigmaPropBytes
+);
+deserialize_via_constructor!(Apply, Apply::new, func: Expr, args: Vec<Expr>);
+deserialize_via_constructor!(Append, Append::new, input: Expr, col_2: Expr);
+deserialize_via_constructor!(
+    ByIndex,
+    ByIndex::new,
+    input: Expr,
+    index: Expr,
+    default: Option<Box<Expr>>
+);
+deserialize_via_constructor!(Exists, Exists::new, input: Expr, condition: Expr);
+deserialize_via_constructor!(Filter, Filter::new, input: Expr, condition: Expr);
+deserialize_via_constructor!(Fold, Fold::new, input: Expr, zero: Expr, fold_op: Expr);
+deserialize_via_constructor!(ForAll, ForAll::new, input: Expr, condition: Expr);
+deserialize_via_constructor!(Map, Map::new, input: Expr, mapper: Expr);
+deserialize_via_constructor!(Slice, Slice::new, input: Expr, from: Expr, until: Expr);
+deserialize_via_constructor!(
+    CreateProveDhTuple,
+    CreateProveDhTuple::new,
+    gv: Expr,
+    hv: Expr,
+    uv: Expr,
+    vv: Expr
+);
+deserialize_via_constructor!(
+    ExtractRegisterAs,
+    ExtractRegisterAs::new,
+    input: Expr,
+    register_id: i8,
+    elem_tpe: SType
+);
+deserialize_via_constructor!(
+    MethodCall,
+    MethodCall::new,
+    obj: Expr,
+    method: SMethod,
+    args: Vec<Expr>
+);
+deserialize_via_constructor!(MultiplyGroup, MultiplyGroup::new, left: Expr, right: Expr);
+deserialize_via_constructor!(
+    OptionGetOrElse,
+    OptionGetOrElse::new,
+    input: Expr,
+    default: Expr
+);
+deserialize_via_constructor!(PropertyCall, PropertyCall::new, obj: Expr, method: SMethod);
+deserialize_via_constructor!(
+    SelectField,
+    SelectField::new,
+    input: Expr,
+    field_index: TupleFieldIndex
+);
+deserialize_via_constructor!(SigmaAnd, SigmaAnd::new, items: Vec<Expr>);
+deserialize_via_constructor!(SigmaOr, SigmaOr::new, items: Vec<Expr>);
+deserialize_via_constructor!(Tuple, Tuple::new, items: Vec<Expr>);
+deserialize_via_constructor!(Upcast, Upcast::new, input: Expr, tpe: SType);
+deserialize_via_constructor!(Xor, Xor::new, left: Expr, right: Expr);
+
+impl<'de> Deserialize<'de> for Collection {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        enum CollectionJson {
+            BoolConstants(Vec<bool>),
+            Exprs { elem_tpe: SType, items: Vec<Expr> },
+        }
+        match CollectionJson::deserialize(deserializer)? {
+            CollectionJson::BoolConstants(bools) => Ok(Collection::BoolConstants(bools)),
+            CollectionJson::Exprs { elem_tpe, items } => {
+                Collection::new(elem_tpe, items).map_err(|e| D::Error::custom(e.0))
+            }
+        }
+    }
+}
+
+/// Encoded as Base16 string of the sigma-serialized point
+impl Serialize for EcPoint {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&base16::encode_lower(&self.sigma_serialize_bytes()))
+    }
+}
+
+impl<'de> Deserialize<'de> for EcPoint {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        let bytes = base16::decode(&s).map_err(D::Error::custom)?;
+        EcPoint::sigma_parse_bytes(&bytes).map_err(D::Error::custom)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SMethodJson {
+    type_id: u8,
+    method_id: u8,
+    tpe: SFunc,
+}
+
+/// Encoded as object type id, method id and the (specialized) method signature
+impl Serialize for SMethod {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SMethodJson {
+            type_id: self.obj_type.type_id().value(),
+            method_id: self.method_id().0,
+            tpe: self.tpe().clone(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SMethod {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let json = SMethodJson::deserialize(deserializer)?;
+        let type_id = TypeCode::new(json.type_id);
+        if STypeCompanion::try_type_by_id(type_id).is_none() {
+            return Err(D::Error::custom(format!(
+                "unknown method object type id: {}",
+                json.type_id
+            )));
+        }
+        let method =
+            SMethod::from_ids(type_id, MethodId(json.method_id)).map_err(D::Error::custom)?;
+        // the signature is derived from the declared one (as when parsing a method call),
+        // the signature from JSON must be its specialization for the object and argument types
+        let (obj_tpe, arg_types) = json
+            .tpe
+            .t_dom
+            .split_first()
+            .ok_or_else(|| D::Error::custom("method signature without the object type"))?;
+        let specialized = method
+            .specialize_for(obj_tpe.clone(), arg_types.to_vec())
+            .map_err(|e| D::Error::custom(format!("{:?}", e)))?;
+        if specialized.tpe() != &json.tpe {
+            return Err(D::Error::custom(format!(
+                "method signature {:?} does not match the declared one {:?}",
+                json.tpe,
+                specialized.tpe()
+            )));
+        }
+        Ok(specialized)
+    }
+}
+
+/// Encoding for [`bounded_vec::BoundedVec`] as JSON array
+pub(crate) mod bounded_vec {
+    use std::convert::TryFrom;
+
+    use bounded_vec::BoundedVec;
+    use serde::de::Error;
+    use serde::Deserialize;
+    use serde::Deserializer;
+    use serde::Serialize;
+    use serde::Serializer;
+
+    pub(crate) fn serialize<T, S, const L: usize, const U: usize>(
+        items: &BoundedVec<T, L, U>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        items.as_vec().serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, T, D, const L: usize, const U: usize>(
+        deserializer: D,
+    ) -> Result<BoundedVec<T, L, U>, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        let items = Vec::<T>::deserialize(deserializer)?;
+        BoundedVec::try_from(items).map_err(|e| D::Error::custom(format!("{:?}", e)))
+    }
+}
+
+/// Encoding for an atomically reference counted [`bounded_vec::BoundedVec`] as JSON array
+pub(crate) mod arc_bounded_vec {
+    use std::sync::Arc;
+
+    use bounded_vec::BoundedVec;
+    use serde::Deserialize;
+    use serde::Deserializer;
+    use serde::Serialize;
+    use serde::Serializer;
+
+    pub(crate) fn serialize<T, S, const L: usize, const U: usize>(
+        items: &Arc<BoundedVec<T, L, U>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        super::bounded_vec::serialize(items.as_ref(), serializer)
+    }
+
+    pub(crate) fn deserialize<'de, T, D, const L: usize, const U: usize>(
+        deserializer: D,
+    ) -> Result<Arc<BoundedVec<T, L, U>>, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        super::bounded_vec::deserialize(deserializer).map(Arc::new)
+    }
+}
+
+/// Encoding for [`num_bigint::BigInt`] as a decimal string
+pub(crate) mod bigint {
+    use num_bigint::BigInt;
+    use serde::de::Error;
+    use serde::Deserialize;
+    use serde::Deserializer;
+    use serde::Serializer;
+
+    pub(crate) fn serialize<S: Serializer>(v: &BigInt, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&v.to_string())
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BigInt, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse::<BigInt>().map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "arbitrary")]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::mir::bin_op::ArithOp;
+    use crate::mir::bin_op::BinOp;
+    use crate::mir::constant::Constant;
+    use crate::mir::global_vars::GlobalVars;
+    use crate::serialization::sigma_serialize_roundtrip;
+    use crate::types::scoll;
+    use crate::types::stype::SType;
+    use expect_test::expect;
+    use proptest::prelude::*;
+
+    #[test]
+    fn expr_json_format() {
+        let expr: Expr = BinOp {
+            kind: ArithOp::Plus.into(),
+            left: Box::new(GlobalVars::Height.into()),
+            right: Box::new(Expr::Const(1i32.into())),
+        }
+        .into();
+        let json = serde_json::to_string_pretty(&expr).unwrap();
+        expect![[r#"
+            {
+              "kind": "BinOp",
+              "tpe": "SInt",
+              "node": {
+                "kind": {
+                  "Arith": "Plus"
+                },
+                "left": {
+                  "kind": "GlobalVars",
+                  "tpe": "SInt",
+                  "node": "Height"
+                },
+                "right": {
+                  "kind": "Const",
+                  "tpe": "SInt",
+                  "node": {
+                    "tpe": "SInt",
+                    "v": {
+                      "Int": 1
+                    }
+                  }
+                }
+              }
+            }"#]]
+        .assert_eq(&json);
+    }
+
+    #[test]
+    fn method_unknown_type_id() {
+        let json = r#"{"type_id": 250, "method_id": 1, "tpe": {"t_dom": [], "t_range": "SInt", "tpe_params": []}}"#;
+        assert!(serde_json::from_str::<SMethod>(json).is_err());
+    }
+
+    #[test]
+    fn method_signature_checked() {
+        let method = scoll::INDEX_OF_METHOD
+            .clone()
+            .specialize_for(
+                SType::SColl(SType::SLong.into()),
+                vec![SType::SLong, SType::SInt],
+            )
+            .unwrap();
+        let json = serde_json::to_string(&method).unwrap();
+        assert_eq!(serde_json::from_str::<SMethod>(&json).unwrap(), method);
+        let wrong_range = json.replace(r#""t_range":"SInt""#, r#""t_range":"SLong""#);
+        assert_ne!(wrong_range, json);
+        assert!(serde_json::from_str::<SMethod>(&wrong_range).is_err());
+        let wrong_arg = json.replace(r#""SLong","SInt"]"#, r#""SInt","SInt"]"#);
+        assert_ne!(wrong_arg, json);
+        assert!(serde_json::from_str::<SMethod>(&wrong_arg).is_err());
+    }
+
+    #[test]
+    fn invalid_node_rejected() {
+        let int_const = r#"{"kind": "Const", "node": {"tpe": "SInt", "v": {"Int": 1}}}"#;
+        let select_field = format!(
+            r#"{{"kind": "SelectField", "node": {{"input": {}, "field_index": 1}}}}"#,
+            int_const
+        );
+        assert!(serde_json::from_str::<Expr>(&select_field).is_err());
+        let not = format!(
+            r#"{{"kind": "LogicalNot", "node": {{"input": {}}}}}"#,
+            int_const
+        );
+        assert!(serde_json::from_str::<Expr>(&not).is_err());
+        let not_bool = r#"{"kind": "LogicalNot", "node": {"input": {"kind": "Const", "node": {"tpe": "SBoolean", "v": {"Boolean": true}}}}}"#;
+        assert!(serde_json::from_str::<Expr>(not_bool).is_ok());
+    }
+
+    proptest! {
+
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn expr_json_roundtrip(e in any::<Expr>()) {
+            let json = serde_json::to_string(&e).unwrap();
+            let parsed: Expr = serde_json::from_str(&json).unwrap();
+            prop_assert_eq![&parsed, &e];
+            prop_assert_eq![sigma_serialize_roundtrip(&parsed), e];
+        }
+
+        #[test]
+        fn constant_json_roundtrip(c in any::<Constant>()) {
+            let json = serde_json::to_string(&c).unwrap();
+            let parsed: Constant = serde_json::from_str(&json).unwrap();
+            prop_assert_eq![parsed, c];
+        }
+
+        #[test]
+        fn stype_json_roundtrip(t in any::<SType>()) {
+            let json = serde_json::to_string(&t).unwrap();
+            let parsed: SType = serde_json::from_str(&json).unwrap();
+            prop_assert_eq![parsed, t];
+        }
+    }
+}
@@ -16,11 +16,15 @@
 #![deny(clippy::expect_used)]
 
 mod has_opcode;
+#[cfg(feature = "json")]
+mod json;
 
 pub mod address;
 pub mod ergo_tree;
 pub mod ir_ergo_box;
 pub mod mir;
+pub mod pretty_printer;
+pub mod script_kind;
 pub mod serialization;
 pub mod sigma_protocol;
 pub mod type_check;
@@ -94,5 +94,6 @@ pub mod val_def;
 /// Variable reference
 pub mod val_use;
 pub mod value;
+pub mod visitor;
 /// Byte-wise XOR op
 pub mod xor;
@@ -6,12 +6,15 @@ use crate::serialization::sigma_byte_writer::SigmaByteWrite;
 use crate::serialization::SerializationError;
 use crate::serialization::SigmaSerializable;
 use crate::types::stype::SType;
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
 
 use super::expr::Expr;
 use crate::has_opcode::HasStaticOpCode;
 
 /// AND logical conjunction
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct And {
     /// Collection of SBoolean
     pub input: Box<Expr>,
@@ -1,4 +1,6 @@
 //! Application of function
+#[cfg(feature = "json")]
+use serde::Serialize;
 use std::io;
 
 use crate::serialization::op_code::OpCode;
@@ -14,6 +16,7 @@ use crate::has_opcode::HasStaticOpCode;
 
 /// Application of function `func` to given arguments `args`
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize))]
 pub struct Apply {
     /// Function
     pub func: Box<Expr>,
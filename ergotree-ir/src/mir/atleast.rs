@@ -5,12 +5,15 @@ use crate::serialization::sigma_byte_writer::SigmaByteWrite;
 use crate::serialization::SerializationError;
 use crate::serialization::SigmaSerializable;
 use crate::types::stype::SType;
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
 
 use super::expr::Expr;
 use crate::has_opcode::HasStaticOpCode;
 
 /// THRESHOLD composition for sigma expressions
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct Atleast {
     /// Number of Sigma-expression that should be proved
     pub n_required: Box<Expr>,
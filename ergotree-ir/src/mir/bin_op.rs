@@ -4,6 +4,8 @@ use super::expr::Expr;
 use crate::has_opcode::HasOpCode;
 use crate::serialization::op_code::OpCode;
 use crate::types::stype::SType;
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
 
 extern crate derive_more;
 use derive_more::From;
@@ -13,6 +15,7 @@ use proptest_derive::Arbitrary;
 
 /// Operations for numerical types
 #[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 pub enum ArithOp {
     /// Addition
@@ -53,6 +56,7 @@ impl From<ArithOp> for OpCode {
 
 /// Relational operations
 #[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 pub enum RelationOp {
     /// Equality
@@ -90,6 +94,7 @@ impl From<RelationOp> for OpCode {
 
 /// Binary operations
 #[derive(PartialEq, Eq, Debug, Clone, Copy, From)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 pub enum BinOpKind {
     /// Arithmetic operations
@@ -109,6 +114,7 @@ impl From<BinOpKind> for OpCode {
 
 /// Binary operation
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct BinOp {
     /// Operation kind
     pub kind: BinOpKind,
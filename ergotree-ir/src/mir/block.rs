@@ -1,4 +1,6 @@
 //! Block of statements ending with an expression
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
 use std::io;
 
 use super::expr::Expr;
@@ -19,6 +21,7 @@ use crate::types::stype::SType;
  * in a fixed well defined order.
  */
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct BlockValue {
     /// Statements
     pub items: Vec<Expr>,
@@ -4,6 +4,8 @@ use crate::mir::unary_op::UnaryOp;
 use crate::mir::unary_op::UnaryOpTryBuild;
 use crate::serialization::op_code::OpCode;
 use crate::types::stype::SType;
+#[cfg(feature = "json")]
+use serde::Serialize;
 
 use super::expr::Expr;
 use super::expr::InvalidArgumentError;
@@ -13,6 +15,7 @@ use super::expr::InvalidArgumentError;
  * During execution results to either `TrueProp` or `FalseProp` values of SigmaProp type.
  */
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize))]
 pub struct BoolToSigmaProp {
     /// Expr of type SBoolean
     pub input: Box<Expr>,
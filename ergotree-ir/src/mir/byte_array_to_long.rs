@@ -1,6 +1,8 @@
 //! Convert byte array to SLong
 use crate::serialization::op_code::OpCode;
 use crate::types::stype::SType;
+#[cfg(feature = "json")]
+use serde::Serialize;
 
 use super::expr::Expr;
 use super::expr::InvalidArgumentError;
@@ -10,6 +12,7 @@ use crate::has_opcode::HasStaticOpCode;
 
 /// Convert byte array to SLong
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize))]
 pub struct ByteArrayToLong {
     /// Byte array with SColl(SByte) expr type
     pub input: Box<Expr>,
@@ -1,6 +1,8 @@
 use crate::has_opcode::HasStaticOpCode;
 use crate::serialization::op_code::OpCode;
 use crate::types::stype::SType;
+#[cfg(feature = "json")]
+use serde::Serialize;
 
 use super::expr::Expr;
 use super::expr::InvalidArgumentError;
@@ -9,6 +11,7 @@ use super::unary_op::UnaryOpTryBuild;
 
 /// Calc Blake2b 256-bit hash
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize))]
 pub struct CalcBlake2b256 {
     /// Byte array with SColl(SByte) expr type
     pub input: Box<Expr>,
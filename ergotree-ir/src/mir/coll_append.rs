@@ -3,6 +3,8 @@ use crate::serialization::sigma_byte_reader::SigmaByteRead;
 use crate::serialization::sigma_byte_writer::SigmaByteWrite;
 use crate::serialization::SerializationError;
 use crate::serialization::SigmaSerializable;
+#[cfg(feature = "json")]
+use serde::Serialize;
 // use crate::types::stuple::STuple;
 use crate::types::stype::SType;
 
@@ -12,6 +14,7 @@ use crate::has_opcode::HasStaticOpCode;
 
 /// Takes two collections as input and produces the concatenated collection (input + col2)
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize))]
 pub struct Append {
     /// Collection - First Parameter; first half of the combined collection
     pub input: Box<Expr>,
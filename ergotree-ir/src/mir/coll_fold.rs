@@ -5,6 +5,8 @@ use crate::serialization::SerializationError;
 use crate::serialization::SigmaSerializable;
 use crate::types::stuple::STuple;
 use crate::types::stype::SType;
+#[cfg(feature = "json")]
+use serde::Serialize;
 
 use super::expr::Expr;
 use super::expr::InvalidArgumentError;
@@ -13,6 +15,7 @@ use crate::has_opcode::HasStaticOpCode;
 /// Applies a binary function to a start value and all elements of this collection,
 /// going left to right.
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize))]
 pub struct Fold {
     /// Collection
     pub input: Box<Expr>,
@@ -5,6 +5,8 @@ use crate::serialization::SerializationError;
 use crate::serialization::SigmaSerializable;
 use crate::types::sfunc::SFunc;
 use crate::types::stype::SType;
+#[cfg(feature = "json")]
+use serde::Serialize;
 
 use super::expr::Expr;
 use super::expr::InvalidArgumentError;
@@ -12,6 +14,7 @@ use crate::has_opcode::HasStaticOpCode;
 
 /// Builds a new collection by applying a function to all elements of this collection.
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize))]
 pub struct Map {
     /// Collection
     pub input: Box<Expr>,
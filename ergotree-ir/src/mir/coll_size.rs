@@ -1,5 +1,7 @@
 use crate::serialization::op_code::OpCode;
 use crate::types::stype::SType;
+#[cfg(feature = "json")]
+use serde::Serialize;
 
 use super::expr::Expr;
 use super::expr::InvalidArgumentError;
@@ -9,6 +11,7 @@ use crate::has_opcode::HasStaticOpCode;
 
 /// Collection size
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize))]
 pub struct SizeOf {
     /// Collection
     pub input: Box<Expr>,
@@ -5,6 +5,8 @@ use crate::serialization::sigma_byte_writer::SigmaByteWrite;
 use crate::serialization::SerializationError;
 use crate::serialization::SigmaSerializable;
 use crate::types::stype::SType;
+#[cfg(feature = "json")]
+use serde::Serialize;
 
 use super::constant::Constant;
 use super::constant::TryExtractFromError;
@@ -14,6 +16,7 @@ use super::expr::InvalidArgumentError;
 
 /// Collection of elements
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize))]
 pub enum Collection {
     /// Special representation for an array of boolean constants
     BoolConstants(Vec<bool>),
@@ -92,6 +95,7 @@ pub(crate) fn coll_sigma_parse<R: SigmaByteRead>(
     r: &mut R,
 ) -> Result<Collection, SerializationError> {
     let items_count = r.get_u16()?;
+    r.check_coll_len(items_count as usize)?;
     let elem_tpe = SType::sigma_parse(r)?;
     let mut items = Vec::with_capacity(items_count as usize);
     for _ in 0..items_count {
@@ -104,6 +108,7 @@ pub(crate) fn bool_const_coll_sigma_parse<R: SigmaByteRead>(
     r: &mut R,
 ) -> Result<Collection, SerializationError> {
     let items_count = r.get_u16()?;
+    r.check_coll_len(items_count as usize)?;
     let bools = r.get_bits(items_count as usize)?;
     Ok(Collection::BoolConstants(bools))
 }
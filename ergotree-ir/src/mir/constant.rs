@@ -9,7 +9,10 @@ use crate::types::stype::LiftIntoSType;
 use crate::types::stype::SType;
 use impl_trait_for_tuples::impl_for_tuples;
 use num_bigint::BigInt;
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
 use std::convert::TryInto;
+use std::sync::Arc;
 
 mod constant_placeholder;
 
@@ -23,6 +26,7 @@ use super::value::Value;
 use thiserror::Error;
 
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 /// Constant
 pub struct Constant {
     /// Constant type
@@ -168,7 +172,7 @@ impl Into<Constant> for Tuple {
             constants.into_iter().map(|c| (c.tpe, c.v)).unzip();
         Constant {
             tpe: SType::STuple(types.try_into().unwrap()),
-            v: Value::Tup(values.try_into().unwrap()),
+            v: Value::Tup(Arc::new(values.try_into().unwrap())),
         }
     }
 }
@@ -238,23 +242,7 @@ pub(crate) mod arbitrary {
     fn coll_from_constant(c: Constant, length: usize) -> Constant {
         Constant {
             tpe: SType::SColl(Box::new(c.tpe.clone())),
-            v: Value::Coll(if c.tpe == SType::SByte {
-                let mut values: Vec<i8> = Vec::with_capacity(length);
-                let byte: i8 = c.v.try_extract_into().unwrap();
-                for _ in 0..length {
-                    values.push(byte);
-                }
-                CollKind::NativeColl(NativeColl::CollByte(values))
-            } else {
-                let mut values: Vec<Value> = Vec::with_capacity(length);
-                for _ in 0..length {
-                    values.push(c.v.clone());
-                }
-                CollKind::WrappedColl {
-                    elem_tpe: c.tpe,
-                    items: values,
-                }
-            }),
+            v: Value::Coll(CollKind::from_vec(c.tpe, vec![c.v; length]).unwrap()),
         }
     }
 
@@ -344,14 +332,14 @@ pub(crate) mod arbitrary {
                                         )
                                         .unwrap()
                                     ),
-                                    v: Value::Tup(
+                                    v: Value::Tup(Arc::new(
                                         constants
                                             .into_iter()
                                             .map(|c| c.v)
                                             .collect::<Vec<Value>>()
                                             .try_into()
                                             .unwrap()
-                                    )
+                                    ))
                                 }),
                             ]
                         }
@@ -1,9 +1,12 @@
 use crate::has_opcode::HasStaticOpCode;
 use crate::serialization::op_code::OpCode;
 use crate::types::stype::SType;
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
 
 /// Placeholder for a constant in ErgoTree.
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct ConstantPlaceholder {
     /// Zero based index in ErgoTree.constants array.
     pub id: u32,
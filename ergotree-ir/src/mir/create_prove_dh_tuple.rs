@@ -4,6 +4,8 @@ use crate::serialization::sigma_byte_writer::SigmaByteWrite;
 use crate::serialization::SerializationError;
 use crate::serialization::SigmaSerializable;
 use crate::types::stype::SType;
+#[cfg(feature = "json")]
+use serde::Serialize;
 
 use super::expr::Expr;
 use crate::has_opcode::HasStaticOpCode;
@@ -11,6 +13,7 @@ use crate::mir::expr::InvalidArgumentError;
 
 /// Diffie-Hellman tuple.
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize))]
 pub struct CreateProveDhTuple {
     /// Group generator `g`
     pub gv: Box<Expr>,
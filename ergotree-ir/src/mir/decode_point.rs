@@ -2,6 +2,8 @@
 
 use crate::serialization::op_code::OpCode;
 use crate::types::stype::SType;
+#[cfg(feature = "json")]
+use serde::Serialize;
 
 use super::expr::Expr;
 use super::expr::InvalidArgumentError;
@@ -11,6 +13,7 @@ use crate::has_opcode::HasStaticOpCode;
 
 /// Decode byte array to EC point
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize))]
 pub struct DecodePoint {
     /// Byte array to be decoded
     pub input: Box<Expr>,
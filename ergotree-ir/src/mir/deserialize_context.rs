@@ -7,12 +7,15 @@ use crate::serialization::sigma_byte_writer::SigmaByteWrite;
 use crate::serialization::SerializationError;
 use crate::serialization::SigmaSerializable;
 use crate::types::stype::SType;
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
 
 /// Extracts context variable as `Coll[Byte]`, deserializes it to script and then executes
 /// this script in the current context. The original `Coll[Byte]` of the script is
 /// available as `getVar[Coll[Byte]](id)` On evaluation returns the result of the
 /// script execution in the current context
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct DeserializeContext {
     /// Result type of the deserialized script
     pub tpe: SType,
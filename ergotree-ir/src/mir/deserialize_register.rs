@@ -8,10 +8,13 @@ use crate::serialization::sigma_byte_writer::SigmaByteWrite;
 use crate::serialization::SerializationError;
 use crate::serialization::SigmaSerializable;
 use crate::types::stype::SType;
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
 
 /// Extract register of SELF box as `Coll[Byte]`, deserialize it into
 /// Value and inline into executing script.
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct DeserializeRegister {
     /// Register number
     pub reg: u8,
@@ -72,8 +72,15 @@ use crate::mir::get_var::GetVar;
 use bounded_vec::BoundedVecOutOfBounds;
 use derive_more::From;
 use derive_more::TryInto;
+#[cfg(feature = "json")]
+use serde::Deserialize;
 
 #[derive(PartialEq, Eq, Debug, Clone, From, TryInto)]
+#[cfg_attr(
+    feature = "json",
+    derive(Deserialize),
+    serde(tag = "kind", content = "node")
+)]
 /// Expression in ErgoTree
 pub enum Expr {
     /// Append - Concatenation of two collections
@@ -112,6 +119,7 @@ pub enum Expr {
     /// Method call
     MethodCall(MethodCall),
     /// Property call
+    #[cfg_attr(feature = "json", serde(rename = "PropertyCall"))]
     ProperyCall(PropertyCall),
     /// Block (statements, followed by an expression)
     BlockValue(BlockValue),
@@ -265,6 +273,78 @@ impl Expr {
         }
     }
 
+    /// Op code byte of the node (`None` for constants, which are serialized with the type code)
+    pub fn op_code_value(&self) -> Option<u8> {
+        match self {
+            Expr::Const(_) => None,
+            _ => Some(self.op_code().value()),
+        }
+    }
+
+    /// Name of the node kind (i.e. "BinOp")
+    pub fn node_name(&self) -> &'static str {
+        match self {
+            Expr::Append(_) => "Append",
+            Expr::Const(_) => "Const",
+            Expr::ConstPlaceholder(_) => "ConstPlaceholder",
+            Expr::SubstConstants(_) => "SubstConstants",
+            Expr::ByteArrayToLong(_) => "ByteArrayToLong",
+            Expr::ByteArrayToBigInt(_) => "ByteArrayToBigInt",
+            Expr::LongToByteArray(_) => "LongToByteArray",
+            Expr::Collection(_) => "Collection",
+            Expr::Tuple(_) => "Tuple",
+            Expr::CalcBlake2b256(_) => "CalcBlake2b256",
+            Expr::CalcSha256(_) => "CalcSha256",
+            Expr::Context => "Context",
+            Expr::Global => "Global",
+            Expr::GlobalVars(_) => "GlobalVars",
+            Expr::FuncValue(_) => "FuncValue",
+            Expr::Apply(_) => "Apply",
+            Expr::MethodCall(_) => "MethodCall",
+            Expr::ProperyCall(_) => "PropertyCall",
+            Expr::BlockValue(_) => "BlockValue",
+            Expr::ValDef(_) => "ValDef",
+            Expr::ValUse(_) => "ValUse",
+            Expr::If(_) => "If",
+            Expr::BinOp(_) => "BinOp",
+            Expr::And(_) => "And",
+            Expr::Or(_) => "Or",
+            Expr::Xor(_) => "Xor",
+            Expr::Atleast(_) => "Atleast",
+            Expr::LogicalNot(_) => "LogicalNot",
+            Expr::Negation(_) => "Negation",
+            Expr::OptionGet(_) => "OptionGet",
+            Expr::OptionIsDefined(_) => "OptionIsDefined",
+            Expr::OptionGetOrElse(_) => "OptionGetOrElse",
+            Expr::ExtractAmount(_) => "ExtractAmount",
+            Expr::ExtractRegisterAs(_) => "ExtractRegisterAs",
+            Expr::ExtractScriptBytes(_) => "ExtractScriptBytes",
+            Expr::ExtractCreationInfo(_) => "ExtractCreationInfo",
+            Expr::ExtractId(_) => "ExtractId",
+            Expr::ByIndex(_) => "ByIndex",
+            Expr::SizeOf(_) => "SizeOf",
+            Expr::Slice(_) => "Slice",
+            Expr::Fold(_) => "Fold",
+            Expr::Map(_) => "Map",
+            Expr::Filter(_) => "Filter",
+            Expr::Exists(_) => "Exists",
+            Expr::ForAll(_) => "ForAll",
+            Expr::SelectField(_) => "SelectField",
+            Expr::BoolToSigmaProp(_) => "BoolToSigmaProp",
+            Expr::Upcast(_) => "Upcast",
+            Expr::CreateProveDlog(_) => "CreateProveDlog",
+            Expr::CreateProveDhTuple(_) => "CreateProveDhTuple",
+            Expr::SigmaPropBytes(_) => "SigmaPropBytes",
+            Expr::DecodePoint(_) => "DecodePoint",
+            Expr::SigmaAnd(_) => "SigmaAnd",
+            Expr::SigmaOr(_) => "SigmaOr",
+            Expr::GetVar(_) => "GetVar",
+            Expr::DeserializeRegister(_) => "DeserializeRegister",
+            Expr::DeserializeContext(_) => "DeserializeContext",
+            Expr::MultiplyGroup(_) => "MultiplyGroup",
+        }
+    }
+
     /// Type of the expression
     pub fn tpe(&self) -> SType {
         match self {
@@ -399,10 +479,14 @@ impl<T: TryFrom<Expr>> TryExtractFrom<Expr> for T {
     }
 }
 
+#[cfg(feature = "arbitrary")]
+#[allow(clippy::unwrap_used)]
+pub mod well_typed;
+
 #[cfg(feature = "arbitrary")]
 #[allow(clippy::unwrap_used)]
 /// Arbitrary impl
-pub(crate) mod arbitrary {
+pub mod arbitrary {
     use super::*;
     use crate::mir::func_value::FuncArg;
     use crate::sigma_protocol::sigma_boolean::ProveDlog;
@@ -0,0 +1,1093 @@
+//! Arbitrary well-typed expressions (for property testing)
+//!
+//! Unlike the per-node `Arbitrary` impls, [`well_typed_expr`] builds whole expression trees of the
+//! requested type over (almost) the full node set, with `ValDef`/`ValUse` scoping and lambdas.
+//! `ConstantPlaceholder` is not generated (constant segregation produces it from `ErgoTree::new`),
+//! nor are `Context` and `Global` outside of the method calls.
+
+use std::convert::TryFrom;
+use std::sync::Arc;
+
+use proptest::collection::vec;
+use proptest::option;
+use proptest::prelude::*;
+use proptest::sample::select;
+
+use super::Expr;
+use crate::mir::and::And;
+use crate::mir::apply::Apply;
+use crate::mir::atleast::Atleast;
+use crate::mir::bin_op::ArithOp;
+use crate::mir::bin_op::BinOp;
+use crate::mir::bin_op::RelationOp;
+use crate::mir::block::BlockValue;
+use crate::mir::bool_to_sigma::BoolToSigmaProp;
+use crate::mir::byte_array_to_bigint::ByteArrayToBigInt;
+use crate::mir::byte_array_to_long::ByteArrayToLong;
+use crate::mir::calc_blake2b256::CalcBlake2b256;
+use crate::mir::calc_sha256::CalcSha256;
+use crate::mir::coll_append::Append;
+use crate::mir::coll_by_index::ByIndex;
+use crate::mir::coll_exists::Exists;
+use crate::mir::coll_filter::Filter;
+use crate::mir::coll_fold::Fold;
+use crate::mir::coll_forall::ForAll;
+use crate::mir::coll_map::Map;
+use crate::mir::coll_size::SizeOf;
+use crate::mir::coll_slice::Slice;
+use crate::mir::collection::Collection;
+use crate::mir::constant::arbitrary::ArbConstantParams;
+use crate::mir::constant::Constant;
+use crate::mir::create_prove_dh_tuple::CreateProveDhTuple;
+use crate::mir::create_provedlog::CreateProveDlog;
+use crate::mir::decode_point::DecodePoint;
+use crate::mir::deserialize_context::DeserializeContext;
+use crate::mir::deserialize_register::DeserializeRegister;
+use crate::mir::extract_amount::ExtractAmount;
+use crate::mir::extract_creation_info::ExtractCreationInfo;
+use crate::mir::extract_id::ExtractId;
+use crate::mir::extract_reg_as::ExtractRegisterAs;
+use crate::mir::extract_script_bytes::ExtractScriptBytes;
+use crate::mir::func_value::FuncArg;
+use crate::mir::func_value::FuncValue;
+use crate::mir::get_var::GetVar;
+use crate::mir::global_vars::GlobalVars;
+use crate::mir::if_op::If;
+use crate::mir::logical_not::LogicalNot;
+use crate::mir::long_to_byte_array::LongToByteArray;
+use crate::mir::method_call::MethodCall;
+use crate::mir::multiply_group::MultiplyGroup;
+use crate::mir::negation::Negation;
+use crate::mir::option_get::OptionGet;
+use crate::mir::option_get_or_else::OptionGetOrElse;
+use crate::mir::option_is_defined::OptionIsDefined;
+use crate::mir::or::Or;
+use crate::mir::property_call::PropertyCall;
+use crate::mir::select_field::SelectField;
+use crate::mir::select_field::TupleFieldIndex;
+use crate::mir::sigma_and::SigmaAnd;
+use crate::mir::sigma_or::SigmaOr;
+use crate::mir::sigma_prop_bytes::SigmaPropBytes;
+use crate::mir::subst_const::SubstConstants;
+use crate::mir::tuple::Tuple;
+use crate::mir::unary_op::UnaryOpTryBuild;
+use crate::mir::upcast::Upcast;
+use crate::mir::val_def::ValDef;
+use crate::mir::val_def::ValId;
+use crate::mir::val_use::ValUse;
+use crate::mir::value::CollKind;
+use crate::mir::value::Value;
+use crate::types::sbox;
+use crate::types::scoll;
+use crate::types::scontext;
+use crate::types::stuple::STuple;
+use crate::types::stype::SType;
+
+/// Max nesting of the generated types (i.e. `Coll[Coll[Byte]]`)
+const MAX_TYPE_DEPTH: usize = 2;
+
+/// Types of the generated sub-expressions, `ValDef`s and lambda arguments
+pub fn arb_type() -> BoxedStrategy<SType> {
+    select(vec![
+        SType::SBoolean,
+        SType::SByte,
+        SType::SShort,
+        SType::SInt,
+        SType::SLong,
+        SType::SBigInt,
+        SType::SGroupElement,
+        SType::SSigmaProp,
+        SType::SBox,
+        coll(SType::SBoolean),
+        coll(SType::SByte),
+        coll(SType::SInt),
+        coll(SType::SLong),
+        coll(SType::SBox),
+        coll(SType::SSigmaProp),
+        coll(coll(SType::SByte)),
+        STuple::pair(SType::SInt, coll(SType::SByte)).into(),
+        SType::SOption(SType::SInt.into()),
+        SType::SOption(coll(SType::SByte).into()),
+    ])
+    .boxed()
+}
+
+/// Well-typed expressions of the given type with up to `depth` levels of nested nodes
+/// (`depth` 0 gives leaves only: constants, context variables and `ValUse`)
+pub fn well_typed_expr(tpe: SType, depth: usize) -> BoxedStrategy<Expr> {
+    expr(&tpe, depth, &Scope::default())
+}
+
+/// Well-typed expressions of any of the [`arb_type`] types
+pub fn any_well_typed_expr(depth: usize) -> BoxedStrategy<Expr> {
+    arb_type()
+        .prop_flat_map(move |tpe| well_typed_expr(tpe, depth))
+        .boxed()
+}
+
+fn coll(elem_tpe: SType) -> SType {
+    SType::SColl(elem_tpe.into())
+}
+
+fn type_depth(tpe: &SType) -> usize {
+    match tpe {
+        SType::SColl(t) | SType::SOption(t) => 1 + type_depth(t),
+        SType::STuple(t) => 1 + t.items.iter().map(type_depth).max().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+fn numeric_types() -> Vec<SType> {
+    vec![
+        SType::SByte,
+        SType::SShort,
+        SType::SInt,
+        SType::SLong,
+        SType::SBigInt,
+    ]
+}
+
+/// Variables in scope (`ValDef` and lambda arguments)
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+struct Scope(Vec<(ValId, SType)>);
+
+impl Scope {
+    /// New scope with the variable of the given type. Ids are assigned by the nesting level, so
+    /// a new variable never shadows another variable in scope.
+    fn bind(&self, tpe: SType) -> (ValId, Scope) {
+        let id = ValId(self.0.len() as u32 + 1);
+        let mut vars = self.0.clone();
+        vars.push((id, tpe));
+        (id, Scope(vars))
+    }
+
+    /// `ValUse` of the variables of the given type and field selections of the tuple variables
+    fn uses(&self, tpe: &SType) -> Vec<Expr> {
+        let mut uses = Vec::new();
+        for (id, var_tpe) in &self.0 {
+            let val_use: Expr = ValUse {
+                val_id: *id,
+                tpe: var_tpe.clone(),
+            }
+            .into();
+            if var_tpe == tpe {
+                uses.push(val_use.clone());
+            }
+            if let SType::STuple(t) = var_tpe {
+                for (idx, item_tpe) in t.items.iter().enumerate() {
+                    if item_tpe == tpe {
+                        uses.push(
+                            SelectField::new(val_use.clone(), field_index(idx + 1))
+                                .unwrap()
+                                .into(),
+                        );
+                    }
+                }
+            }
+        }
+        uses
+    }
+}
+
+fn field_index(idx: usize) -> TupleFieldIndex {
+    TupleFieldIndex::try_from(idx as u8).unwrap()
+}
+
+type GenFn = fn(&SType, usize, &Scope) -> BoxedStrategy<Expr>;
+
+/// Weighted alternatives for the expression of the given type, only the chosen one is built
+/// (building all of them for every level would grow exponentially with depth)
+struct Alts {
+    tpe: SType,
+    child_depth: usize,
+    scope: Scope,
+    alts: Vec<(u32, GenFn)>,
+}
+
+impl Alts {
+    fn add(&mut self, weight: u32, gen: GenFn) {
+        self.alts.push((weight, gen));
+    }
+
+    fn into_strategy(self) -> BoxedStrategy<Expr> {
+        let total: u32 = self.alts.iter().map(|(w, _)| *w).sum();
+        let alts = Arc::new(self.alts);
+        let (tpe, child_depth, scope) = (self.tpe, self.child_depth, self.scope);
+        (0..total)
+            .prop_flat_map(move |n| {
+                let mut upper = 0;
+                let gen = alts
+                    .iter()
+                    .find(|(w, _)| {
+                        upper += w;
+                        n < upper
+                    })
+                    .map(|(_, gen)| *gen)
+                    .unwrap_or(alts[0].1);
+                gen(&tpe, child_depth, &scope)
+            })
+            .boxed()
+    }
+}
+
+fn expr(tpe: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    let mut alts = Alts {
+        tpe: tpe.clone(),
+        child_depth: depth.saturating_sub(1),
+        scope: scope.clone(),
+        alts: Vec::new(),
+    };
+    add_leaves(&mut alts);
+    if depth > 0 {
+        add_nested(&mut alts);
+    }
+    alts.into_strategy()
+}
+
+fn add_leaves(alts: &mut Alts) {
+    if is_constant_type(&alts.tpe) {
+        alts.add(40, constant);
+    }
+    if !alts.scope.uses(&alts.tpe).is_empty() {
+        alts.add(60, val_use);
+    }
+    match &alts.tpe {
+        SType::SInt => alts.add(12, height),
+        SType::SBox => alts.add(40, self_box),
+        SType::SColl(elem) if **elem == SType::SBox => alts.add(40, boxes),
+        SType::SColl(elem) if **elem == SType::SByte => alts.add(8, miner_pubkey),
+        SType::SColl(_) if !is_constant_type(&alts.tpe) => alts.add(40, coll_literal),
+        SType::STuple(_) => alts.add(40, tuple_literal),
+        SType::SOption(_) => alts.add(40, get_var),
+        _ => (),
+    }
+    if alts.tpe == sbox::TOKENS_METHOD.tpe().t_range.as_ref().clone() {
+        alts.add(20, box_tokens);
+    }
+    alts.add(1, deserialize_context);
+}
+
+fn add_nested(alts: &mut Alts) {
+    let tpe = alts.tpe.clone();
+    alts.add(12, if_expr);
+    alts.add(12, block);
+    alts.add(8, apply);
+    alts.add(4, fold);
+    alts.add(1, deserialize_register);
+    if type_depth(&tpe) < MAX_TYPE_DEPTH {
+        alts.add(4, option_get);
+        alts.add(4, option_get_or_else);
+        alts.add(8, by_index);
+        alts.add(4, select_field);
+    }
+    match &tpe {
+        SType::SBoolean => {
+            alts.add(16, relation_eq);
+            alts.add(16, relation_cmp);
+            alts.add(16, bin_and_or);
+            alts.add(8, and_or_coll);
+            alts.add(8, logical_not);
+            alts.add(8, exists_forall);
+            alts.add(4, option_is_defined);
+        }
+        t if t.is_numeric() => {
+            alts.add(24, arith);
+            alts.add(8, negation);
+            if *t != SType::SByte {
+                alts.add(8, upcast);
+            }
+        }
+        _ => (),
+    }
+    match &tpe {
+        SType::SInt => {
+            alts.add(8, size_of);
+            alts.add(4, index_of);
+        }
+        SType::SLong => {
+            alts.add(8, byte_array_to_long);
+            alts.add(8, extract_amount);
+            alts.add(4, box_value);
+        }
+        SType::SBigInt => alts.add(8, byte_array_to_bigint),
+        SType::SColl(elem) => {
+            alts.add(16, coll_literal);
+            alts.add(8, append);
+            alts.add(8, slice);
+            alts.add(8, filter);
+            alts.add(12, map);
+            alts.add(4, flat_map);
+            if **elem == SType::SByte {
+                alts.add(8, hash);
+                alts.add(8, long_to_byte_array);
+                alts.add(8, xor);
+                alts.add(4, sigma_prop_bytes);
+                alts.add(8, extract_id);
+                alts.add(8, extract_script_bytes);
+                alts.add(4, subst_constants);
+            }
+        }
+        SType::SSigmaProp => {
+            alts.add(24, bool_to_sigma_prop);
+            alts.add(8, sigma_and_or);
+            alts.add(8, create_prove_dlog);
+            alts.add(4, create_prove_dh_tuple);
+            alts.add(4, atleast);
+        }
+        SType::SGroupElement => {
+            alts.add(8, decode_point);
+            alts.add(8, multiply_group);
+        }
+        SType::SOption(_) => alts.add(12, extract_register_as),
+        _ => (),
+    }
+    if tpe == STuple::pair(SType::SInt, coll(SType::SByte)).into() {
+        alts.add(12, extract_creation_info);
+    }
+}
+
+fn is_constant_type(tpe: &SType) -> bool {
+    match tpe {
+        SType::SBoolean
+        | SType::SByte
+        | SType::SShort
+        | SType::SInt
+        | SType::SLong
+        | SType::SBigInt
+        | SType::SGroupElement
+        | SType::SSigmaProp => true,
+        SType::SColl(elem) => !matches!(**elem, SType::SColl(_)) && is_constant_type(elem),
+        _ => false,
+    }
+}
+
+/// Lambda with one argument of `arg_tpe` type
+fn lambda(arg_tpe: SType, body_tpe: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    let (idx, body_scope) = scope.bind(arg_tpe.clone());
+    expr(body_tpe, depth, &body_scope)
+        .prop_map(move |body| {
+            FuncValue::new(
+                vec![FuncArg {
+                    idx,
+                    tpe: arg_tpe.clone(),
+                }],
+                body,
+            )
+            .into()
+        })
+        .boxed()
+}
+
+// Leaves
+
+fn constant(tpe: &SType, _: usize, _: &Scope) -> BoxedStrategy<Expr> {
+    match tpe {
+        SType::SColl(elem) => {
+            let elem_tpe = *elem.clone();
+            // short collections to keep the nested lambdas evaluation fast
+            vec(
+                any_with::<Constant>(ArbConstantParams::Exact(elem_tpe.clone())),
+                0..8,
+            )
+            .prop_map(move |items| {
+                Constant {
+                    tpe: coll(elem_tpe.clone()),
+                    v: Value::Coll(
+                        CollKind::from_vec(
+                            elem_tpe.clone(),
+                            items.into_iter().map(|c| c.v).collect(),
+                        )
+                        .unwrap(),
+                    ),
+                }
+                .into()
+            })
+            .boxed()
+        }
+        _ => any_with::<Constant>(ArbConstantParams::Exact(tpe.clone()))
+            .prop_map_into()
+            .boxed(),
+    }
+}
+
+fn val_use(tpe: &SType, _: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    select(scope.uses(tpe)).boxed()
+}
+
+fn height(_: &SType, _: usize, _: &Scope) -> BoxedStrategy<Expr> {
+    Just(GlobalVars::Height.into()).boxed()
+}
+
+fn self_box(_: &SType, _: usize, _: &Scope) -> BoxedStrategy<Expr> {
+    Just(GlobalVars::SelfBox.into()).boxed()
+}
+
+fn boxes(_: &SType, _: usize, _: &Scope) -> BoxedStrategy<Expr> {
+    select(vec![
+        GlobalVars::Inputs.into(),
+        GlobalVars::Outputs.into(),
+        PropertyCall::new(Expr::Context, scontext::DATA_INPUTS_PROPERTY.clone())
+            .unwrap()
+            .into(),
+    ])
+    .boxed()
+}
+
+fn miner_pubkey(_: &SType, _: usize, _: &Scope) -> BoxedStrategy<Expr> {
+    Just(GlobalVars::MinerPubKey.into()).boxed()
+}
+
+fn box_tokens(_: &SType, _: usize, _: &Scope) -> BoxedStrategy<Expr> {
+    Just(
+        PropertyCall::new(GlobalVars::SelfBox.into(), sbox::TOKENS_METHOD.clone())
+            .unwrap()
+            .into(),
+    )
+    .boxed()
+}
+
+fn coll_literal(tpe: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    let elem_tpe = coll_elem(tpe);
+    vec(expr(&elem_tpe, depth, scope), 0..4)
+        .prop_map(move |items| Collection::new(elem_tpe.clone(), items).unwrap().into())
+        .boxed()
+}
+
+fn tuple_literal(tpe: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    let items: Vec<BoxedStrategy<Expr>> = match tpe {
+        SType::STuple(t) => t.items.iter().map(|t| expr(t, depth, scope)).collect(),
+        _ => Vec::new(),
+    };
+    items
+        .prop_map(|items| Tuple::new(items).unwrap().into())
+        .boxed()
+}
+
+fn get_var(tpe: &SType, _: usize, _: &Scope) -> BoxedStrategy<Expr> {
+    let var_tpe = option_elem(tpe);
+    any::<u8>()
+        .prop_map(move |var_id| {
+            GetVar {
+                var_id,
+                var_tpe: var_tpe.clone(),
+            }
+            .into()
+        })
+        .boxed()
+}
+
+fn deserialize_context(tpe: &SType, _: usize, _: &Scope) -> BoxedStrategy<Expr> {
+    let tpe = tpe.clone();
+    any::<u8>()
+        .prop_map(move |id| {
+            DeserializeContext {
+                tpe: tpe.clone(),
+                id,
+            }
+            .into()
+        })
+        .boxed()
+}
+
+fn coll_elem(tpe: &SType) -> SType {
+    match tpe {
+        SType::SColl(elem) => *elem.clone(),
+        t => t.clone(),
+    }
+}
+
+fn option_elem(tpe: &SType) -> SType {
+    match tpe {
+        SType::SOption(elem) => *elem.clone(),
+        t => t.clone(),
+    }
+}
+
+// Nodes of any type
+
+fn if_expr(tpe: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    (
+        expr(&SType::SBoolean, depth, scope),
+        expr(tpe, depth, scope),
+        expr(tpe, depth, scope),
+    )
+        .prop_map(|(condition, true_branch, false_branch)| {
+            If {
+                condition: condition.into(),
+                true_branch: true_branch.into(),
+                false_branch: false_branch.into(),
+            }
+            .into()
+        })
+        .boxed()
+}
+
+fn block(tpe: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    let (tpe, scope) = (tpe.clone(), scope.clone());
+    arb_type()
+        .prop_flat_map(move |val_tpe| {
+            let (id, body_scope) = scope.bind(val_tpe.clone());
+            (
+                expr(&val_tpe, depth, &scope),
+                expr(&tpe, depth, &body_scope),
+            )
+                .prop_map(move |(rhs, result)| {
+                    BlockValue {
+                        items: vec![ValDef {
+                            id,
+                            rhs: rhs.into(),
+                        }
+                        .into()],
+                        result: result.into(),
+                    }
+                    .into()
+                })
+        })
+        .boxed()
+}
+
+fn apply(tpe: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    let (tpe, scope) = (tpe.clone(), scope.clone());
+    arb_type()
+        .prop_flat_map(move |arg_tpe| {
+            (
+                lambda(arg_tpe.clone(), &tpe, depth, &scope),
+                expr(&arg_tpe, depth, &scope),
+            )
+                .prop_map(|(func, arg)| Apply::new(func, vec![arg]).unwrap().into())
+        })
+        .boxed()
+}
+
+fn fold(tpe: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    let (tpe, scope) = (tpe.clone(), scope.clone());
+    arb_type()
+        .prop_flat_map(move |elem_tpe| {
+            let arg_tpe: SType = STuple::pair(tpe.clone(), elem_tpe.clone()).into();
+            (
+                expr(&coll(elem_tpe), depth, &scope),
+                expr(&tpe, depth, &scope),
+                lambda(arg_tpe, &tpe, depth, &scope),
+            )
+                .prop_map(|(input, zero, fold_op)| Fold::new(input, zero, fold_op).unwrap().into())
+        })
+        .boxed()
+}
+
+fn deserialize_register(tpe: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    let tpe = tpe.clone();
+    (0u8..10, option::of(expr(&tpe, depth, scope)))
+        .prop_map(move |(reg, default)| {
+            DeserializeRegister {
+                reg,
+                tpe: tpe.clone(),
+                default: default.map(Box::new),
+            }
+            .into()
+        })
+        .boxed()
+}
+
+fn option_get(tpe: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    expr(&SType::SOption(tpe.clone().into()), depth, scope)
+        .prop_map(|input| OptionGet::try_build(input).unwrap().into())
+        .boxed()
+}
+
+fn option_get_or_else(tpe: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    (
+        expr(&SType::SOption(tpe.clone().into()), depth, scope),
+        expr(tpe, depth, scope),
+    )
+        .prop_map(|(input, default)| OptionGetOrElse::new(input, default).unwrap().into())
+        .boxed()
+}
+
+fn by_index(tpe: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    (
+        expr(&coll(tpe.clone()), depth, scope),
+        expr(&SType::SInt, depth, scope),
+        option::of(expr(tpe, depth, scope)),
+    )
+        .prop_map(|(input, index, default)| {
+            ByIndex::new(input, index, default.map(Box::new))
+                .unwrap()
+                .into()
+        })
+        .boxed()
+}
+
+fn select_field(tpe: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    let (tpe, scope) = (tpe.clone(), scope.clone());
+    (arb_type(), 1usize..=2)
+        .prop_flat_map(move |(other_tpe, idx)| {
+            let tuple_tpe: SType = if idx == 1 {
+                STuple::pair(tpe.clone(), other_tpe)
+            } else {
+                STuple::pair(other_tpe, tpe.clone())
+            }
+            .into();
+            expr(&tuple_tpe, depth, &scope)
+                .prop_map(move |input| SelectField::new(input, field_index(idx)).unwrap().into())
+        })
+        .boxed()
+}
+
+// Boolean
+
+fn relation_eq(_: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    let scope = scope.clone();
+    (arb_type(), select(vec![RelationOp::Eq, RelationOp::NEq]))
+        .prop_flat_map(move |(tpe, kind)| {
+            (expr(&tpe, depth, &scope), expr(&tpe, depth, &scope))
+                .prop_map(move |(left, right)| bin_op(kind.into(), left, right))
+        })
+        .boxed()
+}
+
+fn relation_cmp(_: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    let scope = scope.clone();
+    let kinds = vec![
+        RelationOp::Gt,
+        RelationOp::Ge,
+        RelationOp::Lt,
+        RelationOp::Le,
+    ];
+    (select(numeric_types()), select(kinds))
+        .prop_flat_map(move |(tpe, kind)| {
+            (expr(&tpe, depth, &scope), expr(&tpe, depth, &scope))
+                .prop_map(move |(left, right)| bin_op(kind.into(), left, right))
+        })
+        .boxed()
+}
+
+fn bin_and_or(_: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    (
+        select(vec![RelationOp::And, RelationOp::Or]),
+        expr(&SType::SBoolean, depth, scope),
+        expr(&SType::SBoolean, depth, scope),
+    )
+        .prop_map(|(kind, left, right)| bin_op(kind.into(), left, right))
+        .boxed()
+}
+
+fn and_or_coll(_: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    (any::<bool>(), expr(&coll(SType::SBoolean), depth, scope))
+        .prop_map(|(is_and, input)| {
+            if is_and {
+                And {
+                    input: input.into(),
+                }
+                .into()
+            } else {
+                Or {
+                    input: input.into(),
+                }
+                .into()
+            }
+        })
+        .boxed()
+}
+
+fn logical_not(_: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    expr(&SType::SBoolean, depth, scope)
+        .prop_map(|input| LogicalNot::try_build(input).unwrap().into())
+        .boxed()
+}
+
+fn exists_forall(_: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    let scope = scope.clone();
+    (arb_type(), any::<bool>())
+        .prop_flat_map(move |(elem_tpe, is_exists)| {
+            (
+                expr(&coll(elem_tpe.clone()), depth, &scope),
+                lambda(elem_tpe, &SType::SBoolean, depth, &scope),
+            )
+                .prop_map(move |(input, condition)| {
+                    if is_exists {
+                        Exists::new(input, condition).unwrap().into()
+                    } else {
+                        ForAll::new(input, condition).unwrap().into()
+                    }
+                })
+        })
+        .boxed()
+}
+
+fn option_is_defined(_: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    let scope = scope.clone();
+    arb_type()
+        .prop_flat_map(move |tpe| {
+            expr(&SType::SOption(tpe.into()), depth, &scope)
+                .prop_map(|input| OptionIsDefined::try_build(input).unwrap().into())
+        })
+        .boxed()
+}
+
+// Numeric
+
+fn bin_op(kind: crate::mir::bin_op::BinOpKind, left: Expr, right: Expr) -> Expr {
+    // boolean collection literal on the left can't be told apart from the packed pair of
+    // boolean constants (same op code) when parsed, use a constant instead
+    let left = match left {
+        Expr::Collection(Collection::BoolConstants(bools)) => Expr::Const(bools.into()),
+        e => e,
+    };
+    BinOp {
+        kind,
+        left: left.into(),
+        right: right.into(),
+    }
+    .into()
+}
+
+fn arith(tpe: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    let kinds = vec![
+        ArithOp::Plus,
+        ArithOp::Minus,
+        ArithOp::Multiply,
+        ArithOp::Divide,
+        ArithOp::Max,
+        ArithOp::Min,
+        ArithOp::BitOr,
+        ArithOp::BitAnd,
+        ArithOp::BitXor,
+    ];
+    (
+        select(kinds),
+        expr(tpe, depth, scope),
+        expr(tpe, depth, scope),
+    )
+        .prop_map(|(kind, left, right)| bin_op(kind.into(), left, right))
+        .boxed()
+}
+
+fn negation(tpe: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    expr(tpe, depth, scope)
+        .prop_map(|input| Negation::try_build(input).unwrap().into())
+        .boxed()
+}
+
+fn upcast(tpe: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    let (tpe, scope) = (tpe.clone(), scope.clone());
+    let narrower: Vec<SType> = numeric_types()
+        .into_iter()
+        .take_while(|t| *t != tpe)
+        .collect();
+    select(narrower)
+        .prop_flat_map(move |input_tpe| {
+            let tpe = tpe.clone();
+            expr(&input_tpe, depth, &scope)
+                .prop_map(move |input| Upcast::new(input, tpe.clone()).unwrap().into())
+        })
+        .boxed()
+}
+
+fn size_of(_: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    let scope = scope.clone();
+    arb_type()
+        .prop_flat_map(move |elem_tpe| {
+            expr(&coll(elem_tpe), depth, &scope)
+                .prop_map(|input| SizeOf::try_build(input).unwrap().into())
+        })
+        .boxed()
+}
+
+fn index_of(_: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    let scope = scope.clone();
+    arb_type()
+        .prop_flat_map(move |elem_tpe| {
+            (
+                expr(&coll(elem_tpe.clone()), depth, &scope),
+                expr(&elem_tpe, depth, &scope),
+                expr(&SType::SInt, depth, &scope),
+            )
+                .prop_map(|(obj, elem, from)| {
+                    let method = scoll::INDEX_OF_METHOD
+                        .clone()
+                        .specialize_for(obj.tpe(), vec![elem.tpe(), from.tpe()])
+                        .unwrap();
+                    MethodCall::new(obj, method, vec![elem, from])
+                        .unwrap()
+                        .into()
+                })
+        })
+        .boxed()
+}
+
+fn byte_array_to_long(_: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    expr(&coll(SType::SByte), depth, scope)
+        .prop_map(|input| ByteArrayToLong::try_build(input).unwrap().into())
+        .boxed()
+}
+
+fn byte_array_to_bigint(_: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    expr(&coll(SType::SByte), depth, scope)
+        .prop_map(|input| ByteArrayToBigInt::try_build(input).unwrap().into())
+        .boxed()
+}
+
+fn extract_amount(_: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    expr(&SType::SBox, depth, scope)
+        .prop_map(|input| ExtractAmount::try_build(input).unwrap().into())
+        .boxed()
+}
+
+fn box_value(_: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    expr(&SType::SBox, depth, scope)
+        .prop_map(|obj| {
+            PropertyCall::new(obj, sbox::VALUE_METHOD.clone())
+                .unwrap()
+                .into()
+        })
+        .boxed()
+}
+
+// Collections
+
+fn append(tpe: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    (expr(tpe, depth, scope), expr(tpe, depth, scope))
+        .prop_map(|(input, col_2)| Append::new(input, col_2).unwrap().into())
+        .boxed()
+}
+
+fn slice(tpe: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    (
+        expr(tpe, depth, scope),
+        expr(&SType::SInt, depth, scope),
+        expr(&SType::SInt, depth, scope),
+    )
+        .prop_map(|(input, from, until)| Slice::new(input, from, until).unwrap().into())
+        .boxed()
+}
+
+fn filter(tpe: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    (
+        expr(tpe, depth, scope),
+        lambda(coll_elem(tpe), &SType::SBoolean, depth, scope),
+    )
+        .prop_map(|(input, condition)| Filter::new(input, condition).unwrap().into())
+        .boxed()
+}
+
+fn map(tpe: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    let (out_elem_tpe, scope) = (coll_elem(tpe), scope.clone());
+    arb_type()
+        .prop_flat_map(move |elem_tpe| {
+            (
+                expr(&coll(elem_tpe.clone()), depth, &scope),
+                lambda(elem_tpe, &out_elem_tpe, depth, &scope),
+            )
+                .prop_map(|(input, mapper)| Map::new(input, mapper).unwrap().into())
+        })
+        .boxed()
+}
+
+fn flat_map(tpe: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    let (tpe, scope) = (tpe.clone(), scope.clone());
+    arb_type()
+        .prop_flat_map(move |elem_tpe| {
+            (
+                expr(&coll(elem_tpe.clone()), depth, &scope),
+                lambda(elem_tpe, &tpe, depth, &scope),
+            )
+                .prop_map(|(obj, f)| {
+                    let method = scoll::FLATMAP_METHOD
+                        .clone()
+                        .specialize_for(obj.tpe(), vec![f.tpe()])
+                        .unwrap();
+                    MethodCall::new(obj, method, vec![f]).unwrap().into()
+                })
+        })
+        .boxed()
+}
+
+fn hash(_: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    (any::<bool>(), expr(&coll(SType::SByte), depth, scope))
+        .prop_map(|(is_blake2b, input)| {
+            if is_blake2b {
+                CalcBlake2b256::try_build(input).unwrap().into()
+            } else {
+                CalcSha256::try_build(input).unwrap().into()
+            }
+        })
+        .boxed()
+}
+
+fn long_to_byte_array(_: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    expr(&SType::SLong, depth, scope)
+        .prop_map(|input| LongToByteArray::try_build(input).unwrap().into())
+        .boxed()
+}
+
+fn xor(tpe: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    (expr(tpe, depth, scope), expr(tpe, depth, scope))
+        .prop_map(|(left, right)| crate::mir::xor::Xor::new(left, right).unwrap().into())
+        .boxed()
+}
+
+fn sigma_prop_bytes(_: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    expr(&SType::SSigmaProp, depth, scope)
+        .prop_map(|input| SigmaPropBytes::try_build(input).unwrap().into())
+        .boxed()
+}
+
+fn extract_id(_: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    expr(&SType::SBox, depth, scope)
+        .prop_map(|input| ExtractId::try_build(input).unwrap().into())
+        .boxed()
+}
+
+fn extract_script_bytes(_: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    expr(&SType::SBox, depth, scope)
+        .prop_map(|input| ExtractScriptBytes::try_build(input).unwrap().into())
+        .boxed()
+}
+
+fn subst_constants(tpe: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    let (tpe, scope) = (tpe.clone(), scope.clone());
+    arb_type()
+        .prop_flat_map(move |value_tpe| {
+            (
+                expr(&tpe, depth, &scope),
+                expr(&coll(SType::SInt), depth, &scope),
+                expr(&coll(value_tpe), depth, &scope),
+            )
+                .prop_map(|(script_bytes, positions, new_values)| {
+                    SubstConstants {
+                        script_bytes: script_bytes.into(),
+                        positions: positions.into(),
+                        new_values: new_values.into(),
+                    }
+                    .into()
+                })
+        })
+        .boxed()
+}
+
+// Sigma propositions and group elements
+
+fn bool_to_sigma_prop(_: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    expr(&SType::SBoolean, depth, scope)
+        .prop_map(|input| BoolToSigmaProp::try_build(input).unwrap().into())
+        .boxed()
+}
+
+fn sigma_and_or(tpe: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    (any::<bool>(), vec(expr(tpe, depth, scope), 2..4))
+        .prop_map(|(is_and, items)| {
+            if is_and {
+                SigmaAnd::new(items).unwrap().into()
+            } else {
+                SigmaOr::new(items).unwrap().into()
+            }
+        })
+        .boxed()
+}
+
+fn create_prove_dlog(_: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    expr(&SType::SGroupElement, depth, scope)
+        .prop_map(|input| CreateProveDlog::try_build(input).unwrap().into())
+        .boxed()
+}
+
+fn create_prove_dh_tuple(_: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    let ge = || expr(&SType::SGroupElement, depth, scope);
+    (ge(), ge(), ge(), ge())
+        .prop_map(|(gv, hv, uv, vv)| CreateProveDhTuple::new(gv, hv, uv, vv).unwrap().into())
+        .boxed()
+}
+
+fn atleast(_: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    (
+        expr(&SType::SInt, depth, scope),
+        expr(&coll(SType::SSigmaProp), depth, scope),
+    )
+        .prop_map(|(n_required, expressions)| {
+            Atleast {
+                n_required: n_required.into(),
+                expressions: expressions.into(),
+            }
+            .into()
+        })
+        .boxed()
+}
+
+fn decode_point(_: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    expr(&coll(SType::SByte), depth, scope)
+        .prop_map(|input| DecodePoint::try_build(input).unwrap().into())
+        .boxed()
+}
+
+fn multiply_group(tpe: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    (expr(tpe, depth, scope), expr(tpe, depth, scope))
+        .prop_map(|(left, right)| MultiplyGroup::new(left, right).unwrap().into())
+        .boxed()
+}
+
+// Boxes
+
+fn extract_register_as(tpe: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    let tpe = tpe.clone();
+    (expr(&SType::SBox, depth, scope), 0i8..10)
+        .prop_map(move |(input, register_id)| {
+            ExtractRegisterAs::new(input, register_id, tpe.clone())
+                .unwrap()
+                .into()
+        })
+        .boxed()
+}
+
+fn extract_creation_info(_: &SType, depth: usize, scope: &Scope) -> BoxedStrategy<Expr> {
+    expr(&SType::SBox, depth, scope)
+        .prop_map(|input| ExtractCreationInfo::try_build(input).unwrap().into())
+        .boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ergo_tree::ErgoTree;
+    use crate::ergo_tree::ErgoTreeHeader;
+    use crate::serialization::sigma_serialize_roundtrip;
+    use crate::serialization::SigmaSerializable;
+    use crate::type_check::type_check;
+
+    fn typed_expr(depth: usize) -> impl Strategy<Value = (SType, Expr)> {
+        arb_type().prop_flat_map(move |tpe| (Just(tpe.clone()), well_typed_expr(tpe, depth)))
+    }
+
+    proptest! {
+
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn type_checks((tpe, e) in typed_expr(4)) {
+            prop_assert_eq!(e.tpe(), tpe);
+            prop_assert!(type_check(e.clone()).is_ok(), "{:?}", type_check(e));
+        }
+
+        #[test]
+        fn ser_roundtrip(e in any_well_typed_expr(4)) {
+            prop_assert_eq![sigma_serialize_roundtrip(&e), e];
+        }
+
+        #[test]
+        fn constant_segregation_roundtrip(e in well_typed_expr(SType::SSigmaProp, 3)) {
+            let tree = ErgoTree::new(ErgoTreeHeader::v0(true), &e);
+            prop_assert_eq![tree.proposition().unwrap(), Arc::new(e)];
+            let parsed = ErgoTree::sigma_parse_bytes(&tree.sigma_serialize_bytes()).unwrap();
+            prop_assert_eq![parsed, tree];
+        }
+    }
+
+    #[test]
+    fn scope_ids_are_unique() {
+        let (id1, s1) = Scope::default().bind(SType::SInt);
+        let (id2, s2) = s1.bind(STuple::pair(SType::SInt, SType::SBoolean).into());
+        assert_ne!(id1, id2);
+        // the variable itself and the first field of the tuple
+        assert_eq!(s2.uses(&SType::SInt).len(), 2);
+        assert_eq!(s2.uses(&SType::SBoolean).len(), 1);
+    }
+}
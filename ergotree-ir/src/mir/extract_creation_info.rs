@@ -1,6 +1,8 @@
 use crate::serialization::op_code::OpCode;
 use crate::types::stuple::STuple;
 use crate::types::stype::SType;
+#[cfg(feature = "json")]
+use serde::Serialize;
 
 use super::expr::Expr;
 use super::expr::InvalidArgumentError;
@@ -11,6 +13,7 @@ use crate::has_opcode::HasStaticOpCode;
 /// Tuple of height when block got included into the blockchain and transaction identifier with
 /// box index in the transaction outputs serialized to the byte array.
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize))]
 pub struct ExtractCreationInfo {
     /// Box (SBox type)
     pub input: Box<Expr>,
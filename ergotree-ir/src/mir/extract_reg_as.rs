@@ -4,6 +4,8 @@ use crate::serialization::sigma_byte_writer::SigmaByteWrite;
 use crate::serialization::SerializationError;
 use crate::serialization::SigmaSerializable;
 use crate::types::stype::SType;
+#[cfg(feature = "json")]
+use serde::Serialize;
 
 use super::expr::Expr;
 use super::expr::InvalidArgumentError;
@@ -11,6 +13,7 @@ use crate::has_opcode::HasStaticOpCode;
 
 /// Get box register value (Box.R0 - R9)
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize))]
 pub struct ExtractRegisterAs {
     /// Box
     pub input: Box<Expr>,
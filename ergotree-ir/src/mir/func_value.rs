@@ -1,3 +1,5 @@
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
 use std::io;
 
 use crate::has_opcode::HasStaticOpCode;
@@ -17,6 +19,7 @@ use proptest_derive::Arbitrary;
 
 /// Argument parameter for the user-defined function [`FuncValue`]
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 #[cfg_attr(test, derive(Arbitrary))]
 pub struct FuncArg {
     /// Value id (defined with [`super::val_def::ValDef`])
@@ -40,6 +43,7 @@ impl SigmaSerializable for FuncArg {
 
 /// User-defined function
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct FuncValue {
     args: Vec<FuncArg>,
     body: Box<Expr>,
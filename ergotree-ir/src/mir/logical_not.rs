@@ -5,9 +5,12 @@ use super::unary_op::UnaryOpTryBuild;
 use crate::has_opcode::HasStaticOpCode;
 use crate::serialization::op_code::OpCode;
 use crate::types::stype::SType;
+#[cfg(feature = "json")]
+use serde::Serialize;
 
 /// Logical NOT (inverts the input)
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize))]
 pub struct LogicalNot {
     /// Input expr of SBoolean type
     pub input: Box<Expr>,
@@ -1,6 +1,8 @@
 //! Convert SLong to byte array
 use crate::serialization::op_code::OpCode;
 use crate::types::stype::SType;
+#[cfg(feature = "json")]
+use serde::Serialize;
 
 use super::expr::Expr;
 use super::expr::InvalidArgumentError;
@@ -10,6 +12,7 @@ use crate::has_opcode::HasStaticOpCode;
 
 /// Convert SLong to byte array
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize))]
 pub struct LongToByteArray {
     /// Value of type SLong
     pub input: Box<Expr>,
@@ -1,6 +1,8 @@
 use crate::serialization::op_code::OpCode;
 use crate::types::smethod::SMethod;
 use crate::types::stype::SType;
+#[cfg(feature = "json")]
+use serde::Serialize;
 
 use super::expr::Expr;
 use super::expr::InvalidArgumentError;
@@ -17,6 +19,7 @@ use crate::has_opcode::HasStaticOpCode;
  * Thus, all ErgoTree instances are monomorphic by construction.
  */
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize))]
 pub struct MethodCall {
     /// Object on which method will be invoked
     pub obj: Box<Expr>,
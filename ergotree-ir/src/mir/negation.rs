@@ -5,9 +5,12 @@ use super::unary_op::UnaryOpTryBuild;
 use crate::has_opcode::HasStaticOpCode;
 use crate::serialization::op_code::OpCode;
 use crate::types::stype::SType;
+#[cfg(feature = "json")]
+use serde::Serialize;
 
 /// Negation operation on numeric type.
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize))]
 pub struct Negation {
     /// Input expr of numeric type
     pub input: Box<Expr>,
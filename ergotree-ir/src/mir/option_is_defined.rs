@@ -5,9 +5,12 @@ use super::unary_op::UnaryOpTryBuild;
 use crate::has_opcode::HasStaticOpCode;
 use crate::serialization::op_code::OpCode;
 use crate::types::stype::SType;
+#[cfg(feature = "json")]
+use serde::Serialize;
 
 /// Returns false if the option is None, true otherwise.
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize))]
 pub struct OptionIsDefined {
     /// Object of SOption type
     pub input: Box<Expr>,
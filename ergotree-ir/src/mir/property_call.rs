@@ -1,6 +1,8 @@
 use crate::serialization::op_code::OpCode;
 use crate::types::smethod::SMethod;
 use crate::types::stype::SType;
+#[cfg(feature = "json")]
+use serde::Serialize;
 
 use super::expr::Expr;
 use super::expr::InvalidArgumentError;
@@ -8,6 +10,7 @@ use crate::has_opcode::HasStaticOpCode;
 
 /// Invocation of object's property
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize))]
 pub struct PropertyCall {
     /// Object on which property will be invoked
     pub obj: Box<Expr>,
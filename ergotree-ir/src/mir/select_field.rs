@@ -1,3 +1,5 @@
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
 use std::convert::TryFrom;
 
 use crate::serialization::op_code::OpCode;
@@ -14,12 +16,29 @@ use crate::has_opcode::HasStaticOpCode;
 
 /// Tuple field access index (1..=255)
 #[derive(PartialEq, Eq, Debug, Copy, Clone)]
+#[cfg_attr(
+    feature = "json",
+    derive(Serialize, Deserialize),
+    serde(into = "u8", try_from = "u8")
+)]
 pub struct TupleFieldIndex(u8);
 
 /// Error for tuple index being out of bounds (1..=255)
 #[derive(Debug)]
 pub struct TupleFieldIndexOutBounds;
 
+impl std::fmt::Display for TupleFieldIndexOutBounds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("tuple field index is out of bounds (1..=255)")
+    }
+}
+
+impl From<TupleFieldIndex> for u8 {
+    fn from(v: TupleFieldIndex) -> Self {
+        v.0
+    }
+}
+
 impl TryFrom<u8> for TupleFieldIndex {
     type Error = TupleFieldIndexOutBounds;
 
@@ -57,6 +76,7 @@ impl SigmaSerializable for TupleFieldIndex {
 
 /// Select a field of the tuple value
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize))]
 pub struct SelectField {
     /// Tuple value
     pub input: Box<Expr>,
@@ -114,9 +134,9 @@ impl SigmaSerializable for SelectField {
     }
 
     fn sigma_parse<R: SigmaByteRead>(r: &mut R) -> Result<Self, SerializationError> {
-        let input = Expr::sigma_parse(r)?.into();
+        let input = Expr::sigma_parse(r)?;
         let field_index = TupleFieldIndex::sigma_parse(r)?;
-        Ok(SelectField { input, field_index })
+        Ok(SelectField::new(input, field_index)?)
     }
 }
 
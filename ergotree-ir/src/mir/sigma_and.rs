@@ -1,5 +1,7 @@
 //! AND conjunction for sigma propositions
 
+#[cfg(feature = "json")]
+use serde::Serialize;
 use std::convert::TryInto;
 
 use crate::serialization::op_code::OpCode;
@@ -16,8 +18,10 @@ use crate::has_opcode::HasStaticOpCode;
 
 /// AND conjunction for sigma propositions
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize))]
 pub struct SigmaAnd {
     /// Collection of SSigmaProp
+    #[cfg_attr(feature = "json", serde(with = "crate::json::bounded_vec"))]
     pub items: SigmaConjectureItems<Expr>,
 }
 
@@ -7,8 +7,11 @@ use crate::serialization::sigma_byte_writer::SigmaByteWrite;
 use crate::serialization::SerializationError;
 use crate::serialization::SigmaSerializable;
 use crate::types::stype::SType;
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
 
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 /// Substitute constants in the serialized representation of sigma script. Returns
 /// original scriptBytes array where only specified constants are replaced and all
 /// other bytes remain exactly the same.
@@ -1,3 +1,5 @@
+#[cfg(feature = "json")]
+use serde::Serialize;
 use std::convert::TryInto;
 
 use crate::serialization::op_code::OpCode;
@@ -15,8 +17,10 @@ use crate::has_opcode::HasStaticOpCode;
 
 /// Tuple of elements
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize))]
 pub struct Tuple {
     /// Tuple items
+    #[cfg_attr(feature = "json", serde(with = "crate::json::bounded_vec"))]
     pub items: TupleItems<Expr>,
 }
 
@@ -8,12 +8,15 @@ use crate::serialization::sigma_byte_writer::SigmaByteWrite;
 use crate::serialization::SerializationError;
 use crate::serialization::SigmaSerializable;
 use crate::types::stype::SType;
+#[cfg(feature = "json")]
+use serde::Serialize;
 
 use crate::has_opcode::HasStaticOpCode;
 use std::io::Error;
 
 /// Numerical upcast
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize))]
 pub struct Upcast {
     /// Numerical value to be upcasted
     pub input: Box<Expr>,
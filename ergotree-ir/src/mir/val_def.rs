@@ -1,3 +1,5 @@
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
 use std::io::Error;
 
 use crate::serialization::op_code::OpCode;
@@ -18,6 +20,7 @@ use proptest_derive::Arbitrary;
 
 /// Variable id
 #[derive(PartialEq, Eq, Hash, Debug, Clone, Copy, From)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 pub struct ValId(pub u32);
 
@@ -37,6 +40,7 @@ impl SigmaSerializable for ValId {
  * This representation is more compact in serialized form.
  * @param id unique identifier of the variable in the current scope. */
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 #[cfg_attr(test, derive(Arbitrary))]
 pub struct ValDef {
     /// Variable id
@@ -1,3 +1,5 @@
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
 use std::io;
 
 use super::val_def::ValId;
@@ -11,6 +13,7 @@ use crate::types::stype::SType;
 
 /** Special node which represents a reference to ValDef in was introduced as result of CSE. */
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct ValUse {
     /// Variable id
     pub val_id: ValId,
@@ -1,7 +1,11 @@
 //! Ergo data type
 
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
 use std::convert::TryFrom;
 use std::convert::TryInto;
+use std::ops::Range;
+use std::sync::Arc;
 
 use impl_trait_for_tuples::impl_for_tuples;
 use num_bigint::BigInt;
@@ -26,11 +30,24 @@ use super::func_value::FuncArg;
 extern crate derive_more;
 use derive_more::From;
 
+mod shared_slice;
+
+pub use shared_slice::SharedSlice;
+
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 /// Collection for primitive values (i.e byte array)
 pub enum NativeColl {
     /// Collection of bytes
-    CollByte(Vec<i8>),
+    CollByte(SharedSlice<i8>),
+    /// Collection of booleans
+    CollBoolean(SharedSlice<bool>),
+    /// Collection of shorts
+    CollShort(SharedSlice<i16>),
+    /// Collection of ints
+    CollInt(SharedSlice<i32>),
+    /// Collection of longs
+    CollLong(SharedSlice<i64>),
 }
 
 impl NativeColl {
@@ -38,12 +55,111 @@ impl NativeColl {
     pub fn elem_tpe(&self) -> &SType {
         match self {
             NativeColl::CollByte(_) => &SType::SByte,
+            NativeColl::CollBoolean(_) => &SType::SBoolean,
+            NativeColl::CollShort(_) => &SType::SShort,
+            NativeColl::CollInt(_) => &SType::SInt,
+            NativeColl::CollLong(_) => &SType::SLong,
+        }
+    }
+
+    /// Number of elements
+    pub fn len(&self) -> usize {
+        match self {
+            NativeColl::CollByte(v) => v.len(),
+            NativeColl::CollBoolean(v) => v.len(),
+            NativeColl::CollShort(v) => v.len(),
+            NativeColl::CollInt(v) => v.len(),
+            NativeColl::CollLong(v) => v.len(),
+        }
+    }
+
+    /// Returns true if the collection has no elements
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Element at the given index (if any)
+    pub fn get(&self, index: usize) -> Option<Value> {
+        match self {
+            NativeColl::CollByte(v) => v.get(index).map(|&i| i.into()),
+            NativeColl::CollBoolean(v) => v.get(index).map(|&i| i.into()),
+            NativeColl::CollShort(v) => v.get(index).map(|&i| i.into()),
+            NativeColl::CollInt(v) => v.get(index).map(|&i| i.into()),
+            NativeColl::CollLong(v) => v.get(index).map(|&i| i.into()),
         }
     }
+
+    /// Elements in the given range (`None` if the range is out of bounds)
+    pub fn slice(&self, range: Range<usize>) -> Option<NativeColl> {
+        Some(match self {
+            NativeColl::CollByte(v) => NativeColl::CollByte(v.slice(range)?),
+            NativeColl::CollBoolean(v) => NativeColl::CollBoolean(v.slice(range)?),
+            NativeColl::CollShort(v) => NativeColl::CollShort(v.slice(range)?),
+            NativeColl::CollInt(v) => NativeColl::CollInt(v.slice(range)?),
+            NativeColl::CollLong(v) => NativeColl::CollLong(v.slice(range)?),
+        })
+    }
+
+    /// Iterator over elements (wrapped in Value)
+    pub fn iter(&self) -> CollIter<'_> {
+        CollIter(match self {
+            NativeColl::CollByte(v) => CollIterKind::Byte(v.iter()),
+            NativeColl::CollBoolean(v) => CollIterKind::Boolean(v.iter()),
+            NativeColl::CollShort(v) => CollIterKind::Short(v.iter()),
+            NativeColl::CollInt(v) => CollIterKind::Int(v.iter()),
+            NativeColl::CollLong(v) => CollIterKind::Long(v.iter()),
+        })
+    }
+}
+
+/// Iterator over collection elements (wrapped in Value), see [`CollKind::iter`]
+#[derive(Clone, Debug)]
+pub struct CollIter<'a>(CollIterKind<'a>);
+
+#[derive(Clone, Debug)]
+enum CollIterKind<'a> {
+    Byte(std::slice::Iter<'a, i8>),
+    Boolean(std::slice::Iter<'a, bool>),
+    Short(std::slice::Iter<'a, i16>),
+    Int(std::slice::Iter<'a, i32>),
+    Long(std::slice::Iter<'a, i64>),
+    Wrapped(std::slice::Iter<'a, Value>),
 }
 
+impl Iterator for CollIter<'_> {
+    type Item = Value;
+
+    fn next(&mut self) -> Option<Value> {
+        match &mut self.0 {
+            CollIterKind::Byte(it) => it.next().map(|&i| i.into()),
+            CollIterKind::Boolean(it) => it.next().map(|&i| i.into()),
+            CollIterKind::Short(it) => it.next().map(|&i| i.into()),
+            CollIterKind::Int(it) => it.next().map(|&i| i.into()),
+            CollIterKind::Long(it) => it.next().map(|&i| i.into()),
+            CollIterKind::Wrapped(it) => it.next().cloned(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.0 {
+            CollIterKind::Byte(it) => it.size_hint(),
+            CollIterKind::Boolean(it) => it.size_hint(),
+            CollIterKind::Short(it) => it.size_hint(),
+            CollIterKind::Int(it) => it.size_hint(),
+            CollIterKind::Long(it) => it.size_hint(),
+            CollIterKind::Wrapped(it) => it.size_hint(),
+        }
+    }
+}
+
+impl ExactSizeIterator for CollIter<'_> {}
+
 /// Collection elements
-#[derive(PartialEq, Eq, Debug, Clone)]
+/// Elements of the types that have a native representation (see [`NativeColl`]) are stored as
+/// [`CollKind::NativeColl`] when built with [`CollKind::from_vec`]. Collections are compared by
+/// their elements, regardless of the representation.
+#[derive(Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub enum CollKind {
     /// Collection elements stored as a vector of Rust values
     NativeColl(NativeColl),
@@ -52,21 +168,55 @@ pub enum CollKind {
         /// Collection element type
         elem_tpe: SType,
         /// Collection elements
-        items: Vec<Value>,
+        items: SharedSlice<Value>,
     },
 }
 
+impl PartialEq for CollKind {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (CollKind::NativeColl(l), CollKind::NativeColl(r)) => l == r,
+            (
+                CollKind::WrappedColl {
+                    elem_tpe: l_tpe,
+                    items: l_items,
+                },
+                CollKind::WrappedColl {
+                    elem_tpe: r_tpe,
+                    items: r_items,
+                },
+            ) => l_tpe == r_tpe && l_items == r_items,
+            _ => {
+                self.elem_tpe() == other.elem_tpe()
+                    && self.len() == other.len()
+                    && self.iter().eq(other.iter())
+            }
+        }
+    }
+}
+
+fn extract_items<T: TryExtractFrom<Value>>(
+    items: Vec<Value>,
+) -> Result<SharedSlice<T>, TryExtractFromError> {
+    items.into_iter().map(T::try_extract_from).collect()
+}
+
 impl CollKind {
     /// Build a collection from items, storing them as Rust types values when neccessary
     pub fn from_vec(elem_tpe: SType, items: Vec<Value>) -> Result<CollKind, TryExtractFromError> {
-        match elem_tpe {
-            SType::SByte => items
-                .into_iter()
-                .map(|v| v.try_extract_into::<i8>())
-                .collect::<Result<Vec<_>, _>>()
-                .map(|bytes| CollKind::NativeColl(NativeColl::CollByte(bytes))),
-            _ => Ok(CollKind::WrappedColl { elem_tpe, items }),
-        }
+        Ok(CollKind::NativeColl(match elem_tpe {
+            SType::SByte => NativeColl::CollByte(extract_items(items)?),
+            SType::SBoolean => NativeColl::CollBoolean(extract_items(items)?),
+            SType::SShort => NativeColl::CollShort(extract_items(items)?),
+            SType::SInt => NativeColl::CollInt(extract_items(items)?),
+            SType::SLong => NativeColl::CollLong(extract_items(items)?),
+            _ => {
+                return Ok(CollKind::WrappedColl {
+                    elem_tpe,
+                    items: items.into(),
+                })
+            }
+        }))
     }
 
     /// Build a collection from items where each is a collection as well, storing them as Rust types values when neccessary
@@ -79,44 +229,117 @@ impl CollKind {
                 .into_iter()
                 .map(|v| v.try_extract_into::<Vec<i8>>())
                 .collect::<Result<Vec<_>, _>>()
-                .map(|bytes| CollKind::NativeColl(NativeColl::CollByte(bytes.concat()))),
+                .map(|bytes| CollKind::NativeColl(NativeColl::CollByte(bytes.concat().into()))),
             _ => items
                 .into_iter()
-                .map(|v| v.try_extract_into::<Vec<Value>>())
+                .map(|v| match v {
+                    Value::Coll(coll) => Ok(coll.into_vec()),
+                    _ => Err(TryExtractFromError(format!(
+                        "expected collection, found {:?}",
+                        v
+                    ))),
+                })
                 .collect::<Result<Vec<_>, _>>()
-                .map(|v| CollKind::WrappedColl {
-                    elem_tpe,
-                    items: v.concat(),
-                }),
+                .and_then(|v| CollKind::from_vec(elem_tpe, v.concat())),
         }
     }
 
     /// Collection element type
     pub fn elem_tpe(&self) -> &SType {
         match self {
-            cp @ CollKind::NativeColl(_) => cp.elem_tpe(),
+            CollKind::NativeColl(coll) => coll.elem_tpe(),
             CollKind::WrappedColl { elem_tpe, .. } => elem_tpe,
         }
     }
 
+    /// Number of elements
+    pub fn len(&self) -> usize {
+        match self {
+            CollKind::NativeColl(coll) => coll.len(),
+            CollKind::WrappedColl { items, .. } => items.len(),
+        }
+    }
+
+    /// Returns true if the collection has no elements
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Element at the given index (if any)
+    pub fn get(&self, index: usize) -> Option<Value> {
+        match self {
+            CollKind::NativeColl(coll) => coll.get(index),
+            CollKind::WrappedColl { items, .. } => items.get(index).cloned(),
+        }
+    }
+
+    /// Elements in the given range (`None` if the range is out of bounds)
+    pub fn slice(&self, range: Range<usize>) -> Option<CollKind> {
+        match self {
+            CollKind::NativeColl(coll) => coll.slice(range).map(CollKind::NativeColl),
+            CollKind::WrappedColl { elem_tpe, items } => {
+                items.slice(range).map(|slice| CollKind::WrappedColl {
+                    elem_tpe: elem_tpe.clone(),
+                    items: slice,
+                })
+            }
+        }
+    }
+
+    /// Iterator over elements (wrapped in Value)
+    pub fn iter(&self) -> CollIter<'_> {
+        match self {
+            CollKind::NativeColl(coll) => coll.iter(),
+            CollKind::WrappedColl { items, .. } => CollIter(CollIterKind::Wrapped(items.iter())),
+        }
+    }
+
+    /// Concatenate with another collection (of the same element type)
+    pub fn append(self, other: CollKind) -> Result<CollKind, TryExtractFromError> {
+        use NativeColl::*;
+        Ok(CollKind::NativeColl(match (self, other) {
+            (CollKind::NativeColl(CollByte(l)), CollKind::NativeColl(CollByte(r))) => {
+                CollByte(l.append(&r))
+            }
+            (CollKind::NativeColl(CollBoolean(l)), CollKind::NativeColl(CollBoolean(r))) => {
+                CollBoolean(l.append(&r))
+            }
+            (CollKind::NativeColl(CollShort(l)), CollKind::NativeColl(CollShort(r))) => {
+                CollShort(l.append(&r))
+            }
+            (CollKind::NativeColl(CollInt(l)), CollKind::NativeColl(CollInt(r))) => {
+                CollInt(l.append(&r))
+            }
+            (CollKind::NativeColl(CollLong(l)), CollKind::NativeColl(CollLong(r))) => {
+                CollLong(l.append(&r))
+            }
+            (l, r) => {
+                let elem_tpe = l.elem_tpe().clone();
+                return CollKind::from_vec(elem_tpe, [l.into_vec(), r.into_vec()].concat());
+            }
+        }))
+    }
+
     /// Return items, as vector of Values
     pub fn as_vec(&self) -> Vec<Value> {
         match self {
-            CollKind::NativeColl(NativeColl::CollByte(coll_byte)) => coll_byte
-                .clone()
-                .into_iter()
-                .map(|byte| byte.into())
-                .collect(),
-            CollKind::WrappedColl {
-                elem_tpe: _,
-                items: v,
-            } => v.clone(),
+            CollKind::NativeColl(coll) => coll.iter().collect(),
+            CollKind::WrappedColl { items, .. } => items.to_vec(),
+        }
+    }
+
+    /// Convert into items, as vector of Values
+    pub fn into_vec(self) -> Vec<Value> {
+        match self {
+            CollKind::NativeColl(coll) => coll.iter().collect(),
+            CollKind::WrappedColl { items, .. } => items.to_vec(),
         }
     }
 }
 
 /// Lambda
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct Lambda {
     /// Argument placeholders
     pub args: Vec<FuncArg>,
@@ -126,6 +349,7 @@ pub struct Lambda {
 
 /// Runtime value
 #[derive(PartialEq, Eq, Debug, Clone, From)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub enum Value {
     /// Boolean
     Boolean(bool),
@@ -138,7 +362,7 @@ pub enum Value {
     /// Long
     Long(i64),
     /// Big integer
-    BigInt(BigInt),
+    BigInt(#[cfg_attr(feature = "json", serde(with = "crate::json::bigint"))] BigInt),
     /// GroupElement
     GroupElement(Box<EcPoint>),
     /// Sigma property
@@ -150,7 +374,10 @@ pub enum Value {
     /// Collection of values of the same type
     Coll(CollKind),
     /// Tuple (arbitrary type values)
-    Tup(TupleItems<Value>),
+    Tup(
+        #[cfg_attr(feature = "json", serde(with = "crate::json::arc_bounded_vec"))]
+        Arc<TupleItems<Value>>,
+    ),
     /// Transaction(and blockchain) context info
     Context,
     /// Global which is used to define global methods
@@ -182,13 +409,15 @@ impl From<EcPoint> for Value {
 
 impl From<Vec<i8>> for Value {
     fn from(v: Vec<i8>) -> Self {
-        Value::Coll(CollKind::NativeColl(NativeColl::CollByte(v)))
+        Value::Coll(CollKind::NativeColl(NativeColl::CollByte(v.into())))
     }
 }
 
 impl From<Vec<u8>> for Value {
     fn from(v: Vec<u8>) -> Self {
-        Value::Coll(CollKind::NativeColl(NativeColl::CollByte(v.as_vec_i8())))
+        Value::Coll(CollKind::NativeColl(NativeColl::CollByte(
+            v.as_vec_i8().into(),
+        )))
     }
 }
 
@@ -218,10 +447,10 @@ impl StoreWrapped for Tuple {}
 
 impl<T: LiftIntoSType + StoreWrapped + Into<Value>> From<Vec<T>> for Value {
     fn from(v: Vec<T>) -> Self {
-        Value::Coll(CollKind::WrappedColl {
-            elem_tpe: T::stype(),
-            items: v.into_iter().map(|i| i.into()).collect(),
-        })
+        let items = v.into_iter().map(|i| i.into()).collect();
+        // items are of T::stype() type, so it cannot fail
+        #[allow(clippy::unwrap_used)]
+        Value::Coll(CollKind::from_vec(T::stype(), items).unwrap())
     }
 }
 
@@ -231,7 +460,7 @@ impl<T: LiftIntoSType + StoreWrapped + Into<Value>> From<Vec<T>> for Value {
 impl Into<Value> for Tuple {
     fn into(self) -> Value {
         let v: Vec<Value> = [for_tuples!(  #( Tuple.into() ),* )].to_vec();
-        Value::Tup(v.try_into().unwrap())
+        Value::Tup(Arc::new(v.try_into().unwrap()))
     }
 }
 
@@ -322,17 +551,11 @@ impl TryExtractFrom<Value> for IrBoxId {
 impl<T: TryExtractFrom<Value> + StoreWrapped> TryExtractFrom<Value> for Vec<T> {
     fn try_extract_from(c: Value) -> Result<Self, TryExtractFromError> {
         match c {
-            Value::Coll(coll) => match coll {
-                CollKind::WrappedColl {
-                    elem_tpe: _,
-                    items: v,
-                } => v.into_iter().map(T::try_extract_from).collect(),
-                _ => Err(TryExtractFromError(format!(
-                    "expected {:?}, found {:?}",
-                    std::any::type_name::<Self>(),
-                    coll
-                ))),
-            },
+            Value::Coll(coll) => coll
+                .into_vec()
+                .into_iter()
+                .map(T::try_extract_from)
+                .collect(),
             _ => Err(TryExtractFromError(format!(
                 "expected {:?}, found {:?}",
                 std::any::type_name::<Self>(),
@@ -346,7 +569,7 @@ impl TryExtractFrom<Value> for Vec<i8> {
     fn try_extract_from(v: Value) -> Result<Self, TryExtractFromError> {
         match v {
             Value::Coll(v) => match v {
-                CollKind::NativeColl(NativeColl::CollByte(bs)) => Ok(bs),
+                CollKind::NativeColl(NativeColl::CollByte(bs)) => Ok(bs.to_vec()),
                 _ => Err(TryExtractFromError(format!(
                     "expected {:?}, found {:?}",
                     std::any::type_name::<Self>(),
@@ -415,7 +638,7 @@ impl TryFrom<Value> for ProveDlog {
     }
 }
 
-// impl TryExtractFrom<Value> for Rc<Context> {
+// impl TryExtractFrom<Value> for Arc<Context> {
 //     fn try_extract_from(v: Value) -> Result<Self, TryExtractFromError> {
 //         match v {
 //             Value::Context(ctx) => Ok(ctx),
@@ -502,18 +725,67 @@ mod tests {
     }
 
     #[test]
-    fn wrapped_from_vec_roundtrip() {
+    fn long_from_vec_roundtrip() {
         let longs = vec![1i64, 2i64, 3i64];
         let wrapped: Vec<Value> = longs.into_iter().map(|b| b.into()).collect();
         let coll = CollKind::from_vec(SType::SLong, wrapped.clone()).unwrap();
+        assert!(matches!(
+            coll,
+            CollKind::NativeColl(NativeColl::CollLong(_))
+        ));
+        let as_vec = coll.as_vec();
+        assert_eq!(as_vec, wrapped);
+    }
+
+    #[test]
+    fn wrapped_from_vec_roundtrip() {
+        let bigints = vec![BigInt::from(1), BigInt::from(2)];
+        let wrapped: Vec<Value> = bigints.into_iter().map(|b| b.into()).collect();
+        let coll = CollKind::from_vec(SType::SBigInt, wrapped.clone()).unwrap();
         assert!(matches!(
             coll,
             CollKind::WrappedColl {
-                elem_tpe: SType::SLong,
+                elem_tpe: SType::SBigInt,
                 items: _,
             }
         ));
         let as_vec = coll.as_vec();
         assert_eq!(as_vec, wrapped);
     }
+
+    #[test]
+    fn native_and_wrapped_are_equal() {
+        let ints = vec![1i32, 2i32, 3i32];
+        let native: Value = ints.clone().into();
+        assert!(matches!(
+            native,
+            Value::Coll(CollKind::NativeColl(NativeColl::CollInt(_)))
+        ));
+        let wrapped = Value::Coll(CollKind::WrappedColl {
+            elem_tpe: SType::SInt,
+            items: ints.iter().map(|&i| i.into()).collect(),
+        });
+        assert_eq!(native, wrapped);
+        assert_eq!(wrapped.try_extract_into::<Vec<i32>>().unwrap(), ints);
+        assert_eq!(native.try_extract_into::<Vec<i32>>().unwrap(), ints);
+    }
+
+    #[test]
+    fn native_append_slice() {
+        let l = CollKind::from_vec(SType::SLong, vec![1i64.into(), 2i64.into()]).unwrap();
+        let r = CollKind::from_vec(SType::SLong, vec![3i64.into()]).unwrap();
+        let appended = l.append(r).unwrap();
+        assert_eq!(
+            appended,
+            CollKind::NativeColl(NativeColl::CollLong(vec![1, 2, 3].into()))
+        );
+        assert_eq!(
+            appended.slice(1..3),
+            Some(CollKind::NativeColl(NativeColl::CollLong(
+                vec![2, 3].into()
+            )))
+        );
+        assert_eq!(appended.slice(2..4), None);
+        assert_eq!(appended.get(0), Some(Value::Long(1)));
+    }
 }
@@ -0,0 +1,141 @@
+//! Immutable slice with shared storage
+
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::Deref;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// Immutable slice backed by an atomically reference counted storage.
+/// Cloning and slicing do not copy the elements.
+#[derive(Clone)]
+pub struct SharedSlice<T> {
+    items: Arc<[T]>,
+    offset: usize,
+    len: usize,
+}
+
+impl<T> SharedSlice<T> {
+    /// Elements as a slice
+    pub fn as_slice(&self) -> &[T] {
+        &self.items[self.offset..self.offset + self.len]
+    }
+
+    /// View of the elements in the given range, sharing the storage with `self`
+    /// (`None` if the range is out of bounds)
+    pub fn slice(&self, range: Range<usize>) -> Option<SharedSlice<T>> {
+        if range.start > range.end || range.end > self.len {
+            return None;
+        }
+        Some(SharedSlice {
+            items: self.items.clone(),
+            offset: self.offset + range.start,
+            len: range.end - range.start,
+        })
+    }
+}
+
+impl<T: Clone> SharedSlice<T> {
+    /// New slice with the elements of `self` followed by the elements of `other`
+    pub fn append(&self, other: &[T]) -> SharedSlice<T> {
+        if other.is_empty() {
+            return self.clone();
+        }
+        [self.as_slice(), other].concat().into()
+    }
+}
+
+impl<T> Deref for SharedSlice<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T> From<Vec<T>> for SharedSlice<T> {
+    fn from(v: Vec<T>) -> Self {
+        let len = v.len();
+        SharedSlice {
+            items: v.into(),
+            offset: 0,
+            len,
+        }
+    }
+}
+
+impl<T> FromIterator<T> for SharedSlice<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        iter.into_iter().collect::<Vec<T>>().into()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a SharedSlice<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.as_slice().iter()
+    }
+}
+
+impl<T: PartialEq> PartialEq for SharedSlice<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: Eq> Eq for SharedSlice<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for SharedSlice<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_slice().fmt(f)
+    }
+}
+
+#[cfg(feature = "json")]
+impl<T: serde::Serialize> serde::Serialize for SharedSlice<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_slice().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "json")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for SharedSlice<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::<T>::deserialize(deserializer).map(SharedSlice::from)
+    }
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slice_shares_storage() {
+        let s: SharedSlice<i32> = vec![1, 2, 3, 4].into();
+        let sub = s.slice(1..3).unwrap();
+        assert_eq!(sub.as_slice(), &[2, 3]);
+        assert!(Arc::ptr_eq(&s.items, &sub.items));
+        assert_eq!(sub.slice(1..2).unwrap().as_slice(), &[3]);
+        assert_eq!(sub.slice(1..3), None);
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = s.slice(2..1);
+        assert_eq!(reversed, None);
+    }
+
+    #[test]
+    fn append() {
+        let s: SharedSlice<i32> = vec![1, 2, 3].into();
+        let sub = s.slice(1..3).unwrap();
+        assert_eq!(sub.append(&[4]).as_slice(), &[2, 3, 4]);
+    }
+
+    #[test]
+    fn eq_by_elements() {
+        let s: SharedSlice<i32> = vec![1, 2, 3, 2, 3].into();
+        assert_eq!(s.slice(1..3), s.slice(3..5));
+        assert_ne!(s.slice(0..2), s.slice(1..3));
+    }
+}
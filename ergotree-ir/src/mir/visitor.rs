@@ -0,0 +1,499 @@
+//! Generic traversal (fold) and rewriting of the [`Expr`] tree
+
+use super::coll_append::Append;
+use super::coll_by_index::ByIndex;
+use super::coll_exists::Exists;
+use super::coll_filter::Filter;
+use super::coll_fold::Fold;
+use super::coll_forall::ForAll;
+use super::coll_map::Map;
+use super::coll_slice::Slice;
+use super::collection::Collection;
+use super::expr::Expr;
+use super::func_value::FuncValue;
+
+/// Returns direct children of the given node (in the order they are serialized)
+pub fn children(e: &Expr) -> Vec<&Expr> {
+    match e {
+        Expr::Const(_)
+        | Expr::ConstPlaceholder(_)
+        | Expr::Context
+        | Expr::Global
+        | Expr::GlobalVars(_)
+        | Expr::ValUse(_)
+        | Expr::GetVar(_)
+        | Expr::DeserializeContext(_) => vec![],
+        Expr::Append(op) => vec![&op.input, &op.col_2],
+        Expr::SubstConstants(op) => vec![&op.script_bytes, &op.positions, &op.new_values],
+        Expr::ByteArrayToLong(op) => vec![&op.input],
+        Expr::ByteArrayToBigInt(op) => vec![&op.input],
+        Expr::LongToByteArray(op) => vec![&op.input],
+        Expr::Collection(Collection::BoolConstants(_)) => vec![],
+        Expr::Collection(Collection::Exprs { items, .. }) => items.iter().collect(),
+        Expr::Tuple(op) => op.items.iter().collect(),
+        Expr::CalcBlake2b256(op) => vec![&op.input],
+        Expr::CalcSha256(op) => vec![&op.input],
+        Expr::FuncValue(op) => vec![op.body()],
+        Expr::Apply(op) => std::iter::once(op.func.as_ref())
+            .chain(op.args.iter())
+            .collect(),
+        Expr::MethodCall(op) => std::iter::once(op.obj.as_ref())
+            .chain(op.args.iter())
+            .collect(),
+        Expr::ProperyCall(op) => vec![&op.obj],
+        Expr::BlockValue(op) => op
+            .items
+            .iter()
+            .chain(std::iter::once(op.result.as_ref()))
+            .collect(),
+        Expr::ValDef(op) => vec![&op.rhs],
+        Expr::If(op) => vec![&op.condition, &op.true_branch, &op.false_branch],
+        Expr::BinOp(op) => vec![&op.left, &op.right],
+        Expr::And(op) => vec![&op.input],
+        Expr::Or(op) => vec![&op.input],
+        Expr::Xor(op) => vec![&op.left, &op.right],
+        Expr::Atleast(op) => vec![&op.n_required, &op.expressions],
+        Expr::LogicalNot(op) => vec![&op.input],
+        Expr::Negation(op) => vec![&op.input],
+        Expr::OptionGet(op) => vec![&op.input],
+        Expr::OptionIsDefined(op) => vec![&op.input],
+        Expr::OptionGetOrElse(op) => vec![&op.input, &op.default],
+        Expr::ExtractAmount(op) => vec![&op.input],
+        Expr::ExtractRegisterAs(op) => vec![&op.input],
+        Expr::ExtractScriptBytes(op) => vec![&op.input],
+        Expr::ExtractCreationInfo(op) => vec![&op.input],
+        Expr::ExtractId(op) => vec![&op.input],
+        Expr::ByIndex(op) => std::iter::once(op.input.as_ref())
+            .chain(std::iter::once(op.index.as_ref()))
+            .chain(op.default.as_deref())
+            .collect(),
+        Expr::SizeOf(op) => vec![&op.input],
+        Expr::Slice(op) => vec![&op.input, &op.from, &op.until],
+        Expr::Fold(op) => vec![&op.input, &op.zero, &op.fold_op],
+        Expr::Map(op) => vec![&op.input, &op.mapper],
+        Expr::Filter(op) => vec![&op.input, &op.condition],
+        Expr::Exists(op) => vec![&op.input, &op.condition],
+        Expr::ForAll(op) => vec![&op.input, &op.condition],
+        Expr::SelectField(op) => vec![&op.input],
+        Expr::BoolToSigmaProp(op) => vec![&op.input],
+        Expr::Upcast(op) => vec![&op.input],
+        Expr::CreateProveDlog(op) => vec![&op.input],
+        Expr::CreateProveDhTuple(op) => vec![&op.gv, &op.hv, &op.uv, &op.vv],
+        Expr::SigmaPropBytes(op) => vec![&op.input],
+        Expr::DecodePoint(op) => vec![&op.input],
+        Expr::SigmaAnd(op) => op.items.iter().collect(),
+        Expr::SigmaOr(op) => op.items.iter().collect(),
+        Expr::DeserializeRegister(op) => op.default.as_deref().into_iter().collect(),
+        Expr::MultiplyGroup(op) => vec![&op.left, &op.right],
+    }
+}
+
+/// Folds the tree in pre-order (node is visited before its children)
+pub fn fold<A, F>(e: &Expr, init: A, f: F) -> A
+where
+    F: FnMut(A, &Expr) -> A,
+{
+    let mut f = f;
+    fold_with(e, init, &mut f)
+}
+
+fn fold_with<A, F>(e: &Expr, acc: A, f: &mut F) -> A
+where
+    F: FnMut(A, &Expr) -> A,
+{
+    let acc = f(acc, e);
+    children(e)
+        .into_iter()
+        .fold(acc, |acc, child| fold_with(child, acc, f))
+}
+
+/// Rewrites the tree starting from the leaves. The node is rewritten after its children,
+/// `f` returns `None` to leave the node as is.
+pub fn rewrite_bottom_up<E, F>(e: Expr, f: F) -> Result<Expr, E>
+where
+    F: FnMut(&Expr) -> Result<Option<Expr>, E>,
+{
+    let mut f = f;
+    rewrite_bottom_up_with(e, &mut f)
+}
+
+fn rewrite_bottom_up_with<E, F>(e: Expr, f: &mut F) -> Result<Expr, E>
+where
+    F: FnMut(&Expr) -> Result<Option<Expr>, E>,
+{
+    let e = map_children(e, &mut |child| rewrite_bottom_up_with(child, f))?;
+    Ok(f(&e)?.unwrap_or(e))
+}
+
+/// Rewrites the tree starting from the root. The children of the rewritten node are rewritten
+/// afterwards, `f` returns `None` to leave the node as is.
+pub fn rewrite_top_down<E, F>(e: Expr, f: F) -> Result<Expr, E>
+where
+    F: FnMut(&Expr) -> Result<Option<Expr>, E>,
+{
+    let mut f = f;
+    rewrite_top_down_with(e, &mut f)
+}
+
+fn rewrite_top_down_with<E, F>(e: Expr, f: &mut F) -> Result<Expr, E>
+where
+    F: FnMut(&Expr) -> Result<Option<Expr>, E>,
+{
+    let e = f(&e)?.unwrap_or(e);
+    map_children(e, &mut |child| rewrite_top_down_with(child, f))
+}
+
+/// Rebuilds the node with each direct child replaced with the result of `f` (children are
+/// processed in the order they are serialized)
+pub fn map_children<E, F>(e: Expr, f: &mut F) -> Result<Expr, E>
+where
+    F: FnMut(Expr) -> Result<Expr, E>,
+{
+    Ok(match e {
+        Expr::Const(_)
+        | Expr::ConstPlaceholder(_)
+        | Expr::Context
+        | Expr::Global
+        | Expr::GlobalVars(_)
+        | Expr::ValUse(_)
+        | Expr::GetVar(_)
+        | Expr::DeserializeContext(_)
+        | Expr::Collection(Collection::BoolConstants(_)) => e,
+        Expr::Append(op) => Append {
+            input: map_box(*op.input, f)?,
+            col_2: map_box(*op.col_2, f)?,
+        }
+        .into(),
+        Expr::SubstConstants(mut op) => {
+            op.script_bytes = map_box(*op.script_bytes, f)?;
+            op.positions = map_box(*op.positions, f)?;
+            op.new_values = map_box(*op.new_values, f)?;
+            op.into()
+        }
+        Expr::ByteArrayToLong(mut op) => {
+            op.input = map_box(*op.input, f)?;
+            op.into()
+        }
+        Expr::ByteArrayToBigInt(mut op) => {
+            op.input = map_box(*op.input, f)?;
+            op.into()
+        }
+        Expr::LongToByteArray(mut op) => {
+            op.input = map_box(*op.input, f)?;
+            op.into()
+        }
+        Expr::Collection(Collection::Exprs { elem_tpe, items }) => Collection::Exprs {
+            elem_tpe,
+            items: items.into_iter().map(&mut *f).collect::<Result<_, _>>()?,
+        }
+        .into(),
+        Expr::Tuple(mut op) => {
+            op.items = op.items.try_mapped(&mut *f)?;
+            op.into()
+        }
+        Expr::CalcBlake2b256(mut op) => {
+            op.input = map_box(*op.input, f)?;
+            op.into()
+        }
+        Expr::CalcSha256(mut op) => {
+            op.input = map_box(*op.input, f)?;
+            op.into()
+        }
+        Expr::FuncValue(op) => {
+            // function type depends on the body type, so it's re-created
+            let body = f(op.body().clone())?;
+            FuncValue::new(op.args().to_vec(), body).into()
+        }
+        Expr::Apply(mut op) => {
+            op.func = map_box(*op.func, f)?;
+            op.args = op.args.into_iter().map(&mut *f).collect::<Result<_, _>>()?;
+            op.into()
+        }
+        Expr::MethodCall(mut op) => {
+            op.obj = map_box(*op.obj, f)?;
+            op.args = op.args.into_iter().map(&mut *f).collect::<Result<_, _>>()?;
+            op.into()
+        }
+        Expr::ProperyCall(mut op) => {
+            op.obj = map_box(*op.obj, f)?;
+            op.into()
+        }
+        Expr::BlockValue(mut op) => {
+            op.items = op
+                .items
+                .into_iter()
+                .map(&mut *f)
+                .collect::<Result<_, _>>()?;
+            op.result = map_box(*op.result, f)?;
+            op.into()
+        }
+        Expr::ValDef(mut op) => {
+            op.rhs = map_box(*op.rhs, f)?;
+            op.into()
+        }
+        Expr::If(mut op) => {
+            op.condition = map_box(*op.condition, f)?;
+            op.true_branch = map_box(*op.true_branch, f)?;
+            op.false_branch = map_box(*op.false_branch, f)?;
+            op.into()
+        }
+        Expr::BinOp(mut op) => {
+            op.left = map_box(*op.left, f)?;
+            op.right = map_box(*op.right, f)?;
+            op.into()
+        }
+        Expr::And(mut op) => {
+            op.input = map_box(*op.input, f)?;
+            op.into()
+        }
+        Expr::Or(mut op) => {
+            op.input = map_box(*op.input, f)?;
+            op.into()
+        }
+        Expr::Xor(mut op) => {
+            op.left = map_box(*op.left, f)?;
+            op.right = map_box(*op.right, f)?;
+            op.into()
+        }
+        Expr::Atleast(mut op) => {
+            op.n_required = map_box(*op.n_required, f)?;
+            op.expressions = map_box(*op.expressions, f)?;
+            op.into()
+        }
+        Expr::LogicalNot(mut op) => {
+            op.input = map_box(*op.input, f)?;
+            op.into()
+        }
+        Expr::Negation(mut op) => {
+            op.input = map_box(*op.input, f)?;
+            op.into()
+        }
+        Expr::OptionGet(mut op) => {
+            op.input = map_box(*op.input, f)?;
+            op.into()
+        }
+        Expr::OptionIsDefined(mut op) => {
+            op.input = map_box(*op.input, f)?;
+            op.into()
+        }
+        Expr::OptionGetOrElse(mut op) => {
+            op.input = map_box(*op.input, f)?;
+            op.default = map_box(*op.default, f)?;
+            op.into()
+        }
+        Expr::ExtractAmount(mut op) => {
+            op.input = map_box(*op.input, f)?;
+            op.into()
+        }
+        Expr::ExtractRegisterAs(mut op) => {
+            op.input = map_box(*op.input, f)?;
+            op.into()
+        }
+        Expr::ExtractScriptBytes(mut op) => {
+            op.input = map_box(*op.input, f)?;
+            op.into()
+        }
+        Expr::ExtractCreationInfo(mut op) => {
+            op.input = map_box(*op.input, f)?;
+            op.into()
+        }
+        Expr::ExtractId(mut op) => {
+            op.input = map_box(*op.input, f)?;
+            op.into()
+        }
+        Expr::ByIndex(op) => ByIndex {
+            input: map_box(*op.input, f)?,
+            index: map_box(*op.index, f)?,
+            default: op.default.map(|b| map_box(*b, f)).transpose()?,
+        }
+        .into(),
+        Expr::SizeOf(mut op) => {
+            op.input = map_box(*op.input, f)?;
+            op.into()
+        }
+        Expr::Slice(op) => Slice {
+            input: map_box(*op.input, f)?,
+            from: map_box(*op.from, f)?,
+            until: map_box(*op.until, f)?,
+        }
+        .into(),
+        Expr::Fold(op) => Fold {
+            input: map_box(*op.input, f)?,
+            zero: map_box(*op.zero, f)?,
+            fold_op: map_box(*op.fold_op, f)?,
+        }
+        .into(),
+        Expr::Map(op) => Map {
+            input: map_box(*op.input, f)?,
+            mapper: map_box(*op.mapper, f)?,
+            ..op
+        }
+        .into(),
+        Expr::Filter(op) => Filter {
+            input: map_box(*op.input, f)?,
+            condition: map_box(*op.condition, f)?,
+            ..op
+        }
+        .into(),
+        Expr::Exists(op) => Exists {
+            input: map_box(*op.input, f)?,
+            condition: map_box(*op.condition, f)?,
+            ..op
+        }
+        .into(),
+        Expr::ForAll(op) => ForAll {
+            input: map_box(*op.input, f)?,
+            condition: map_box(*op.condition, f)?,
+            ..op
+        }
+        .into(),
+        Expr::SelectField(mut op) => {
+            op.input = map_box(*op.input, f)?;
+            op.into()
+        }
+        Expr::BoolToSigmaProp(mut op) => {
+            op.input = map_box(*op.input, f)?;
+            op.into()
+        }
+        Expr::Upcast(mut op) => {
+            op.input = map_box(*op.input, f)?;
+            op.into()
+        }
+        Expr::CreateProveDlog(mut op) => {
+            op.input = map_box(*op.input, f)?;
+            op.into()
+        }
+        Expr::CreateProveDhTuple(mut op) => {
+            op.gv = map_box(*op.gv, f)?;
+            op.hv = map_box(*op.hv, f)?;
+            op.uv = map_box(*op.uv, f)?;
+            op.vv = map_box(*op.vv, f)?;
+            op.into()
+        }
+        Expr::SigmaPropBytes(mut op) => {
+            op.input = map_box(*op.input, f)?;
+            op.into()
+        }
+        Expr::DecodePoint(mut op) => {
+            op.input = map_box(*op.input, f)?;
+            op.into()
+        }
+        Expr::SigmaAnd(mut op) => {
+            op.items = op.items.try_mapped(&mut *f)?;
+            op.into()
+        }
+        Expr::SigmaOr(mut op) => {
+            op.items = op.items.try_mapped(&mut *f)?;
+            op.into()
+        }
+        Expr::DeserializeRegister(mut op) => {
+            op.default = op.default.map(|b| map_box(*b, f)).transpose()?;
+            op.into()
+        }
+        Expr::MultiplyGroup(mut op) => {
+            op.left = map_box(*op.left, f)?;
+            op.right = map_box(*op.right, f)?;
+            op.into()
+        }
+    })
+}
+
+fn map_box<E, F>(e: Expr, f: &mut F) -> Result<Box<Expr>, E>
+where
+    F: FnMut(Expr) -> Result<Expr, E>,
+{
+    f(e).map(Box::new)
+}
+
+#[cfg(test)]
+#[cfg(feature = "arbitrary")]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::mir::bin_op::ArithOp;
+    use crate::mir::bin_op::BinOp;
+    use crate::mir::constant::Constant;
+    use crate::mir::global_vars::GlobalVars;
+    use proptest::prelude::*;
+    use std::convert::Infallible;
+
+    fn plus(left: Expr, right: Expr) -> Expr {
+        BinOp {
+            kind: ArithOp::Plus.into(),
+            left: left.into(),
+            right: right.into(),
+        }
+        .into()
+    }
+
+    #[test]
+    fn fold_pre_order() {
+        let e = plus(plus(GlobalVars::Height.into(), 1i32.into()), 2i32.into());
+        let consts = fold(&e, Vec::new(), |mut acc, e| {
+            if let Expr::Const(c) = e {
+                acc.push(c.clone());
+            }
+            acc
+        });
+        assert_eq!(consts, vec![Constant::from(1i32), Constant::from(2i32)]);
+    }
+
+    #[test]
+    fn rewrite_order() {
+        let e = plus(GlobalVars::Height.into(), 1i32.into());
+        let mut bottom_up = Vec::new();
+        rewrite_bottom_up(e.clone(), |e| {
+            bottom_up.push(e.clone());
+            Ok::<_, Infallible>(None)
+        })
+        .unwrap();
+        assert_eq!(bottom_up.last(), Some(&e));
+        let mut top_down = Vec::new();
+        rewrite_top_down(e.clone(), |e| {
+            top_down.push(e.clone());
+            Ok::<_, Infallible>(None)
+        })
+        .unwrap();
+        assert_eq!(top_down.first(), Some(&e));
+    }
+
+    #[test]
+    fn rewrite_replaces_nodes() {
+        let e = plus(GlobalVars::Height.into(), plus(1i32.into(), 2i32.into()));
+        let res = rewrite_bottom_up(e, |e| {
+            Ok::<_, Infallible>(match e {
+                Expr::Const(_) => Some(GlobalVars::Height.into()),
+                _ => None,
+            })
+        })
+        .unwrap();
+        let expected = plus(
+            GlobalVars::Height.into(),
+            plus(GlobalVars::Height.into(), GlobalVars::Height.into()),
+        );
+        assert_eq!(res, expected);
+    }
+
+    proptest! {
+
+        #[test]
+        fn identity_rewrite(e in any::<Expr>()) {
+            let bottom_up = rewrite_bottom_up(e.clone(), |_| Ok::<_, Infallible>(None)).unwrap();
+            prop_assert_eq![&bottom_up, &e];
+            let top_down = rewrite_top_down(e.clone(), |_| Ok::<_, Infallible>(None)).unwrap();
+            prop_assert_eq![&top_down, &e];
+        }
+
+        #[test]
+        fn fold_visits_every_child(e in any::<Expr>()) {
+            let count = fold(&e, 0usize, |acc, _| acc + 1);
+            let mut rewritten = 0usize;
+            rewrite_bottom_up(e, |_| {
+                rewritten += 1;
+                Ok::<_, Infallible>(None)
+            })
+            .unwrap();
+            prop_assert_eq![count, rewritten];
+        }
+    }
+}
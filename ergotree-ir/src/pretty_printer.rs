@@ -0,0 +1,814 @@
+//! Pretty printer for ErgoTree IR (ErgoScript-like source code)
+//!
+//! [`ValDef`]/[`ValUse`] and function arguments are named after their ids (`v1`, `v2`, ...),
+//! constant placeholders (in segregated trees) are printed as `placeholder[T](index)`.
+
+use crate::mir::bin_op::ArithOp;
+use crate::mir::bin_op::BinOp;
+use crate::mir::bin_op::BinOpKind;
+use crate::mir::bin_op::RelationOp;
+use crate::mir::block::BlockValue;
+use crate::mir::collection::Collection;
+use crate::mir::constant::Constant;
+use crate::mir::expr::Expr;
+use crate::mir::func_value::FuncArg;
+use crate::mir::global_vars::GlobalVars;
+use crate::mir::val_def::ValDef;
+use crate::mir::val_def::ValId;
+use crate::mir::value::CollKind;
+use crate::mir::value::NativeColl;
+use crate::mir::value::Value;
+use crate::serialization::SigmaSerializable;
+use crate::sigma_protocol::dlog_group::EcPoint;
+use crate::sigma_protocol::sigma_boolean::SigmaBoolean;
+use crate::sigma_protocol::sigma_boolean::SigmaConjecture;
+use crate::sigma_protocol::sigma_boolean::SigmaProofOfKnowledgeTree;
+use crate::types::stype::SType;
+use crate::util::AsVecU8;
+
+/// Number of spaces per indentation level
+const INDENT_WIDTH: usize = 2;
+
+/// Operator precedence (higher binds tighter), follows Scala (ErgoScript) rules
+mod prec {
+    /// `if`, lambdas, `val` definitions
+    pub const LOWEST: u8 = 0;
+    /// `||`, `|`
+    pub const OR: u8 = 1;
+    /// `^`
+    pub const XOR: u8 = 2;
+    /// `&&`, `&`
+    pub const AND: u8 = 3;
+    /// `==`, `!=`
+    pub const EQ: u8 = 4;
+    /// `<`, `>`, `<=`, `>=`
+    pub const CMP: u8 = 5;
+    /// `+`, `-`
+    pub const ADD: u8 = 6;
+    /// `*`, `/`
+    pub const MUL: u8 = 7;
+    /// prefix `!`, `-`
+    pub const PREFIX: u8 = 8;
+    /// method/property calls, function application, literals and identifiers
+    pub const POSTFIX: u8 = 9;
+}
+
+/// Prints the expression as ErgoScript-like source code
+pub fn print_expr(expr: &Expr) -> String {
+    let mut p = Printer::default();
+    p.expr(expr);
+    p.out
+}
+
+/// Prints the value of the given type as ErgoScript literal
+pub fn print_value(v: &Value, tpe: &SType) -> String {
+    let mut p = Printer::default();
+    p.value(v, tpe);
+    p.out
+}
+
+/// Prints the type in ErgoScript syntax (e.g. `Coll[(Int, Long)]`)
+pub fn print_type(tpe: &SType) -> String {
+    match tpe {
+        SType::STypeVar(v) => v.name.clone(),
+        SType::SAny => "Any".to_string(),
+        SType::SBoolean => "Boolean".to_string(),
+        SType::SByte => "Byte".to_string(),
+        SType::SShort => "Short".to_string(),
+        SType::SInt => "Int".to_string(),
+        SType::SLong => "Long".to_string(),
+        SType::SBigInt => "BigInt".to_string(),
+        SType::SGroupElement => "GroupElement".to_string(),
+        SType::SSigmaProp => "SigmaProp".to_string(),
+        SType::SBox => "Box".to_string(),
+        SType::SAvlTree => "AvlTree".to_string(),
+        SType::SOption(t) => format!("Option[{}]", print_type(t)),
+        SType::SColl(t) => format!("Coll[{}]", print_type(t)),
+        SType::STuple(t) => format!(
+            "({})",
+            t.items
+                .iter()
+                .map(print_type)
+                .collect::<Vec<String>>()
+                .join(", ")
+        ),
+        SType::SFunc(f) => format!(
+            "({}) => {}",
+            f.t_dom
+                .iter()
+                .map(print_type)
+                .collect::<Vec<String>>()
+                .join(", "),
+            print_type(&f.t_range)
+        ),
+        SType::SContext => "Context".to_string(),
+        SType::SHeader => "Header".to_string(),
+        SType::SPreHeader => "PreHeader".to_string(),
+        SType::SGlobal => "Global".to_string(),
+    }
+}
+
+fn val_name(id: &ValId) -> String {
+    format!("v{}", id.0)
+}
+
+fn precedence(expr: &Expr) -> u8 {
+    match expr {
+        Expr::BinOp(BinOp { kind, .. }) => match kind {
+            BinOpKind::Relation(RelationOp::Or) | BinOpKind::Arith(ArithOp::BitOr) => prec::OR,
+            BinOpKind::Arith(ArithOp::BitXor) => prec::XOR,
+            BinOpKind::Relation(RelationOp::And) | BinOpKind::Arith(ArithOp::BitAnd) => prec::AND,
+            BinOpKind::Relation(RelationOp::Eq) | BinOpKind::Relation(RelationOp::NEq) => prec::EQ,
+            BinOpKind::Relation(_) => prec::CMP,
+            BinOpKind::Arith(ArithOp::Plus) | BinOpKind::Arith(ArithOp::Minus) => prec::ADD,
+            BinOpKind::Arith(ArithOp::Multiply) | BinOpKind::Arith(ArithOp::Divide) => prec::MUL,
+            BinOpKind::Arith(ArithOp::Max) | BinOpKind::Arith(ArithOp::Min) => prec::POSTFIX,
+        },
+        Expr::SigmaOr(_) => prec::OR,
+        Expr::SigmaAnd(_) => prec::AND,
+        Expr::LogicalNot(_) | Expr::Negation(_) => prec::PREFIX,
+        Expr::Const(c) => const_precedence(c),
+        Expr::If(_) | Expr::FuncValue(_) | Expr::ValDef(_) => prec::LOWEST,
+        _ => prec::POSTFIX,
+    }
+}
+
+fn const_precedence(c: &Constant) -> u8 {
+    match &c.v {
+        Value::Byte(v) if *v < 0 => prec::POSTFIX,
+        Value::Short(v) if *v < 0 => prec::POSTFIX,
+        Value::Int(v) if *v < 0 => prec::PREFIX,
+        Value::Long(v) if *v < 0 => prec::PREFIX,
+        Value::SigmaProp(sp) => match sp.value() {
+            SigmaBoolean::SigmaConjecture(SigmaConjecture::Cand(_))
+            | SigmaBoolean::SigmaConjecture(SigmaConjecture::Cor(_)) => prec::LOWEST,
+            _ => prec::POSTFIX,
+        },
+        _ => prec::POSTFIX,
+    }
+}
+
+fn bin_op_symbol(kind: &BinOpKind) -> &'static str {
+    match kind {
+        BinOpKind::Arith(op) => match op {
+            ArithOp::Plus => "+",
+            ArithOp::Minus => "-",
+            ArithOp::Multiply => "*",
+            ArithOp::Divide => "/",
+            ArithOp::Max => "max",
+            ArithOp::Min => "min",
+            ArithOp::BitOr => "|",
+            ArithOp::BitAnd => "&",
+            ArithOp::BitXor => "^",
+        },
+        BinOpKind::Relation(op) => match op {
+            RelationOp::Eq => "==",
+            RelationOp::NEq => "!=",
+            RelationOp::Ge => ">=",
+            RelationOp::Gt => ">",
+            RelationOp::Le => "<=",
+            RelationOp::Lt => "<",
+            RelationOp::And => "&&",
+            RelationOp::Or => "||",
+        },
+    }
+}
+
+#[derive(Default)]
+struct Printer {
+    out: String,
+    indent: usize,
+}
+
+impl Printer {
+    fn write(&mut self, s: &str) {
+        self.out.push_str(s);
+    }
+
+    fn newline(&mut self) {
+        self.out.push('\n');
+        self.out.push_str(&" ".repeat(self.indent * INDENT_WIDTH));
+    }
+
+    /// Print `expr` wrapping it in parentheses if it binds weaker than `min_prec`
+    fn operand(&mut self, expr: &Expr, min_prec: u8) {
+        if precedence(expr) < min_prec {
+            self.write("(");
+            self.expr(expr);
+            self.write(")");
+        } else {
+            self.expr(expr);
+        }
+    }
+
+    fn args(&mut self, args: &[&Expr]) {
+        self.write("(");
+        for (i, arg) in args.iter().enumerate() {
+            if i > 0 {
+                self.write(", ");
+            }
+            self.expr(arg);
+        }
+        self.write(")");
+    }
+
+    /// `name(args)`
+    fn call(&mut self, name: &str, args: &[&Expr]) {
+        self.write(name);
+        self.args(args);
+    }
+
+    /// `obj.name`
+    fn property(&mut self, obj: &Expr, name: &str) {
+        self.operand(obj, prec::POSTFIX);
+        self.write(".");
+        self.write(name);
+    }
+
+    /// `obj.name(args)`
+    fn method(&mut self, obj: &Expr, name: &str, args: &[&Expr]) {
+        self.property(obj, name);
+        self.args(args);
+    }
+
+    fn infix(&mut self, left: &Expr, op: &str, right: &Expr, op_prec: u8) {
+        // all operators are left-associative
+        self.operand(left, op_prec);
+        self.write(" ");
+        self.write(op);
+        self.write(" ");
+        self.operand(right, op_prec + 1);
+    }
+
+    fn block_body(&mut self, items: &[Expr], result: &Expr) {
+        self.indent += 1;
+        for item in items {
+            self.newline();
+            self.expr(item);
+        }
+        self.newline();
+        self.expr(result);
+        self.indent -= 1;
+        self.newline();
+    }
+
+    fn func(&mut self, args: &[FuncArg], body: &Expr) {
+        let args_str = args
+            .iter()
+            .map(|a| format!("{}: {}", val_name(&a.idx), print_type(&a.tpe)))
+            .collect::<Vec<String>>()
+            .join(", ");
+        self.write(&format!("{{ ({}) =>", args_str));
+        match body {
+            Expr::BlockValue(BlockValue { items, result }) => {
+                self.block_body(items, result);
+            }
+            _ => {
+                self.write(" ");
+                self.expr(body);
+                self.write(" ");
+            }
+        }
+        self.write("}");
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Append(op) => self.method(&op.input, "append", &[&op.col_2]),
+            Expr::Const(c) => self.value(&c.v, &c.tpe),
+            Expr::ConstPlaceholder(cp) => {
+                self.write(&format!("placeholder[{}]({})", print_type(&cp.tpe), cp.id))
+            }
+            Expr::SubstConstants(op) => self.call(
+                "substConstants",
+                &[&op.script_bytes, &op.positions, &op.new_values],
+            ),
+            Expr::ByteArrayToLong(op) => self.call("byteArrayToLong", &[&op.input]),
+            Expr::ByteArrayToBigInt(op) => self.call("byteArrayToBigInt", &[&op.input]),
+            Expr::LongToByteArray(op) => self.call("longToByteArray", &[&op.input]),
+            Expr::Collection(coll) => match coll {
+                Collection::BoolConstants(bools) => {
+                    let items: Vec<Expr> = bools.iter().map(|b| (*b).into()).collect();
+                    self.coll_exprs(&SType::SBoolean, &items)
+                }
+                Collection::Exprs { elem_tpe, items } => self.coll_exprs(elem_tpe, items),
+            },
+            Expr::Tuple(op) => {
+                let items: Vec<&Expr> = op.items.iter().collect();
+                self.args(&items)
+            }
+            Expr::CalcBlake2b256(op) => self.call("blake2b256", &[&op.input]),
+            Expr::CalcSha256(op) => self.call("sha256", &[&op.input]),
+            Expr::Context => self.write("CONTEXT"),
+            Expr::Global => self.write("Global"),
+            Expr::GlobalVars(gv) => self.write(match gv {
+                GlobalVars::Inputs => "INPUTS",
+                GlobalVars::Outputs => "OUTPUTS",
+                GlobalVars::Height => "HEIGHT",
+                GlobalVars::SelfBox => "SELF",
+                GlobalVars::MinerPubKey => "CONTEXT.minerPubKey",
+            }),
+            Expr::FuncValue(op) => self.func(op.args(), op.body()),
+            Expr::Apply(op) => {
+                self.operand(&op.func, prec::POSTFIX);
+                let args: Vec<&Expr> = op.args.iter().collect();
+                self.args(&args)
+            }
+            Expr::MethodCall(op) => {
+                let args: Vec<&Expr> = op.args.iter().collect();
+                self.method(&op.obj, op.method.name(), &args)
+            }
+            Expr::ProperyCall(op) => self.property(&op.obj, op.method.name()),
+            Expr::BlockValue(op) => {
+                self.write("{");
+                self.block_body(&op.items, &op.result);
+                self.write("}");
+            }
+            Expr::ValDef(ValDef { id, rhs }) => {
+                self.write(&format!("val {} = ", val_name(id)));
+                self.expr(rhs)
+            }
+            Expr::ValUse(op) => self.write(&val_name(&op.val_id)),
+            Expr::If(op) => {
+                self.write("if (");
+                self.expr(&op.condition);
+                self.write(") ");
+                self.operand(&op.true_branch, prec::OR);
+                self.write(" else ");
+                self.expr(&op.false_branch)
+            }
+            Expr::BinOp(op) => match op.kind {
+                BinOpKind::Arith(ArithOp::Max) | BinOpKind::Arith(ArithOp::Min) => {
+                    self.call(bin_op_symbol(&op.kind), &[&op.left, &op.right])
+                }
+                _ => self.infix(
+                    &op.left,
+                    bin_op_symbol(&op.kind),
+                    &op.right,
+                    precedence(expr),
+                ),
+            },
+            Expr::And(op) => self.call("allOf", &[&op.input]),
+            Expr::Or(op) => self.call("anyOf", &[&op.input]),
+            Expr::Xor(op) => self.call("xor", &[&op.left, &op.right]),
+            Expr::Atleast(op) => self.call("atLeast", &[&op.n_required, &op.expressions]),
+            Expr::LogicalNot(op) => {
+                self.write("!");
+                self.operand(&op.input, prec::PREFIX)
+            }
+            Expr::Negation(op) => {
+                self.write("-");
+                self.operand(&op.input, prec::POSTFIX)
+            }
+            Expr::OptionGet(op) => self.property(&op.input, "get"),
+            Expr::OptionIsDefined(op) => self.property(&op.input, "isDefined"),
+            Expr::OptionGetOrElse(op) => self.method(&op.input, "getOrElse", &[&op.default]),
+            Expr::ExtractAmount(op) => self.property(&op.input, "value"),
+            Expr::ExtractRegisterAs(op) => self.property(
+                &op.input,
+                &format!("R{}[{}]", op.register_id, print_type(&op.elem_tpe)),
+            ),
+            Expr::ExtractScriptBytes(op) => self.property(&op.input, "propositionBytes"),
+            Expr::ExtractCreationInfo(op) => self.property(&op.input, "creationInfo"),
+            Expr::ExtractId(op) => self.property(&op.input, "id"),
+            Expr::ByIndex(op) => match &op.default {
+                Some(default) => self.method(&op.input, "getOrElse", &[&op.index, default]),
+                None => {
+                    self.operand(&op.input, prec::POSTFIX);
+                    self.args(&[&op.index])
+                }
+            },
+            Expr::SizeOf(op) => self.property(&op.input, "size"),
+            Expr::Slice(op) => self.method(&op.input, "slice", &[&op.from, &op.until]),
+            Expr::Fold(op) => self.method(&op.input, "fold", &[&op.zero, &op.fold_op]),
+            Expr::Map(op) => self.method(&op.input, "map", &[&op.mapper]),
+            Expr::Filter(op) => self.method(&op.input, "filter", &[&op.condition]),
+            Expr::Exists(op) => self.method(&op.input, "exists", &[&op.condition]),
+            Expr::ForAll(op) => self.method(&op.input, "forall", &[&op.condition]),
+            Expr::SelectField(op) => self.property(
+                &op.input,
+                &format!("_{}", op.field_index.zero_based_index() + 1),
+            ),
+            Expr::BoolToSigmaProp(op) => self.call("sigmaProp", &[&op.input]),
+            Expr::Upcast(op) => {
+                let name = format!("to{}", print_type(&op.tpe));
+                self.property(&op.input, &name)
+            }
+            Expr::CreateProveDlog(op) => self.call("proveDlog", &[&op.input]),
+            Expr::CreateProveDhTuple(op) => {
+                self.call("proveDHTuple", &[&op.gv, &op.hv, &op.uv, &op.vv])
+            }
+            Expr::SigmaPropBytes(op) => self.property(&op.input, "propBytes"),
+            Expr::DecodePoint(op) => self.call("decodePoint", &[&op.input]),
+            Expr::SigmaAnd(op) => self.infix_chain(op.items.as_vec(), "&&", prec::AND),
+            Expr::SigmaOr(op) => self.infix_chain(op.items.as_vec(), "||", prec::OR),
+            Expr::GetVar(op) => self.write(&format!(
+                "getVar[{}]({})",
+                print_type(&op.var_tpe),
+                op.var_id
+            )),
+            Expr::DeserializeRegister(op) => match &op.default {
+                Some(default) => {
+                    self.write(&format!(
+                        "executeFromSelfRegWithDefault[{}]({}, ",
+                        print_type(&op.tpe),
+                        op.reg
+                    ));
+                    self.expr(default);
+                    self.write(")")
+                }
+                None => self.write(&format!(
+                    "executeFromSelfReg[{}]({})",
+                    print_type(&op.tpe),
+                    op.reg
+                )),
+            },
+            Expr::DeserializeContext(op) => self.write(&format!(
+                "executeFromVar[{}]({})",
+                print_type(&op.tpe),
+                op.id
+            )),
+            Expr::MultiplyGroup(op) => self.method(&op.left, "multiply", &[&op.right]),
+        }
+    }
+
+    fn infix_chain(&mut self, items: &[Expr], op: &str, op_prec: u8) {
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                self.write(&format!(" {} ", op));
+            }
+            self.operand(item, op_prec + 1);
+        }
+    }
+
+    fn coll_exprs(&mut self, elem_tpe: &SType, items: &[Expr]) {
+        if items.is_empty() {
+            self.write(&format!("Coll[{}]()", print_type(elem_tpe)));
+        } else {
+            let items: Vec<&Expr> = items.iter().collect();
+            self.call("Coll", &items);
+        }
+    }
+
+    fn ec_point(&mut self, p: &EcPoint) {
+        self.write(&format!(
+            "decodePoint(fromBase16(\"{}\"))",
+            base16::encode_lower(&p.sigma_serialize_bytes())
+        ));
+    }
+
+    fn sigma_boolean(&mut self, sb: &SigmaBoolean) {
+        match sb {
+            SigmaBoolean::TrivialProp(b) => self.write(&format!("sigmaProp({})", b)),
+            SigmaBoolean::ProofOfKnowledge(SigmaProofOfKnowledgeTree::ProveDlog(pd)) => {
+                self.write("proveDlog(");
+                self.ec_point(&pd.h);
+                self.write(")");
+            }
+            SigmaBoolean::ProofOfKnowledge(SigmaProofOfKnowledgeTree::ProveDhTuple(dht)) => {
+                self.write("proveDHTuple(");
+                for (i, p) in [&dht.gv, &dht.hv, &dht.uv, &dht.vv].iter().enumerate() {
+                    if i > 0 {
+                        self.write(", ");
+                    }
+                    self.ec_point(p);
+                }
+                self.write(")");
+            }
+            SigmaBoolean::SigmaConjecture(conj) => {
+                let (items, sep) = match conj {
+                    SigmaConjecture::Cand(c) => (c.items.as_vec(), " && "),
+                    SigmaConjecture::Cor(c) => (c.items.as_vec(), " || "),
+                    SigmaConjecture::Cthreshold(c) => {
+                        self.write(&format!("atLeast({}, Coll(", c.n));
+                        self.sigma_boolean_items(c.items.as_vec(), ", ");
+                        self.write("))");
+                        return;
+                    }
+                };
+                self.sigma_boolean_items(items, sep);
+            }
+        }
+    }
+
+    fn sigma_boolean_items(&mut self, items: &[SigmaBoolean], sep: &str) {
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                self.write(sep);
+            }
+            let nested_conj = matches!(
+                item,
+                SigmaBoolean::SigmaConjecture(SigmaConjecture::Cand(_))
+                    | SigmaBoolean::SigmaConjecture(SigmaConjecture::Cor(_))
+            );
+            if nested_conj {
+                self.write("(");
+                self.sigma_boolean(item);
+                self.write(")");
+            } else {
+                self.sigma_boolean(item);
+            }
+        }
+    }
+
+    fn value(&mut self, v: &Value, tpe: &SType) {
+        match v {
+            Value::Boolean(b) => self.write(&b.to_string()),
+            Value::Byte(b) if *b < 0 => self.write(&format!("({}).toByte", b)),
+            Value::Byte(b) => self.write(&format!("{}.toByte", b)),
+            Value::Short(s) if *s < 0 => self.write(&format!("({}).toShort", s)),
+            Value::Short(s) => self.write(&format!("{}.toShort", s)),
+            Value::Int(i) => self.write(&i.to_string()),
+            Value::Long(l) => self.write(&format!("{}L", l)),
+            Value::BigInt(bi) => self.write(&format!("bigInt(\"{}\")", bi)),
+            Value::GroupElement(p) => self.ec_point(p),
+            Value::SigmaProp(sp) => self.sigma_boolean(sp.value()),
+            Value::CBox(id) => self.write(&format!(
+                "box(\"{}\")",
+                base16::encode_lower(&id.to_bytes().as_vec_u8())
+            )),
+            Value::AvlTree => self.write("avlTree"),
+            Value::Coll(CollKind::NativeColl(NativeColl::CollByte(bytes))) => self.write(&format!(
+                "fromBase16(\"{}\")",
+                base16::encode_lower(&bytes.to_vec().as_vec_u8())
+            )),
+            Value::Coll(coll) => {
+                let elem_tpe = coll.elem_tpe();
+                if coll.is_empty() {
+                    self.write(&format!("Coll[{}]()", print_type(elem_tpe)));
+                } else {
+                    let items = coll.as_vec();
+                    self.write("Coll(");
+                    self.values(items.iter().map(|it| (it, elem_tpe)).collect());
+                    self.write(")");
+                }
+            }
+            Value::Tup(items) => {
+                let types: Vec<SType> = match tpe {
+                    SType::STuple(t) => t.items.as_vec().clone(),
+                    _ => items.iter().map(|_| SType::SAny).collect(),
+                };
+                self.write("(");
+                self.values(items.iter().zip(types.iter()).collect());
+                self.write(")");
+            }
+            Value::Context => self.write("CONTEXT"),
+            Value::Global => self.write("Global"),
+            Value::Opt(opt) => match opt.as_ref() {
+                Some(v) => {
+                    let elem_tpe = match tpe {
+                        SType::SOption(t) => *t.clone(),
+                        _ => SType::SAny,
+                    };
+                    self.write("Some(");
+                    self.value(v, &elem_tpe);
+                    self.write(")");
+                }
+                None => self.write("None"),
+            },
+            Value::Lambda(l) => self.func(&l.args, &l.body),
+        }
+    }
+
+    fn values(&mut self, items: Vec<(&Value, &SType)>) {
+        for (i, (v, t)) in items.into_iter().enumerate() {
+            if i > 0 {
+                self.write(", ");
+            }
+            self.value(v, t);
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::ergo_tree::ErgoTree;
+    use crate::ergo_tree::ErgoTreeHeader;
+    use crate::mir::bool_to_sigma::BoolToSigmaProp;
+    use crate::mir::coll_exists::Exists;
+    use crate::mir::extract_amount::ExtractAmount;
+    use crate::mir::func_value::FuncValue;
+    use crate::mir::if_op::If;
+    use crate::mir::logical_not::LogicalNot;
+    use crate::mir::method_call::MethodCall;
+    use crate::mir::property_call::PropertyCall;
+    use crate::mir::val_use::ValUse;
+    use crate::types::scoll;
+    use crate::types::scontext;
+    use crate::types::stype_param::STypeVar;
+    use expect_test::expect;
+
+    fn bin_op(kind: BinOpKind, left: Expr, right: Expr) -> Expr {
+        BinOp {
+            kind,
+            left: left.into(),
+            right: right.into(),
+        }
+        .into()
+    }
+
+    fn height() -> Expr {
+        GlobalVars::Height.into()
+    }
+
+    #[test]
+    fn operator_precedence() {
+        // (HEIGHT + 1) * 2
+        let e = bin_op(
+            ArithOp::Multiply.into(),
+            bin_op(ArithOp::Plus.into(), height(), 1i32.into()),
+            2i32.into(),
+        );
+        expect!["(HEIGHT + 1) * 2"].assert_eq(&print_expr(&e));
+        // HEIGHT + 1 * 2
+        let e = bin_op(
+            ArithOp::Plus.into(),
+            height(),
+            bin_op(ArithOp::Multiply.into(), 1i32.into(), 2i32.into()),
+        );
+        expect!["HEIGHT + 1 * 2"].assert_eq(&print_expr(&e));
+    }
+
+    #[test]
+    fn left_associativity() {
+        // (HEIGHT - 1) - 2 and HEIGHT - (1 - 2)
+        let left = bin_op(
+            ArithOp::Minus.into(),
+            bin_op(ArithOp::Minus.into(), height(), 1i32.into()),
+            2i32.into(),
+        );
+        expect!["HEIGHT - 1 - 2"].assert_eq(&print_expr(&left));
+        let right = bin_op(
+            ArithOp::Minus.into(),
+            height(),
+            bin_op(ArithOp::Minus.into(), 1i32.into(), 2i32.into()),
+        );
+        expect!["HEIGHT - (1 - 2)"].assert_eq(&print_expr(&right));
+    }
+
+    #[test]
+    fn logical_ops() {
+        let e: Expr = BoolToSigmaProp {
+            input: Box::new(bin_op(
+                RelationOp::And.into(),
+                LogicalNot {
+                    input: Box::new(bin_op(RelationOp::Gt.into(), height(), 100i32.into())),
+                }
+                .into(),
+                bin_op(
+                    RelationOp::Or.into(),
+                    true.into(),
+                    bin_op(RelationOp::Eq.into(), height(), 1i32.into()),
+                ),
+            )),
+        }
+        .into();
+        expect!["sigmaProp(!(HEIGHT > 100) && (true || HEIGHT == 1))"].assert_eq(&print_expr(&e));
+    }
+
+    #[test]
+    fn block_with_lambda() {
+        let arg = FuncArg {
+            idx: 2.into(),
+            tpe: SType::SBox,
+        };
+        let lambda: Expr = FuncValue::new(
+            vec![arg],
+            bin_op(
+                RelationOp::Gt.into(),
+                ExtractAmount {
+                    input: Box::new(
+                        ValUse {
+                            val_id: 2.into(),
+                            tpe: SType::SBox,
+                        }
+                        .into(),
+                    ),
+                }
+                .into(),
+                ValUse {
+                    val_id: 1.into(),
+                    tpe: SType::SLong,
+                }
+                .into(),
+            ),
+        )
+        .into();
+        let e: Expr = BlockValue {
+            items: vec![ValDef {
+                id: 1.into(),
+                rhs: Box::new(1000i64.into()),
+            }
+            .into()],
+            result: Box::new(
+                Exists::new(GlobalVars::Outputs.into(), lambda)
+                    .unwrap()
+                    .into(),
+            ),
+        }
+        .into();
+        expect![[r#"
+            {
+              val v1 = 1000L
+              OUTPUTS.exists({ (v2: Box) => v2.value > v1 })
+            }"#]]
+        .assert_eq(&print_expr(&e));
+    }
+
+    #[test]
+    fn lambda_with_block_body() {
+        let body: Expr = BlockValue {
+            items: vec![ValDef {
+                id: 2.into(),
+                rhs: Box::new(bin_op(
+                    ArithOp::Plus.into(),
+                    ValUse {
+                        val_id: 1.into(),
+                        tpe: SType::SInt,
+                    }
+                    .into(),
+                    1i32.into(),
+                )),
+            }
+            .into()],
+            result: Box::new(bin_op(
+                ArithOp::Multiply.into(),
+                ValUse {
+                    val_id: 2.into(),
+                    tpe: SType::SInt,
+                }
+                .into(),
+                (-2i32).into(),
+            )),
+        }
+        .into();
+        let e: Expr = FuncValue::new(
+            vec![FuncArg {
+                idx: 1.into(),
+                tpe: SType::SInt,
+            }],
+            body,
+        )
+        .into();
+        expect![[r#"
+            { (v1: Int) =>
+              val v2 = v1 + 1
+              v2 * -2
+            }"#]]
+        .assert_eq(&print_expr(&e));
+    }
+
+    #[test]
+    fn method_and_property_calls() {
+        let index_of: Expr = MethodCall::new(
+            vec![1i64, 2i64].into(),
+            scoll::INDEX_OF_METHOD
+                .clone()
+                .with_concrete_types(&[(STypeVar::t(), SType::SLong)].iter().cloned().collect()),
+            vec![2i64.into(), 0i32.into()],
+        )
+        .unwrap()
+        .into();
+        expect!["Coll(1L, 2L).indexOf(2L, 0)"].assert_eq(&print_expr(&index_of));
+        let data_inputs: Expr =
+            PropertyCall::new(Expr::Context, scontext::DATA_INPUTS_PROPERTY.clone())
+                .unwrap()
+                .into();
+        expect!["CONTEXT.dataInputs"].assert_eq(&print_expr(&data_inputs));
+    }
+
+    #[test]
+    fn if_as_operand() {
+        let e = bin_op(
+            ArithOp::Plus.into(),
+            If {
+                condition: Box::new(bin_op(RelationOp::Lt.into(), height(), 10i32.into())),
+                true_branch: Box::new(1i32.into()),
+                false_branch: Box::new(2i32.into()),
+            }
+            .into(),
+            height(),
+        );
+        expect!["(if (HEIGHT < 10) 1 else 2) + HEIGHT"].assert_eq(&print_expr(&e));
+    }
+
+    #[test]
+    fn constants() {
+        expect!["fromBase16(\"01ff\")"]
+            .assert_eq(&print_expr(&Expr::Const(vec![1u8, 255u8].into())));
+        expect!["(-1).toByte"].assert_eq(&print_expr(&(-1i8).into()));
+        expect!["Coll[Int]()"].assert_eq(&print_expr(&Vec::<i32>::new().into()));
+        expect!["(1L, true)"].assert_eq(&print_expr(&Expr::Const((1i64, true).into())));
+        expect!["Some(1)"].assert_eq(&print_expr(&Expr::Const(Some(1i32).into())));
+    }
+
+    #[test]
+    fn ergo_tree_with_placeholders() {
+        let e = bin_op(RelationOp::Gt.into(), height(), 100i32.into());
+        let tree = ErgoTree::new(ErgoTreeHeader::v0(true), &e);
+        expect!["HEIGHT > placeholder[Int](0)"].assert_eq(&tree.pretty_print_template().unwrap());
+        expect!["HEIGHT > 100"].assert_eq(&tree.pretty_print().unwrap());
+    }
+}
@@ -0,0 +1,630 @@
+//! Recognition of the standard scripts (P2PK, P2SH, miner fee, emission, etc.)
+//!
+//! Scripts are recognized by their template, i.e. [`ErgoTree::template_bytes`] of the tree with
+//! all constants segregated (regardless of the header of the given tree), so the same script is
+//! recognized in any header variant (version, size and constant segregation flags). The
+//! parameters of the script are extracted from the constants.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::convert::TryInto;
+
+use lazy_static::lazy_static;
+
+use crate::address::Address;
+use crate::ergo_tree::ErgoTree;
+use crate::ergo_tree::ErgoTreeHeader;
+use crate::ergo_tree::ErgoTreeParsingError;
+use crate::mir::bin_op::ArithOp;
+use crate::mir::bin_op::BinOp;
+use crate::mir::bin_op::RelationOp;
+use crate::mir::bool_to_sigma::BoolToSigmaProp;
+use crate::mir::coll_by_index::ByIndex;
+use crate::mir::coll_size::SizeOf;
+use crate::mir::collection::Collection;
+use crate::mir::constant::Constant;
+use crate::mir::constant::TryExtractFrom;
+use crate::mir::constant::TryExtractInto;
+use crate::mir::create_provedlog::CreateProveDlog;
+use crate::mir::decode_point::DecodePoint;
+use crate::mir::expr::Expr;
+use crate::mir::expr::InvalidArgumentError;
+use crate::mir::extract_amount::ExtractAmount;
+use crate::mir::extract_creation_info::ExtractCreationInfo;
+use crate::mir::extract_reg_as::ExtractRegisterAs;
+use crate::mir::extract_script_bytes::ExtractScriptBytes;
+use crate::mir::global_vars::GlobalVars;
+use crate::mir::select_field::SelectField;
+use crate::mir::subst_const::SubstConstants;
+use crate::mir::unary_op::UnaryOpTryBuild;
+use crate::mir::value::Value;
+use crate::serialization::SigmaSerializable;
+use crate::sigma_protocol::dlog_group;
+use crate::sigma_protocol::sigma_boolean::ProveDlog;
+use crate::types::stuple::STuple;
+use crate::types::stype::SType;
+
+/// Standard script with the parameters extracted from it
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum ScriptKind {
+    /// Pay-to-PublicKey
+    P2Pk(ProveDlog),
+    /// Pay-to-Script-Hash (see [`Address::P2SH`]) with the hash of the script
+    P2SH([u8; Address::P2SH_HASH_LENGTH]),
+    /// Miner's reward, spendable by the miner after the delay
+    /// (`ErgoScriptPredef.rewardOutputScript` in sigmastate)
+    MinerReward {
+        /// Delay (in blocks) after the box creation
+        delay: i32,
+        /// Miner's public key
+        pk: ProveDlog,
+    },
+    /// Transaction fee, spendable by the miner of the block (with the reward delay)
+    MinerFee {
+        /// Delay (in blocks) of the miner's reward
+        reward_delay: i32,
+    },
+    /// Emission contract
+    Emission(EmissionParams),
+    /// Pay-to-reemission contract (EIP-27), paying to the re-emission contract
+    PayToReemission {
+        /// Id of the re-emission contract NFT
+        reemission_nft_id: Vec<u8>,
+    },
+    /// Re-emission contract (EIP-27)
+    Reemission {
+        /// Id of the re-emission contract NFT
+        reemission_nft_id: Vec<u8>,
+        /// Height the re-emission starts at
+        reemission_start_height: i32,
+        /// Delay (in blocks) of the miner's reward
+        reward_delay: i32,
+    },
+}
+
+impl ScriptKind {
+    /// Recognize the standard script (with the templates of
+    /// [`ScriptTemplateRegistry::standard`]), returns `None` for non-standard scripts
+    pub fn classify(tree: &ErgoTree) -> Option<ScriptKind> {
+        ScriptTemplateRegistry::standard().classify(tree)
+    }
+}
+
+/// Emission contract parameters
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct EmissionParams {
+    /// Delay (in blocks) of the miner's reward
+    pub miner_reward_delay: i32,
+    /// Height until the miner's reward is fixed at `miner_fixed_rate`
+    pub miner_fixed_rate_period: i32,
+    /// Miner's reward (in nanoERGs) per block during the `miner_fixed_rate_period`
+    pub miner_fixed_rate: i64,
+    /// Length (in blocks) of the fixed rate emission period
+    pub fixed_rate_period: i32,
+    /// Emission (in nanoERGs) per block during the fixed rate period
+    pub fixed_rate: i64,
+    /// Length (in blocks) of the emission epoch (the emission is reduced every epoch after the
+    /// fixed rate period)
+    pub epoch_length: i32,
+    /// Emission reduction (in nanoERGs) per epoch
+    pub one_epoch_reduction: i64,
+}
+
+/// Mainnet emission contract
+const EMISSION_TREE: &str = "101004020e36100204a00b08cd0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798ea02d192a39a8cc7a7017300730110010204020404040004c0fd4f05808c82f5f6030580b8c9e5ae040580f882ad16040204c0944004c0f407040004000580f882ad16d19683030191a38cc7a7019683020193c2b2a57300007473017302830108cdeeac93a38cc7b2a573030001978302019683040193b1a5730493c2a7c2b2a573050093958fa3730673079973089c73097e9a730a9d99a3730b730c0599c1a7c1b2a5730d00938cc7b2a5730e0001a390c1a7730f";
+
+/// Miner fee contract (mainnet and testnet)
+const MINER_FEE_TREE: &str = "1005040004000e36100204a00b08cd0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798ea02d192a39a8cc7a701730073011001020402d19683030193a38cc7b2a57300000193c2b2a57301007473027303830108cdeeac93b1a57304";
+
+/// Miner's reward script with the generic public key (group generator) as it is embedded in
+/// the emission and miner fee contracts
+const MINER_REWARD_TREE: &str = "100204a00b08cd0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798ea02d192a39a8cc7a70173007301";
+
+/// Registry of the script templates
+#[derive(Default)]
+pub struct ScriptTemplateRegistry {
+    templates: HashMap<Vec<u8>, ScriptTemplate>,
+}
+
+/// Script template with the constants of the reference script
+struct ScriptTemplate {
+    constants: Vec<Constant>,
+    /// Indices of the constants that are the parameters of the script (the other constants must
+    /// be equal to the reference script constants)
+    params: Vec<usize>,
+    /// Builds the script kind from the constants of the recognized script
+    extract: fn(&[Constant]) -> Option<ScriptKind>,
+}
+
+lazy_static! {
+    static ref STANDARD_TEMPLATES: ScriptTemplateRegistry = ScriptTemplateRegistry::new_standard();
+}
+
+impl ScriptTemplateRegistry {
+    /// Registry of the standard scripts templates: P2PK, P2SH, miner's reward, miner fee, emission,
+    /// pay-to-reemission and re-emission
+    pub fn standard() -> &'static ScriptTemplateRegistry {
+        &STANDARD_TEMPLATES
+    }
+
+    /// Empty registry
+    pub fn new() -> ScriptTemplateRegistry {
+        ScriptTemplateRegistry {
+            templates: HashMap::new(),
+        }
+    }
+
+    // built from the known scripts, cannot fail
+    #[allow(clippy::unwrap_used)]
+    fn new_standard() -> ScriptTemplateRegistry {
+        let generic_pk = ProveDlog::new(dlog_group::generator());
+        let mut registry = ScriptTemplateRegistry::new();
+        registry
+            .register(&ErgoTree::from(Expr::Const(generic_pk.into())), &[0], |c| {
+                ProveDlog::try_from(c[0].v.clone())
+                    .ok()
+                    .map(ScriptKind::P2Pk)
+            })
+            .unwrap();
+        registry
+            .register(
+                &Address::P2SH([0; Address::P2SH_HASH_LENGTH])
+                    .script()
+                    .unwrap(),
+                &[2],
+                |c| {
+                    let hash: Vec<u8> = extract(&c[2])?;
+                    hash.as_slice().try_into().ok().map(ScriptKind::P2SH)
+                },
+            )
+            .unwrap();
+        registry
+            .register(&parse_tree(MINER_REWARD_TREE), &[0, 1], |c| {
+                Some(ScriptKind::MinerReward {
+                    delay: extract(&c[0])?,
+                    pk: ProveDlog::try_from(c[1].v.clone()).ok()?,
+                })
+            })
+            .unwrap();
+        registry
+            .register(&parse_tree(MINER_FEE_TREE), &[2], |c| {
+                Some(ScriptKind::MinerFee {
+                    reward_delay: generic_reward_delay(&c[2])?,
+                })
+            })
+            .unwrap();
+        registry
+            .register(
+                &parse_tree(EMISSION_TREE),
+                &[1, 6, 7, 8, 9, 11, 12, 15],
+                |c| {
+                    Some(ScriptKind::Emission(EmissionParams {
+                        miner_reward_delay: generic_reward_delay(&c[1])?,
+                        miner_fixed_rate_period: extract(&c[6])?,
+                        miner_fixed_rate: extract(&c[7])?,
+                        fixed_rate: extract(&c[8])?,
+                        one_epoch_reduction: extract(&c[9])?,
+                        fixed_rate_period: extract(&c[11])?,
+                        epoch_length: extract(&c[12])?,
+                    }))
+                },
+            )
+            .unwrap();
+        registry
+            .register(
+                &ErgoTree::new(
+                    ErgoTreeHeader::v1(true),
+                    &pay_to_reemission(vec![0; 32]).unwrap(),
+                ),
+                &[2],
+                |c| {
+                    Some(ScriptKind::PayToReemission {
+                        reemission_nft_id: extract(&c[2])?,
+                    })
+                },
+            )
+            .unwrap();
+        registry
+            .register(
+                &ErgoTree::new(
+                    ErgoTreeHeader::v1(true),
+                    &reemission(vec![0; 32], 0, &parse_tree(MINER_REWARD_TREE)).unwrap(),
+                ),
+                &[2, 10, 13],
+                |c| {
+                    Some(ScriptKind::Reemission {
+                        reemission_nft_id: extract(&c[2])?,
+                        reward_delay: generic_reward_delay(&c[10])?,
+                        reemission_start_height: extract(&c[13])?,
+                    })
+                },
+            )
+            .unwrap();
+        registry
+    }
+
+    /// Recognize the script, returns `None` if the script does not match any of the templates
+    pub fn classify(&self, tree: &ErgoTree) -> Option<ScriptKind> {
+        let (template_bytes, constants) = segregated_template(tree).ok()?;
+        let template = self.templates.get(&template_bytes)?;
+        if template.constants.len() != constants.len() {
+            return None;
+        }
+        let constants_match = template
+            .constants
+            .iter()
+            .zip(constants.iter())
+            .enumerate()
+            .all(|(idx, (expected, actual))| {
+                if template.params.contains(&idx) {
+                    expected.tpe == actual.tpe
+                } else {
+                    expected == actual
+                }
+            });
+        if constants_match {
+            (template.extract)(&constants)
+        } else {
+            None
+        }
+    }
+
+    /// Register the template of the reference script (e.g. the mainnet tree of the contract).
+    /// A script is recognized if it has the same template (the tree with all the constants
+    /// segregated) and the same constants as the reference one, except the constants with the
+    /// `params` indices, which only need to be of the same type. The constants of the recognized
+    /// script are passed to `extract`. Replaces the previously registered script with the same
+    /// template
+    pub fn register(
+        &mut self,
+        reference: &ErgoTree,
+        params: &[usize],
+        extract: fn(&[Constant]) -> Option<ScriptKind>,
+    ) -> Result<(), ErgoTreeParsingError> {
+        let (template_bytes, constants) = segregated_template(reference)?;
+        self.templates.insert(
+            template_bytes,
+            ScriptTemplate {
+                constants,
+                params: params.to_vec(),
+                extract,
+            },
+        );
+        Ok(())
+    }
+}
+
+/// Template bytes and constants of the tree with all the constants segregated
+fn segregated_template(tree: &ErgoTree) -> Result<(Vec<u8>, Vec<Constant>), ErgoTreeParsingError> {
+    let segregated = ErgoTree::new(ErgoTreeHeader::v0(true), &*tree.proposition()?);
+    let template_bytes = segregated
+        .template_bytes()
+        .map_err(ErgoTreeParsingError::TreeParsingError)?;
+    let constants_len = segregated
+        .constants_len()
+        .map_err(ErgoTreeParsingError::TreeParsingError)?;
+    let constants = (0..constants_len)
+        .map(|idx| segregated.get_constant(idx))
+        .collect::<Result<Vec<Option<Constant>>, _>>()
+        .map_err(ErgoTreeParsingError::TreeParsingError)?;
+    Ok((template_bytes, constants.into_iter().flatten().collect()))
+}
+
+#[allow(clippy::unwrap_used)]
+fn parse_tree(hex: &str) -> ErgoTree {
+    ErgoTree::sigma_parse_bytes(&base16::decode(hex).unwrap()).unwrap()
+}
+
+fn extract<T: TryExtractFrom<Value>>(c: &Constant) -> Option<T> {
+    c.v.clone().try_extract_into::<T>().ok()
+}
+
+/// Reward delay of the miner's reward script with the generic public key (the script embedded in
+/// the emission and miner fee contracts)
+fn generic_reward_delay(script_bytes: &Constant) -> Option<i32> {
+    let bytes: Vec<u8> = extract(script_bytes)?;
+    let tree = ErgoTree::sigma_parse_bytes(&bytes).ok()?;
+    match ScriptKind::classify(&tree)? {
+        ScriptKind::MinerReward { delay, pk } if *pk.h == dlog_group::generator() => Some(delay),
+        _ => None,
+    }
+}
+
+fn outputs(index: i32) -> Result<Expr, InvalidArgumentError> {
+    Ok(ByIndex::new(GlobalVars::Outputs.into(), index.into(), None)?.into())
+}
+
+fn creation_height(b: Expr) -> Result<Expr, InvalidArgumentError> {
+    Ok(SelectField::new(
+        ExtractCreationInfo::try_build(b)?.into(),
+        #[allow(clippy::unwrap_used)]
+        1.try_into().unwrap(),
+    )?
+    .into())
+}
+
+fn rel(kind: RelationOp, left: Expr, right: Expr) -> Expr {
+    BinOp {
+        kind: kind.into(),
+        left: left.into(),
+        right: right.into(),
+    }
+    .into()
+}
+
+fn all_of(items: Vec<Expr>) -> Result<Expr, InvalidArgumentError> {
+    Ok(crate::mir::and::And {
+        input: Box::new(Collection::new(SType::SBoolean, items)?.into()),
+    }
+    .into())
+}
+
+fn any_of(items: Vec<Expr>) -> Result<Expr, InvalidArgumentError> {
+    Ok(crate::mir::or::Or {
+        input: Box::new(Collection::new(SType::SBoolean, items)?.into()),
+    }
+    .into())
+}
+
+/// Id of the first token of the first output
+fn first_output_token_id() -> Result<Expr, InvalidArgumentError> {
+    let tokens_tpe = SType::SColl(
+        SType::STuple(STuple::pair(
+            SType::SColl(SType::SByte.into()),
+            SType::SLong,
+        ))
+        .into(),
+    );
+    let tokens: Expr = crate::mir::option_get::OptionGet::try_build(
+        ExtractRegisterAs::new(outputs(0)?, 2, SType::SOption(tokens_tpe.into()))?.into(),
+    )?
+    .into();
+    Ok(SelectField::new(
+        ByIndex::new(tokens, 0i32.into(), None)?.into(),
+        #[allow(clippy::unwrap_used)]
+        1.try_into().unwrap(),
+    )?
+    .into())
+}
+
+/// Pay-to-reemission contract (`ReemissionContracts.payToReemission` in the node)
+fn pay_to_reemission(reemission_nft_id: Vec<u8>) -> Result<Expr, InvalidArgumentError> {
+    Ok(BoolToSigmaProp {
+        input: Box::new(rel(
+            RelationOp::Eq,
+            first_output_token_id()?,
+            Constant::from(reemission_nft_id).into(),
+        )),
+    }
+    .into())
+}
+
+/// Re-emission contract (`ReemissionContracts.reemissionBoxProp` in the node)
+fn reemission(
+    reemission_nft_id: Vec<u8>,
+    reemission_start_height: i32,
+    generic_miner_reward: &ErgoTree,
+) -> Result<Expr, InvalidArgumentError> {
+    let self_box: Expr = GlobalVars::SelfBox.into();
+    let height: Expr = GlobalVars::Height.into();
+    let amount =
+        |b: Expr| -> Result<Expr, InvalidArgumentError> { Ok(ExtractAmount::try_build(b)?.into()) };
+    let script_bytes = |b: Expr| -> Result<Expr, InvalidArgumentError> {
+        Ok(ExtractScriptBytes::try_build(b)?.into())
+    };
+    let correct_nft_id = rel(
+        RelationOp::Eq,
+        first_output_token_id()?,
+        Constant::from(reemission_nft_id).into(),
+    );
+    // miner's pk is the second constant of the miner's reward script
+    let expected_miner_out_script: Expr = SubstConstants {
+        script_bytes: Box::new(Constant::from(generic_miner_reward.sigma_serialize_bytes()).into()),
+        positions: Box::new(Constant::from(vec![1i32]).into()),
+        new_values: Box::new(
+            Collection::new(
+                SType::SSigmaProp,
+                vec![CreateProveDlog::try_build(
+                    DecodePoint::try_build(GlobalVars::MinerPubKey.into())?.into(),
+                )?
+                .into()],
+            )?
+            .into(),
+        ),
+    }
+    .into();
+    let correct_miner_output = all_of(vec![
+        rel(
+            RelationOp::Eq,
+            script_bytes(outputs(1)?)?,
+            expected_miner_out_script,
+        ),
+        rel(
+            RelationOp::Eq,
+            height.clone(),
+            creation_height(outputs(1)?)?,
+        ),
+    ])?;
+    let height_correct = rel(
+        RelationOp::Eq,
+        creation_height(outputs(0)?)?,
+        height.clone(),
+    );
+    let height_increased = rel(
+        RelationOp::Gt,
+        height.clone(),
+        creation_height(self_box.clone())?,
+    );
+    let after_emission = rel(RelationOp::Ge, height, reemission_start_height.into());
+    let same_script = rel(
+        RelationOp::Eq,
+        script_bytes(self_box.clone())?,
+        script_bytes(outputs(0)?)?,
+    );
+    let correct_coins_issued = rel(
+        RelationOp::Eq,
+        3_000_000_000i64.into(),
+        BinOp {
+            kind: ArithOp::Minus.into(),
+            left: amount(self_box.clone())?.into(),
+            right: amount(outputs(0)?)?.into(),
+        }
+        .into(),
+    );
+    let sponsored = all_of(vec![
+        rel(RelationOp::Gt, amount(outputs(0)?)?, amount(self_box)?),
+        rel(RelationOp::Le, amount(outputs(1)?)?, 10_000_000i64.into()),
+        rel(
+            RelationOp::Eq,
+            SizeOf::try_build(GlobalVars::Outputs.into())?.into(),
+            2i32.into(),
+        ),
+    ])?;
+    Ok(BoolToSigmaProp {
+        input: Box::new(all_of(vec![
+            correct_nft_id,
+            same_script,
+            any_of(vec![
+                sponsored,
+                all_of(vec![
+                    height_correct,
+                    correct_miner_output,
+                    after_emission,
+                    height_increased,
+                    correct_coins_issued,
+                ])?,
+            ])?,
+        ])?),
+    }
+    .into())
+}
+
+#[cfg(test)]
+#[cfg(feature = "arbitrary")]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::address::AddressEncoder;
+    use proptest::prelude::*;
+
+    proptest! {
+
+        #[test]
+        fn p2pk(pk in any::<ProveDlog>()) {
+            let tree = ErgoTree::new(ErgoTreeHeader::v1(true), &Expr::Const(pk.clone().into()));
+            prop_assert_eq![ScriptKind::classify(&tree), Some(ScriptKind::P2Pk(pk))];
+        }
+
+        #[test]
+        fn p2sh(hash in any::<[u8; Address::P2SH_HASH_LENGTH]>()) {
+            let tree = Address::P2SH(hash).script().unwrap();
+            prop_assert_eq![ScriptKind::classify(&tree), Some(ScriptKind::P2SH(hash))];
+        }
+
+        #[test]
+        fn non_standard(tree in any::<ErgoTree>()) {
+            prop_assume!(ProveDlog::try_from(tree.clone()).is_err());
+            prop_assert_eq![ScriptKind::classify(&tree), None];
+        }
+    }
+
+    #[test]
+    fn miner_reward() {
+        assert_eq!(
+            ScriptKind::classify(&parse_tree(MINER_REWARD_TREE)),
+            Some(ScriptKind::MinerReward {
+                delay: 720,
+                pk: ProveDlog::new(dlog_group::generator())
+            })
+        );
+    }
+
+    #[test]
+    fn miner_fee() {
+        let mainnet = AddressEncoder::unchecked_parse_address_from_str("2iHkR7CWvD1R4j1yZg5bkeDRQavjAaVPeTDFGGLZduHyfWMuYpmhHocX8GJoaieTx78FntzJbCBVL6rf96ocJoZdmWBL2fci7NqWgAirppPQmZ7fN9V6z13Ay6brPriBKYqLp1bT2Fk4FkFLCfdPpe").unwrap();
+        let testnet = AddressEncoder::unchecked_parse_address_from_str("Bf1X9JgQTUtgntaer91B24n6kP8L2kqEiQqNf1z97BKo9UbnW3WRP9VXu8BXd1LsYCiYbHJEdWKxkF5YNx5n7m31wsDjbEuB3B13ZMDVBWkepGmWfGa71otpFViHDCuvbw1uNicAQnfuWfnj8fbCa4").unwrap();
+        for address in &[mainnet, testnet] {
+            assert_eq!(
+                ScriptKind::classify(&address.script().unwrap()),
+                Some(ScriptKind::MinerFee { reward_delay: 720 })
+            );
+        }
+    }
+
+    #[test]
+    fn miner_fee_changed_constant() {
+        let mut tree = parse_tree(MINER_FEE_TREE);
+        // OUTPUTS.size == 2
+        tree.set_constant(4, 2i32.into()).unwrap();
+        assert_eq!(ScriptKind::classify(&tree), None);
+    }
+
+    #[test]
+    fn emission() {
+        assert_eq!(
+            ScriptKind::classify(&parse_tree(EMISSION_TREE)),
+            Some(ScriptKind::Emission(EmissionParams {
+                miner_reward_delay: 720,
+                miner_fixed_rate_period: 655200,
+                miner_fixed_rate: 67_500_000_000,
+                fixed_rate_period: 525600,
+                fixed_rate: 75_000_000_000,
+                epoch_length: 64800,
+                one_epoch_reduction: 3_000_000_000,
+            }))
+        );
+    }
+
+    #[test]
+    fn custom_registry() {
+        let mut registry = ScriptTemplateRegistry::new();
+        let fee_tree = parse_tree(MINER_FEE_TREE);
+        assert_eq!(registry.classify(&fee_tree), None);
+        registry
+            .register(&fee_tree, &[2], |_| {
+                Some(ScriptKind::MinerFee { reward_delay: 0 })
+            })
+            .unwrap();
+        assert_eq!(
+            registry.classify(&fee_tree),
+            Some(ScriptKind::MinerFee { reward_delay: 0 })
+        );
+        assert_eq!(registry.classify(&parse_tree(EMISSION_TREE)), None);
+
+        let unparsed = ErgoTree::sigma_parse_bytes(&[0x10, 0x01, 0xff]).unwrap();
+        assert!(registry
+            .register(&unparsed, &[], |_| Some(ScriptKind::MinerFee {
+                reward_delay: 0
+            }))
+            .is_err());
+    }
+
+    #[test]
+    fn pay_to_reemission_and_reemission() {
+        let nft_id = vec![7; 32];
+        let pay_to_reemission_tree = ErgoTree::new(
+            ErgoTreeHeader::v0(false),
+            &pay_to_reemission(nft_id.clone()).unwrap(),
+        );
+        assert_eq!(
+            ScriptKind::classify(&pay_to_reemission_tree),
+            Some(ScriptKind::PayToReemission {
+                reemission_nft_id: nft_id.clone()
+            })
+        );
+        let mut reward = parse_tree(MINER_REWARD_TREE);
+        reward.set_constant(0, 1440i32.into()).unwrap();
+        let reemission_tree = ErgoTree::new(
+            ErgoTreeHeader::v1(true),
+            &reemission(nft_id.clone(), 2_080_800, &reward).unwrap(),
+        );
+        assert_eq!(
+            ScriptKind::classify(&reemission_tree),
+            Some(ScriptKind::Reemission {
+                reemission_nft_id: nft_id,
+                reemission_start_height: 2_080_800,
+                reward_delay: 1440,
+            })
+        );
+    }
+}
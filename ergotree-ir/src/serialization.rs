@@ -15,6 +15,7 @@ pub(crate) mod op_code;
 pub(crate) mod types;
 
 pub mod constant_store;
+pub mod parser_limits;
 pub mod sigma_byte_reader;
 pub mod sigma_byte_writer;
 
@@ -18,6 +18,7 @@ use crate::util::AsVecU8;
 use super::sigma_byte_writer::SigmaByteWrite;
 use std::convert::TryInto;
 use std::io;
+use std::sync::Arc;
 
 pub struct DataSerializer {}
 
@@ -42,7 +43,23 @@ impl DataSerializer {
             Value::Coll(ct) => match ct {
                 CollKind::NativeColl(NativeColl::CollByte(b)) => {
                     w.put_usize_as_u16(b.len())?;
-                    w.write_all(b.clone().as_vec_u8().as_slice())
+                    w.write_all(b.to_vec().as_vec_u8().as_slice())
+                }
+                CollKind::NativeColl(NativeColl::CollBoolean(bools)) => {
+                    w.put_usize_as_u16(bools.len())?;
+                    w.put_bits(bools.as_slice())
+                }
+                CollKind::NativeColl(NativeColl::CollShort(v)) => {
+                    w.put_usize_as_u16(v.len())?;
+                    v.iter().try_for_each(|i| w.put_i16(*i))
+                }
+                CollKind::NativeColl(NativeColl::CollInt(v)) => {
+                    w.put_usize_as_u16(v.len())?;
+                    v.iter().try_for_each(|i| w.put_i32(*i))
+                }
+                CollKind::NativeColl(NativeColl::CollLong(v)) => {
+                    w.put_usize_as_u16(v.len())?;
+                    v.iter().try_for_each(|i| w.put_i64(*i))
                 }
                 CollKind::WrappedColl {
                     elem_tpe: SType::SBoolean,
@@ -50,9 +67,8 @@ impl DataSerializer {
                 } => {
                     w.put_usize_as_u16(v.len())?;
                     let maybe_bools: Result<Vec<bool>, TryExtractFromError> = v
-                        .clone()
-                        .into_iter()
-                        .map(|i| i.try_extract_into::<bool>())
+                        .iter()
+                        .map(|i| i.clone().try_extract_into::<bool>())
                         .collect();
                     #[allow(clippy::unwrap_used)]
                     w.put_bits(maybe_bools.unwrap().as_slice())
@@ -102,6 +118,7 @@ impl DataSerializer {
             SSigmaProp => Value::sigma_prop(SigmaProp::new(SigmaBoolean::sigma_parse(r)?)),
             SColl(elem_type) if **elem_type == SByte => {
                 let len = r.get_u16()? as usize;
+                r.check_coll_len(len)?;
                 let mut buf = vec![0u8; len];
                 r.read_exact(&mut buf)?;
                 Value::Coll(CollKind::NativeColl(NativeColl::CollByte(
@@ -110,21 +127,39 @@ impl DataSerializer {
             }
             SColl(elem_type) if **elem_type == SBoolean => {
                 let len = r.get_u16()? as usize;
-                let bools = r.get_bits(len)?;
-                Value::Coll(CollKind::WrappedColl {
-                    elem_tpe: *elem_type.clone(),
-                    items: bools.into_iter().map(|b| b.into()).collect(),
-                })
+                r.check_coll_len(len)?;
+                Value::Coll(CollKind::NativeColl(NativeColl::CollBoolean(
+                    r.get_bits(len)?.into(),
+                )))
+            }
+            SColl(elem_type) if **elem_type == SShort => {
+                let len = r.get_u16()? as usize;
+                r.check_coll_len(len)?;
+                let v = (0..len).map(|_| r.get_i16()).collect::<Result<_, _>>()?;
+                Value::Coll(CollKind::NativeColl(NativeColl::CollShort(v)))
+            }
+            SColl(elem_type) if **elem_type == SInt => {
+                let len = r.get_u16()? as usize;
+                r.check_coll_len(len)?;
+                let v = (0..len).map(|_| r.get_i32()).collect::<Result<_, _>>()?;
+                Value::Coll(CollKind::NativeColl(NativeColl::CollInt(v)))
+            }
+            SColl(elem_type) if **elem_type == SLong => {
+                let len = r.get_u16()? as usize;
+                r.check_coll_len(len)?;
+                let v = (0..len).map(|_| r.get_i64()).collect::<Result<_, _>>()?;
+                Value::Coll(CollKind::NativeColl(NativeColl::CollLong(v)))
             }
             SColl(elem_type) => {
                 let len = r.get_u16()? as usize;
-                let mut elems = Vec::with_capacity(len as usize);
+                r.check_coll_len(len)?;
+                let mut elems = Vec::with_capacity(len);
                 for _ in 0..len {
                     elems.push(DataSerializer::sigma_parse(elem_type, r)?);
                 }
                 Value::Coll(CollKind::WrappedColl {
                     elem_tpe: *elem_type.clone(),
-                    items: elems,
+                    items: elems.into(),
                 })
             }
             STuple(stuple::STuple { items: types }) => {
@@ -135,7 +170,7 @@ impl DataSerializer {
                 // we get the tuple item value for each tuple item type,
                 // since items types quantity has checked bounds, we can be sure that items count
                 // is correct
-                Value::Tup(items.try_into()?)
+                Value::Tup(Arc::new(items.try_into()?))
             }
 
             c => {
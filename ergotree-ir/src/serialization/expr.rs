@@ -77,7 +77,11 @@ impl Expr {
         r: &mut R,
         tag: u8,
     ) -> Result<Self, SerializationError> {
-        let res = if tag <= OpCode::LAST_CONSTANT_CODE.value() {
+        r.parse_nested_expr(|r| Expr::parse_tagged(r, tag))
+    }
+
+    fn parse_tagged<R: SigmaByteRead>(r: &mut R, tag: u8) -> Result<Self, SerializationError> {
+        if tag <= OpCode::LAST_CONSTANT_CODE.value() {
             let t_code = TypeCode::parse(tag)?;
             let constant = Constant::parse_with_type_code(r, t_code)?;
             Ok(Expr::Const(constant))
@@ -178,8 +182,7 @@ impl Expr {
                     o.shift()
                 ))),
             }
-        };
-        res
+        }
     }
 }
 
@@ -0,0 +1,245 @@
+//! Resource limits enforced by Sigma byte reader during parsing
+
+use crate::ergo_tree::ErgoTree;
+
+/// Limits enforced during parsing to guard against malicious input (deep nesting, huge
+/// declared lengths, etc.). Defaults mirror the consensus limits of the reference
+/// implementation (sigmastate).
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct ParserLimits {
+    /// Max nesting depth of the expressions (and sigma boolean trees)
+    pub max_tree_depth: usize,
+    /// Max nesting depth of the types
+    pub max_type_depth: usize,
+    /// Max number of items in a collection (collection values, collection expressions and
+    /// lists of nodes)
+    pub max_coll_len: usize,
+    /// Max number of segregated constants in ErgoTree
+    pub max_constants: usize,
+    /// Max size of the serialized ErgoTree in bytes
+    pub max_tree_bytes: usize,
+    /// Max total number of bytes to read
+    pub max_bytes: usize,
+}
+
+impl ParserLimits {
+    /// Max depth of the expression tree (`SigmaConstants.MaxTreeDepth` in sigmastate)
+    pub const MAX_TREE_DEPTH: usize = 110;
+    /// Max size of the serialized ErgoTree (`SigmaConstants.MaxPropositionBytes` in sigmastate)
+    pub const MAX_TREE_BYTES: usize = 4096;
+    /// Max size of the input (`SigmaSerializer.MaxInputSize` in sigmastate)
+    pub const MAX_INPUT_BYTES: usize = 1024 * 1024;
+}
+
+impl Default for ParserLimits {
+    fn default() -> Self {
+        ParserLimits {
+            max_tree_depth: ParserLimits::MAX_TREE_DEPTH,
+            max_type_depth: ParserLimits::MAX_TREE_DEPTH,
+            // collection lengths are serialized as u16
+            max_coll_len: u16::MAX as usize,
+            max_constants: ErgoTree::MAX_CONSTANTS_COUNT,
+            max_tree_bytes: ParserLimits::MAX_TREE_BYTES,
+            max_bytes: ParserLimits::MAX_INPUT_BYTES,
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::ergo_tree::ErgoTreeConstantsParsingError;
+    use crate::ergo_tree::ErgoTreeParsingError;
+    use crate::has_opcode::HasStaticOpCode;
+    use crate::mir::constant::Constant;
+    use crate::mir::expr::Expr;
+    use crate::mir::logical_not::LogicalNot;
+    use crate::mir::value::CollKind;
+    use crate::mir::value::Value;
+    use crate::serialization::sigma_byte_reader::from_bytes;
+    use crate::serialization::sigma_byte_reader::SigmaByteRead;
+    use crate::serialization::types::TypeCode;
+    use crate::serialization::SerializationError;
+    use crate::serialization::SigmaSerializable;
+    use crate::types::stype::SType;
+    use sigma_ser::vlq_encode::WriteSigmaVlqExt;
+
+    fn nested_not(depth: usize) -> Expr {
+        (0..depth).fold(true.into(), |acc, _| {
+            LogicalNot {
+                input: Box::new(acc),
+            }
+            .into()
+        })
+    }
+
+    #[test]
+    fn expr_depth_limit() {
+        let limits = ParserLimits {
+            max_tree_depth: 10,
+            ..Default::default()
+        };
+        // constant at the bottom adds one more level
+        let max_depth_expr = nested_not(9);
+        assert_eq!(
+            Expr::sigma_parse_bytes_with_limits(
+                &max_depth_expr.sigma_serialize_bytes(),
+                limits.clone()
+            )
+            .unwrap(),
+            max_depth_expr
+        );
+        let too_deep_expr = nested_not(10);
+        assert_eq!(
+            Expr::sigma_parse_bytes_with_limits(&too_deep_expr.sigma_serialize_bytes(), limits),
+            Err(SerializationError::TreeDepthLimitExceeded(10))
+        );
+    }
+
+    #[test]
+    fn depth_restored_on_parse_error() {
+        let limits = ParserLimits {
+            max_tree_depth: 1,
+            max_type_depth: 1,
+            ..Default::default()
+        };
+        let mut r = from_bytes(&[]).with_limits(limits);
+        let fail = |_: &mut _| Err::<(), _>(SerializationError::Misc("fail".to_string()));
+        for _ in 0..2 {
+            assert_eq!(
+                r.parse_nested_expr(fail),
+                Err(SerializationError::Misc("fail".to_string()))
+            );
+            assert_eq!(
+                r.parse_nested_type(fail),
+                Err(SerializationError::Misc("fail".to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn hostile_nesting_does_not_overflow_stack() {
+        // nested LogicalNot without the innermost input, the parser should bail out long before
+        // reaching the end of the input
+        let bytes = vec![LogicalNot::OP_CODE.value(); 100_000];
+        // parser frames are large in debug builds, give it the room the default limit needs
+        let res = std::thread::Builder::new()
+            .stack_size(32 * 1024 * 1024)
+            .spawn(move || Expr::sigma_parse_bytes(&bytes))
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(
+            res,
+            Err(SerializationError::TreeDepthLimitExceeded(
+                ParserLimits::MAX_TREE_DEPTH
+            ))
+        );
+    }
+
+    #[test]
+    fn type_depth_limit() {
+        let tpe = (0..300).fold(SType::SInt, |acc, _| SType::SColl(acc.into()));
+        assert_eq!(
+            SType::sigma_parse_bytes(&tpe.sigma_serialize_bytes()),
+            Err(SerializationError::TypeDepthLimitExceeded(
+                ParserLimits::MAX_TREE_DEPTH
+            ))
+        );
+    }
+
+    #[test]
+    fn type_depth_does_not_depend_on_encoding() {
+        let limits = ParserLimits {
+            max_type_depth: 10,
+            ..Default::default()
+        };
+        // Coll[Coll[_]] of a non-primitive type encoded with a single type code per two levels
+        let nested_coll_bytes = |depth: usize| {
+            let mut bytes = vec![TypeCode::NESTED_COLLECTION.value(); depth];
+            bytes.push(TypeCode::SBOX.value());
+            bytes
+        };
+        let nested_coll =
+            |depth: usize| (0..2 * depth).fold(SType::SBox, |acc, _| SType::SColl(acc.into()));
+        assert_eq!(
+            SType::sigma_parse_bytes_with_limits(&nested_coll_bytes(4), limits.clone()),
+            Ok(nested_coll(4))
+        );
+        assert_eq!(
+            SType::sigma_parse_bytes_with_limits(
+                &nested_coll(4).sigma_serialize_bytes(),
+                limits.clone()
+            ),
+            Ok(nested_coll(4))
+        );
+        assert_eq!(
+            SType::sigma_parse_bytes_with_limits(&nested_coll_bytes(5), limits),
+            Err(SerializationError::TypeDepthLimitExceeded(10))
+        );
+    }
+
+    #[test]
+    fn coll_len_limit() {
+        let c = Constant {
+            tpe: SType::SColl(SType::SInt.into()),
+            v: Value::Coll(CollKind::WrappedColl {
+                elem_tpe: SType::SInt,
+                items: vec![Value::Int(1), Value::Int(2), Value::Int(3)].into(),
+            }),
+        };
+        let limits = ParserLimits {
+            max_coll_len: 2,
+            ..Default::default()
+        };
+        assert_eq!(
+            Constant::sigma_parse_bytes_with_limits(&c.sigma_serialize_bytes(), limits),
+            Err(SerializationError::CollLengthLimitExceeded(3, 2))
+        );
+    }
+
+    #[test]
+    fn declared_tree_size_limit() {
+        // header with size flag
+        let mut bytes = vec![0x08];
+        bytes.put_u32(u32::MAX).unwrap();
+        assert_eq!(
+            ErgoTree::sigma_parse_bytes(&bytes),
+            Err(SerializationError::TreeSizeLimitExceeded(
+                u32::MAX as usize,
+                ParserLimits::MAX_TREE_BYTES
+            ))
+        );
+    }
+
+    #[test]
+    fn constants_count_limit() {
+        // header with constant segregation flag
+        let mut bytes = vec![0x10];
+        bytes.put_u32(5000).unwrap();
+        let tree = ErgoTree::sigma_parse_bytes(&bytes).unwrap();
+        assert!(matches!(
+            tree.proposition(),
+            Err(ErgoTreeParsingError::TreeParsingError(
+                ErgoTreeConstantsParsingError {
+                    error: SerializationError::ConstantsCountLimitExceeded(5000, 4096),
+                    ..
+                }
+            ))
+        ));
+    }
+
+    #[test]
+    fn input_size_limit() {
+        let bytes = nested_not(10).sigma_serialize_bytes();
+        let limits = ParserLimits {
+            max_bytes: 5,
+            ..Default::default()
+        };
+        assert_eq!(
+            Expr::sigma_parse_bytes_with_limits(&bytes, limits),
+            Err(SerializationError::InputSizeLimitExceeded(5))
+        );
+    }
+}
@@ -5,6 +5,7 @@ use crate::types::type_unify::TypeUnificationError;
 
 use super::{
     constant_store::ConstantStore,
+    parser_limits::ParserLimits,
     sigma_byte_reader::{SigmaByteRead, SigmaByteReader},
     sigma_byte_writer::{SigmaByteWrite, SigmaByteWriter},
 };
@@ -60,6 +61,27 @@ pub enum SerializationError {
     /// Unknown method ID for given type code
     #[error("No method id {0:?} found in type companion with type id {1:?} ")]
     UnknownMethodId(MethodId, TypeCode),
+    /// No type companion for given type code
+    #[error("No type companion found for type id {0:?}")]
+    UnknownTypeId(TypeCode),
+    /// Expression tree is nested deeper than the limit
+    #[error("Expression tree depth exceeds the limit {0}")]
+    TreeDepthLimitExceeded(usize),
+    /// Type is nested deeper than the limit
+    #[error("Type nesting depth exceeds the limit {0}")]
+    TypeDepthLimitExceeded(usize),
+    /// Declared collection length exceeds the limit
+    #[error("Collection length {0} exceeds the limit {1}")]
+    CollLengthLimitExceeded(usize, usize),
+    /// Number of segregated constants exceeds the limit
+    #[error("Constants count {0} exceeds the limit {1}")]
+    ConstantsCountLimitExceeded(usize, usize),
+    /// Serialized ErgoTree size exceeds the limit
+    #[error("ErgoTree size {0} bytes exceeds the limit {1}")]
+    TreeSizeLimitExceeded(usize, usize),
+    /// Total number of bytes read exceeds the limit
+    #[error("Input size exceeds the limit of {0} bytes")]
+    InputSizeLimitExceeded(usize),
 }
 
 impl From<io::Error> for SerializationError {
@@ -121,8 +143,19 @@ pub trait SigmaSerializable: Sized {
 
     /// Parse `self` from the bytes
     fn sigma_parse_bytes(bytes: &[u8]) -> Result<Self, SerializationError> {
+        Self::sigma_parse_bytes_with_limits(bytes, ParserLimits::default())
+    }
+
+    /// Parse `self` from the bytes enforcing the given limits
+    fn sigma_parse_bytes_with_limits(
+        bytes: &[u8],
+        limits: ParserLimits,
+    ) -> Result<Self, SerializationError> {
+        if bytes.len() > limits.max_bytes {
+            return Err(SerializationError::InputSizeLimitExceeded(limits.max_bytes));
+        }
         let cursor = Cursor::new(bytes);
-        let mut sr = SigmaByteReader::new(cursor, ConstantStore::empty());
+        let mut sr = SigmaByteReader::new(cursor, ConstantStore::empty()).with_limits(limits);
         Self::sigma_parse(&mut sr)
     }
 }
@@ -134,8 +167,9 @@ impl<T: SigmaSerializable> SigmaSerializable for Vec<T> {
     }
 
     fn sigma_parse<R: SigmaByteRead>(r: &mut R) -> Result<Self, SerializationError> {
-        let items_count = r.get_u32()?;
-        let mut items = Vec::with_capacity(items_count as usize);
+        let items_count = r.get_u32()? as usize;
+        r.check_coll_len(items_count)?;
+        let mut items = Vec::with_capacity(items_count);
         for _ in 0..items_count {
             items.push(T::sigma_parse(r)?);
         }
@@ -1,6 +1,8 @@
 //! Sigma byte stream writer
 use super::constant_store::ConstantStore;
+use super::parser_limits::ParserLimits;
 use super::val_def_type_store::ValDefTypeStore;
+use super::SerializationError;
 use sigma_ser::vlq_encode::ReadSigmaVlqExt;
 use std::io::Cursor;
 use std::io::Read;
@@ -11,6 +13,10 @@ pub struct SigmaByteReader<R> {
     constant_store: ConstantStore,
     substitute_placeholders: bool,
     val_def_type_store: ValDefTypeStore,
+    limits: ParserLimits,
+    position: usize,
+    tree_depth: usize,
+    type_depth: usize,
 }
 
 impl<R: Read> SigmaByteReader<R> {
@@ -21,6 +27,10 @@ impl<R: Read> SigmaByteReader<R> {
             constant_store,
             substitute_placeholders: false,
             val_def_type_store: ValDefTypeStore::new(),
+            limits: ParserLimits::default(),
+            position: 0,
+            tree_depth: 0,
+            type_depth: 0,
         }
     }
 
@@ -35,18 +45,22 @@ impl<R: Read> SigmaByteReader<R> {
             constant_store,
             substitute_placeholders: true,
             val_def_type_store: ValDefTypeStore::new(),
+            limits: ParserLimits::default(),
+            position: 0,
+            tree_depth: 0,
+            type_depth: 0,
         }
     }
+
+    /// Set limits to be enforced during parsing (default is [`ParserLimits::default`])
+    pub fn with_limits(self, limits: ParserLimits) -> SigmaByteReader<R> {
+        SigmaByteReader { limits, ..self }
+    }
 }
 
 /// Create SigmaByteReader from a byte array (with empty constant store)
 pub fn from_bytes<T: AsRef<[u8]>>(bytes: T) -> SigmaByteReader<Cursor<T>> {
-    SigmaByteReader {
-        inner: Cursor::new(bytes),
-        constant_store: ConstantStore::empty(),
-        substitute_placeholders: false,
-        val_def_type_store: ValDefTypeStore::new(),
-    }
+    SigmaByteReader::new(Cursor::new(bytes), ConstantStore::empty())
 }
 
 /// Sigma byte reader trait with a constant store to resolve segregated constants
@@ -62,11 +76,73 @@ pub trait SigmaByteRead: ReadSigmaVlqExt {
 
     /// ValDef types store (resolves tpe on ValUse parsing)
     fn val_def_type_store(&mut self) -> &mut ValDefTypeStore;
+
+    /// Limits enforced during parsing
+    fn limits(&self) -> &ParserLimits;
+
+    /// Number of bytes read so far
+    fn position(&self) -> usize;
+
+    /// Called before parsing a (nested) expression or sigma boolean. Fails if the tree depth or
+    /// the total bytes read exceed the limits. Use [`SigmaByteRead::parse_nested_expr`] instead,
+    /// which pairs it with [`SigmaByteRead::exit_expr`]
+    fn enter_expr(&mut self) -> Result<(), SerializationError>;
+
+    /// Called after parsing a (nested) expression or sigma boolean
+    fn exit_expr(&mut self);
+
+    /// Called before parsing a (nested) type. Fails if the type depth exceeds the limit.
+    /// Use [`SigmaByteRead::parse_nested_type`] instead, which pairs it with
+    /// [`SigmaByteRead::exit_type`]
+    fn enter_type(&mut self) -> Result<(), SerializationError>;
+
+    /// Called after parsing a (nested) type
+    fn exit_type(&mut self);
+
+    /// Parse a (nested) expression or sigma boolean with `parse`, counting it against the tree
+    /// depth limit. The depth is restored whatever the `parse` outcome
+    fn parse_nested_expr<T, F>(&mut self, parse: F) -> Result<T, SerializationError>
+    where
+        Self: Sized,
+        F: FnOnce(&mut Self) -> Result<T, SerializationError>,
+    {
+        self.enter_expr()?;
+        let res = parse(self);
+        self.exit_expr();
+        res
+    }
+
+    /// Parse a (nested) type with `parse`, counting it against the type depth limit. The depth
+    /// is restored whatever the `parse` outcome
+    fn parse_nested_type<T, F>(&mut self, parse: F) -> Result<T, SerializationError>
+    where
+        Self: Sized,
+        F: FnOnce(&mut Self) -> Result<T, SerializationError>,
+    {
+        self.enter_type()?;
+        let res = parse(self);
+        self.exit_type();
+        res
+    }
+
+    /// Check the declared collection length against the limit
+    fn check_coll_len(&self, len: usize) -> Result<(), SerializationError> {
+        if len > self.limits().max_coll_len {
+            Err(SerializationError::CollLengthLimitExceeded(
+                len,
+                self.limits().max_coll_len,
+            ))
+        } else {
+            Ok(())
+        }
+    }
 }
 
 impl<R: Read> Read for SigmaByteReader<R> {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-        self.inner.read(buf)
+        let n = self.inner.read(buf)?;
+        self.position += n;
+        Ok(n)
     }
 }
 
@@ -86,4 +162,45 @@ impl<R: ReadSigmaVlqExt> SigmaByteRead for SigmaByteReader<R> {
     fn val_def_type_store(&mut self) -> &mut ValDefTypeStore {
         &mut self.val_def_type_store
     }
+
+    fn limits(&self) -> &ParserLimits {
+        &self.limits
+    }
+
+    fn position(&self) -> usize {
+        self.position
+    }
+
+    fn enter_expr(&mut self) -> Result<(), SerializationError> {
+        if self.position > self.limits.max_bytes {
+            return Err(SerializationError::InputSizeLimitExceeded(
+                self.limits.max_bytes,
+            ));
+        }
+        if self.tree_depth >= self.limits.max_tree_depth {
+            return Err(SerializationError::TreeDepthLimitExceeded(
+                self.limits.max_tree_depth,
+            ));
+        }
+        self.tree_depth += 1;
+        Ok(())
+    }
+
+    fn exit_expr(&mut self) {
+        self.tree_depth = self.tree_depth.saturating_sub(1);
+    }
+
+    fn enter_type(&mut self) -> Result<(), SerializationError> {
+        if self.type_depth >= self.limits.max_type_depth {
+            return Err(SerializationError::TypeDepthLimitExceeded(
+                self.limits.max_type_depth,
+            ));
+        }
+        self.type_depth += 1;
+        Ok(())
+    }
+
+    fn exit_type(&mut self) {
+        self.type_depth = self.type_depth.saturating_sub(1);
+    }
 }
@@ -5,9 +5,15 @@ use crate::serialization::{
 };
 use crate::sigma_protocol::{
     dlog_group::EcPoint,
-    sigma_boolean::{ProveDlog, SigmaBoolean, SigmaConjecture, SigmaProofOfKnowledgeTree},
+    sigma_boolean::{
+        ProveDhTuple, ProveDlog, SigmaBoolean, SigmaConjecture, SigmaConjectureItems,
+        SigmaProofOfKnowledgeTree,
+    },
 };
 
+use crate::sigma_protocol::sigma_boolean::cand::Cand;
+use crate::sigma_protocol::sigma_boolean::cor::Cor;
+
 use crate::sigma_protocol::sigma_boolean::cthreshold::Cthreshold;
 use std::io;
 
@@ -16,12 +22,12 @@ impl SigmaSerializable for SigmaBoolean {
         self.op_code().sigma_serialize(w)?;
         match self {
             SigmaBoolean::ProofOfKnowledge(proof) => match proof {
-                SigmaProofOfKnowledgeTree::ProveDhTuple { .. } => todo!(),
+                SigmaProofOfKnowledgeTree::ProveDhTuple(v) => v.sigma_serialize(w),
                 SigmaProofOfKnowledgeTree::ProveDlog(v) => v.sigma_serialize(w),
             },
             SigmaBoolean::SigmaConjecture(conj) => match conj {
-                SigmaConjecture::Cand(_) => todo!(),
-                SigmaConjecture::Cor(_) => todo!(),
+                SigmaConjecture::Cand(c) => c.items.sigma_serialize(w),
+                SigmaConjecture::Cor(c) => c.items.sigma_serialize(w),
                 SigmaConjecture::Cthreshold(c) => c.sigma_serialize(w),
             },
             SigmaBoolean::TrivialProp(_) => Ok(()), // besides opCode no additional bytes
@@ -29,19 +35,53 @@ impl SigmaSerializable for SigmaBoolean {
     }
 
     fn sigma_parse<R: SigmaByteRead>(r: &mut R) -> Result<Self, SerializationError> {
-        let op_code = OpCode::sigma_parse(r)?;
-        match op_code {
-            OpCode::PROVE_DLOG => Ok(SigmaBoolean::ProofOfKnowledge(
-                SigmaProofOfKnowledgeTree::ProveDlog(ProveDlog::sigma_parse(r)?),
-            )),
-            OpCode::ATLEAST => {
-                let c = Cthreshold::sigma_parse(r)?;
-                Ok(SigmaBoolean::SigmaConjecture(SigmaConjecture::Cthreshold(
-                    c,
-                )))
-            }
-            _ => todo!(),
-        }
+        r.parse_nested_expr(|r| {
+            OpCode::sigma_parse(r).and_then(|op_code| match op_code {
+                OpCode::PROVE_DLOG => Ok(SigmaBoolean::ProofOfKnowledge(
+                    SigmaProofOfKnowledgeTree::ProveDlog(ProveDlog::sigma_parse(r)?),
+                )),
+                OpCode::PROVE_DIFFIE_HELLMAN_TUPLE => Ok(SigmaBoolean::ProofOfKnowledge(
+                    SigmaProofOfKnowledgeTree::ProveDhTuple(ProveDhTuple::sigma_parse(r)?),
+                )),
+                OpCode::SIGMA_AND => {
+                    Ok(SigmaBoolean::SigmaConjecture(SigmaConjecture::Cand(Cand {
+                        items: SigmaConjectureItems::<_>::sigma_parse(r)?,
+                    })))
+                }
+                OpCode::SIGMA_OR => Ok(SigmaBoolean::SigmaConjecture(SigmaConjecture::Cor(Cor {
+                    items: SigmaConjectureItems::<_>::sigma_parse(r)?,
+                }))),
+                OpCode::TRIVIAL_PROP_TRUE => Ok(SigmaBoolean::TrivialProp(true)),
+                OpCode::TRIVIAL_PROP_FALSE => Ok(SigmaBoolean::TrivialProp(false)),
+                OpCode::ATLEAST => {
+                    let c = Cthreshold::sigma_parse(r)?;
+                    Ok(SigmaBoolean::SigmaConjecture(SigmaConjecture::Cthreshold(
+                        c,
+                    )))
+                }
+                o => Err(SerializationError::NotImplementedYet(format!(
+                    "parsing of sigma boolean with op code {:?} is not yet supported",
+                    o
+                ))),
+            })
+        })
+    }
+}
+
+impl SigmaSerializable for ProveDhTuple {
+    fn sigma_serialize<W: SigmaByteWrite>(&self, w: &mut W) -> Result<(), io::Error> {
+        self.gv.sigma_serialize(w)?;
+        self.hv.sigma_serialize(w)?;
+        self.uv.sigma_serialize(w)?;
+        self.vv.sigma_serialize(w)
+    }
+
+    fn sigma_parse<R: SigmaByteRead>(r: &mut R) -> Result<Self, SerializationError> {
+        let gv = EcPoint::sigma_parse(r)?;
+        let hv = EcPoint::sigma_parse(r)?;
+        let uv = EcPoint::sigma_parse(r)?;
+        let vv = EcPoint::sigma_parse(r)?;
+        Ok(ProveDhTuple::new(gv, hv, uv, vv))
     }
 }
 
@@ -55,3 +95,42 @@ impl SigmaSerializable for ProveDlog {
         Ok(ProveDlog::new(p))
     }
 }
+
+#[cfg(test)]
+#[cfg(feature = "arbitrary")]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::serialization::sigma_serialize_roundtrip;
+    use sigma_test_util::force_any_val;
+    use std::convert::TryInto;
+
+    #[test]
+    fn ser_roundtrip_all_kinds() {
+        let pk = || -> SigmaBoolean { force_any_val::<ProveDlog>().into() };
+        let dht = SigmaBoolean::ProofOfKnowledge(SigmaProofOfKnowledgeTree::ProveDhTuple(
+            ProveDhTuple::new(
+                force_any_val::<EcPoint>(),
+                force_any_val::<EcPoint>(),
+                force_any_val::<EcPoint>(),
+                force_any_val::<EcPoint>(),
+            ),
+        ));
+        let cand = SigmaBoolean::SigmaConjecture(SigmaConjecture::Cand(Cand {
+            items: vec![pk(), dht.clone()].try_into().unwrap(),
+        }));
+        let cor = SigmaBoolean::SigmaConjecture(SigmaConjecture::Cor(Cor {
+            items: vec![cand.clone(), pk(), pk()].try_into().unwrap(),
+        }));
+        for sb in [
+            pk(),
+            dht,
+            cand,
+            cor,
+            SigmaBoolean::TrivialProp(true),
+            SigmaBoolean::TrivialProp(false),
+        ] {
+            assert_eq!(sigma_serialize_roundtrip(&sb), sb);
+        }
+    }
+}
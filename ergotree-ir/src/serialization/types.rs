@@ -75,7 +75,7 @@ impl TypeCode {
     pub const OPTION_COLLECTION: TypeCode =
         Self::new((TypeCode::MAX_PRIM_TYPECODE + 1) * TypeCode::OPTION_COLLECTION_TYPE_CONSTR_ID);
 
-    const fn new(c: u8) -> TypeCode {
+    pub(crate) const fn new(c: u8) -> TypeCode {
         TypeCode(c)
     }
 
@@ -158,7 +158,7 @@ impl SType {
                 }
                 // Coll[Coll[_]]
                 2 => {
-                    let t_elem = get_arg_type(r, prim_id)?;
+                    let t_elem = get_nested_arg_type(r, prim_id)?;
                     SType::SColl(Box::new(SType::SColl(Box::new(t_elem))))
                 }
                 // Option[_]
@@ -168,7 +168,7 @@ impl SType {
                 }
                 // Option[Coll[_]]
                 4 => {
-                    let t_elem = get_arg_type(r, prim_id)?;
+                    let t_elem = get_nested_arg_type(r, prim_id)?;
                     SType::SOption(SType::SColl(t_elem.into()).into())
                 }
                 TypeCode::TUPLE_PAIR1_CONSTR_ID => {
@@ -366,8 +366,9 @@ impl SigmaSerializable for SType {
 
     fn sigma_parse<R: SigmaByteRead>(r: &mut R) -> Result<Self, SerializationError> {
         // for reference see http://github.com/ScorexFoundation/sigmastate-interpreter/blob/25251c1313b0131835f92099f02cef8a5d932b5e/sigmastate/src/main/scala/sigmastate/serialization/TypeSerializer.scala#L118-L118
-        let c = TypeCode::sigma_parse(r)?;
-        Self::parse_with_type_code(r, c)
+        r.parse_nested_type(|r| {
+            TypeCode::sigma_parse(r).and_then(|c| Self::parse_with_type_code(r, c))
+        })
     }
 }
 
@@ -379,6 +380,20 @@ fn get_arg_type<R: SigmaByteRead>(r: &mut R, prim_id: u8) -> Result<SType, Seria
     }
 }
 
+/// Same as [`get_arg_type`] for the codes of two nested type constructors (`Coll[Coll[_]]`,
+/// `Option[Coll[_]]`), the inner constructor is counted against the type depth limit, so that
+/// the depth does not depend on the chosen encoding
+fn get_nested_arg_type<R: SigmaByteRead>(
+    r: &mut R,
+    prim_id: u8,
+) -> Result<SType, SerializationError> {
+    if prim_id == 0 {
+        r.parse_nested_type(SType::sigma_parse)
+    } else {
+        get_embeddable_type(prim_id)
+    }
+}
+
 #[cfg(test)]
 #[cfg(feature = "arbitrary")]
 mod tests {
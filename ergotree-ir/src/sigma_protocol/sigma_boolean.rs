@@ -3,6 +3,8 @@
 use self::cand::Cand;
 use self::cor::Cor;
 use self::cthreshold::Cthreshold;
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
 
 use super::dlog_group::EcPoint;
 use crate::ergo_tree::ErgoTree;
@@ -29,6 +31,7 @@ pub type SigmaConjectureItems<T> = BoundedVec<T, 2, 1000>;
 
 /// Construct a new SigmaBoolean value representing public key of discrete logarithm signature protocol.
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct ProveDlog {
     /// public key
     pub h: Box<EcPoint>,
@@ -52,6 +55,7 @@ impl From<EcPoint> for ProveDlog {
 /// Construct a new SigmaProp value representing public key of Diffie Hellman signature protocol.
 /// Common input: (g,h,u,v)
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct ProveDhTuple {
     /// Generator `g`
     pub gv: Box<EcPoint>,
@@ -63,8 +67,21 @@ pub struct ProveDhTuple {
     pub vv: Box<EcPoint>,
 }
 
+impl ProveDhTuple {
+    /// create new Diffie Hellman tuple
+    pub fn new(gv: EcPoint, hv: EcPoint, uv: EcPoint, vv: EcPoint) -> Self {
+        ProveDhTuple {
+            gv: gv.into(),
+            hv: hv.into(),
+            uv: uv.into(),
+            vv: vv.into(),
+        }
+    }
+}
+
 /// Sigma proposition
 #[derive(PartialEq, Eq, Debug, Clone, From)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub enum SigmaProofOfKnowledgeTree {
     /// public key of Diffie Hellman signature protocol
     ProveDhTuple(ProveDhTuple),
@@ -74,6 +91,7 @@ pub enum SigmaProofOfKnowledgeTree {
 
 /// Conjunctions for sigma propositions
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub enum SigmaConjecture {
     /// AND
     Cand(Cand),
@@ -86,6 +104,7 @@ pub enum SigmaConjecture {
 /// Algebraic data type of sigma proposition expressions
 /// Values of this type are used as values of SigmaProp type
 #[derive(PartialEq, Eq, Debug, Clone, From, TryInto)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub enum SigmaBoolean {
     /// Represents boolean values (true/false)
     TrivialProp(bool),
@@ -102,12 +121,16 @@ impl HasOpCode for SigmaBoolean {
             SigmaBoolean::ProofOfKnowledge(SigmaProofOfKnowledgeTree::ProveDlog(_)) => {
                 OpCode::PROVE_DLOG
             }
+            SigmaBoolean::ProofOfKnowledge(SigmaProofOfKnowledgeTree::ProveDhTuple(_)) => {
+                OpCode::PROVE_DIFFIE_HELLMAN_TUPLE
+            }
             SigmaBoolean::SigmaConjecture(conj) => match conj {
-                SigmaConjecture::Cand(_) => todo!(),
-                SigmaConjecture::Cor(_) => todo!(),
+                SigmaConjecture::Cand(_) => OpCode::SIGMA_AND,
+                SigmaConjecture::Cor(_) => OpCode::SIGMA_OR,
                 SigmaConjecture::Cthreshold(_) => OpCode::ATLEAST,
             },
-            _ => todo!(),
+            SigmaBoolean::TrivialProp(true) => OpCode::TRIVIAL_PROP_TRUE,
+            SigmaBoolean::TrivialProp(false) => OpCode::TRIVIAL_PROP_FALSE,
         }
     }
 }
@@ -134,6 +157,7 @@ impl From<ProveDlog> for SigmaBoolean {
 
 /// Proposition which can be proven and verified by sigma protocol.
 #[derive(PartialEq, Eq, Debug, Clone, From, Into)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct SigmaProp(SigmaBoolean);
 
 impl SigmaProp {
@@ -1,4 +1,6 @@
 //! OR conjunction for sigma proposition
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
 use std::convert::TryInto;
 
 use super::SigmaBoolean;
@@ -7,8 +9,10 @@ use crate::sigma_protocol::sigma_boolean::SigmaConjecture;
 
 /// OR conjunction for sigma proposition
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct Cor {
     /// Items of the conjunctions
+    #[cfg_attr(feature = "json", serde(with = "crate::json::bounded_vec"))]
     pub items: SigmaConjectureItems<SigmaBoolean>,
 }
 
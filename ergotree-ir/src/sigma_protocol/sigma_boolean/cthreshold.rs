@@ -5,15 +5,19 @@ use super::SigmaConjectureItems;
 use crate::serialization::sigma_byte_reader::SigmaByteRead;
 use crate::serialization::sigma_byte_writer::SigmaByteWrite;
 use crate::serialization::{SerializationError, SigmaSerializable};
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
 use std::io::Error;
 // use crate::sigma_protocol::sigma_boolean::SigmaConjecture;
 
 /// THRESHOLD conjunction for sigma proposition
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct Cthreshold {
     /// Number of conjectures to be proven
     pub n: i32,
     /// Items of the proposal
+    #[cfg_attr(feature = "json", serde(with = "crate::json::bounded_vec"))]
     pub items: SigmaConjectureItems<SigmaBoolean>,
 }
 
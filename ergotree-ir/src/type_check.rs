@@ -1,39 +1,709 @@
 //! Type checking
 
+use std::collections::HashMap;
+
+use crate::mir::bin_op::ArithOp;
+use crate::mir::bin_op::BinOpKind;
+use crate::mir::bin_op::RelationOp;
+use crate::mir::collection::Collection;
 use crate::mir::expr::Expr;
+use crate::mir::val_def::ValId;
+use crate::mir::value::CollKind;
+use crate::mir::value::Value;
+use crate::mir::visitor::children;
+use crate::types::sfunc::SFunc;
+use crate::types::smethod::SMethod;
+use crate::types::stuple::STuple;
+use crate::types::stype::SType;
 
 /// Typecheck error
 #[derive(Debug, PartialEq)]
 pub struct TypeCheckError {
     msg: String,
+    path: Vec<usize>,
+    path_desc: String,
 }
 
 impl TypeCheckError {
     /// Create new
     pub fn new(msg: String) -> Self {
-        Self { msg }
+        Self {
+            msg,
+            path: Vec::new(),
+            path_desc: String::new(),
+        }
+    }
+
+    /// Path from the root to the offending node, as indices of the children (in the order of
+    /// [`crate::mir::visitor::children`])
+    pub fn path(&self) -> &[usize] {
+        &self.path
     }
 
     /// Get error description
     pub fn pretty_desc(&self) -> String {
-        self.msg.clone()
+        if self.path_desc.is_empty() {
+            self.msg.clone()
+        } else {
+            format!("{} (at {})", self.msg, self.path_desc)
+        }
     }
 }
 
-/// Type checks the given expression
+/// Type checks the given expression (every node of the tree)
 pub fn type_check(e: Expr) -> Result<Expr, TypeCheckError> {
-    // not really a relevant check, since such kind of check should be in BinOp::new()
-    match &e {
-        Expr::BinOp(bin) => {
-            if bin.left.tpe() == bin.right.tpe() {
-                Ok(e)
+    let mut path = Vec::new();
+    check(&e, &HashMap::new(), &mut path).map_err(|msg| {
+        let mut path_desc = e.node_name().to_string();
+        let mut node = &e;
+        for idx in &path {
+            if let Some(child) = children(node).get(*idx) {
+                node = child;
+                path_desc.push_str(&format!("[{}]/{}", idx, node.node_name()));
+            }
+        }
+        TypeCheckError {
+            msg: format!("Type check error: {}", msg),
+            path,
+            path_desc,
+        }
+    })?;
+    Ok(e)
+}
+
+/// Checks children first (bottom-up), then the node itself. On error `path` is left pointing at
+/// the offending node.
+fn check(e: &Expr, env: &HashMap<ValId, SType>, path: &mut Vec<usize>) -> Result<(), String> {
+    let mut scope = match e {
+        Expr::FuncValue(f) => {
+            let mut scope = env.clone();
+            for arg in f.args() {
+                scope.insert(arg.idx, arg.tpe.clone());
+            }
+            scope
+        }
+        Expr::BlockValue(_) => env.clone(),
+        _ => HashMap::new(),
+    };
+    let has_scope = matches!(e, Expr::FuncValue(_) | Expr::BlockValue(_));
+    for (idx, child) in children(e).into_iter().enumerate() {
+        path.push(idx);
+        check(child, if has_scope { &scope } else { env }, path)?;
+        path.pop();
+        if let (Expr::BlockValue(_), Expr::ValDef(vd)) = (e, child) {
+            scope.insert(vd.id, vd.rhs.tpe());
+        }
+    }
+    check_node(e, env)
+}
+
+fn check_node(e: &Expr, env: &HashMap<ValId, SType>) -> Result<(), String> {
+    match e {
+        Expr::Const(c) => {
+            if value_conforms(&c.v, &c.tpe) {
+                Ok(())
             } else {
-                Err(TypeCheckError::new(format!(
-                    "Type check error: binary op operands types do not match: {0:?}",
-                    bin
-                )))
+                Err(format!(
+                    "constant value {:?} is not of type {:?}",
+                    c.v, c.tpe
+                ))
+            }
+        }
+        Expr::ConstPlaceholder(_)
+        | Expr::Context
+        | Expr::Global
+        | Expr::GlobalVars(_)
+        | Expr::Tuple(_)
+        | Expr::ValDef(_)
+        | Expr::GetVar(_)
+        | Expr::DeserializeContext(_)
+        | Expr::Collection(Collection::BoolConstants(_)) => Ok(()),
+        Expr::Append(op) => {
+            let input_tpe = expect_coll(&op.input, "Append input")?;
+            let col_2_tpe = expect_coll(&op.col_2, "Append col_2")?;
+            expect_eq(&input_tpe, &col_2_tpe, "Append col_2 element")
+        }
+        Expr::SubstConstants(op) => {
+            expect_tpe(
+                &op.script_bytes,
+                &coll_byte(),
+                "SubstConstants script_bytes",
+            )?;
+            expect_tpe(
+                &op.positions,
+                &SType::SColl(SType::SInt.into()),
+                "SubstConstants positions",
+            )?;
+            expect_coll(&op.new_values, "SubstConstants new_values").map(|_| ())
+        }
+        Expr::ByteArrayToLong(op) => expect_tpe(&op.input, &coll_byte(), "ByteArrayToLong input"),
+        Expr::ByteArrayToBigInt(op) => {
+            expect_tpe(&op.input, &coll_byte(), "ByteArrayToBigInt input")
+        }
+        Expr::LongToByteArray(op) => expect_tpe(&op.input, &SType::SLong, "LongToByteArray input"),
+        Expr::Collection(Collection::Exprs { elem_tpe, items }) => items
+            .iter()
+            .try_for_each(|item| expect_tpe(item, elem_tpe, "Collection item")),
+        Expr::CalcBlake2b256(op) => expect_tpe(&op.input, &coll_byte(), "CalcBlake2b256 input"),
+        Expr::CalcSha256(op) => expect_tpe(&op.input, &coll_byte(), "CalcSha256 input"),
+        Expr::FuncValue(op) => match op.tpe() {
+            // the type is kept in the node (e.g. parsed from JSON), check it against the
+            // declared argument types and the body type
+            SType::SFunc(sfunc) => {
+                let arg_types: Vec<SType> = op.args().iter().map(|a| a.tpe.clone()).collect();
+                if sfunc.t_dom != arg_types {
+                    return Err(format!(
+                        "FuncValue: expected args of types {:?}, got {:?}",
+                        sfunc.t_dom, arg_types
+                    ));
+                }
+                if !sfunc.tpe_params.is_empty() {
+                    return Err(format!(
+                        "FuncValue: unexpected type parameters {:?}",
+                        sfunc.tpe_params
+                    ));
+                }
+                expect_tpe(op.body(), &sfunc.t_range, "FuncValue body")
+            }
+            tpe => Err(format!(
+                "FuncValue: expected type to be SFunc, got {:?}",
+                tpe
+            )),
+        },
+        Expr::Apply(op) => match op.func.tpe() {
+            SType::SFunc(sfunc) => {
+                let arg_types: Vec<SType> = op.args.iter().map(|a| a.tpe()).collect();
+                if sfunc.t_dom == arg_types {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "Apply: expected args of types {:?}, got {:?}",
+                        sfunc.t_dom, arg_types
+                    ))
+                }
+            }
+            tpe => Err(format!("Apply: expected func to be SFunc, got {:?}", tpe)),
+        },
+        Expr::MethodCall(op) => {
+            let actual: Vec<SType> = op.args.iter().map(|a| a.tpe()).collect();
+            check_method(&op.method, op.obj.tpe(), actual, "MethodCall")
+        }
+        Expr::ProperyCall(op) => check_method(&op.method, op.obj.tpe(), vec![], "PropertyCall"),
+        Expr::BlockValue(op) => op.items.iter().try_for_each(|item| match item {
+            Expr::ValDef(_) => Ok(()),
+            _ => Err(format!(
+                "BlockValue: expected items to be ValDef, got {}",
+                item.node_name()
+            )),
+        }),
+        Expr::ValUse(op) => match env.get(&op.val_id) {
+            Some(tpe) => expect_eq(tpe, &op.tpe, "ValUse"),
+            None => Err(format!("ValUse: {:?} is not defined", op.val_id)),
+        },
+        Expr::If(op) => {
+            expect_tpe(&op.condition, &SType::SBoolean, "If condition")?;
+            expect_eq(&op.true_branch.tpe(), &op.false_branch.tpe(), "If branches")
+        }
+        Expr::BinOp(op) => {
+            let left_tpe = op.left.post_eval_tpe();
+            expect_tpe(&op.right, &left_tpe, "BinOp right operand")?;
+            match op.kind {
+                BinOpKind::Relation(RelationOp::Eq) | BinOpKind::Relation(RelationOp::NEq) => {
+                    Ok(())
+                }
+                BinOpKind::Relation(RelationOp::And) | BinOpKind::Relation(RelationOp::Or) => {
+                    expect_tpe(&op.left, &SType::SBoolean, "BinOp operand")
+                }
+                BinOpKind::Relation(_)
+                | BinOpKind::Arith(ArithOp::Plus)
+                | BinOpKind::Arith(ArithOp::Minus)
+                | BinOpKind::Arith(ArithOp::Multiply)
+                | BinOpKind::Arith(ArithOp::Divide)
+                | BinOpKind::Arith(ArithOp::Max)
+                | BinOpKind::Arith(ArithOp::Min)
+                | BinOpKind::Arith(ArithOp::BitOr)
+                | BinOpKind::Arith(ArithOp::BitAnd)
+                | BinOpKind::Arith(ArithOp::BitXor) => expect_numeric(&op.left, "BinOp operand"),
+            }
+        }
+        Expr::And(op) => expect_tpe(
+            &op.input,
+            &SType::SColl(SType::SBoolean.into()),
+            "And input",
+        ),
+        Expr::Or(op) => expect_tpe(&op.input, &SType::SColl(SType::SBoolean.into()), "Or input"),
+        Expr::Xor(op) => {
+            expect_tpe(&op.left, &coll_byte(), "Xor left")?;
+            expect_tpe(&op.right, &coll_byte(), "Xor right")
+        }
+        Expr::Atleast(op) => {
+            expect_tpe(&op.n_required, &SType::SInt, "Atleast bound")?;
+            expect_tpe(
+                &op.expressions,
+                &SType::SColl(SType::SSigmaProp.into()),
+                "Atleast input",
+            )
+        }
+        Expr::LogicalNot(op) => expect_tpe(&op.input, &SType::SBoolean, "LogicalNot input"),
+        Expr::Negation(op) => expect_numeric(&op.input, "Negation input"),
+        Expr::OptionGet(op) => expect_option(&op.input, "OptionGet input").map(|_| ()),
+        Expr::OptionIsDefined(op) => expect_option(&op.input, "OptionIsDefined input").map(|_| ()),
+        Expr::OptionGetOrElse(op) => {
+            let elem_tpe = expect_option(&op.input, "OptionGetOrElse input")?;
+            expect_tpe(&op.default, &elem_tpe, "OptionGetOrElse default")
+        }
+        Expr::ExtractAmount(op) => expect_tpe(&op.input, &SType::SBox, "ExtractAmount input"),
+        Expr::ExtractRegisterAs(op) => {
+            expect_tpe(&op.input, &SType::SBox, "ExtractRegisterAs input")?;
+            if (0..=9).contains(&op.register_id) {
+                Ok(())
+            } else {
+                Err(format!(
+                    "ExtractRegisterAs: invalid register id {}",
+                    op.register_id
+                ))
+            }
+        }
+        Expr::ExtractScriptBytes(op) => {
+            expect_tpe(&op.input, &SType::SBox, "ExtractScriptBytes input")
+        }
+        Expr::ExtractCreationInfo(op) => {
+            expect_tpe(&op.input, &SType::SBox, "ExtractCreationInfo input")
+        }
+        Expr::ExtractId(op) => expect_tpe(&op.input, &SType::SBox, "ExtractId input"),
+        Expr::ByIndex(op) => {
+            let elem_tpe = expect_coll(&op.input, "ByIndex input")?;
+            expect_tpe(&op.index, &SType::SInt, "ByIndex index")?;
+            match &op.default {
+                Some(default) => expect_tpe(default, &elem_tpe, "ByIndex default"),
+                None => Ok(()),
+            }
+        }
+        Expr::SizeOf(op) => expect_coll(&op.input, "SizeOf input").map(|_| ()),
+        Expr::Slice(op) => {
+            expect_coll(&op.input, "Slice input")?;
+            expect_tpe(&op.from, &SType::SInt, "Slice from")?;
+            expect_tpe(&op.until, &SType::SInt, "Slice until")
+        }
+        Expr::Fold(op) => {
+            let elem_tpe = expect_coll(&op.input, "Fold input")?;
+            let zero_tpe = op.zero.tpe();
+            let expected = SType::SFunc(SFunc::new(
+                vec![STuple::pair(zero_tpe.clone(), elem_tpe).into()],
+                zero_tpe,
+            ));
+            expect_eq(&expected, &op.fold_op.tpe(), "Fold fold_op")
+        }
+        Expr::Map(op) => {
+            let elem_tpe = expect_coll(&op.input, "Map input")?;
+            match op.mapper.tpe() {
+                SType::SFunc(sfunc) if sfunc.t_dom == vec![elem_tpe.clone()] => expect_eq(
+                    &SType::SFunc(sfunc),
+                    &SType::SFunc(op.mapper_sfunc.clone()),
+                    "Map mapper_sfunc",
+                ),
+                tpe => Err(format!(
+                    "Map: expected mapper of type {:?} => _, got {:?}",
+                    elem_tpe, tpe
+                )),
+            }
+        }
+        Expr::Filter(op) => check_predicate(&op.input, &op.condition, &op.elem_tpe, "Filter"),
+        Expr::Exists(op) => check_predicate(&op.input, &op.condition, &op.elem_tpe, "Exists"),
+        Expr::ForAll(op) => check_predicate(&op.input, &op.condition, &op.elem_tpe, "ForAll"),
+        Expr::SelectField(op) => match op.input.tpe() {
+            SType::STuple(STuple { items }) => {
+                if op.field_index.zero_based_index() < items.len() {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "SelectField: field index {:?} is out of bounds of tuple {:?}",
+                        op.field_index, items
+                    ))
+                }
+            }
+            tpe => Err(format!(
+                "SelectField: expected input to be STuple, got {:?}",
+                tpe
+            )),
+        },
+        Expr::BoolToSigmaProp(op) => {
+            expect_tpe(&op.input, &SType::SBoolean, "BoolToSigmaProp input")
+        }
+        Expr::Upcast(op) => {
+            expect_numeric(&op.input, "Upcast input")?;
+            if op.tpe.is_numeric() {
+                Ok(())
+            } else {
+                Err(format!(
+                    "Upcast: expected target type to be numeric, got {:?}",
+                    op.tpe
+                ))
+            }
+        }
+        Expr::CreateProveDlog(op) => {
+            expect_tpe(&op.input, &SType::SGroupElement, "CreateProveDlog input")
+        }
+        Expr::CreateProveDhTuple(op) => [&op.gv, &op.hv, &op.uv, &op.vv]
+            .iter()
+            .try_for_each(|i| expect_tpe(i, &SType::SGroupElement, "CreateProveDhTuple input")),
+        Expr::SigmaPropBytes(op) => {
+            expect_tpe(&op.input, &SType::SSigmaProp, "SigmaPropBytes input")
+        }
+        Expr::DecodePoint(op) => expect_tpe(&op.input, &coll_byte(), "DecodePoint input"),
+        Expr::SigmaAnd(op) => op
+            .items
+            .iter()
+            .try_for_each(|i| expect_tpe(i, &SType::SSigmaProp, "SigmaAnd item")),
+        Expr::SigmaOr(op) => op
+            .items
+            .iter()
+            .try_for_each(|i| expect_tpe(i, &SType::SSigmaProp, "SigmaOr item")),
+        Expr::DeserializeRegister(op) => match &op.default {
+            Some(default) => expect_tpe(default, &op.tpe, "DeserializeRegister default"),
+            None => Ok(()),
+        },
+        Expr::MultiplyGroup(op) => {
+            expect_tpe(&op.left, &SType::SGroupElement, "MultiplyGroup left")?;
+            expect_tpe(&op.right, &SType::SGroupElement, "MultiplyGroup right")
+        }
+    }
+}
+
+fn coll_byte() -> SType {
+    SType::SColl(SType::SByte.into())
+}
+
+/// Specializes the declared signature of the method (by the type and method ids) for the object
+/// and argument types, and checks that it is the signature of the given method
+fn check_method(
+    method: &SMethod,
+    obj_tpe: SType,
+    arg_types: Vec<SType>,
+    what: &str,
+) -> Result<(), String> {
+    let declared = SMethod::from_ids(method.obj_type.type_id(), method.method_id())
+        .map_err(|e| format!("{} {}: {:?}", what, method.name(), e))?;
+    let declared_args_count = declared.tpe().t_dom.len().saturating_sub(1);
+    if declared_args_count != arg_types.len() {
+        return Err(format!(
+            "{} {}: expected {} arguments, got {}",
+            what,
+            method.name(),
+            declared_args_count,
+            arg_types.len()
+        ));
+    }
+    let specialized = declared.specialize_for(obj_tpe, arg_types).map_err(|e| {
+        format!(
+            "{} {}: obj and args types do not match the signature: {:?}",
+            what,
+            method.name(),
+            e
+        )
+    })?;
+    if specialized.tpe() == method.tpe() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} {}: expected signature {:?}, got {:?}",
+            what,
+            method.name(),
+            specialized.tpe(),
+            method.tpe()
+        ))
+    }
+}
+
+fn expect_eq(expected: &SType, actual: &SType, what: &str) -> Result<(), String> {
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(format!(
+            "{}: expected type {:?}, got {:?}",
+            what, expected, actual
+        ))
+    }
+}
+
+fn expect_tpe(e: &Expr, expected: &SType, what: &str) -> Result<(), String> {
+    expect_eq(expected, &e.post_eval_tpe(), what)
+}
+
+fn expect_numeric(e: &Expr, what: &str) -> Result<(), String> {
+    let tpe = e.post_eval_tpe();
+    if tpe.is_numeric() {
+        Ok(())
+    } else {
+        Err(format!("{}: expected numeric type, got {:?}", what, tpe))
+    }
+}
+
+fn expect_coll(e: &Expr, what: &str) -> Result<SType, String> {
+    match e.post_eval_tpe() {
+        SType::SColl(elem_tpe) => Ok(*elem_tpe),
+        tpe => Err(format!("{}: expected SColl, got {:?}", what, tpe)),
+    }
+}
+
+fn expect_option(e: &Expr, what: &str) -> Result<SType, String> {
+    match e.post_eval_tpe() {
+        SType::SOption(elem_tpe) => Ok(*elem_tpe),
+        tpe => Err(format!("{}: expected SOption, got {:?}", what, tpe)),
+    }
+}
+
+fn check_predicate(
+    input: &Expr,
+    condition: &Expr,
+    elem_tpe: &SType,
+    what: &str,
+) -> Result<(), String> {
+    let input_elem_tpe = expect_coll(input, what)?;
+    expect_eq(&input_elem_tpe, elem_tpe, what)?;
+    let expected = SType::SFunc(SFunc::new(vec![input_elem_tpe], SType::SBoolean));
+    expect_eq(&expected, &condition.tpe(), what)
+}
+
+/// Checks that the value is of the given type
+fn value_conforms(v: &Value, tpe: &SType) -> bool {
+    match (v, tpe) {
+        (Value::Boolean(_), SType::SBoolean)
+        | (Value::Byte(_), SType::SByte)
+        | (Value::Short(_), SType::SShort)
+        | (Value::Int(_), SType::SInt)
+        | (Value::Long(_), SType::SLong)
+        | (Value::BigInt(_), SType::SBigInt)
+        | (Value::GroupElement(_), SType::SGroupElement)
+        | (Value::SigmaProp(_), SType::SSigmaProp)
+        | (Value::CBox(_), SType::SBox)
+        | (Value::AvlTree, SType::SAvlTree)
+        | (Value::Context, SType::SContext)
+        | (Value::Global, SType::SGlobal) => true,
+        (Value::Coll(CollKind::NativeColl(coll)), SType::SColl(elem_tpe)) => {
+            coll.elem_tpe() == elem_tpe.as_ref()
+        }
+        (Value::Coll(CollKind::WrappedColl { elem_tpe, items }), SType::SColl(t)) => {
+            elem_tpe == t.as_ref() && items.iter().all(|i| value_conforms(i, elem_tpe))
+        }
+        (Value::Tup(items), SType::STuple(STuple { items: types })) => {
+            items.len() == types.len()
+                && items
+                    .iter()
+                    .zip(types.iter())
+                    .all(|(i, t)| value_conforms(i, t))
+        }
+        (Value::Opt(opt), SType::SOption(t)) => match opt.as_ref() {
+            Some(v) => value_conforms(v, t),
+            None => true,
+        },
+        (Value::Lambda(l), SType::SFunc(sfunc)) => {
+            l.args.iter().map(|a| &a.tpe).eq(sfunc.t_dom.iter()) && l.body.tpe() == *sfunc.t_range
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::mir::bin_op::BinOp;
+    use crate::mir::block::BlockValue;
+    use crate::mir::func_value::FuncArg;
+    use crate::mir::func_value::FuncValue;
+    use crate::mir::global_vars::GlobalVars;
+    use crate::mir::if_op::If;
+    use crate::mir::method_call::MethodCall;
+    use crate::mir::val_def::ValDef;
+    use crate::mir::val_use::ValUse;
+    use crate::types::scoll;
+    use expect_test::expect;
+
+    fn check_err(e: Expr) -> String {
+        type_check(e).unwrap_err().pretty_desc()
+    }
+
+    #[test]
+    fn well_typed_block() {
+        let e: Expr = BlockValue {
+            items: vec![ValDef {
+                id: 1.into(),
+                rhs: Box::new(GlobalVars::Height.into()),
+            }
+            .into()],
+            result: Box::new(
+                BinOp {
+                    kind: ArithOp::Plus.into(),
+                    left: Box::new(
+                        ValUse {
+                            val_id: 1.into(),
+                            tpe: SType::SInt,
+                        }
+                        .into(),
+                    ),
+                    right: Box::new(1i32.into()),
+                }
+                .into(),
+            ),
+        }
+        .into();
+        assert_eq!(type_check(e.clone()), Ok(e));
+    }
+
+    #[test]
+    fn binop_operands_mismatch() {
+        let e: Expr = If {
+            condition: Box::new(true.into()),
+            true_branch: Box::new(1i32.into()),
+            false_branch: Box::new(
+                BinOp {
+                    kind: ArithOp::Plus.into(),
+                    left: Box::new(GlobalVars::Height.into()),
+                    right: Box::new(1i64.into()),
+                }
+                .into(),
+            ),
+        }
+        .into();
+        let err = type_check(e).unwrap_err();
+        assert_eq!(err.path(), &[2]);
+        expect![[r#"Type check error: BinOp right operand: expected type SInt, got SLong (at If[2]/BinOp)"#]]
+            .assert_eq(&err.pretty_desc());
+    }
+
+    #[test]
+    fn undefined_val_use() {
+        let body: Expr = ValUse {
+            val_id: 2.into(),
+            tpe: SType::SInt,
+        }
+        .into();
+        let e: Expr = FuncValue::new(
+            vec![FuncArg {
+                idx: 1.into(),
+                tpe: SType::SInt,
+            }],
+            body,
+        )
+        .into();
+        expect![[r#"Type check error: ValUse: ValId(2) is not defined (at FuncValue[0]/ValUse)"#]]
+            .assert_eq(&check_err(e));
+    }
+
+    #[test]
+    fn val_use_type_mismatch() {
+        let e: Expr = FuncValue::new(
+            vec![FuncArg {
+                idx: 1.into(),
+                tpe: SType::SInt,
+            }],
+            ValUse {
+                val_id: 1.into(),
+                tpe: SType::SLong,
             }
+            .into(),
+        )
+        .into();
+        expect![[
+            r#"Type check error: ValUse: expected type SInt, got SLong (at FuncValue[0]/ValUse)"#
+        ]]
+        .assert_eq(&check_err(e));
+    }
+
+    #[test]
+    fn method_call_signature_mismatch() {
+        let coll: Expr = vec![1i32, 2i32].into();
+        let specialized = scoll::INDEX_OF_METHOD
+            .clone()
+            .specialize_for(coll.tpe(), vec![SType::SInt, SType::SInt])
+            .unwrap();
+        let e: Expr = MethodCall::new(coll.clone(), specialized, vec![2i32.into(), 0i32.into()])
+            .unwrap()
+            .into();
+        assert_eq!(type_check(e.clone()), Ok(e));
+
+        // not specialized, the type variable unifies with any type of the args
+        let e: Expr = MethodCall {
+            obj: Box::new(coll.clone()),
+            method: scoll::INDEX_OF_METHOD.clone(),
+            args: vec![2i32.into(), 0i32.into()],
         }
-        _ => Ok(e),
+        .into();
+        assert!(
+            check_err(e).starts_with("Type check error: MethodCall indexOf: expected signature")
+        );
+
+        let e: Expr = MethodCall {
+            obj: Box::new(coll),
+            method: scoll::INDEX_OF_METHOD.clone(),
+            args: vec![],
+        }
+        .into();
+        expect![[
+            r#"Type check error: MethodCall indexOf: expected 2 arguments, got 0 (at MethodCall)"#
+        ]]
+        .assert_eq(&check_err(e));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn func_value_type_mismatch() {
+        let e: Expr = FuncValue::new(
+            vec![FuncArg {
+                idx: 1.into(),
+                tpe: SType::SInt,
+            }],
+            ValUse {
+                val_id: 1.into(),
+                tpe: SType::SInt,
+            }
+            .into(),
+        )
+        .into();
+        assert_eq!(type_check(e.clone()), Ok(e.clone()));
+        let json = serde_json::to_string(&e).unwrap();
+        let wrong_range = json.replace(r#""t_range":"SInt""#, r#""t_range":"SLong""#);
+        assert_ne!(wrong_range, json);
+        let e: Expr = serde_json::from_str(&wrong_range).unwrap();
+        expect![[
+            r#"Type check error: FuncValue body: expected type SLong, got SInt (at FuncValue)"#
+        ]]
+        .assert_eq(&check_err(e));
+    }
+
+    #[test]
+    fn if_branches_mismatch() {
+        let e: Expr = If {
+            condition: Box::new(true.into()),
+            true_branch: Box::new(1i32.into()),
+            false_branch: Box::new(1i64.into()),
+        }
+        .into();
+        expect![[r#"Type check error: If branches: expected type SInt, got SLong (at If)"#]]
+            .assert_eq(&check_err(e));
+    }
+
+    #[test]
+    fn collection_item_mismatch() {
+        let e: Expr = Collection::Exprs {
+            elem_tpe: SType::SInt,
+            items: vec![1i32.into(), 1i64.into()],
+        }
+        .into();
+        expect![[
+            r#"Type check error: Collection item: expected type SInt, got SLong (at Collection)"#
+        ]]
+        .assert_eq(&check_err(e));
+    }
+
+    #[test]
+    fn constant_value_mismatch() {
+        let e: Expr = Expr::Const(crate::mir::constant::Constant {
+            tpe: SType::SLong,
+            v: Value::Int(1),
+        });
+        expect![[r#"Type check error: constant value Int(1) is not of type SLong (at Const)"#]]
+            .assert_eq(&check_err(e));
     }
 }
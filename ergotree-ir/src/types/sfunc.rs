@@ -1,3 +1,5 @@
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use super::stype::SType;
@@ -6,6 +8,7 @@ use super::stype_param::STypeVar;
 
 /// Function signature type
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct SFunc {
     /// Function parameter types
     pub t_dom: Vec<SType>,
@@ -47,7 +47,8 @@ impl SMethod {
 
     /// Get method from type and method ids
     pub fn from_ids(type_id: TypeCode, method_id: MethodId) -> Result<Self, SerializationError> {
-        let obj_type = STypeCompanion::type_by_id(type_id);
+        let obj_type = STypeCompanion::try_type_by_id(type_id)
+            .ok_or(SerializationError::UnknownTypeId(type_id))?;
         match obj_type.method_by_id(&method_id) {
             Some(m) => Ok(m),
             None => Err(UnknownMethodId(method_id, type_id)),
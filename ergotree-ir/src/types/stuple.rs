@@ -1,3 +1,5 @@
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::convert::TryInto;
@@ -23,8 +25,10 @@ impl TryFrom<Vec<SType>> for STuple {
 
 /// Tuple type
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct STuple {
     /// Tuple element types
+    #[cfg_attr(feature = "json", serde(with = "crate::json::bounded_vec"))]
     pub items: TupleItems<SType>,
 }
 
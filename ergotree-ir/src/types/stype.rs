@@ -1,5 +1,7 @@
 //! SType hierarchy
 
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::convert::TryInto;
 
@@ -21,6 +23,7 @@ use super::stype_param::STypeVar;
 
 /// Every type descriptor is a tree represented by nodes in SType hierarchy.
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub enum SType {
     /// Type variable (generic)
     STypeVar(STypeVar),
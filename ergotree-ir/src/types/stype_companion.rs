@@ -35,20 +35,28 @@ impl STypeCompanion {
 
     /// Get type companion for a givec type id
     pub fn type_by_id(type_id: TypeCode) -> &'static STypeCompanion {
+        match STypeCompanion::try_type_by_id(type_id) {
+            Some(obj_type) => obj_type,
+            None => todo!("cannot find STypeCompanion for {0:?} type id", type_id),
+        }
+    }
+
+    /// Get type companion for a given type id, or None if there is no such type companion
+    pub(crate) fn try_type_by_id(type_id: TypeCode) -> Option<&'static STypeCompanion> {
         if type_id == scontext::S_CONTEXT_TYPE_COMPANION.type_id() {
-            &scontext::S_CONTEXT_TYPE_COMPANION
+            Some(&scontext::S_CONTEXT_TYPE_COMPANION)
         } else if type_id == sbox::S_BOX_TYPE_COMPANION.type_id() {
-            &sbox::S_BOX_TYPE_COMPANION
+            Some(&sbox::S_BOX_TYPE_COMPANION)
         } else if type_id == scoll::S_COLL_TYPE_COMPANION.type_id() {
-            &scoll::S_COLL_TYPE_COMPANION
+            Some(&scoll::S_COLL_TYPE_COMPANION)
         } else if type_id == sglobal::S_GLOBAL_TYPE_COMPANION.type_id() {
-            &sglobal::S_GLOBAL_TYPE_COMPANION
+            Some(&sglobal::S_GLOBAL_TYPE_COMPANION)
         } else if type_id == sheader::S_HEADER_TYPE_COMPANION.type_id() {
-            &sheader::S_HEADER_TYPE_COMPANION
+            Some(&sheader::S_HEADER_TYPE_COMPANION)
         } else if type_id == spreheader::S_PRE_HEADER_TYPE_COMPANION.type_id() {
-            &spreheader::S_PRE_HEADER_TYPE_COMPANION
+            Some(&spreheader::S_PRE_HEADER_TYPE_COMPANION)
         } else {
-            todo!("cannot find STypeCompanion for {0:?} type id", type_id)
+            None
         }
     }
 
@@ -2,11 +2,14 @@ use crate::serialization::sigma_byte_reader::SigmaByteRead;
 use crate::serialization::sigma_byte_writer::SigmaByteWrite;
 use crate::serialization::SerializationError;
 use crate::serialization::SigmaSerializable;
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
 
 use super::stype::SType;
 
 /// Type variable for generic signatures
 #[derive(PartialEq, Eq, Debug, Clone, Hash)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct STypeVar {
     /// Type variable name (e.g. "T")
     pub name: String,
@@ -62,6 +65,7 @@ impl SigmaSerializable for STypeVar {
 
 /// Type parameter
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct STypeParam {
     pub(crate) ident: STypeVar,
     upper_bound: Option<SType>,
@@ -0,0 +1,79 @@
+//! Fuzz targets for the parsers consuming untrusted input
+//!
+//! Targets are run with [cargo-fuzz](https://github.com/rust-fuzz/cargo-fuzz) (see `README.md`).
+//! Each target panics on a crash or a violated property (i.e. parsed value does not survive
+//! serialization roundtrip). Seed corpora (`seeds/<target>`) are replayed in tests.
+
+// Coding conventions
+#![forbid(unsafe_code)]
+#![deny(non_upper_case_globals)]
+#![deny(non_camel_case_types)]
+#![deny(non_snake_case)]
+#![deny(unused_mut)]
+#![deny(dead_code)]
+#![deny(unused_imports)]
+#![deny(missing_docs)]
+
+pub mod parse;
+pub mod structured;
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use super::*;
+
+    fn replay_seeds(target: &str, f: fn(&[u8])) {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("seeds")
+            .join(target);
+        let mut count = 0;
+        for entry in fs::read_dir(&dir).unwrap() {
+            let data = fs::read(entry.unwrap().path()).unwrap();
+            f(&data);
+            count += 1;
+        }
+        assert!(count > 0, "no seeds in {:?}", dir);
+    }
+
+    #[test]
+    fn ergo_tree_parse_seeds() {
+        replay_seeds("ergo_tree_parse", parse::ergo_tree_parse);
+    }
+
+    #[test]
+    fn constant_parse_seeds() {
+        replay_seeds("constant_parse", parse::constant_parse);
+    }
+
+    #[test]
+    fn ergo_box_parse_seeds() {
+        replay_seeds("ergo_box_parse", parse::ergo_box_parse);
+    }
+
+    #[test]
+    fn transaction_parse_seeds() {
+        replay_seeds("transaction_parse", parse::transaction_parse);
+    }
+
+    #[test]
+    fn address_parse_seeds() {
+        replay_seeds("address_parse", parse::address_parse);
+    }
+
+    #[test]
+    fn sig_parse_seeds() {
+        replay_seeds("sig_parse", parse::sig_parse);
+    }
+
+    #[test]
+    fn structured_targets_on_fixed_inputs() {
+        for len in &[0usize, 1, 31, 32, 33, 64, 1024] {
+            let data: Vec<u8> = (0..*len).map(|i| (i * 31 % 251) as u8).collect();
+            structured::ergo_tree_structured(&data);
+            structured::expr_structured(&data);
+            structured::sig_structured(&data);
+        }
+    }
+}
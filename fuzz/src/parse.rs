@@ -0,0 +1,99 @@
+//! Targets feeding the fuzzer input to the parsers as is
+
+use std::fmt::Debug;
+use std::str;
+use std::sync::Arc;
+
+use ergo_lib::chain::ergo_box::ErgoBox;
+use ergo_lib::chain::transaction::Transaction;
+use ergotree_interpreter::eval::context::Context;
+use ergotree_interpreter::eval::env::Env;
+use ergotree_interpreter::sigma_protocol::prover::ProofBytes;
+use ergotree_interpreter::sigma_protocol::verifier::TestVerifier;
+use ergotree_interpreter::sigma_protocol::verifier::Verifier;
+use ergotree_ir::address::AddressEncoder;
+use ergotree_ir::ergo_tree::ErgoTree;
+use ergotree_ir::mir::constant::Constant;
+use ergotree_ir::mir::expr::Expr;
+use ergotree_ir::serialization::sigma_byte_reader;
+use ergotree_ir::serialization::sigma_byte_reader::SigmaByteRead;
+use ergotree_ir::serialization::SigmaSerializable;
+use ergotree_ir::sigma_protocol::sigma_boolean::SigmaBoolean;
+use ergotree_ir::sigma_protocol::sigma_boolean::SigmaProp;
+use sigma_test_util::force_any_val;
+
+thread_local! {
+    static CONTEXT: Arc<Context> = Arc::new(force_any_val::<Context>());
+}
+
+/// Message the proofs are checked against in [`sig_parse`]
+pub const SIG_MESSAGE: &[u8] = &[
+    0x1d, 0xc0, 0x17, 0x72, 0xee, 0x01, 0x71, 0xf5, 0xf6, 0x14, 0xc6, 0x73, 0xe3, 0xc7, 0xfa, 0x11,
+    0x07, 0xa8, 0xcf, 0x72, 0x7b, 0xdf, 0x5a, 0x6d, 0xad, 0xb3, 0x79, 0xe9, 0x3c, 0x0d, 0x1d, 0x00,
+];
+
+fn parse_roundtrip<T: SigmaSerializable + PartialEq + Debug>(data: &[u8]) {
+    if let Ok(v) = T::sigma_parse_bytes(data) {
+        let bytes = v.sigma_serialize_bytes();
+        assert_eq!(T::sigma_parse_bytes(&bytes), Ok(v));
+    }
+}
+
+/// `ErgoTree` parsing
+pub fn ergo_tree_parse(data: &[u8]) {
+    parse_roundtrip::<ErgoTree>(data);
+    if let Ok(tree) = ErgoTree::sigma_parse_bytes(data) {
+        let _ = tree.proposition();
+    }
+}
+
+/// `Constant` parsing
+pub fn constant_parse(data: &[u8]) {
+    parse_roundtrip::<Constant>(data)
+}
+
+/// `ErgoBox` parsing
+pub fn ergo_box_parse(data: &[u8]) {
+    parse_roundtrip::<ErgoBox>(data)
+}
+
+/// `Transaction` parsing
+pub fn transaction_parse(data: &[u8]) {
+    parse_roundtrip::<Transaction>(data)
+}
+
+/// Address parsing from the base58 string
+pub fn address_parse(data: &[u8]) {
+    if let Ok(s) = str::from_utf8(data) {
+        let _ = AddressEncoder::unchecked_parse_address_from_str(s);
+        if let Ok(addr) = AddressEncoder::unchecked_parse_network_address_from_str(s) {
+            assert_eq!(
+                AddressEncoder::unchecked_parse_network_address_from_str(&addr.to_base58()),
+                Ok(addr)
+            );
+        }
+    }
+}
+
+/// Proof parsing and verification, the input is a serialized `SigmaBoolean` followed by
+/// the proof bytes (for [`SIG_MESSAGE`])
+pub fn sig_parse(data: &[u8]) {
+    let mut r = sigma_byte_reader::from_bytes(data);
+    if let Ok(sb) = SigmaBoolean::sigma_parse(&mut r) {
+        let proof = data[r.position()..].to_vec();
+        verify_proof(sb, proof);
+    }
+}
+
+/// Verifies the proof of the given proposition (for [`SIG_MESSAGE`])
+pub(crate) fn verify_proof(sb: SigmaBoolean, proof: Vec<u8>) {
+    let expr = Expr::Const(SigmaProp::new(sb).into());
+    let proof = if proof.is_empty() {
+        ProofBytes::Empty
+    } else {
+        ProofBytes::Some(proof)
+    };
+    CONTEXT.with(|ctx| {
+        let _ = TestVerifier.verify(&expr.into(), &Env::empty(), ctx.clone(), proof, SIG_MESSAGE);
+    })
+}
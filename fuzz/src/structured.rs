@@ -0,0 +1,141 @@
+//! Structure-aware targets, the fuzzer input seeds the `proptest` strategies (`arbitrary` feature)
+//! generating the values to check
+
+use std::convert::TryInto;
+use std::sync::Arc;
+
+use ergotree_interpreter::eval::context::Context;
+use ergotree_interpreter::eval::env::Env;
+use ergotree_interpreter::eval::partial_eval::partial_eval;
+use ergotree_interpreter::eval::partial_eval::ContextVar;
+use ergotree_interpreter::eval::partial_eval::PartialContext;
+use ergotree_interpreter::eval::Evaluator;
+use ergotree_interpreter::sigma_protocol::verifier::TestVerifier;
+use ergotree_ir::ergo_tree::ErgoTree;
+use ergotree_ir::ergo_tree::ErgoTreeHeader;
+use ergotree_ir::mir::expr::well_typed::well_typed_expr;
+use ergotree_ir::mir::expr::Expr;
+use ergotree_ir::serialization::SigmaSerializable;
+use ergotree_ir::sigma_protocol::dlog_group::EcPoint;
+use ergotree_ir::sigma_protocol::sigma_boolean::cand::Cand;
+use ergotree_ir::sigma_protocol::sigma_boolean::cor::Cor;
+use ergotree_ir::sigma_protocol::sigma_boolean::ProveDhTuple;
+use ergotree_ir::sigma_protocol::sigma_boolean::ProveDlog;
+use ergotree_ir::sigma_protocol::sigma_boolean::SigmaBoolean;
+use ergotree_ir::sigma_protocol::sigma_boolean::SigmaConjecture;
+use ergotree_ir::sigma_protocol::sigma_boolean::SigmaProofOfKnowledgeTree;
+use ergotree_ir::types::stype::SType;
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::strategy::ValueTree;
+use proptest::test_runner::Config;
+use proptest::test_runner::RngAlgorithm;
+use proptest::test_runner::TestRng;
+use proptest::test_runner::TestRunner;
+use sigma_util::hash::blake2b256_hash;
+
+use crate::parse::verify_proof;
+
+/// Max nesting depth of the generated expressions
+const EXPR_DEPTH: usize = 4;
+
+/// Value generated by the strategy using ChaCha RNG seeded with the hash of `data`.
+/// `None` if the strategy rejected the input.
+// Pass-through RNG (input bytes as is) does not fit here since it gives the half of the remaining
+// input to every forked RNG (on each `prop_flat_map`) and the range sampling never ends on zeroes
+// after the input is exhausted.
+pub fn value_from_bytes<S: Strategy>(strategy: S, data: &[u8]) -> Option<S::Value> {
+    let rng = TestRng::from_seed(RngAlgorithm::ChaCha, blake2b256_hash(data).as_ref());
+    let mut runner = TestRunner::new_with_rng(Config::default(), rng);
+    strategy
+        .new_tree(&mut runner)
+        .ok()
+        .map(|tree| tree.current())
+}
+
+fn ergo_tree_header() -> impl Strategy<Value = ErgoTreeHeader> {
+    prop_oneof![
+        any::<bool>().prop_map(ErgoTreeHeader::v0),
+        any::<bool>().prop_map(ErgoTreeHeader::v1),
+    ]
+}
+
+/// Well-typed proposition in `ErgoTree` with random header (version, constant segregation)
+/// survives serialization roundtrip
+pub fn ergo_tree_structured(data: &[u8]) {
+    let strategy = (
+        ergo_tree_header(),
+        well_typed_expr(SType::SSigmaProp, EXPR_DEPTH),
+    );
+    if let Some((header, expr)) = value_from_bytes(strategy, data) {
+        let tree = ErgoTree::new(header, &expr);
+        let bytes = tree.sigma_serialize_bytes();
+        let parsed = ErgoTree::sigma_parse_bytes(&bytes).unwrap();
+        assert_eq!(parsed, tree);
+        assert_eq!(parsed.proposition().unwrap().as_ref(), &expr);
+    }
+}
+
+/// Well-typed proposition survives serialization roundtrip and its evaluation (full and
+/// partial) in the generated context does not panic
+pub fn expr_structured(data: &[u8]) {
+    let strategy = (
+        well_typed_expr(SType::SSigmaProp, EXPR_DEPTH),
+        any::<Context>(),
+    );
+    if let Some((expr, ctx)) = value_from_bytes(strategy, data) {
+        assert_eq!(
+            Expr::sigma_parse_bytes(&expr.sigma_serialize_bytes()),
+            Ok(expr.clone())
+        );
+        let ctx = Arc::new(ctx);
+        let _ = TestVerifier.reduce_to_crypto(&expr, &Env::empty(), ctx.clone());
+        let _ = partial_eval(&expr, &PartialContext::new(ctx, vec![ContextVar::Height]));
+    }
+}
+
+fn sigma_boolean() -> impl Strategy<Value = SigmaBoolean> {
+    let leaf = prop_oneof![
+        any::<ProveDlog>().prop_map(SigmaBoolean::from),
+        (
+            any::<EcPoint>(),
+            any::<EcPoint>(),
+            any::<EcPoint>(),
+            any::<EcPoint>()
+        )
+            .prop_map(|(gv, hv, uv, vv)| {
+                SigmaProofOfKnowledgeTree::ProveDhTuple(ProveDhTuple::new(gv, hv, uv, vv)).into()
+            }),
+        any::<bool>().prop_map(SigmaBoolean::TrivialProp),
+    ];
+    leaf.prop_recursive(3, 16, 4, |inner| {
+        prop_oneof![
+            vec(inner.clone(), 2..4).prop_map(|items| {
+                SigmaConjecture::Cand(Cand {
+                    items: items.try_into().unwrap(),
+                })
+                .into()
+            }),
+            vec(inner, 2..4).prop_map(|items| {
+                SigmaConjecture::Cor(Cor {
+                    items: items.try_into().unwrap(),
+                })
+                .into()
+            }),
+        ]
+    })
+}
+
+/// Proof parsing and verification for the generated proposition, the proposition
+/// survives serialization roundtrip. The first 32 bytes of the input seed the proposition,
+/// the rest is the proof.
+pub fn sig_structured(data: &[u8]) {
+    let (sb_seed, proof) = data.split_at(data.len().min(32));
+    if let Some(sb) = value_from_bytes(sigma_boolean(), sb_seed) {
+        assert_eq!(
+            SigmaBoolean::sigma_parse_bytes(&sb.sigma_serialize_bytes()),
+            Ok(sb.clone())
+        );
+        verify_proof(sb, proof.to_vec());
+    }
+}